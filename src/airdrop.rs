@@ -0,0 +1,62 @@
+//! Requesting SOL from the faucet of a test cluster.
+
+use anyhow::{Context, Result};
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use tracing::info;
+
+use crate::client::MetadataClient;
+use crate::cluster::Cluster;
+use crate::types::AirdropResult;
+
+impl MetadataClient {
+    /// Request `lamports` from the cluster's faucet for `recipient` and wait
+    /// for the airdrop to land. Mainnet has no faucet.
+    pub async fn airdrop(&self, recipient: &Pubkey, lamports: u64) -> Result<AirdropResult> {
+        if self.is_dry_run() || self.is_sign_only() {
+            anyhow::bail!("Airdrops can't be simulated or signed offline");
+        }
+        if self.cluster() == Cluster::MainnetBeta {
+            anyhow::bail!("Airdrops are only available on devnet, testnet and localnet");
+        }
+        let signature = self
+            .rpc()
+            .request_airdrop(recipient, lamports)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to request an airdrop of {} SOL; faucets limit how much can be \
+                     requested at once",
+                    lamports as f64 / LAMPORTS_PER_SOL as f64
+                )
+            })?;
+        self.rpc()
+            .poll_for_signature_with_commitment(&signature, self.rpc().commitment())
+            .await
+            .context("Airdrop was requested but not confirmed")?;
+        let balance = self
+            .rpc()
+            .get_balance(recipient)
+            .await
+            .context("Failed to fetch balance")?;
+
+        Ok(AirdropResult {
+            recipient: recipient.to_string(),
+            lamports,
+            balance,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Top up the payer from the faucet when it is short. At least 1 SOL is
+    /// requested so that a run of transactions doesn't need an airdrop each.
+    pub(crate) async fn airdrop_shortfall(&self, payer: &Pubkey, shortfall: u64) -> Result<()> {
+        let lamports = shortfall.max(LAMPORTS_PER_SOL);
+        info!(
+            "Payer is short {} SOL; requesting an airdrop of {} SOL...",
+            shortfall as f64 / LAMPORTS_PER_SOL as f64,
+            lamports as f64 / LAMPORTS_PER_SOL as f64
+        );
+        self.airdrop(payer, lamports).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+//! Health checks of on-chain metadata, for reviewing a mint or a whole
+//! drop before release.
+
+use anyhow::Result;
+use futures_util::{StreamExt, stream};
+use mpl_token_metadata::{accounts::Metadata, types::TokenStandard};
+use solana_sdk::pubkey::Pubkey;
+use std::time::Duration;
+
+use crate::client::MetadataClient;
+use crate::error::MetadataCliError;
+use crate::offchain::{Gateways, OffChainMetadata};
+use crate::pda::find_metadata_pda;
+use crate::types::{AuditFinding, AuditReport, MintAudit};
+
+/// Mints audited at the same time
+const CONCURRENT_AUDITS: usize = 8;
+
+/// Royalties above this many basis points are flagged as unusual
+const HIGH_SELLER_FEE_BASIS_POINTS: u16 = 1000;
+
+/// Royalties above this many basis points are flagged as a likely mistake
+const EXCESSIVE_SELLER_FEE_BASIS_POINTS: u16 = 5000;
+
+/// What [`MetadataClient::audit`] checks beyond the on-chain account
+#[derive(Clone, Debug)]
+pub struct AuditOptions {
+    /// Treat mutable metadata as an error rather than a warning
+    pub require_immutable: bool,
+    /// Fetch the JSON behind every URI, checking it is reachable and agrees
+    /// with the on-chain royalties
+    pub check_uri: bool,
+    pub gateways: Gateways,
+    pub timeout: Duration,
+}
+
+impl Default for AuditOptions {
+    fn default() -> Self {
+        Self {
+            require_immutable: false,
+            check_uri: true,
+            gateways: Gateways::default(),
+            timeout: Duration::from_secs(15),
+        }
+    }
+}
+
+impl MetadataClient {
+    /// Check the metadata of every mint for common problems: mutable when
+    /// it shouldn't be, a URI that is empty, plain HTTP or unreachable,
+    /// unverified creators, an unverified collection and odd royalties.
+    /// Each mint is scored out of 100, losing 25 points per error and 10 per
+    /// warning, and the report's score is the lowest. A mint without
+    /// metadata scores 0; only failing to reach the RPC node is an error.
+    pub async fn audit(&self, mints: &[Pubkey], options: &AuditOptions) -> Result<AuditReport> {
+        let progress = self.progress();
+        if let Some(progress) = progress {
+            progress.start(mints.len());
+        }
+        let audits: Vec<Result<MintAudit>> = stream::iter(mints)
+            .map(|mint| async move {
+                let audit = self.audit_mint(mint, options).await;
+                if let Some(progress) = progress {
+                    progress.advance(audit.as_ref().is_ok_and(|audit| audit.errors == 0));
+                }
+                audit
+            })
+            .buffered(CONCURRENT_AUDITS)
+            .collect()
+            .await;
+        if let Some(progress) = progress {
+            progress.finish();
+        }
+        Ok(AuditReport::new(audits.into_iter().collect::<Result<_>>()?))
+    }
+
+    async fn audit_mint(&self, mint: &Pubkey, options: &AuditOptions) -> Result<MintAudit> {
+        let metadata_pda = find_metadata_pda(mint);
+        let metadata = match self.fetch(mint).await {
+            Ok(metadata) => metadata,
+            Err(err) => match err.downcast_ref::<MetadataCliError>() {
+                Some(
+                    MetadataCliError::AccountNotFound { .. }
+                    | MetadataCliError::InvalidAccountData { .. },
+                ) => return Ok(MintAudit::unreadable(mint, &metadata_pda, err.to_string())),
+                _ => return Err(err),
+            },
+        };
+        let mut findings = check_metadata(&metadata, options);
+        if options.check_uri && !metadata.uri.trim_end_matches('\0').is_empty() {
+            findings.extend(self.check_offchain(&metadata, options).await);
+        }
+        Ok(MintAudit::new(mint, &metadata_pda, findings))
+    }
+
+    /// Check that the JSON behind the URI can be fetched and agrees with the
+    /// on-chain royalties
+    async fn check_offchain(
+        &self,
+        metadata: &Metadata,
+        options: &AuditOptions,
+    ) -> Vec<AuditFinding> {
+        let uri = metadata.uri.trim_end_matches('\0');
+        let json = match self
+            .fetch_offchain_json(&options.gateways, uri, options.timeout)
+            .await
+        {
+            Ok(json) => json,
+            Err(err) => {
+                return vec![AuditFinding::error(
+                    "uri",
+                    format!("The URI can't be fetched: {:#}", err),
+                )];
+            }
+        };
+        let Ok(offchain) = serde_json::from_value::<OffChainMetadata>(json) else {
+            return vec![AuditFinding::error(
+                "uri",
+                "The URI doesn't point at metadata JSON".to_string(),
+            )];
+        };
+        match offchain.seller_fee_basis_points {
+            Some(fee) if fee != metadata.seller_fee_basis_points => vec![AuditFinding::warning(
+                "seller_fee_basis_points",
+                format!(
+                    "The JSON says {} bps but the on-chain royalties are {} bps; \
+                     marketplaces pay the on-chain value",
+                    fee, metadata.seller_fee_basis_points
+                ),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The checks that only need the on-chain account
+fn check_metadata(metadata: &Metadata, options: &AuditOptions) -> Vec<AuditFinding> {
+    let mut findings = Vec::new();
+
+    if metadata.is_mutable {
+        let message = format!(
+            "The metadata is mutable, so update authority {} can still change it",
+            metadata.update_authority
+        );
+        findings.push(if options.require_immutable {
+            AuditFinding::error("is_mutable", message)
+        } else {
+            AuditFinding::warning("is_mutable", message)
+        });
+    }
+
+    let uri = metadata.uri.trim_end_matches('\0');
+    if uri.is_empty() {
+        findings.push(AuditFinding::error("uri", "The URI is empty".to_string()));
+    } else if uri.starts_with("http://") {
+        findings.push(AuditFinding::warning(
+            "uri",
+            "The URI uses plain HTTP; wallets may refuse it or be served something else"
+                .to_string(),
+        ));
+    } else if !["https://", "ipfs://", "ar://"]
+        .iter()
+        .any(|scheme| uri.starts_with(scheme))
+    {
+        findings.push(AuditFinding::error(
+            "uri",
+            format!("The URI '{}' isn't an HTTPS, IPFS or Arweave URI", uri),
+        ));
+    }
+
+    match metadata.creators.as_deref() {
+        None | Some([]) if metadata.seller_fee_basis_points > 0 => {
+            findings.push(AuditFinding::error(
+                "creators",
+                format!(
+                    "Royalties of {} bps are set but there are no creators to receive them",
+                    metadata.seller_fee_basis_points
+                ),
+            ));
+        }
+        Some(creators) => {
+            for creator in creators.iter().filter(|creator| !creator.verified) {
+                findings.push(AuditFinding::warning(
+                    "creators",
+                    format!(
+                        "Creator {} hasn't verified, so marketplaces may not trust the \
+                         listing",
+                        creator.address
+                    ),
+                ));
+            }
+        }
+        None => {}
+    }
+
+    match &metadata.collection {
+        Some(collection) if !collection.verified => findings.push(AuditFinding::error(
+            "collection",
+            format!(
+                "Collection {} isn't verified, so marketplaces won't group the token with it",
+                collection.key
+            ),
+        )),
+        None if matches!(
+            metadata.token_standard,
+            Some(TokenStandard::NonFungible | TokenStandard::ProgrammableNonFungible)
+        ) =>
+        {
+            findings.push(AuditFinding::warning(
+                "collection",
+                "The NFT doesn't belong to a collection".to_string(),
+            ))
+        }
+        _ => {}
+    }
+
+    let fee = metadata.seller_fee_basis_points;
+    if fee > EXCESSIVE_SELLER_FEE_BASIS_POINTS {
+        findings.push(AuditFinding::error(
+            "seller_fee_basis_points",
+            format!(
+                "Royalties of {} bps ({:.2}%) are more than half of every sale",
+                fee,
+                fee as f64 / 100.0
+            ),
+        ));
+    } else if fee > HIGH_SELLER_FEE_BASIS_POINTS {
+        findings.push(AuditFinding::warning(
+            "seller_fee_basis_points",
+            format!(
+                "Royalties of {} bps ({:.2}%) are unusually high",
+                fee,
+                fee as f64 / 100.0
+            ),
+        ));
+    }
+
+    findings
+}
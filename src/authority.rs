@@ -0,0 +1,142 @@
+//! Update authority and mutability changes.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::instructions::{
+    UpdateMetadataAccountV2Builder, UpdatePrimarySaleHappenedViaTokenBuilder,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account_client::address::get_associated_token_address_with_program_id;
+
+use crate::client::{MetadataClient, ensure_update_authority};
+use crate::error::MetadataCliError;
+use crate::pda::find_metadata_pda;
+use crate::types::{MakeImmutableResult, PrimarySaleResult, SetAuthorityResult};
+
+impl MetadataClient {
+    /// Hand the update authority of a mint's metadata to another key. Only
+    /// the current update authority can do this, and only the new authority
+    /// can undo it.
+    pub async fn set_update_authority(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        new_authority: &Pubkey,
+    ) -> Result<SetAuthorityResult> {
+        let metadata_pda = find_metadata_pda(mint);
+
+        let existing = self.fetch_fresh(mint).await?;
+        ensure_update_authority(&existing, &authority.pubkey())?;
+
+        let ix = UpdateMetadataAccountV2Builder::new()
+            .metadata(metadata_pda)
+            .update_authority(authority.pubkey())
+            .new_update_authority(*new_authority)
+            .instruction();
+
+        let signature = self
+            .send(&[ix], authority, &[])
+            .await
+            .context("Failed to send set authority transaction")?;
+
+        Ok(SetAuthorityResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            old_authority: existing.update_authority.to_string(),
+            new_authority: new_authority.to_string(),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Permanently lock a mint's metadata by clearing its `is_mutable` flag
+    pub async fn make_immutable(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+    ) -> Result<MakeImmutableResult> {
+        let metadata_pda = find_metadata_pda(mint);
+
+        let existing = self.fetch_fresh(mint).await?;
+        if !existing.is_mutable {
+            return Err(MetadataCliError::Immutable { address: *mint }.into());
+        }
+        ensure_update_authority(&existing, &authority.pubkey())?;
+
+        let ix = UpdateMetadataAccountV2Builder::new()
+            .metadata(metadata_pda)
+            .update_authority(authority.pubkey())
+            .is_mutable(false)
+            .instruction();
+
+        let signature = self
+            .send(&[ix], authority, &[])
+            .await
+            .context("Failed to send make immutable transaction")?;
+
+        Ok(MakeImmutableResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Mark a mint's primary sale as happened, which marketplaces use to
+    /// apply secondary-sale royalties. The update authority can set it, and
+    /// so can a holder of the token. The flag can never be cleared again.
+    pub async fn set_primary_sale_happened(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+    ) -> Result<PrimarySaleResult> {
+        let metadata_pda = find_metadata_pda(mint);
+
+        let existing = self.fetch_fresh(mint).await?;
+        if existing.primary_sale_happened {
+            return Err(MetadataCliError::InvalidField {
+                field: "primary_sale_happened",
+                reason: format!("the primary sale of mint {} has already happened", mint),
+            }
+            .into());
+        }
+
+        let (ix, token_account) = if existing.update_authority == authority.pubkey() {
+            let ix = UpdateMetadataAccountV2Builder::new()
+                .metadata(metadata_pda)
+                .update_authority(authority.pubkey())
+                .primary_sale_happened(true)
+                .instruction();
+            (ix, None)
+        } else {
+            let token_program = self.fetch_mint(mint).await?.program.id();
+            let token = get_associated_token_address_with_program_id(
+                &authority.pubkey(),
+                mint,
+                &token_program,
+            );
+            if self.token_balance(&token).await.unwrap_or(0) == 0 {
+                anyhow::bail!(
+                    "{} is neither the update authority nor a holder of mint {}",
+                    authority.pubkey(),
+                    mint
+                );
+            }
+            let ix = UpdatePrimarySaleHappenedViaTokenBuilder::new()
+                .metadata(metadata_pda)
+                .owner(authority.pubkey())
+                .token(token)
+                .instruction();
+            (ix, Some(token))
+        };
+
+        let signature = self
+            .send(&[ix], authority, &[])
+            .await
+            .context("Failed to send set primary sale transaction")?;
+
+        Ok(PrimarySaleResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            token_account: token_account.map(|token| token.to_string()),
+            signature: signature.to_string(),
+        })
+    }
+}
@@ -0,0 +1,346 @@
+//! Applying an operation to many mints at once, driven by a manifest file.
+
+use anyhow::{Context, Result};
+use futures_util::{StreamExt, stream};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::client::MetadataClient;
+use crate::types::{
+    BatchItemResult, BatchResult, CreateMetadataArgs, CreatorShare, UpdateMetadataArgs,
+};
+
+/// One row of a batch create manifest
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub mint: String,
+    pub name: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub uri: String,
+    #[serde(default)]
+    pub seller_fee_basis_points: u16,
+    #[serde(default = "default_is_mutable")]
+    pub is_mutable: bool,
+}
+
+impl ManifestEntry {
+    /// The metadata `batch create` creates for this row
+    pub(crate) fn create_args(&self) -> CreateMetadataArgs {
+        CreateMetadataArgs {
+            name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            uri: self.uri.clone(),
+            seller_fee_basis_points: self.seller_fee_basis_points,
+            is_mutable: self.is_mutable,
+            creators: None,
+            uses: None,
+            uri_hash: None,
+            update_authority: None,
+        }
+    }
+}
+
+/// One row of a batch compressed mint manifest
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompressedManifestEntry {
+    pub name: String,
+    #[serde(default)]
+    pub symbol: String,
+    pub uri: String,
+    #[serde(default)]
+    pub seller_fee_basis_points: u16,
+    #[serde(default = "default_is_mutable")]
+    pub is_mutable: bool,
+    /// Wallet to mint to; the payer when empty
+    #[serde(default)]
+    pub owner: Option<String>,
+}
+
+/// One entry of a batch update file. Only the fields that are present are
+/// changed; everything else keeps its on-chain value.
+#[derive(Clone, Debug, Deserialize)]
+pub struct UpdateEntry {
+    pub mint: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub uri: Option<String>,
+    /// Creators as `<PUBKEY>:<SHARE>`, replacing the existing list
+    #[serde(default)]
+    pub creators: Option<Vec<String>>,
+}
+
+/// Told how a long-running batch is getting on, e.g. to draw a progress
+/// bar. Set one with [`MetadataClient::with_progress`].
+pub trait Progress: Send + Sync {
+    /// A batch of `total` items is starting
+    fn start(&self, total: usize);
+    /// One more item finished, successfully or not
+    fn advance(&self, succeeded: bool);
+    /// Every item has finished
+    fn finish(&self);
+}
+
+fn default_is_mutable() -> bool {
+    true
+}
+
+/// Read a batch create manifest. Files ending in `.json` are parsed as a
+/// JSON array of entries, anything else as CSV with a header row.
+pub fn load_manifest(path: impl AsRef<Path>) -> Result<Vec<ManifestEntry>> {
+    load_rows(path.as_ref())
+}
+
+/// Read a batch compressed mint manifest, as JSON or CSV like
+/// [`load_manifest`]
+pub fn load_compressed_manifest(path: impl AsRef<Path>) -> Result<Vec<CompressedManifestEntry>> {
+    load_rows(path.as_ref())
+}
+
+fn load_rows<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest '{}'", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse manifest '{}'", path.display()))
+    } else {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .with_context(|| format!("Failed to read manifest '{}'", path.display()))?;
+        reader
+            .deserialize()
+            .enumerate()
+            .map(|(i, row)| row.with_context(|| format!("Failed to parse manifest row {}", i + 1)))
+            .collect()
+    }
+}
+
+/// Read a batch update file: a JSON array of [`UpdateEntry`]
+pub fn load_updates(path: impl AsRef<Path>) -> Result<Vec<UpdateEntry>> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read update file '{}'", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse update file '{}'", path.display()))
+}
+
+/// Read a list of mint addresses, one per line. Blank lines and lines
+/// starting with `#` are ignored.
+pub fn load_mints(path: impl AsRef<Path>) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read mint list '{}'", path.display()))?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+impl MetadataClient {
+    /// Create metadata for every manifest entry, sending up to
+    /// `concurrency` transactions at a time. The payer must be the mint
+    /// authority of every mint. A failing row does not stop the others.
+    pub async fn batch_create(
+        &self,
+        payer: &(dyn Signer + Sync),
+        entries: &[ManifestEntry],
+        concurrency: usize,
+    ) -> BatchResult {
+        let outcomes = self
+            .run_batch(entries, concurrency, |entry| async move {
+                let mint = Pubkey::from_str(&entry.mint).context("Invalid mint address")?;
+                Ok(Some(
+                    self.create(payer, &mint, entry.create_args())
+                        .await?
+                        .signature,
+                ))
+            })
+            .await;
+
+        let items = entries
+            .iter()
+            .zip(outcomes)
+            .enumerate()
+            .map(|(i, (entry, outcome))| BatchItemResult::new(i + 1, &entry.mint, outcome))
+            .collect();
+        BatchResult::from_items(items)
+    }
+
+    /// Apply every update entry, sending up to `concurrency` transactions at
+    /// a time. Entries whose fields already match on-chain are skipped.
+    pub async fn batch_update(
+        &self,
+        authority: &(dyn Signer + Sync),
+        entries: &[UpdateEntry],
+        concurrency: usize,
+    ) -> BatchResult {
+        let outcomes = self
+            .run_batch(entries, concurrency, |entry| async move {
+                let mint = Pubkey::from_str(&entry.mint).context("Invalid mint address")?;
+                let creators = entry
+                    .creators
+                    .as_deref()
+                    .map(|creators| {
+                        creators
+                            .iter()
+                            .map(|c| CreatorShare::from_str(c))
+                            .collect::<Result<Vec<_>>>()
+                    })
+                    .transpose()?;
+                let args = UpdateMetadataArgs {
+                    name: entry.name.clone(),
+                    symbol: entry.symbol.clone(),
+                    uri: entry.uri.clone(),
+                    creators,
+                    uses: None,
+                    primary_sale_happened: false,
+                    uri_hash: None,
+                };
+
+                let existing = self.fetch_fresh(&mint).await?;
+                let trimmed = |s: &str| s.trim_end_matches('\0').to_string();
+                let differs = |new: &Option<String>, old: &str| {
+                    new.as_deref().is_some_and(|new| new != trimmed(old))
+                };
+                let creators_differ = args.creators.as_deref().is_some_and(|new| {
+                    let old = existing.creators.as_deref().unwrap_or_default();
+                    new.len() != old.len()
+                        || new
+                            .iter()
+                            .zip(old)
+                            .any(|(n, o)| n.address != o.address || n.share != o.share)
+                });
+                if !differs(&args.name, &existing.name)
+                    && !differs(&args.symbol, &existing.symbol)
+                    && !differs(&args.uri, &existing.uri)
+                    && !creators_differ
+                {
+                    return Ok(None);
+                }
+
+                Ok(Some(self.update(authority, &mint, args).await?.signature))
+            })
+            .await;
+
+        let items = entries
+            .iter()
+            .zip(outcomes)
+            .enumerate()
+            .map(|(i, (entry, outcome))| BatchItemResult::new(i + 1, &entry.mint, outcome))
+            .collect();
+        BatchResult::from_items(items)
+    }
+}
+
+impl MetadataClient {
+    /// Mint a compressed NFT into `tree` for every manifest row, sending up
+    /// to `concurrency` transactions at a time, each into `collection` when
+    /// given. Rows are reported by asset ID once minted. A failing row does
+    /// not stop the others.
+    pub async fn batch_mint_compressed(
+        &self,
+        payer: &(dyn Signer + Sync),
+        tree: &Pubkey,
+        collection: Option<&Pubkey>,
+        entries: &[CompressedManifestEntry],
+        concurrency: usize,
+    ) -> BatchResult {
+        let outcomes = self
+            .run_batch(entries, concurrency, |entry| async move {
+                let owner = match entry.owner.as_deref().filter(|owner| !owner.is_empty()) {
+                    Some(owner) => Pubkey::from_str(owner).context("Invalid owner address")?,
+                    None => payer.pubkey(),
+                };
+                let args = CreateMetadataArgs {
+                    name: entry.name.clone(),
+                    symbol: entry.symbol.clone(),
+                    uri: entry.uri.clone(),
+                    seller_fee_basis_points: entry.seller_fee_basis_points,
+                    is_mutable: entry.is_mutable,
+                    creators: None,
+                    uses: None,
+                    uri_hash: None,
+                    update_authority: None,
+                };
+                self.mint_compressed(payer, tree, &owner, collection, args)
+                    .await
+            })
+            .await;
+
+        let items = entries
+            .iter()
+            .zip(outcomes)
+            .enumerate()
+            .map(|(i, (entry, outcome))| {
+                let label = match &outcome {
+                    Ok(result) => result.asset_id.clone(),
+                    Err(_) => entry.name.clone(),
+                };
+                BatchItemResult::new(i + 1, &label, outcome.map(|result| Some(result.signature)))
+            })
+            .collect();
+        BatchResult::from_items(items)
+    }
+}
+
+impl MetadataClient {
+    /// Run `f` over every item with up to `concurrency` in flight at a time,
+    /// each building, sending and confirming its own transactions. A slow
+    /// item doesn't hold up the ones after it, and the outcomes are
+    /// returned in input order, and each is reported to the progress
+    /// reporter as it comes in. Dry runs and sign-only runs go one item at a
+    /// time, so their simulations and signed transactions are in input order
+    /// too. So do runs on a durable nonce, since every transaction in flight
+    /// would use the same nonce and only one could land.
+    pub(crate) async fn run_batch<'a, T, R, F, Fut>(
+        &self,
+        items: &'a [T],
+        concurrency: usize,
+        f: F,
+    ) -> Vec<Result<R>>
+    where
+        F: Fn(&'a T) -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        let concurrency = if self.is_dry_run() || self.is_sign_only() || self.uses_nonce() {
+            1
+        } else {
+            concurrency.max(1)
+        };
+        let progress = self.progress();
+        if let Some(progress) = progress {
+            progress.start(items.len());
+        }
+        let mut outcomes: Vec<(usize, Result<R>)> = stream::iter(items.iter().enumerate())
+            .map(|(i, item)| {
+                let outcome = f(item);
+                async move {
+                    let outcome = outcome.await;
+                    if let Some(progress) = progress {
+                        progress.advance(outcome.is_ok());
+                    }
+                    (i, outcome)
+                }
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+        if let Some(progress) = progress {
+            progress.finish();
+        }
+        outcomes.sort_by_key(|(i, _)| *i);
+        outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+    }
+}
@@ -0,0 +1,980 @@
+//! Compressed NFTs through Metaplex Bubblegum, which keeps each NFT as a
+//! leaf of a concurrent Merkle tree owned by the SPL Account Compression
+//! program rather than in accounts of its own.
+
+use anyhow::{Context, Result};
+use mpl_bubblegum::{
+    accounts::TreeConfig,
+    instructions::{
+        BurnBuilder, CreateTreeConfigBuilder, DecompressV1Builder, MintToCollectionV1Builder,
+        MintV1Builder, RedeemBuilder, TransferBuilder, UpdateMetadataBuilder,
+    },
+    types::{
+        Collection, Creator, DecompressibleState, MetadataArgs, TokenProgramVersion, TokenStandard,
+        UpdateArgs,
+    },
+    utils::get_asset_id,
+};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::AccountMeta,
+    pubkey,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+use solana_transaction_status_client_types::{UiInstruction, UiTransactionEncoding};
+use spl_associated_token_account_client::address::get_associated_token_address_with_program_id;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::warn;
+
+use crate::client::{MetadataClient, data_from_args, ensure_update_authority};
+use crate::das::{AssetState, CompressedLeaf};
+use crate::error::MetadataCliError;
+use crate::pda::{find_master_edition_pda, find_metadata_pda};
+use crate::types::{
+    CompressedBurnResult, CompressedMintResult, CompressedTransferResult, CompressedUpdateResult,
+    CreateMetadataArgs, CreateTreeResult, DecompressResult, FieldChange,
+};
+use crate::validate::check_fields;
+
+/// The SPL Account Compression program, which owns the Merkle trees
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey =
+    pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+/// The SPL Noop program, which Bubblegum logs leaves through for indexers
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+/// The (max depth, max buffer size) pairs the Account Compression program
+/// accepts
+pub const DEPTH_BUFFER_PAIRS: &[(u32, u32)] = &[
+    (3, 8),
+    (5, 8),
+    (6, 16),
+    (7, 16),
+    (8, 16),
+    (9, 16),
+    (10, 32),
+    (11, 32),
+    (12, 32),
+    (13, 32),
+    (14, 64),
+    (14, 256),
+    (14, 1024),
+    (14, 2048),
+    (15, 64),
+    (16, 64),
+    (17, 64),
+    (18, 64),
+    (19, 64),
+    (20, 64),
+    (20, 256),
+    (20, 1024),
+    (20, 2048),
+    (24, 64),
+    (24, 256),
+    (24, 512),
+    (24, 1024),
+    (24, 2048),
+    (26, 512),
+    (26, 1024),
+    (26, 2048),
+    (30, 512),
+    (30, 1024),
+    (30, 2048),
+];
+
+/// Offset of the leaf nonce in the leaf schema event Bubblegum logs through
+/// the noop program: the event type, version and schema version bytes, then
+/// the asset ID, owner and delegate
+const LEAF_EVENT_NONCE_OFFSET: usize = 3 + 32 * 3;
+
+/// How many times to ask the DAS API whether a change has been indexed, and
+/// how long to wait in between
+const INDEX_POLL_ATTEMPTS: u32 = 10;
+const INDEX_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Largest account the system program will allocate
+const MAX_ACCOUNT_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Size of the tree account's header: the account type, the header version,
+/// then max buffer size, max depth, authority, creation slot and padding
+const TREE_HEADER_SIZE: u64 = 2 + 4 + 4 + 32 + 8 + 6;
+
+/// The shape of an existing Merkle tree, read from its account
+#[derive(Clone, Copy, Debug)]
+pub struct TreeShape {
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub canopy_depth: u32,
+}
+
+/// Leaves a tree of `max_depth` can hold
+pub fn tree_capacity(max_depth: u32) -> u64 {
+    1u64 << max_depth
+}
+
+/// Size in bytes of a tree account: the header, the tree itself (its
+/// sequence number, active index and buffer size, a change log per buffer
+/// entry and the rightmost proof) and the canopy, which caches the top
+/// `canopy_depth` levels so proofs sent in transactions can be shorter
+pub fn tree_account_size(max_depth: u32, max_buffer_size: u32, canopy_depth: u32) -> u64 {
+    let depth = max_depth as u64;
+    // A root and a path of nodes, then a u32 index and u32 padding
+    let change_log = 32 + 32 * depth + 8;
+    // A path of nodes and the leaf, then a u32 index and u32 padding
+    let rightmost_proof = 32 * depth + 32 + 8;
+    let tree = 3 * 8 + max_buffer_size as u64 * change_log + rightmost_proof;
+    let canopy = ((1u64 << (canopy_depth + 1)) - 2) * 32;
+    TREE_HEADER_SIZE + tree + canopy
+}
+
+/// Check that a tree shape is one the Account Compression program accepts
+/// and fits in an account
+pub fn check_tree_shape(max_depth: u32, max_buffer_size: u32, canopy_depth: u32) -> Result<()> {
+    if !DEPTH_BUFFER_PAIRS.contains(&(max_depth, max_buffer_size)) {
+        let buffers: Vec<String> = DEPTH_BUFFER_PAIRS
+            .iter()
+            .filter(|(depth, _)| *depth == max_depth)
+            .map(|(_, buffer)| buffer.to_string())
+            .collect();
+        let reason = if buffers.is_empty() {
+            let mut depths: Vec<String> = DEPTH_BUFFER_PAIRS
+                .iter()
+                .map(|(depth, _)| depth.to_string())
+                .collect();
+            depths.dedup();
+            format!(
+                "max depth {} isn't supported; use one of {}",
+                max_depth,
+                depths.join(", ")
+            )
+        } else {
+            format!(
+                "a tree of max depth {} takes a max buffer size of {}, not {}",
+                max_depth,
+                buffers.join(", "),
+                max_buffer_size
+            )
+        };
+        return Err(MetadataCliError::InvalidField {
+            field: "tree shape",
+            reason,
+        }
+        .into());
+    }
+    if canopy_depth >= max_depth {
+        return Err(MetadataCliError::InvalidField {
+            field: "canopy depth",
+            reason: format!(
+                "must be less than the max depth of {}, got {}",
+                max_depth, canopy_depth
+            ),
+        }
+        .into());
+    }
+    let size = tree_account_size(max_depth, max_buffer_size, canopy_depth);
+    if size > MAX_ACCOUNT_SIZE {
+        return Err(MetadataCliError::InvalidField {
+            field: "tree shape",
+            reason: format!(
+                "the tree account would be {} bytes, more than the {} bytes an account can \
+                 hold; use a smaller canopy or buffer",
+                size, MAX_ACCOUNT_SIZE
+            ),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+impl MetadataClient {
+    /// Create a Merkle tree for compressed NFTs in one transaction: allocate
+    /// the tree account, owned by the Account Compression program, and
+    /// create its Bubblegum tree config with the payer as tree creator.
+    /// Only the creator (or its delegate) can mint into the tree unless it
+    /// is `public`. The tree keypair must sign.
+    pub async fn create_tree(
+        &self,
+        payer: &dyn Signer,
+        tree: &dyn Signer,
+        max_depth: u32,
+        max_buffer_size: u32,
+        canopy_depth: u32,
+        public: bool,
+    ) -> Result<CreateTreeResult> {
+        check_tree_shape(max_depth, max_buffer_size, canopy_depth)?;
+        let payer_pubkey = payer.pubkey();
+        let tree_pubkey = tree.pubkey();
+        let (tree_config, _) = TreeConfig::find_pda(&tree_pubkey);
+
+        let space = tree_account_size(max_depth, max_buffer_size, canopy_depth);
+        let lamports = self
+            .rpc()
+            .get_minimum_balance_for_rent_exemption(space as usize)
+            .await
+            .map_err(MetadataCliError::rpc("fetch rent for the tree account"))?;
+
+        let instructions = [
+            solana_system_interface::instruction::create_account(
+                &self.rent_payer(&payer_pubkey),
+                &tree_pubkey,
+                lamports,
+                space,
+                &ACCOUNT_COMPRESSION_PROGRAM_ID,
+            ),
+            CreateTreeConfigBuilder::new()
+                .tree_config(tree_config)
+                .merkle_tree(tree_pubkey)
+                .payer(self.rent_payer(&payer_pubkey))
+                .tree_creator(payer_pubkey)
+                .log_wrapper(NOOP_PROGRAM_ID)
+                .compression_program(ACCOUNT_COMPRESSION_PROGRAM_ID)
+                .max_depth(max_depth)
+                .max_buffer_size(max_buffer_size)
+                .public(public)
+                .instruction(),
+        ];
+
+        let signature = self
+            .send(&instructions, payer, &[tree])
+            .await
+            .context("Failed to send create tree transaction")?;
+
+        Ok(CreateTreeResult {
+            tree: tree_pubkey.to_string(),
+            tree_config: tree_config.to_string(),
+            tree_creator: payer_pubkey.to_string(),
+            max_depth,
+            max_buffer_size,
+            canopy_depth,
+            public,
+            capacity: tree_capacity(max_depth),
+            account_size: space,
+            lamports,
+            signature: signature.to_string(),
+        })
+    }
+}
+
+impl MetadataClient {
+    /// Fetch and decode the Bubblegum tree config of a Merkle tree
+    pub async fn fetch_tree_config(&self, tree: &Pubkey) -> Result<TreeConfig> {
+        let (address, _) = TreeConfig::find_pda(tree);
+        let account = self
+            .rpc()
+            .get_account_with_commitment(&address, self.rpc().commitment())
+            .await
+            .map_err(MetadataCliError::rpc("fetch tree config account"))?
+            .value
+            .ok_or(MetadataCliError::AccountNotFound {
+                kind: "Tree config",
+                address,
+            })?;
+        TreeConfig::from_bytes(&account.data).map_err(|err| {
+            MetadataCliError::InvalidAccountData {
+                kind: "tree config",
+                address,
+                reason: err.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Read the depth, buffer size and canopy depth of a Merkle tree
+    pub async fn fetch_tree_shape(&self, tree: &Pubkey) -> Result<TreeShape> {
+        let account = self
+            .rpc()
+            .get_account_with_commitment(tree, self.rpc().commitment())
+            .await
+            .map_err(MetadataCliError::rpc("fetch tree account"))?
+            .value
+            .ok_or(MetadataCliError::AccountNotFound {
+                kind: "Merkle tree",
+                address: *tree,
+            })?;
+        let invalid = |reason: &str| MetadataCliError::InvalidAccountData {
+            kind: "Merkle tree",
+            address: *tree,
+            reason: reason.to_string(),
+        };
+        if account.owner != ACCOUNT_COMPRESSION_PROGRAM_ID {
+            return Err(invalid("it isn't owned by the Account Compression program").into());
+        }
+        // A concurrent Merkle tree account with a version 1 header
+        if account.data.len() < TREE_HEADER_SIZE as usize || account.data[..2] != [1, 0] {
+            return Err(invalid("it doesn't hold a concurrent Merkle tree").into());
+        }
+        let u32_at = |offset: usize| {
+            u32::from_le_bytes(account.data[offset..offset + 4].try_into().unwrap())
+        };
+        let max_buffer_size = u32_at(2);
+        let max_depth = u32_at(6);
+        let canopy_bytes = (account.data.len() as u64)
+            .checked_sub(tree_account_size(max_depth, max_buffer_size, 0))
+            .ok_or_else(|| invalid("it is smaller than its header says"))?;
+        let canopy_depth = (canopy_bytes / 32 + 2).ilog2() - 1;
+        Ok(TreeShape {
+            max_depth,
+            max_buffer_size,
+            canopy_depth,
+        })
+    }
+
+    /// The proof nodes of a leaf to pass as an instruction's remaining
+    /// accounts. The top levels are left out where the tree's canopy
+    /// already holds them, keeping the transaction small.
+    pub(crate) async fn proof_accounts(&self, leaf: &CompressedLeaf) -> Result<Vec<AccountMeta>> {
+        let shape = self.fetch_tree_shape(&leaf.tree).await?;
+        let needed = leaf.proof.len().saturating_sub(shape.canopy_depth as usize);
+        Ok(leaf.proof[..needed]
+            .iter()
+            .map(|node| AccountMeta::new_readonly(*node, false))
+            .collect())
+    }
+
+    /// Fail unless `authority` may change a leaf's metadata: the update
+    /// authority of its verified collection, or else the tree's creator or
+    /// delegate
+    async fn ensure_leaf_authority(&self, leaf: &CompressedLeaf, authority: &Pubkey) -> Result<()> {
+        match leaf
+            .collection
+            .as_ref()
+            .filter(|collection| collection.verified)
+        {
+            Some(collection) => {
+                ensure_update_authority(&self.fetch_fresh(&collection.key).await?, authority)
+            }
+            None => {
+                let config = self.fetch_tree_config(&leaf.tree).await?;
+                if config.tree_creator != *authority && config.tree_delegate != *authority {
+                    let err = MetadataCliError::NotTreeAuthority {
+                        signer: *authority,
+                        tree: leaf.tree,
+                        tree_creator: config.tree_creator,
+                        tree_delegate: config.tree_delegate,
+                    };
+                    return Err(
+                        anyhow::Error::from(err).context(format!("Can't update asset {}", leaf.id))
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Mint a compressed NFT into `tree`, owned by `owner`. The payer must be
+    /// the tree creator or its delegate unless the tree is public. With a
+    /// `collection` the NFT is minted straight into it, verified, which
+    /// takes the payer being the collection's update authority. Creators
+    /// are checked as for regular metadata, and only the payer's entry is
+    /// verified.
+    ///
+    /// The asset ID is derived from the leaf the mint appended, read back
+    /// from the transaction's log of it.
+    pub async fn mint_compressed(
+        &self,
+        payer: &dyn Signer,
+        tree: &Pubkey,
+        owner: &Pubkey,
+        collection: Option<&Pubkey>,
+        args: CreateMetadataArgs,
+    ) -> Result<CompressedMintResult> {
+        let payer_pubkey = payer.pubkey();
+        let data = data_from_args(&args, &payer_pubkey)?;
+
+        let config = self.fetch_tree_config(tree).await?;
+        if !config.is_public
+            && config.tree_creator != payer_pubkey
+            && config.tree_delegate != payer_pubkey
+        {
+            let err = MetadataCliError::NotTreeAuthority {
+                signer: payer_pubkey,
+                tree: *tree,
+                tree_creator: config.tree_creator,
+                tree_delegate: config.tree_delegate,
+            };
+            return Err(anyhow::Error::from(err)
+                .context(format!("Can't mint into tree {}, which isn't public", tree)));
+        }
+        if config.num_minted >= config.total_mint_capacity {
+            anyhow::bail!(
+                "Tree {} is full: all {} leaves are minted",
+                tree,
+                config.total_mint_capacity
+            );
+        }
+        if let Some(collection) = collection {
+            ensure_update_authority(&self.fetch_fresh(collection).await?, &payer_pubkey)?;
+        }
+
+        let metadata = MetadataArgs {
+            name: data.name.clone(),
+            symbol: data.symbol.clone(),
+            uri: data.uri.clone(),
+            seller_fee_basis_points: data.seller_fee_basis_points,
+            primary_sale_happened: false,
+            is_mutable: args.is_mutable,
+            edition_nonce: None,
+            token_standard: Some(TokenStandard::NonFungible),
+            collection: collection.map(|collection| Collection {
+                verified: false,
+                key: *collection,
+            }),
+            uses: None,
+            token_program_version: TokenProgramVersion::Original,
+            creators: data
+                .creators
+                .iter()
+                .flatten()
+                .map(|creator| Creator {
+                    address: creator.address,
+                    verified: creator.verified,
+                    share: creator.share,
+                })
+                .collect(),
+        };
+        let (tree_config, _) = TreeConfig::find_pda(tree);
+        let ix = match collection {
+            Some(collection) => MintToCollectionV1Builder::new()
+                .tree_config(tree_config)
+                .leaf_owner(*owner)
+                .leaf_delegate(*owner)
+                .merkle_tree(*tree)
+                .payer(self.rent_payer(&payer_pubkey))
+                .tree_creator_or_delegate(payer_pubkey)
+                .collection_authority(payer_pubkey)
+                .collection_authority_record_pda(None)
+                .collection_mint(*collection)
+                .collection_metadata(find_metadata_pda(collection))
+                .collection_edition(find_master_edition_pda(collection))
+                .log_wrapper(NOOP_PROGRAM_ID)
+                .compression_program(ACCOUNT_COMPRESSION_PROGRAM_ID)
+                .metadata(metadata)
+                .instruction(),
+            None => MintV1Builder::new()
+                .tree_config(tree_config)
+                .leaf_owner(*owner)
+                .leaf_delegate(*owner)
+                .merkle_tree(*tree)
+                .payer(self.rent_payer(&payer_pubkey))
+                .tree_creator_or_delegate(payer_pubkey)
+                .log_wrapper(NOOP_PROGRAM_ID)
+                .compression_program(ACCOUNT_COMPRESSION_PROGRAM_ID)
+                .metadata(metadata)
+                .instruction(),
+        };
+
+        let signature = self
+            .send(&[ix], payer, &[])
+            .await
+            .context("Failed to send compressed mint transaction")?;
+
+        // Simulated and signed-only mints never land, so the leaf they would
+        // take is the next one
+        let leaf_index = if self.is_dry_run() || self.is_sign_only() {
+            config.num_minted
+        } else {
+            match self.minted_leaf(&signature).await {
+                Ok(nonce) => nonce,
+                Err(err) => {
+                    warn!(
+                        "Couldn't read the minted leaf from transaction {} ({:#}); assuming \
+                         leaf {}, the next free one before minting",
+                        signature, err, config.num_minted
+                    );
+                    config.num_minted
+                }
+            }
+        };
+
+        Ok(CompressedMintResult {
+            asset_id: get_asset_id(tree, leaf_index).to_string(),
+            tree: tree.to_string(),
+            leaf_index,
+            owner: owner.to_string(),
+            collection: collection.map(|collection| collection.to_string()),
+            name: args.name,
+            symbol: args.symbol,
+            uri: args.uri,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Change the name, symbol or URI of a compressed NFT; `None` keeps the
+    /// current value. The asset's leaf and a proof of it are fetched from the
+    /// DAS API. The authority is the update authority of the asset's
+    /// verified collection, or else the tree's creator or delegate, and the
+    /// asset must still be mutable.
+    pub async fn update_compressed(
+        &self,
+        authority: &dyn Signer,
+        asset_id: &Pubkey,
+        name: Option<String>,
+        symbol: Option<String>,
+        uri: Option<String>,
+    ) -> Result<CompressedUpdateResult> {
+        let authority_pubkey = authority.pubkey();
+        let leaf = self.fetch_compressed_leaf(asset_id).await?;
+        if !leaf.is_mutable {
+            return Err(MetadataCliError::Immutable { address: *asset_id }.into());
+        }
+        self.ensure_leaf_authority(&leaf, &authority_pubkey).await?;
+
+        let new_name = name.clone().unwrap_or_else(|| leaf.name.clone());
+        let new_symbol = symbol.clone().unwrap_or_else(|| leaf.symbol.clone());
+        let new_uri = uri.clone().unwrap_or_else(|| leaf.uri.clone());
+        check_fields(
+            &new_name,
+            &new_symbol,
+            &new_uri,
+            leaf.seller_fee_basis_points,
+        )?;
+
+        let (tree_config, _) = TreeConfig::find_pda(&leaf.tree);
+        let collection = leaf
+            .collection
+            .as_ref()
+            .filter(|collection| collection.verified)
+            .map(|collection| collection.key);
+        let ix = UpdateMetadataBuilder::new()
+            .tree_config(tree_config)
+            .authority(authority_pubkey)
+            .collection_mint(collection)
+            .collection_metadata(collection.as_ref().map(find_metadata_pda))
+            .leaf_owner(leaf.owner)
+            .leaf_delegate(leaf.delegate)
+            .payer(self.rent_payer(&authority_pubkey))
+            .merkle_tree(leaf.tree)
+            .log_wrapper(NOOP_PROGRAM_ID)
+            .compression_program(ACCOUNT_COMPRESSION_PROGRAM_ID)
+            .root(leaf.root)
+            .nonce(leaf.leaf_id)
+            .index(leaf.leaf_id as u32)
+            .current_metadata(current_metadata(&leaf))
+            .update_args(UpdateArgs {
+                name,
+                symbol,
+                uri,
+                creators: None,
+                seller_fee_basis_points: None,
+                primary_sale_happened: None,
+                is_mutable: None,
+            })
+            .add_remaining_accounts(&self.proof_accounts(&leaf).await?)
+            .instruction();
+
+        let signature = self
+            .send(&[ix], authority, &[])
+            .await
+            .context("Failed to send compressed update transaction")?;
+
+        Ok(CompressedUpdateResult {
+            asset_id: asset_id.to_string(),
+            tree: leaf.tree.to_string(),
+            name: FieldChange {
+                old: leaf.name,
+                new: new_name,
+            },
+            symbol: FieldChange {
+                old: leaf.symbol,
+                new: new_symbol,
+            },
+            uri: FieldChange {
+                old: leaf.uri,
+                new: new_uri,
+            },
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Transfer a compressed NFT to a new owner. The asset's leaf and a proof
+    /// of it are fetched from the DAS API, and the signer must be the leaf's
+    /// owner or delegate. Once the transfer lands, the DAS API is polled
+    /// until it reports the new owner.
+    pub async fn transfer_compressed(
+        &self,
+        signer: &dyn Signer,
+        asset_id: &Pubkey,
+        to: &Pubkey,
+    ) -> Result<CompressedTransferResult> {
+        let leaf = self.fetch_compressed_leaf(asset_id).await?;
+        let signs_as_owner = signs_as_owner(&leaf, &signer.pubkey())?;
+
+        let (tree_config, _) = TreeConfig::find_pda(&leaf.tree);
+        let ix = TransferBuilder::new()
+            .tree_config(tree_config)
+            .leaf_owner(leaf.owner, signs_as_owner)
+            .leaf_delegate(leaf.delegate, !signs_as_owner)
+            .new_leaf_owner(*to)
+            .merkle_tree(leaf.tree)
+            .log_wrapper(NOOP_PROGRAM_ID)
+            .compression_program(ACCOUNT_COMPRESSION_PROGRAM_ID)
+            .root(leaf.root)
+            .data_hash(leaf.data_hash)
+            .creator_hash(leaf.creator_hash)
+            .nonce(leaf.leaf_id)
+            .index(leaf.leaf_id as u32)
+            .add_remaining_accounts(&self.proof_accounts(&leaf).await?)
+            .instruction();
+
+        let signature = self
+            .send(&[ix], signer, &[])
+            .await
+            .context("Failed to send compressed transfer transaction")?;
+        let owner_confirmed = if self.is_dry_run() || self.is_sign_only() {
+            false
+        } else {
+            self.wait_until_indexed(asset_id, "the new owner", |state| state.owner == Some(*to))
+                .await
+        };
+
+        Ok(CompressedTransferResult {
+            asset_id: asset_id.to_string(),
+            tree: leaf.tree.to_string(),
+            from: leaf.owner.to_string(),
+            to: to.to_string(),
+            owner_confirmed,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Burn a compressed NFT, removing its leaf from the tree. The asset's
+    /// leaf and a proof of it are fetched from the DAS API, and the signer
+    /// must be the leaf's owner or delegate. Once the burn lands, the DAS API
+    /// is polled until it reports the asset as burned.
+    pub async fn burn_compressed(
+        &self,
+        signer: &dyn Signer,
+        asset_id: &Pubkey,
+    ) -> Result<CompressedBurnResult> {
+        let leaf = self.fetch_compressed_leaf(asset_id).await?;
+        let signs_as_owner = signs_as_owner(&leaf, &signer.pubkey())?;
+
+        let (tree_config, _) = TreeConfig::find_pda(&leaf.tree);
+        let ix = BurnBuilder::new()
+            .tree_config(tree_config)
+            .leaf_owner(leaf.owner, signs_as_owner)
+            .leaf_delegate(leaf.delegate, !signs_as_owner)
+            .merkle_tree(leaf.tree)
+            .log_wrapper(NOOP_PROGRAM_ID)
+            .compression_program(ACCOUNT_COMPRESSION_PROGRAM_ID)
+            .root(leaf.root)
+            .data_hash(leaf.data_hash)
+            .creator_hash(leaf.creator_hash)
+            .nonce(leaf.leaf_id)
+            .index(leaf.leaf_id as u32)
+            .add_remaining_accounts(&self.proof_accounts(&leaf).await?)
+            .instruction();
+
+        let signature = self
+            .send(&[ix], signer, &[])
+            .await
+            .context("Failed to send compressed burn transaction")?;
+        let burn_confirmed = if self.is_dry_run() || self.is_sign_only() {
+            false
+        } else {
+            self.wait_until_indexed(asset_id, "the burn", |state| state.burnt)
+                .await
+        };
+
+        Ok(CompressedBurnResult {
+            asset_id: asset_id.to_string(),
+            tree: leaf.tree.to_string(),
+            owner: leaf.owner.to_string(),
+            burn_confirmed,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Turn a compressed NFT into a regular Token Metadata NFT owned by the
+    /// signer, which must be the leaf's owner. The leaf is first redeemed
+    /// into a voucher, taking it out of the tree, and the voucher is then
+    /// decompressed into a mint, metadata account and master edition at
+    /// the asset ID. These are two transactions, since a proof and the
+    /// metadata rarely fit in one; when the asset was already redeemed, only
+    /// the decompression is sent.
+    pub async fn decompress(
+        &self,
+        owner: &dyn Signer,
+        asset_id: &Pubkey,
+    ) -> Result<DecompressResult> {
+        if self.is_dry_run() {
+            anyhow::bail!(
+                "Decompressing is not supported with --dry-run, as the decompression can't be simulated before the redemption lands"
+            );
+        }
+        let owner_pubkey = owner.pubkey();
+        let leaf = self.fetch_compressed_leaf(asset_id).await?;
+        if owner_pubkey != leaf.owner {
+            anyhow::bail!(
+                "Only the owner ({}) can decompress asset {}",
+                leaf.owner,
+                asset_id
+            );
+        }
+        let config = self.fetch_tree_config(&leaf.tree).await?;
+        if config.is_decompressible != DecompressibleState::Enabled {
+            anyhow::bail!(
+                "Decompression is disabled for tree {}; its creator has to enable it",
+                leaf.tree
+            );
+        }
+
+        let (tree_config, _) = TreeConfig::find_pda(&leaf.tree);
+        let voucher = find_voucher_pda(&leaf.tree, leaf.leaf_id);
+        let redeemed = self
+            .rpc()
+            .get_account_with_commitment(&voucher, self.rpc().commitment())
+            .await
+            .map_err(MetadataCliError::rpc("fetch voucher account"))?
+            .value
+            .is_some();
+        let redeem_signature = if redeemed {
+            None
+        } else {
+            let ix = RedeemBuilder::new()
+                .tree_config(tree_config)
+                .leaf_owner(leaf.owner)
+                .leaf_delegate(leaf.delegate)
+                .merkle_tree(leaf.tree)
+                .voucher(voucher)
+                .log_wrapper(NOOP_PROGRAM_ID)
+                .compression_program(ACCOUNT_COMPRESSION_PROGRAM_ID)
+                .root(leaf.root)
+                .data_hash(leaf.data_hash)
+                .creator_hash(leaf.creator_hash)
+                .nonce(leaf.leaf_id)
+                .index(leaf.leaf_id as u32)
+                .add_remaining_accounts(&self.proof_accounts(&leaf).await?)
+                .instruction();
+            let signature = self
+                .send(&[ix], owner, &[])
+                .await
+                .context("Failed to send redeem transaction")?;
+            Some(signature)
+        };
+
+        // Bubblegum creates the mint at the asset ID, under its own authority
+        let mint = *asset_id;
+        let (mint_authority, _) =
+            Pubkey::find_program_address(&[mint.as_ref()], &mpl_bubblegum::ID);
+        let metadata_pda = find_metadata_pda(&mint);
+        let master_edition_pda = find_master_edition_pda(&mint);
+        let token_account =
+            get_associated_token_address_with_program_id(&leaf.owner, &mint, &spl_token::id());
+        let ix = DecompressV1Builder::new()
+            .voucher(voucher)
+            .leaf_owner(leaf.owner)
+            .token_account(token_account)
+            .mint(mint)
+            .mint_authority(mint_authority)
+            .metadata_account(metadata_pda)
+            .master_edition(master_edition_pda)
+            .log_wrapper(NOOP_PROGRAM_ID)
+            .metadata(current_metadata(&leaf))
+            .instruction();
+        let signature = self.send(&[ix], owner, &[]).await.with_context(|| {
+            format!(
+                "Failed to send decompress transaction; the asset is redeemed into voucher {}, so running decompress again picks up from there",
+                voucher
+            )
+        })?;
+
+        Ok(DecompressResult {
+            asset_id: asset_id.to_string(),
+            tree: leaf.tree.to_string(),
+            voucher: voucher.to_string(),
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            master_edition_pda: master_edition_pda.to_string(),
+            token_account: token_account.to_string(),
+            redeem_signature: redeem_signature.map(|signature| signature.to_string()),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Poll the DAS API until `done` holds for the asset, which can lag
+    /// behind the transaction while the provider indexes it. `change` names
+    /// what is being waited for in the warning when it never does.
+    async fn wait_until_indexed(
+        &self,
+        asset_id: &Pubkey,
+        change: &str,
+        done: impl Fn(&AssetState) -> bool,
+    ) -> bool {
+        for attempt in 0..INDEX_POLL_ATTEMPTS {
+            if attempt > 0 {
+                sleep(INDEX_POLL_INTERVAL).await;
+            }
+            match self.fetch_asset_state(asset_id).await {
+                Ok(state) if done(&state) => return true,
+                Ok(_) => {}
+                Err(err) => warn!("Couldn't check asset {}: {:#}", asset_id, err),
+            }
+        }
+        warn!(
+            "The DAS API doesn't report {} of asset {} yet",
+            change, asset_id
+        );
+        false
+    }
+
+    /// The nonce of the leaf a landed Bubblegum transaction logged through
+    /// the noop program
+    async fn minted_leaf(&self, signature: &Signature) -> Result<u64> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            // Transactions can't be fetched at processed commitment
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let fetched = self
+            .rpc()
+            .get_transaction_with_config(signature, config)
+            .await
+            .map_err(MetadataCliError::rpc("fetch the mint transaction"))?;
+        let tx = fetched
+            .transaction
+            .transaction
+            .decode()
+            .context("The transaction couldn't be decoded")?;
+        let keys = tx.message.static_account_keys();
+        let inner: Option<Vec<_>> = fetched
+            .transaction
+            .meta
+            .context("The transaction has no status")?
+            .inner_instructions
+            .into();
+        inner
+            .into_iter()
+            .flatten()
+            .flat_map(|inner| inner.instructions)
+            .filter_map(|ix| match ix {
+                UiInstruction::Compiled(ix) => Some(ix),
+                UiInstruction::Parsed(_) => None,
+            })
+            .filter(|ix| keys.get(ix.program_id_index as usize) == Some(&NOOP_PROGRAM_ID))
+            .find_map(|ix| {
+                let data = bs58::decode(&ix.data).into_vec().ok()?;
+                // A leaf schema event, rather than the tree's change log
+                if data.first() != Some(&1) {
+                    return None;
+                }
+                let nonce = data.get(LEAF_EVENT_NONCE_OFFSET..LEAF_EVENT_NONCE_OFFSET + 8)?;
+                Some(u64::from_le_bytes(nonce.try_into().ok()?))
+            })
+            .context("The transaction logged no leaf")
+    }
+}
+
+/// The voucher a leaf is redeemed into. Bubblegum seeds it with the nonce's
+/// little-endian bytes, which `Voucher::find_pda` doesn't.
+fn find_voucher_pda(tree: &Pubkey, nonce: u64) -> Pubkey {
+    let (address, _) = Pubkey::find_program_address(
+        &[b"voucher", tree.as_ref(), &nonce.to_le_bytes()],
+        &mpl_bubblegum::ID,
+    );
+    address
+}
+
+/// Whether `signer` acts on the leaf as its owner rather than its delegate;
+/// fails when it is neither
+fn signs_as_owner(leaf: &CompressedLeaf, signer: &Pubkey) -> Result<bool> {
+    if *signer != leaf.owner && *signer != leaf.delegate {
+        anyhow::bail!(
+            "{} is neither the owner ({}) nor the delegate of asset {}",
+            signer,
+            leaf.owner,
+            leaf.id
+        );
+    }
+    Ok(*signer == leaf.owner)
+}
+
+/// The metadata a leaf was minted with, as Bubblegum hashes it to check the
+/// leaf
+fn current_metadata(leaf: &CompressedLeaf) -> MetadataArgs {
+    MetadataArgs {
+        name: leaf.name.clone(),
+        symbol: leaf.symbol.clone(),
+        uri: leaf.uri.clone(),
+        seller_fee_basis_points: leaf.seller_fee_basis_points,
+        primary_sale_happened: leaf.primary_sale_happened,
+        is_mutable: leaf.is_mutable,
+        edition_nonce: leaf.edition_nonce,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection: leaf.collection.as_ref().map(|collection| Collection {
+            verified: collection.verified,
+            key: collection.key,
+        }),
+        uses: None,
+        token_program_version: TokenProgramVersion::Original,
+        creators: leaf
+            .creators
+            .iter()
+            .map(|creator| Creator {
+                address: creator.address,
+                verified: creator.verified,
+                share: creator.share,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_account_size_matches_account_compression() {
+        // Sizes the Account Compression SDK computes for the same shapes
+        assert_eq!(tree_account_size(3, 8, 0), 1304);
+        assert_eq!(tree_account_size(14, 64, 0), 31800);
+        assert_eq!(tree_account_size(14, 64, 10), 31800 + 2046 * 32);
+    }
+
+    #[test]
+    fn tree_capacity_is_two_to_the_depth() {
+        assert_eq!(tree_capacity(3), 8);
+        assert_eq!(tree_capacity(14), 16_384);
+        assert_eq!(tree_capacity(30), 1 << 30);
+    }
+
+    #[test]
+    fn supported_shapes_pass() {
+        for &(max_depth, max_buffer_size) in DEPTH_BUFFER_PAIRS {
+            check_tree_shape(max_depth, max_buffer_size, 0).unwrap();
+        }
+        check_tree_shape(14, 64, 13).unwrap();
+    }
+
+    #[test]
+    fn unsupported_shapes_fail() {
+        let reason = |result: Result<()>| match result.unwrap_err().downcast() {
+            Ok(MetadataCliError::InvalidField { field, reason }) => (field, reason),
+            other => panic!("expected InvalidField, got {:?}", other),
+        };
+
+        let (field, message) = reason(check_tree_shape(21, 64, 0));
+        assert_eq!(field, "tree shape");
+        assert!(
+            message.contains("max depth 21 isn't supported"),
+            "{}",
+            message
+        );
+
+        let (field, message) = reason(check_tree_shape(14, 128, 0));
+        assert_eq!(field, "tree shape");
+        assert!(message.contains("64, 256, 1024, 2048"), "{}", message);
+
+        let (field, _) = reason(check_tree_shape(14, 64, 14));
+        assert_eq!(field, "canopy depth");
+
+        // A full canopy on a depth 30 tree is far bigger than an account
+        let (field, message) = reason(check_tree_shape(30, 2048, 29));
+        assert_eq!(field, "tree shape");
+        assert!(message.contains("bytes"), "{}", message);
+    }
+}
@@ -0,0 +1,132 @@
+//! Burning assets and reclaiming their rent.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{instructions::BurnV1Builder, types::TokenStandard};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account_client::address::get_associated_token_address_with_program_id;
+
+use crate::client::MetadataClient;
+use crate::pda::{find_master_edition_pda, find_metadata_pda, find_token_record_pda};
+use crate::types::BurnResult;
+
+impl MetadataClient {
+    /// Burn the signer's tokens of a mint with the unified Burn instruction.
+    ///
+    /// For non-fungible and programmable non-fungible assets this burns the
+    /// single token and closes the metadata, master edition, token account
+    /// and (for pNFTs) token record, returning their rent to the owner. For
+    /// fungible assets it burns `amount` base units, or the whole balance
+    /// when `amount` is `None`; metadata accounts of fungibles are left open.
+    pub async fn burn(
+        &self,
+        owner: &dyn Signer,
+        mint: &Pubkey,
+        amount: Option<u64>,
+    ) -> Result<BurnResult> {
+        let owner_pubkey = owner.pubkey();
+        let metadata_pda = find_metadata_pda(mint);
+        let edition_pda = find_master_edition_pda(mint);
+        let token_program = self.fetch_mint(mint).await?.program.id();
+        let token_account =
+            get_associated_token_address_with_program_id(&owner_pubkey, mint, &token_program);
+
+        let metadata = self.fetch_fresh(mint).await?;
+        let token_standard = self.token_standard(mint, &metadata).await;
+
+        let mut builder = BurnV1Builder::new();
+        builder
+            .authority(owner_pubkey)
+            .metadata(metadata_pda)
+            .mint(*mint)
+            .token(token_account)
+            .spl_token_program(token_program);
+
+        let mut tracked = vec![token_account];
+        let amount = match token_standard {
+            TokenStandard::NonFungible | TokenStandard::ProgrammableNonFungible => {
+                builder.edition(Some(edition_pda));
+                tracked.extend([metadata_pda, edition_pda]);
+
+                if token_standard == TokenStandard::ProgrammableNonFungible {
+                    let token_record = find_token_record_pda(mint, &token_account);
+                    builder.token_record(Some(token_record));
+                    tracked.push(token_record);
+                }
+
+                if let Some(collection) = metadata.collection.as_ref().filter(|c| c.verified) {
+                    builder.collection_metadata(Some(find_metadata_pda(&collection.key)));
+                }
+
+                1
+            }
+            TokenStandard::Fungible | TokenStandard::FungibleAsset => match amount {
+                Some(amount) => amount,
+                None => self
+                    .rpc()
+                    .get_token_account_balance(&token_account)
+                    .await
+                    .context("Failed to fetch token account balance")?
+                    .amount
+                    .parse()
+                    .context("Invalid token account balance")?,
+            },
+            TokenStandard::NonFungibleEdition | TokenStandard::ProgrammableNonFungibleEdition => {
+                anyhow::bail!("Burning print editions is not supported yet");
+            }
+        };
+        builder.amount(amount);
+
+        let lamports_before = self.account_lamports(&tracked).await?;
+
+        let signature = self
+            .send(&[builder.instruction()], owner, &[])
+            .await
+            .context("Failed to send burn transaction")?;
+
+        let closed = self.closed_accounts(&tracked, lamports_before).await?;
+
+        Ok(BurnResult {
+            mint: mint.to_string(),
+            token_standard: format!("{:?}", token_standard),
+            amount,
+            closed_accounts: closed.as_ref().map(|(accounts, _)| accounts.clone()),
+            lamports_recovered: closed.map(|(_, lamports)| lamports),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// The accounts among `tracked` that a sent transaction closed, and the
+    /// rent they held before it. `None` for dry runs and offline signing,
+    /// where nothing was closed.
+    pub(crate) async fn closed_accounts(
+        &self,
+        tracked: &[Pubkey],
+        lamports_before: Vec<u64>,
+    ) -> Result<Option<(Vec<String>, u64)>> {
+        if self.is_dry_run() || self.is_sign_only() {
+            return Ok(None);
+        }
+        let lamports_after = self.account_lamports(tracked).await?;
+
+        let mut closed_accounts = Vec::new();
+        let mut lamports_recovered = 0;
+        for ((address, before), after) in tracked.iter().zip(lamports_before).zip(lamports_after) {
+            if before > 0 && after == 0 {
+                closed_accounts.push(address.to_string());
+                lamports_recovered += before;
+            }
+        }
+        Ok(Some((closed_accounts, lamports_recovered)))
+    }
+
+    /// Current lamport balance of each account, 0 for accounts that don't exist
+    pub(crate) async fn account_lamports(&self, addresses: &[Pubkey]) -> Result<Vec<u64>> {
+        Ok(self
+            .rpc()
+            .get_multiple_accounts(addresses)
+            .await?
+            .into_iter()
+            .map(|account| account.map_or(0, |a| a.lamports))
+            .collect())
+    }
+}
@@ -0,0 +1,190 @@
+//! A local SQLite cache of fetched metadata accounts and off-chain JSON,
+//! stored in `~/.cache/token-metadata-cli/cache.sqlite`, so repeated reads
+//! don't go back to the RPC node or the gateways.
+//!
+//! Cached entries are used until they are older than the cache's maximum
+//! age, or `--refresh` replaces them. Code about to change metadata always
+//! fetches it fresh, and sending a transaction drops the entries of every
+//! account it touches.
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::client::MetadataClient;
+use crate::keypair::expand_tilde;
+use crate::offchain::Gateways;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS metadata (
+    mint TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    data BLOB NOT NULL,
+    fetched_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS metadata_address ON metadata (address);
+CREATE TABLE IF NOT EXISTS offchain (
+    uri TEXT PRIMARY KEY,
+    json TEXT NOT NULL,
+    fetched_at INTEGER NOT NULL
+);
+";
+
+/// How long cached entries are used before they are fetched again
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(300);
+
+/// Seconds since the Unix epoch
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// Location of the cache database
+pub fn default_cache_path() -> PathBuf {
+    PathBuf::from(expand_tilde("~/.cache/token-metadata-cli/cache.sqlite"))
+}
+
+/// The cache database. Lookups that fail are treated as misses and writes
+/// that fail are skipped, so a broken cache only costs extra requests.
+pub struct Cache {
+    conn: Mutex<Connection>,
+    refresh: bool,
+    max_age: Duration,
+}
+
+impl Cache {
+    /// Open the cache database, creating it and its directory if needed
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create cache directory '{}'", dir.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open cache '{}'", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("Failed to set up cache '{}'", path.display()))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            refresh: false,
+            max_age: DEFAULT_MAX_AGE,
+        })
+    }
+
+    /// Ignore cached entries, fetching everything again and storing the
+    /// fresh copies
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Fetch entries again once they are older than `max_age`
+    /// ([`DEFAULT_MAX_AGE`] unless set)
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// The oldest fetch time of an entry that is still used
+    fn cutoff(&self) -> i64 {
+        now() - self.max_age.as_secs() as i64
+    }
+
+    /// The cached metadata account data of a mint, unless it has expired
+    pub fn metadata(&self, mint: &Pubkey) -> Option<Vec<u8>> {
+        if self.refresh {
+            return None;
+        }
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT data FROM metadata WHERE mint = ?1 AND fetched_at >= ?2",
+                params![mint.to_string(), self.cutoff()],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()
+            .flatten()
+    }
+
+    /// Store a mint's metadata account data, as fetched at `slot`
+    pub fn store_metadata(&self, mint: &Pubkey, address: &Pubkey, slot: u64, data: &[u8]) {
+        let _ = self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO metadata (mint, address, slot, data, fetched_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                mint.to_string(),
+                address.to_string(),
+                slot as i64,
+                data,
+                now()
+            ],
+        );
+    }
+
+    /// Drop the cached metadata stored at any of `addresses`
+    pub fn invalidate(&self, addresses: &[Pubkey]) {
+        let conn = self.conn.lock().unwrap();
+        for address in addresses {
+            let _ = conn.execute(
+                "DELETE FROM metadata WHERE address = ?1",
+                params![address.to_string()],
+            );
+        }
+    }
+
+    /// The cached JSON behind a metadata URI, unless it has expired
+    pub fn offchain(&self, uri: &str) -> Option<Value> {
+        if self.refresh {
+            return None;
+        }
+        let json: String = self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT json FROM offchain WHERE uri = ?1 AND fetched_at >= ?2",
+                params![uri, self.cutoff()],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()
+            .flatten()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Store the JSON behind a metadata URI
+    pub fn store_offchain(&self, uri: &str, json: &Value) {
+        let _ = self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO offchain (uri, json, fetched_at) VALUES (?1, ?2, ?3)",
+            params![uri, json.to_string(), now()],
+        );
+    }
+}
+
+impl MetadataClient {
+    /// Fetch and parse the JSON behind a metadata URI, from the cache when
+    /// it has a copy
+    pub async fn fetch_offchain_json(
+        &self,
+        gateways: &Gateways,
+        uri: &str,
+        timeout: Duration,
+    ) -> Result<Value> {
+        if let Some(json) = self.cache().and_then(|cache| cache.offchain(uri)) {
+            return Ok(json);
+        }
+        let json = gateways.fetch_json(uri, timeout).await?;
+        if let Some(cache) = self.cache() {
+            cache.store_offchain(uri, &json);
+        }
+        Ok(json)
+    }
+}
@@ -0,0 +1,725 @@
+//! High-level client for the Token Metadata program.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{
+    MAX_CREATOR_LIMIT,
+    accounts::Metadata,
+    instructions::{CreateMetadataAccountV3Builder, UpdateMetadataAccountV2Builder},
+    types::{Creator, DataV2, TokenStandard, Uses},
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+    transaction::Transaction,
+};
+use solana_system_interface::instruction::advance_nonce_account;
+use std::sync::Mutex;
+
+use crate::batch::Progress;
+use crate::cache::Cache;
+use crate::cluster::Cluster;
+use crate::error::MetadataCliError;
+use crate::history::History;
+use crate::memo::{memo_instruction, uri_hash_instruction};
+use crate::nonce::DurableNonce;
+use crate::pda::{find_master_edition_pda, find_metadata_pda};
+use crate::types::{
+    CreateMetadataArgs, CreateResult, CreatorInfo, CreatorShare, FieldChange, PriorityFee,
+    SimulationReport, UpdateMetadataArgs, UpdateResult, UsesArgs, UsesInfo,
+};
+use crate::validate::check_fields;
+
+/// Client for creating, updating and fetching token metadata accounts
+pub struct MetadataClient {
+    rpc: RpcClient,
+    das: Option<RpcClient>,
+    cache: Option<Cache>,
+    history: Option<History>,
+    progress: Option<Box<dyn Progress>>,
+    cluster: Cluster,
+    dry_run: bool,
+    priority_fee: Option<PriorityFee>,
+    compute_units: Option<u32>,
+    sign_only: Option<Hash>,
+    nonce: Option<DurableNonce>,
+    fee_payer: Option<Box<dyn Signer + Send + Sync>>,
+    payer: Option<Box<dyn Signer + Send + Sync>>,
+    auto_airdrop: bool,
+    max_attempts: u32,
+    memo: Option<String>,
+    simulations: Mutex<Vec<SimulationReport>>,
+    signed: Mutex<Vec<Transaction>>,
+}
+
+impl MetadataClient {
+    pub fn new(rpc: RpcClient) -> Self {
+        let cluster = Cluster::from_url(&rpc.url());
+        Self {
+            rpc,
+            das: None,
+            cache: None,
+            history: None,
+            progress: None,
+            cluster,
+            dry_run: false,
+            priority_fee: None,
+            compute_units: None,
+            sign_only: None,
+            nonce: None,
+            fee_payer: None,
+            payer: None,
+            auto_airdrop: false,
+            max_attempts: 3,
+            memo: None,
+            simulations: Mutex::new(Vec::new()),
+            signed: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Override the cluster guessed from the RPC URL
+    pub fn with_cluster(mut self, cluster: Cluster) -> Self {
+        self.cluster = cluster;
+        self
+    }
+
+    /// The cluster the RPC endpoint belongs to
+    pub fn cluster(&self) -> Cluster {
+        self.cluster
+    }
+
+    /// Read assets from a DAS API endpoint instead of decoding accounts or
+    /// scanning the program's accounts
+    pub fn with_das(mut self, das: Option<RpcClient>) -> Self {
+        self.das = das;
+        self
+    }
+
+    /// The DAS API endpoint, if one was given
+    pub fn das(&self) -> Option<&RpcClient> {
+        self.das.as_ref()
+    }
+
+    /// Keep fetched metadata accounts and off-chain JSON in a local cache
+    pub fn with_cache(mut self, cache: Option<Cache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// The local cache, if one is used
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+
+    /// Record metadata as it was before each transaction that changes it,
+    /// so the change can be rolled back
+    pub fn with_history(mut self, history: Option<History>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// The local history, if one is kept
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    /// Report how batch operations and exports are getting on
+    pub fn with_progress(mut self, progress: Option<Box<dyn Progress>>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// The progress reporter, if one is set
+    pub fn progress(&self) -> Option<&dyn Progress> {
+        self.progress.as_deref()
+    }
+
+    /// Simulate transactions instead of sending them. The would-be
+    /// signature is returned and the simulations can be collected with
+    /// [`take_simulations`](Self::take_simulations).
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Pay a priority fee on every transaction
+    pub fn with_priority_fee(mut self, priority_fee: Option<PriorityFee>) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
+    /// The priority fee paid on every transaction, if any
+    pub fn priority_fee(&self) -> Option<PriorityFee> {
+        self.priority_fee
+    }
+
+    /// Request a compute unit limit on every transaction instead of the
+    /// default budget
+    pub fn with_compute_units(mut self, compute_units: Option<u32>) -> Self {
+        self.compute_units = compute_units;
+        self
+    }
+
+    /// The compute unit limit requested on every transaction, if any
+    pub fn compute_units(&self) -> Option<u32> {
+        self.compute_units
+    }
+
+    /// Sign transactions with the given blockhash instead of sending them,
+    /// for offline signing. The signed transactions can be collected with
+    /// [`take_signed_transactions`](Self::take_signed_transactions).
+    pub fn with_sign_only(mut self, blockhash: Option<Hash>) -> Self {
+        self.sign_only = blockhash;
+        self
+    }
+
+    /// Whether transactions are signed without being sent
+    pub fn is_sign_only(&self) -> bool {
+        self.sign_only.is_some()
+    }
+
+    /// The transactions signed in sign-only mode since the last call
+    pub fn take_signed_transactions(&self) -> Vec<Transaction> {
+        std::mem::take(&mut *self.signed.lock().unwrap())
+    }
+
+    /// Build every transaction on a durable nonce instead of a recent
+    /// blockhash, advancing the nonce first
+    pub fn with_nonce(mut self, nonce: Option<DurableNonce>) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Whether transactions are built on a durable nonce
+    pub fn uses_nonce(&self) -> bool {
+        self.nonce.is_some()
+    }
+
+    /// Pay the fee of every transaction from this signer, so the signer
+    /// passed to each operation only has to authorize it (and fund any
+    /// accounts it creates)
+    pub fn with_fee_payer(mut self, fee_payer: Option<Box<dyn Signer + Send + Sync>>) -> Self {
+        self.fee_payer = fee_payer;
+        self
+    }
+
+    /// The signer paying transaction fees, when it isn't each operation's
+    /// own signer
+    pub fn fee_payer(&self) -> Option<Pubkey> {
+        self.fee_payer.as_ref().map(|fee_payer| fee_payer.pubkey())
+    }
+
+    /// Pay the fees of every transaction and the rent of every account a
+    /// transaction creates from this signer, so the signer passed to each
+    /// operation only acts as its authority. A fee payer still takes over
+    /// the fees.
+    pub fn with_payer(mut self, payer: Option<Box<dyn Signer + Send + Sync>>) -> Self {
+        self.payer = payer;
+        self
+    }
+
+    /// The signer paying for each operation, when it isn't the operation's
+    /// own signer
+    pub fn payer(&self) -> Option<Pubkey> {
+        self.payer.as_ref().map(|payer| payer.pubkey())
+    }
+
+    /// The account funding new accounts in an operation signed by `signer`
+    pub(crate) fn rent_payer(&self, signer: &Pubkey) -> Pubkey {
+        self.payer().unwrap_or(*signer)
+    }
+
+    /// The signer paying for an operation signed by `signer`, for payments
+    /// that aren't rent, like topping up an upload balance
+    pub(crate) fn paying_signer<'a>(&'a self, signer: &'a dyn Signer) -> &'a dyn Signer {
+        match &self.payer {
+            Some(payer) => payer.as_ref(),
+            None => signer,
+        }
+    }
+
+    /// Request an airdrop for the payer when it can't afford a
+    /// transaction, on devnet, testnet and localnet
+    pub fn with_auto_airdrop(mut self, auto_airdrop: bool) -> Self {
+        self.auto_airdrop = auto_airdrop;
+        self
+    }
+
+    /// Whether the payer is topped up from the faucet when it is short
+    pub fn auto_airdrop(&self) -> bool {
+        self.auto_airdrop
+    }
+
+    /// Try sending each transaction up to `max_attempts` times when the
+    /// node is unavailable, times out or drops the blockhash (3 by default)
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// How many times each transaction is tried
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Append an SPL Memo instruction with this text to every transaction,
+    /// e.g. to tag it with a ticket ID
+    pub fn with_memo(mut self, memo: Option<String>) -> Self {
+        self.memo = memo;
+        self
+    }
+
+    /// The memo added to every transaction, if any
+    pub fn memo(&self) -> Option<&str> {
+        self.memo.as_deref()
+    }
+
+    /// Whether transactions are simulated instead of sent
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// The simulations run since the last call, in the order they ran
+    pub fn take_simulations(&self) -> Vec<SimulationReport> {
+        std::mem::take(&mut *self.simulations.lock().unwrap())
+    }
+
+    /// The underlying RPC client
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// Fetch and deserialize the metadata account for a mint, from the
+    /// cache when it holds a copy that hasn't expired
+    pub async fn fetch(&self, mint: &Pubkey) -> Result<Metadata> {
+        if let Some(data) = self.cache().and_then(|cache| cache.metadata(mint))
+            && let Ok(metadata) = Metadata::from_bytes(&data)
+        {
+            return Ok(metadata);
+        }
+        self.fetch_fresh(mint).await
+    }
+
+    /// Fetch and deserialize the metadata account for a mint from the RPC
+    /// node, for code about to change it. The cache is updated but not read.
+    pub async fn fetch_fresh(&self, mint: &Pubkey) -> Result<Metadata> {
+        let metadata_pda = find_metadata_pda(mint);
+
+        let response = self
+            .rpc
+            .get_account_with_commitment(&metadata_pda, self.rpc.commitment())
+            .await
+            .map_err(MetadataCliError::rpc("fetch metadata account"))?;
+        let metadata_account = response.value.ok_or(MetadataCliError::AccountNotFound {
+            kind: "Metadata",
+            address: metadata_pda,
+        })?;
+
+        let metadata = Metadata::from_bytes(&metadata_account.data).map_err(|e| {
+            MetadataCliError::InvalidAccountData {
+                kind: "metadata",
+                address: metadata_pda,
+                reason: e.to_string(),
+            }
+        })?;
+        if let Some(cache) = self.cache() {
+            cache.store_metadata(
+                mint,
+                &metadata_pda,
+                response.context.slot,
+                &metadata_account.data,
+            );
+        }
+        Ok(metadata)
+    }
+
+    /// The token standard of a mint's metadata. Legacy assets don't record
+    /// one, so for those an edition account means an NFT.
+    pub(crate) async fn token_standard(&self, mint: &Pubkey, metadata: &Metadata) -> TokenStandard {
+        match metadata.token_standard {
+            Some(standard) => standard,
+            None if self
+                .rpc
+                .get_account(&find_master_edition_pda(mint))
+                .await
+                .is_ok() =>
+            {
+                TokenStandard::NonFungible
+            }
+            None => TokenStandard::Fungible,
+        }
+    }
+
+    /// Create metadata for an existing mint. The payer must be the mint
+    /// authority, which is checked before anything is sent unless signing
+    /// offline, and becomes the update authority unless
+    /// `args.update_authority` names another.
+    pub async fn create(
+        &self,
+        payer: &dyn Signer,
+        mint: &Pubkey,
+        args: CreateMetadataArgs,
+    ) -> Result<CreateResult> {
+        let metadata_pda = find_metadata_pda(mint);
+
+        let data = data_from_args(&args, &payer.pubkey())?;
+        // Offline the program is left to check the mint authority
+        if !self.is_sign_only() {
+            self.fetch_mint(mint)
+                .await?
+                .ensure_mint_authority(&payer.pubkey())?;
+        }
+        let creators = data.creators.clone();
+        let update_authority = args.update_authority.unwrap_or(payer.pubkey());
+
+        let ix = CreateMetadataAccountV3Builder::new()
+            .metadata(metadata_pda)
+            .mint(*mint)
+            .mint_authority(payer.pubkey())
+            .payer(self.rent_payer(&payer.pubkey()))
+            .update_authority(update_authority, update_authority == payer.pubkey())
+            .data(data)
+            .is_mutable(args.is_mutable)
+            .instruction();
+        let mut instructions = vec![ix];
+        if let Some(hash) = &args.uri_hash {
+            instructions.push(uri_hash_instruction(hash, &args.uri)?);
+        }
+
+        let signature = self
+            .send(&instructions, payer, &[])
+            .await
+            .context("Failed to send create metadata transaction")?;
+
+        Ok(CreateResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            name: args.name,
+            symbol: args.symbol,
+            uri: args.uri,
+            seller_fee_basis_points: args.seller_fee_basis_points,
+            is_mutable: args.is_mutable,
+            creators: creators.map(|creators| creators.iter().map(CreatorInfo::from).collect()),
+            token_standard: None,
+            uri_sha256: args.uri_hash.map(|hash| hash.sha256),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Update name, symbol and/or URI of an existing metadata account,
+    /// keeping every other field as it is on-chain. Fails before anything is
+    /// sent unless `authority` is the update authority and the metadata is
+    /// mutable.
+    pub async fn update(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        args: UpdateMetadataArgs,
+    ) -> Result<UpdateResult> {
+        let metadata_pda = find_metadata_pda(mint);
+
+        // Fetch existing metadata account to get current values
+        let existing = self.fetch_fresh(mint).await?;
+        ensure_update_authority(&existing, &authority.pubkey())?;
+        if !existing.is_mutable {
+            return Err(MetadataCliError::Immutable { address: *mint }.into());
+        }
+
+        let updated_name = args.name.unwrap_or(existing.name.clone());
+        let updated_symbol = args.symbol.unwrap_or(existing.symbol.clone());
+        let updated_uri = args.uri.unwrap_or(existing.uri.clone());
+        let new_creators = args
+            .creators
+            .as_deref()
+            .map(|shares| {
+                build_creators(
+                    shares,
+                    Some(&authority.pubkey()),
+                    existing.creators.as_deref(),
+                )
+            })
+            .transpose()?;
+
+        let name_change = FieldChange {
+            old: existing.name.trim_end_matches('\0').to_string(),
+            new: updated_name.trim_end_matches('\0').to_string(),
+        };
+        let symbol_change = FieldChange {
+            old: existing.symbol.trim_end_matches('\0').to_string(),
+            new: updated_symbol.trim_end_matches('\0').to_string(),
+        };
+        let uri_change = FieldChange {
+            old: existing.uri.trim_end_matches('\0').to_string(),
+            new: updated_uri.trim_end_matches('\0').to_string(),
+        };
+
+        let new_uses = args
+            .uses
+            .map(|uses| updated_uses(existing.uses.as_ref(), uses))
+            .transpose()?;
+
+        check_fields(
+            &updated_name,
+            &updated_symbol,
+            &updated_uri,
+            existing.seller_fee_basis_points,
+        )?;
+        let current = data_from_metadata(&existing);
+        let new_data = DataV2 {
+            name: updated_name,
+            symbol: updated_symbol,
+            uri: updated_uri,
+            creators: new_creators.clone().or(current.creators.clone()),
+            uses: new_uses.clone().or(current.uses.clone()),
+            ..current
+        };
+
+        let mut builder = UpdateMetadataAccountV2Builder::new();
+        builder
+            .metadata(metadata_pda)
+            .update_authority(authority.pubkey())
+            .data(new_data);
+        if args.primary_sale_happened && !existing.primary_sale_happened {
+            builder.primary_sale_happened(true);
+        }
+        let mut instructions = vec![builder.instruction()];
+        if let Some(hash) = &args.uri_hash {
+            instructions.push(uri_hash_instruction(hash, &uri_change.new)?);
+        }
+
+        let signature = self
+            .send(&instructions, authority, &[])
+            .await
+            .context("Failed to send update metadata transaction")?;
+
+        Ok(UpdateResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            name: name_change,
+            symbol: symbol_change,
+            uri: uri_change,
+            creators: new_creators.map(|creators| creators.iter().map(CreatorInfo::from).collect()),
+            uses: new_uses.as_ref().map(UsesInfo::from),
+            primary_sale_happened: args.primary_sale_happened.then_some(true),
+            uri_sha256: args.uri_hash.map(|hash| hash.sha256),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Sign with the payer (plus any additional signers) and send a
+    /// transaction, waiting for confirmation. A nonce advance and compute
+    /// budget instructions are prepended, and the memo is appended. In a dry
+    /// run the transaction is only simulated, and in sign-only mode it is
+    /// signed with the supplied blockhash and kept.
+    pub(crate) async fn send(
+        &self,
+        instructions: &[Instruction],
+        payer: &dyn Signer,
+        signers: &[&dyn Signer],
+    ) -> Result<Signature> {
+        // The first signer pays the fee
+        let mut all_signers: Vec<&dyn Signer> = Vec::new();
+        if let Some(fee_payer) = &self.fee_payer {
+            all_signers.push(fee_payer.as_ref());
+        }
+        if let Some(rent_payer) = &self.payer {
+            all_signers.push(rent_payer.as_ref());
+        }
+        all_signers.push(payer);
+        all_signers.extend_from_slice(signers);
+
+        let mut all_instructions = Vec::new();
+        if let Some(nonce) = &self.nonce {
+            all_instructions.push(advance_nonce_account(
+                &nonce.account,
+                &nonce.authority.pubkey(),
+            ));
+            all_signers.push(nonce.authority.as_ref());
+        }
+        let mut instructions = instructions.to_vec();
+        if let Some(memo) = &self.memo {
+            instructions.push(memo_instruction(memo));
+        }
+        all_instructions.extend(self.compute_budget_instructions(&instructions).await?);
+        all_instructions.extend(instructions);
+
+        let tx = self.sign(&all_instructions, &all_signers).await?;
+
+        if self.dry_run {
+            let report = self.simulate(&tx).await?;
+            self.simulations.lock().unwrap().push(report);
+            return Ok(tx.signatures[0]);
+        }
+        if self.sign_only.is_some() {
+            let mut signed = self.signed.lock().unwrap();
+            // The first transaction advances the nonce, which invalidates
+            // every later one signed on the same nonce
+            if self.nonce.is_some() && !signed.is_empty() {
+                anyhow::bail!(
+                    "This command sends more than one transaction, and only one can be signed on \
+                     a durable nonce; sign without --nonce-account and broadcast promptly"
+                );
+            }
+            let signature = tx.signatures[0];
+            signed.push(tx);
+            return Ok(signature);
+        }
+
+        self.send_with_retries(tx, &all_instructions, &all_signers)
+            .await
+    }
+
+    /// Sign a transaction paid for by the first signer, on the sign-only
+    /// blockhash, the durable nonce or the latest blockhash
+    pub(crate) async fn sign(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+    ) -> Result<Transaction> {
+        let recent_blockhash = match (self.sign_only, &self.nonce) {
+            (Some(blockhash), _) => blockhash,
+            (None, Some(nonce)) => self.nonce_blockhash(nonce).await?,
+            (None, None) => self.rpc.get_latest_blockhash().await?,
+        };
+        Ok(Transaction::new_signed_with_payer(
+            instructions,
+            Some(&signers[0].pubkey()),
+            signers,
+            recent_blockhash,
+        ))
+    }
+}
+
+/// The writable data fields of an existing metadata account, as used by
+/// update instructions
+pub(crate) fn data_from_metadata(metadata: &Metadata) -> DataV2 {
+    DataV2 {
+        name: metadata.name.clone(),
+        symbol: metadata.symbol.clone(),
+        uri: metadata.uri.clone(),
+        seller_fee_basis_points: metadata.seller_fee_basis_points,
+        creators: metadata.creators.clone(),
+        collection: metadata.collection.clone(),
+        uses: metadata.uses.clone(),
+    }
+}
+
+/// The data fields of a new metadata account
+pub(crate) fn data_from_args(args: &CreateMetadataArgs, signer: &Pubkey) -> Result<DataV2> {
+    check_fields(
+        &args.name,
+        &args.symbol,
+        &args.uri,
+        args.seller_fee_basis_points,
+    )?;
+    // Only the update authority can verify itself while the metadata is created
+    let verified = args
+        .update_authority
+        .is_none_or(|update_authority| update_authority == *signer)
+        .then_some(signer);
+    let creators = args
+        .creators
+        .as_deref()
+        .map(|shares| build_creators(shares, verified, None))
+        .transpose()?;
+
+    Ok(DataV2 {
+        name: args.name.clone(),
+        symbol: args.symbol.clone(),
+        uri: args.uri.clone(),
+        seller_fee_basis_points: args.seller_fee_basis_points,
+        creators,
+        collection: None,
+        uses: args.uses.map(|uses| uses.uses(uses.total)),
+    })
+}
+
+/// The uses to write when changing a token's uses. Uses already consumed
+/// still count against the new total, and the use method can't change once
+/// the token has been used.
+fn updated_uses(current: Option<&Uses>, new: UsesArgs) -> Result<Uses> {
+    let used = current.map_or(0, |current| current.total - current.remaining);
+    if let Some(current) = current
+        && used > 0
+        && current.use_method != new.uses(0).use_method
+    {
+        anyhow::bail!(
+            "The token has been used {} times; its use method can't change",
+            used
+        );
+    }
+    if used > new.total {
+        anyhow::bail!(
+            "The token has already been used {} times, more than the new total of {}",
+            used,
+            new.total
+        );
+    }
+    Ok(new.uses(new.total - used))
+}
+
+/// Build and validate a creators list. Shares must sum to 100. The entry of
+/// `signer`, the signing update authority, is marked verified, and creators
+/// that are already verified on-chain keep their verification.
+pub(crate) fn build_creators(
+    shares: &[CreatorShare],
+    signer: Option<&Pubkey>,
+    existing: Option<&[Creator]>,
+) -> Result<Vec<Creator>> {
+    let invalid = |reason: String| -> Result<Vec<Creator>> {
+        Err(MetadataCliError::InvalidField {
+            field: "creators",
+            reason,
+        }
+        .into())
+    };
+    if shares.is_empty() {
+        return invalid("At least one creator is required".to_string());
+    }
+    if shares.len() > MAX_CREATOR_LIMIT {
+        return invalid(format!(
+            "Too many creators: {} (maximum is {})",
+            shares.len(),
+            MAX_CREATOR_LIMIT
+        ));
+    }
+
+    let total: u32 = shares.iter().map(|c| c.share as u32).sum();
+    if total != 100 {
+        return invalid(format!("Creator shares must sum to 100 (got {})", total));
+    }
+
+    for (i, creator) in shares.iter().enumerate() {
+        if shares[..i].iter().any(|c| c.address == creator.address) {
+            return invalid(format!("Duplicate creator {}", creator.address));
+        }
+    }
+
+    Ok(shares
+        .iter()
+        .map(|c| {
+            let already_verified = existing
+                .and_then(|creators| creators.iter().find(|e| e.address == c.address))
+                .is_some_and(|e| e.verified);
+            Creator {
+                address: c.address,
+                verified: signer == Some(&c.address) || already_verified,
+                share: c.share,
+            }
+        })
+        .collect())
+}
+
+/// Fail unless `authority` is the update authority of the metadata
+pub(crate) fn ensure_update_authority(metadata: &Metadata, authority: &Pubkey) -> Result<()> {
+    if metadata.update_authority != *authority {
+        return Err(MetadataCliError::NotUpdateAuthority {
+            signer: *authority,
+            update_authority: metadata.update_authority,
+        }
+        .into());
+    }
+    Ok(())
+}
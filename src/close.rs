@@ -0,0 +1,105 @@
+//! Closing the metadata and edition accounts left behind by burned mints.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::instructions::CloseAccountsBuilder;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use std::str::FromStr;
+
+use crate::client::MetadataClient;
+use crate::error::MetadataCliError;
+use crate::pda::{find_master_edition_pda, find_metadata_pda};
+use crate::types::{BatchItemResult, BatchResult, CloseResult};
+
+impl MetadataClient {
+    /// Close the metadata and edition accounts of a mint whose supply has
+    /// been burned, sending their rent to the rent payer (the signer unless a
+    /// separate payer is set). The mint must have no tokens left or be
+    /// closed itself.
+    pub async fn close(&self, payer: &dyn Signer, mint: &Pubkey) -> Result<CloseResult> {
+        // A mint that was closed after its supply was burned is gone
+        match self.fetch_mint(mint).await {
+            Ok(mint_account) if mint_account.supply > 0 => anyhow::bail!(
+                "Mint {} still has a supply of {}; only burned mints can be closed",
+                mint,
+                mint_account.supply
+            ),
+            Err(err)
+                if !matches!(
+                    err.downcast_ref(),
+                    Some(MetadataCliError::AccountNotFound { .. })
+                ) =>
+            {
+                return Err(err);
+            }
+            _ => {}
+        }
+        let metadata_pda = find_metadata_pda(mint);
+        let edition_pda = find_master_edition_pda(mint);
+        let tracked = [metadata_pda, edition_pda];
+        let lamports_before = self.account_lamports(&tracked).await?;
+        if lamports_before[0] == 0 {
+            anyhow::bail!("Mint {} has no metadata account to close", mint);
+        }
+
+        let ix = CloseAccountsBuilder::new()
+            .metadata(metadata_pda)
+            .edition(edition_pda)
+            .mint(*mint)
+            .authority(payer.pubkey())
+            .destination(self.rent_payer(&payer.pubkey()))
+            .instruction();
+
+        let signature = self
+            .send(&[ix], payer, &[])
+            .await
+            .context("Failed to send close accounts transaction")?;
+
+        let closed = self.closed_accounts(&tracked, lamports_before).await?;
+
+        Ok(CloseResult {
+            mint: mint.to_string(),
+            closed_accounts: closed.as_ref().map(|(accounts, _)| accounts.clone()),
+            lamports_recovered: closed.map(|(_, lamports)| lamports),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Close the accounts of every mint in a list, sending up to
+    /// `concurrency` transactions at a time. Mints without a metadata
+    /// account are skipped; the rent recovered is totalled.
+    pub async fn batch_close(
+        &self,
+        payer: &(dyn Signer + Sync),
+        mints: &[String],
+        concurrency: usize,
+    ) -> BatchResult {
+        let outcomes = self
+            .run_batch(mints, concurrency, |mint| async move {
+                let mint = Pubkey::from_str(mint).context("Invalid mint address")?;
+                if self.account_lamports(&[find_metadata_pda(&mint)]).await?[0] == 0 {
+                    return Ok(None);
+                }
+                self.close(payer, &mint).await.map(Some)
+            })
+            .await;
+
+        let items = mints
+            .iter()
+            .zip(outcomes)
+            .enumerate()
+            .map(|(i, (mint, outcome))| {
+                let recovered = outcome
+                    .as_ref()
+                    .ok()
+                    .and_then(Option::as_ref)
+                    .and_then(|result| result.lamports_recovered);
+                let outcome = outcome.map(|result| result.map(|result| result.signature));
+                BatchItemResult {
+                    lamports_recovered: recovered,
+                    ..BatchItemResult::new(i + 1, mint, outcome)
+                }
+            })
+            .collect();
+        BatchResult::from_items(items)
+    }
+}
@@ -0,0 +1,191 @@
+//! Solana clusters, resolving `--url` monikers like `solana-cli` does, and
+//! explorer links for each cluster.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// The cluster an RPC endpoint belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cluster {
+    MainnetBeta,
+    Devnet,
+    Testnet,
+    Localnet,
+    /// A custom endpoint whose cluster could not be told from its URL
+    Unknown,
+}
+
+impl Cluster {
+    /// The cluster a moniker (`mainnet`, `devnet`, `testnet`, `localhost`
+    /// or their one-letter forms) refers to
+    pub fn from_moniker(moniker: &str) -> Option<Self> {
+        match moniker {
+            "m" | "mainnet" | "mainnet-beta" => Some(Self::MainnetBeta),
+            "d" | "devnet" => Some(Self::Devnet),
+            "t" | "testnet" => Some(Self::Testnet),
+            "l" | "localhost" | "localnet" => Some(Self::Localnet),
+            _ => None,
+        }
+    }
+
+    /// Best guess at the cluster of an RPC URL
+    pub fn from_url(url: &str) -> Self {
+        let url = url.to_lowercase();
+        if url.contains("mainnet") {
+            Self::MainnetBeta
+        } else if url.contains("devnet") {
+            Self::Devnet
+        } else if url.contains("testnet") {
+            Self::Testnet
+        } else if url.contains("localhost") || url.contains("127.0.0.1") {
+            Self::Localnet
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// The public RPC URL of the cluster
+    pub fn default_url(self) -> Option<&'static str> {
+        match self {
+            Self::MainnetBeta => Some("https://api.mainnet-beta.solana.com"),
+            Self::Devnet => Some("https://api.devnet.solana.com"),
+            Self::Testnet => Some("https://api.testnet.solana.com"),
+            Self::Localnet => Some("http://localhost:8899"),
+            Self::Unknown => None,
+        }
+    }
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::MainnetBeta => "mainnet-beta",
+            Self::Devnet => "devnet",
+            Self::Testnet => "testnet",
+            Self::Localnet => "localnet",
+            Self::Unknown => "unknown cluster",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Resolve a `--url` value, which is either a full URL or a cluster
+/// moniker, to an RPC URL and its cluster
+pub fn resolve_url(url_or_moniker: &str) -> (String, Cluster) {
+    match Cluster::from_moniker(url_or_moniker) {
+        Some(cluster) => (
+            cluster
+                .default_url()
+                .expect("named clusters have a URL")
+                .to_string(),
+            cluster,
+        ),
+        None => (
+            url_or_moniker.to_string(),
+            Cluster::from_url(url_or_moniker),
+        ),
+    }
+}
+
+/// Block explorer to link transactions to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Explorer {
+    #[default]
+    Solana,
+    Solscan,
+    SolanaFm,
+    Xray,
+}
+
+impl FromStr for Explorer {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "solana" => Ok(Self::Solana),
+            "solscan" => Ok(Self::Solscan),
+            "solanafm" => Ok(Self::SolanaFm),
+            "xray" => Ok(Self::Xray),
+            _ => anyhow::bail!(
+                "Unknown explorer '{}' (expected solana, solscan, solanafm or xray)",
+                s
+            ),
+        }
+    }
+}
+
+impl Explorer {
+    /// Link to a transaction on the cluster behind `rpc_url`
+    pub fn tx_url(self, signature: &str, cluster: Cluster, rpc_url: &str) -> String {
+        let base = match self {
+            Self::Solana => "https://explorer.solana.com/tx/",
+            Self::Solscan => "https://solscan.io/tx/",
+            Self::SolanaFm => "https://solana.fm/tx/",
+            Self::Xray => "https://xray.helius.xyz/tx/",
+        };
+        format!(
+            "{}{}{}",
+            base,
+            signature,
+            self.cluster_query(cluster, rpc_url)
+        )
+    }
+
+    /// The query string selecting the cluster, empty for mainnet
+    fn cluster_query(self, cluster: Cluster, rpc_url: &str) -> String {
+        let custom = || format!("?cluster=custom&customUrl={}", rpc_url);
+        match (self, cluster) {
+            (Self::Xray, Cluster::MainnetBeta) => "?network=mainnet".to_string(),
+            (Self::Xray, Cluster::Devnet) => "?network=devnet".to_string(),
+            (_, Cluster::MainnetBeta) => String::new(),
+            (Self::SolanaFm, Cluster::Devnet) => "?cluster=devnet-alpha".to_string(),
+            (Self::SolanaFm, Cluster::Testnet) => "?cluster=testnet-solana".to_string(),
+            (Self::SolanaFm, Cluster::Localnet) => "?cluster=localnet-solana".to_string(),
+            (_, Cluster::Devnet) => "?cluster=devnet".to_string(),
+            (_, Cluster::Testnet) => "?cluster=testnet".to_string(),
+            (_, Cluster::Localnet | Cluster::Unknown) => custom(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monikers_resolve_to_public_endpoints() {
+        for (moniker, url, cluster) in [
+            (
+                "m",
+                "https://api.mainnet-beta.solana.com",
+                Cluster::MainnetBeta,
+            ),
+            (
+                "mainnet-beta",
+                "https://api.mainnet-beta.solana.com",
+                Cluster::MainnetBeta,
+            ),
+            ("d", "https://api.devnet.solana.com", Cluster::Devnet),
+            (
+                "testnet",
+                "https://api.testnet.solana.com",
+                Cluster::Testnet,
+            ),
+            ("l", "http://localhost:8899", Cluster::Localnet),
+        ] {
+            assert_eq!(resolve_url(moniker), (url.to_string(), cluster));
+        }
+    }
+
+    #[test]
+    fn urls_keep_their_value_and_guess_the_cluster() {
+        for (url, cluster) in [
+            ("https://devnet.helius-rpc.com/?api-key=x", Cluster::Devnet),
+            ("https://Solana-Mainnet.example.com", Cluster::MainnetBeta),
+            ("http://127.0.0.1:8899", Cluster::Localnet),
+            ("https://rpc.example.com", Cluster::Unknown),
+        ] {
+            assert_eq!(resolve_url(url), (url.to_string(), cluster));
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Collection membership and verification.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{
+        SetAndVerifyCollectionBuilder, SetAndVerifySizedCollectionItemBuilder,
+        SetCollectionSizeBuilder, UnverifyCollectionBuilder, UnverifySizedCollectionItemBuilder,
+        UpdateMetadataAccountV2Builder,
+    },
+    types::{CollectionDetails, DataV2, SetCollectionSizeArgs},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use crate::client::{MetadataClient, data_from_metadata};
+use crate::error::MetadataCliError;
+use crate::pda::{find_master_edition_pda, find_metadata_pda};
+use crate::types::{
+    CreateCollectionResult, CreateMetadataArgs, SetCollectionResult, SetCollectionSizeResult,
+    UnverifyCollectionResult,
+};
+
+impl MetadataClient {
+    /// Mint a new collection NFT: create the mint, mint one token to the
+    /// payer's associated token account, then create its metadata (as a sized
+    /// collection) and master edition. Everything happens in one transaction,
+    /// signed by the payer and the new mint keypair.
+    pub async fn create_collection(
+        &self,
+        payer: &dyn Signer,
+        mint: &dyn Signer,
+        args: CreateMetadataArgs,
+    ) -> Result<CreateCollectionResult> {
+        let payer_pubkey = payer.pubkey();
+        let mint_pubkey = mint.pubkey();
+        let metadata_pda = find_metadata_pda(&mint_pubkey);
+        let master_edition_pda = find_master_edition_pda(&mint_pubkey);
+
+        let (token_account, instructions) = self
+            .nft_instructions(
+                &payer_pubkey,
+                &mint_pubkey,
+                &args,
+                Some(CollectionDetails::V1 { size: 0 }),
+                Some(0),
+            )
+            .await?;
+
+        let signature = self
+            .send(&instructions, payer, &[mint])
+            .await
+            .context("Failed to send create collection transaction")?;
+
+        Ok(CreateCollectionResult {
+            mint: mint_pubkey.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            master_edition_pda: master_edition_pda.to_string(),
+            token_account: token_account.to_string(),
+            name: args.name,
+            symbol: args.symbol,
+            uri: args.uri,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Set a mint's collection and verify it in one step. The signer must be
+    /// the update authority of the collection NFT and pays for the
+    /// transaction. Sized and unsized collections are both supported.
+    pub async fn set_collection(
+        &self,
+        collection_authority: &dyn Signer,
+        mint: &Pubkey,
+        collection_mint: &Pubkey,
+    ) -> Result<SetCollectionResult> {
+        let metadata_pda = find_metadata_pda(mint);
+        let collection_metadata_pda = find_metadata_pda(collection_mint);
+        let collection_master_edition = find_master_edition_pda(collection_mint);
+
+        let item = self.fetch_fresh(mint).await?;
+        let collection = self
+            .fetch_fresh(collection_mint)
+            .await
+            .context("Failed to fetch collection metadata")?;
+        ensure_collection_authority(&collection, &collection_authority.pubkey())?;
+
+        let sized = collection.collection_details.is_some();
+        let ix = if sized {
+            SetAndVerifySizedCollectionItemBuilder::new()
+                .metadata(metadata_pda)
+                .collection_authority(collection_authority.pubkey())
+                .payer(self.rent_payer(&collection_authority.pubkey()))
+                .update_authority(item.update_authority)
+                .collection_mint(*collection_mint)
+                .collection(collection_metadata_pda)
+                .collection_master_edition_account(collection_master_edition)
+                .instruction()
+        } else {
+            SetAndVerifyCollectionBuilder::new()
+                .metadata(metadata_pda)
+                .collection_authority(collection_authority.pubkey())
+                .payer(self.rent_payer(&collection_authority.pubkey()))
+                .update_authority(item.update_authority)
+                .collection_mint(*collection_mint)
+                .collection(collection_metadata_pda)
+                .collection_master_edition_account(collection_master_edition)
+                .instruction()
+        };
+
+        let signature = self
+            .send(&[ix], collection_authority, &[])
+            .await
+            .context("Failed to send set collection transaction")?;
+
+        Ok(SetCollectionResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            collection_mint: collection_mint.to_string(),
+            sized,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Record the number of items in a collection NFT, turning an unsized
+    /// collection into a sized one. The signer must be the collection
+    /// authority. Sized collections track their size automatically as items
+    /// are verified and unverified, so their size can't be overwritten.
+    pub async fn set_collection_size(
+        &self,
+        collection_authority: &dyn Signer,
+        collection_mint: &Pubkey,
+        size: u64,
+    ) -> Result<SetCollectionSizeResult> {
+        let metadata_pda = find_metadata_pda(collection_mint);
+
+        let collection = self
+            .fetch_fresh(collection_mint)
+            .await
+            .context("Failed to fetch collection metadata")?;
+        ensure_collection_authority(&collection, &collection_authority.pubkey())?;
+        if let Some(CollectionDetails::V1 { size: current }) = collection.collection_details {
+            anyhow::bail!(
+                "Collection {} is already sized (current size: {}); its size is \
+                 maintained on-chain as items are verified and unverified",
+                collection_mint,
+                current
+            );
+        }
+
+        let ix = SetCollectionSizeBuilder::new()
+            .collection_metadata(metadata_pda)
+            .collection_authority(collection_authority.pubkey())
+            .collection_mint(*collection_mint)
+            .set_collection_size_args(SetCollectionSizeArgs { size })
+            .instruction();
+
+        let signature = self
+            .send(&[ix], collection_authority, &[])
+            .await
+            .context("Failed to send set collection size transaction")?;
+
+        Ok(SetCollectionSizeResult {
+            collection_mint: collection_mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            size,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Unverify a mint's collection and clear the collection field. The signer
+    /// must be the collection authority; the field is only cleared when the
+    /// signer is also the item's update authority.
+    pub async fn unverify_collection(
+        &self,
+        collection_authority: &dyn Signer,
+        mint: &Pubkey,
+    ) -> Result<UnverifyCollectionResult> {
+        let metadata_pda = find_metadata_pda(mint);
+
+        let item = self.fetch_fresh(mint).await?;
+        let item_collection = item
+            .collection
+            .as_ref()
+            .with_context(|| format!("Mint {} does not belong to a collection", mint))?;
+        let collection_mint = item_collection.key;
+        let collection_metadata_pda = find_metadata_pda(&collection_mint);
+        let collection_master_edition = find_master_edition_pda(&collection_mint);
+
+        let mut instructions = Vec::new();
+
+        let collection = self
+            .fetch_fresh(&collection_mint)
+            .await
+            .context("Failed to fetch collection metadata")?;
+        let sized = collection.collection_details.is_some();
+
+        if item_collection.verified {
+            ensure_collection_authority(&collection, &collection_authority.pubkey())?;
+
+            instructions.push(if sized {
+                UnverifySizedCollectionItemBuilder::new()
+                    .metadata(metadata_pda)
+                    .collection_authority(collection_authority.pubkey())
+                    .payer(self.rent_payer(&collection_authority.pubkey()))
+                    .collection_mint(collection_mint)
+                    .collection(collection_metadata_pda)
+                    .collection_master_edition_account(collection_master_edition)
+                    .instruction()
+            } else {
+                UnverifyCollectionBuilder::new()
+                    .metadata(metadata_pda)
+                    .collection_authority(collection_authority.pubkey())
+                    .collection_mint(collection_mint)
+                    .collection(collection_metadata_pda)
+                    .collection_master_edition_account(collection_master_edition)
+                    .instruction()
+            });
+        }
+
+        let cleared = item.update_authority == collection_authority.pubkey();
+        if cleared {
+            instructions.push(
+                UpdateMetadataAccountV2Builder::new()
+                    .metadata(metadata_pda)
+                    .update_authority(collection_authority.pubkey())
+                    .data(DataV2 {
+                        collection: None,
+                        ..data_from_metadata(&item)
+                    })
+                    .instruction(),
+            );
+        } else if !item_collection.verified {
+            anyhow::bail!(
+                "Collection is not verified and keypair {} is not the update authority \
+                 (expected {}), so there is nothing to do",
+                collection_authority.pubkey(),
+                item.update_authority
+            );
+        }
+
+        let signature = self
+            .send(&instructions, collection_authority, &[])
+            .await
+            .context("Failed to send unverify collection transaction")?;
+
+        Ok(UnverifyCollectionResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            collection_mint: collection_mint.to_string(),
+            sized,
+            was_verified: item_collection.verified,
+            cleared,
+            signature: signature.to_string(),
+        })
+    }
+}
+
+/// Fail unless `authority` is the update authority of the collection NFT
+fn ensure_collection_authority(collection: &Metadata, authority: &Pubkey) -> Result<()> {
+    if collection.update_authority != *authority {
+        return Err(MetadataCliError::NotUpdateAuthority {
+            signer: *authority,
+            update_authority: collection.update_authority,
+        }
+        .into());
+    }
+    Ok(())
+}
@@ -0,0 +1,276 @@
+//! Named configuration profiles, stored in
+//! `~/.config/token-metadata-cli/config.toml`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    pubkey::Pubkey,
+};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::cluster::Explorer;
+use crate::ipfs::IpfsProvider;
+use crate::keypair::expand_tilde;
+use crate::types::PriorityFee;
+
+/// Profile used when `--profile` is not given
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Keys that can be set on a profile
+pub const PROFILE_KEYS: &[&str] = &[
+    "url",
+    "keypair",
+    "fee-payer",
+    "commitment",
+    "priority-fee",
+    "explorer",
+    "rps",
+    "das-url",
+];
+
+/// All configuration profiles, by name
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Settings of one profile. Unset values fall back to the built-in
+/// defaults, and command-line flags override them.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Profile {
+    /// One RPC URL, or several separated by commas to fail over between.
+    /// The config file may also list them as an array.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "url_list")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keypair: Option<String>,
+    /// Keypair paying transaction fees instead of `keypair`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_fee: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explorer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipfs_provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipfs_api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shadow_storage_account: Option<String>,
+    /// Most RPC requests to send per second
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rps: Option<String>,
+    /// DAS API endpoint to read assets from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub das_url: Option<String>,
+}
+
+/// Location of the config file
+pub fn default_config_path() -> PathBuf {
+    PathBuf::from(expand_tilde("~/.config/token-metadata-cli/config.toml"))
+}
+
+impl Config {
+    /// Read the config file, or an empty config if it doesn't exist
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file '{}'", path.display()))
+    }
+
+    /// Write the config file, creating its directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| {
+                format!("Failed to create config directory '{}'", dir.display())
+            })?;
+        }
+        let contents = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write config file '{}'", path.display()))
+    }
+}
+
+impl Profile {
+    /// The value of a profile key, if set
+    pub fn get(&self, key: &str) -> Result<Option<&str>> {
+        let value = match key {
+            "url" => &self.url,
+            "keypair" => &self.keypair,
+            "fee-payer" => &self.fee_payer,
+            "commitment" => &self.commitment,
+            "priority-fee" => &self.priority_fee,
+            "explorer" => &self.explorer,
+            "ipfs-provider" => &self.ipfs_provider,
+            "ipfs-api-key" => &self.ipfs_api_key,
+            "shadow-storage-account" => &self.shadow_storage_account,
+            "rps" => &self.rps,
+            "das-url" => &self.das_url,
+            _ => anyhow::bail!(
+                "Unknown config key '{}' (expected one of: {})",
+                key,
+                PROFILE_KEYS.join(", ")
+            ),
+        };
+        Ok(value.as_deref())
+    }
+
+    /// Set a profile key, validating the value
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = Some(value.to_string());
+        match key {
+            "url" => self.url = value,
+            "keypair" => self.keypair = value,
+            "fee-payer" => self.fee_payer = value,
+            "commitment" => {
+                self.commitment = value;
+                self.commitment_config()?;
+            }
+            "priority-fee" => {
+                self.priority_fee = value;
+                self.priority_fee()?;
+            }
+            "explorer" => {
+                self.explorer = value;
+                self.explorer()?;
+            }
+            "ipfs-provider" => {
+                self.ipfs_provider = value;
+                self.ipfs_provider()?;
+            }
+            "ipfs-api-key" => self.ipfs_api_key = value,
+            "shadow-storage-account" => {
+                self.shadow_storage_account = value;
+                self.shadow_storage_account()?;
+            }
+            "rps" => {
+                self.rps = value;
+                self.rps()?;
+            }
+            "das-url" => self.das_url = value,
+            _ => anyhow::bail!(
+                "Unknown config key '{}' (expected one of: {})",
+                key,
+                PROFILE_KEYS.join(", ")
+            ),
+        }
+        Ok(())
+    }
+
+    /// The configured RPC URLs, in failover order
+    pub fn urls(&self) -> Vec<String> {
+        self.url.as_deref().map(split_urls).unwrap_or_default()
+    }
+
+    /// The configured commitment, if any
+    pub fn commitment_config(&self) -> Result<Option<CommitmentConfig>> {
+        self.commitment
+            .as_deref()
+            .map(|commitment| {
+                let commitment = CommitmentLevel::from_str(commitment).map_err(|_| {
+                    anyhow::anyhow!(
+                        "Invalid commitment '{}' (expected processed, confirmed or finalized)",
+                        commitment
+                    )
+                })?;
+                Ok(CommitmentConfig { commitment })
+            })
+            .transpose()
+    }
+
+    /// The configured explorer, if any
+    pub fn explorer(&self) -> Result<Option<Explorer>> {
+        self.explorer.as_deref().map(Explorer::from_str).transpose()
+    }
+
+    /// The configured IPFS pinning service, if any
+    pub fn ipfs_provider(&self) -> Result<Option<IpfsProvider>> {
+        self.ipfs_provider
+            .as_deref()
+            .map(IpfsProvider::from_str)
+            .transpose()
+    }
+
+    /// The configured Shadow Drive storage account, if any
+    pub fn shadow_storage_account(&self) -> Result<Option<Pubkey>> {
+        self.shadow_storage_account
+            .as_deref()
+            .map(|account| {
+                Pubkey::from_str(account).context("Invalid Shadow Drive storage account")
+            })
+            .transpose()
+    }
+
+    /// The configured RPC rate limit in requests per second, if any
+    pub fn rps(&self) -> Result<Option<u32>> {
+        self.rps
+            .as_deref()
+            .map(|rps| match rps.parse::<u32>() {
+                Ok(rps) if rps > 0 => Ok(rps),
+                _ => anyhow::bail!("Invalid rps '{}' (expected a positive whole number)", rps),
+            })
+            .transpose()
+    }
+
+    /// The configured priority fee, if any
+    pub fn priority_fee(&self) -> Result<Option<PriorityFee>> {
+        self.priority_fee
+            .as_deref()
+            .map(PriorityFee::from_str)
+            .transpose()
+    }
+}
+
+/// Split a comma-separated list of RPC URLs
+pub fn split_urls(urls: &str) -> Vec<String> {
+    urls.split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Reading a profile's `url` as either a string or an array of strings, and
+/// writing several URLs back as an array
+mod url_list {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Urls {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    pub fn serialize<S: Serializer>(
+        url: &Option<String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match url {
+            Some(urls) if urls.contains(',') => super::split_urls(urls).serialize(serializer),
+            url => url.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<String>, D::Error> {
+        Ok(match Urls::deserialize(deserializer)? {
+            Urls::One(url) => Some(url),
+            Urls::Many(urls) => Some(urls.join(",")),
+        })
+    }
+}
@@ -0,0 +1,139 @@
+//! Sending transactions and waiting for their confirmation, notified over
+//! the RPC node's websocket or, where it has none, by polling.
+
+use anyhow::Result;
+use futures_util::StreamExt;
+use solana_client::{
+    client_error::ClientError,
+    nonblocking::pubsub_client::PubsubClient,
+    rpc_config::{RpcSendTransactionConfig, RpcSignatureSubscribeConfig},
+    rpc_request::RpcError,
+    rpc_response::RpcSignatureResult,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::Signature,
+    transaction::{Transaction, uses_durable_nonce},
+};
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+use crate::client::MetadataClient;
+
+/// How long to wait for the websocket connection before polling instead
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Delay between signature status checks when polling
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// With a websocket subscription, how often to check the signature status
+/// and blockhash anyway, in case a notification is missed
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+impl MetadataClient {
+    /// Send a signed transaction, skipping the node's preflight check, and
+    /// wait until it is confirmed at the client's commitment. Fails if the
+    /// transaction fails, or can no longer land because its blockhash
+    /// expired. Once it lands, cached copies of its accounts are dropped and
+    /// the metadata accounts it changed are recorded in the history as they
+    /// were before.
+    pub(crate) async fn send_and_confirm(&self, tx: &Transaction) -> Result<Signature> {
+        let rpc = self.rpc();
+        let commitment = rpc.commitment();
+        let signature = tx.signatures[0];
+
+        // Subscribe before sending, so the notification can't come too early
+        let pubsub = match websocket_url(&rpc.url()) {
+            Some(url) => timeout(CONNECT_TIMEOUT, PubsubClient::new(&url))
+                .await
+                .ok()
+                .and_then(Result::ok),
+            None => None,
+        };
+        let config = RpcSignatureSubscribeConfig {
+            commitment: Some(commitment),
+            enable_received_notification: Some(false),
+        };
+        let mut subscription = match &pubsub {
+            Some(pubsub) => pubsub
+                .signature_subscribe(&signature, Some(config))
+                .await
+                .ok(),
+            None => None,
+        };
+
+        let before = self.metadata_before(tx).await;
+        let send_config = RpcSendTransactionConfig {
+            skip_preflight: true,
+            ..RpcSendTransactionConfig::default()
+        };
+        rpc.send_transaction_with_config(tx, send_config).await?;
+        // A durable nonce doesn't expire, so the transaction can be
+        // abandoned once a blockhash current at sending time has
+        let blockhash = if uses_durable_nonce(tx).is_some() {
+            rpc.get_latest_blockhash_with_commitment(CommitmentConfig::processed())
+                .await?
+                .0
+        } else {
+            tx.message.recent_blockhash
+        };
+
+        let status: Result<()> = loop {
+            // Some(Some(_)) is a notification, Some(None) a closed
+            // subscription and None a timeout
+            let notification = match &mut subscription {
+                Some((stream, _)) => timeout(CHECK_INTERVAL, stream.next()).await.ok(),
+                None => {
+                    sleep(POLL_INTERVAL).await;
+                    None
+                }
+            };
+            match notification {
+                Some(Some(response)) => {
+                    if let RpcSignatureResult::ProcessedSignature(result) = response.value {
+                        break result.err.map_or(Ok(()), |err| Err(err.into()));
+                    }
+                }
+                Some(None) => subscription = None,
+                None => {}
+            }
+
+            if let Some(status) = rpc
+                .get_signature_status_with_commitment(&signature, commitment)
+                .await?
+            {
+                break status.map_err(anyhow::Error::from);
+            }
+            if !rpc.is_blockhash_valid(&blockhash, commitment).await? {
+                break Err(ClientError::from(RpcError::ForUser(
+                    "unable to confirm transaction: its blockhash expired before it was confirmed"
+                        .to_string(),
+                ))
+                .into());
+            }
+        };
+
+        status?;
+        if let Some(cache) = self.cache() {
+            cache.invalidate(&tx.message.account_keys);
+        }
+        self.record_history(before, &signature);
+        Ok(signature)
+    }
+}
+
+/// The websocket URL of an RPC endpoint, which like `solana-cli` assumes is
+/// on the port after the HTTP one when a port is given
+pub(crate) fn websocket_url(rpc_url: &str) -> Option<String> {
+    let mut url = reqwest::Url::parse(rpc_url).ok()?;
+    let scheme = match url.scheme() {
+        "http" => "ws",
+        "https" => "wss",
+        _ => return None,
+    };
+    url.set_scheme(scheme).ok()?;
+    if let Some(port) = url.port() {
+        url.set_port(Some(port.checked_add(1)?)).ok()?;
+    }
+    Some(url.to_string())
+}
@@ -0,0 +1,268 @@
+//! Metaplex Core assets, which keep an NFT's owner, update authority, name
+//! and URI in a single account of the Core program, with no mint, token
+//! account or metadata account beside it.
+
+use anyhow::{Context, Result};
+use mpl_core::{
+    Asset, Collection,
+    instructions::{BurnV1Builder, CreateV1Builder, UpdateV1Builder},
+    types::{DataState, UpdateAuthority},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use crate::client::MetadataClient;
+use crate::core_plugin::plugin_infos;
+use crate::error::MetadataCliError;
+use crate::types::{
+    CoreAssetInfo, CoreBurnResult, CoreCreateResult, CoreUpdateResult, FieldChange,
+};
+
+impl MetadataClient {
+    /// Fetch and decode a Core asset, with its plugins
+    pub async fn fetch_core_asset(&self, asset: &Pubkey) -> Result<Box<Asset>> {
+        let account = self
+            .rpc()
+            .get_account_with_commitment(asset, self.rpc().commitment())
+            .await
+            .map_err(MetadataCliError::rpc("fetch Core asset account"))?
+            .value
+            .ok_or(MetadataCliError::AccountNotFound {
+                kind: "Core asset",
+                address: *asset,
+            })?;
+        if account.owner != mpl_core::ID {
+            return Err(MetadataCliError::InvalidAccountData {
+                kind: "Core asset",
+                address: *asset,
+                reason: "it isn't owned by the Core program".to_string(),
+            }
+            .into());
+        }
+        Asset::from_bytes(&account.data).map_err(|err| {
+            MetadataCliError::InvalidAccountData {
+                kind: "Core asset",
+                address: *asset,
+                reason: err.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Fetch and decode a Core collection, with its plugins
+    pub async fn fetch_core_collection(&self, collection: &Pubkey) -> Result<Box<Collection>> {
+        let account = self
+            .rpc()
+            .get_account_with_commitment(collection, self.rpc().commitment())
+            .await
+            .map_err(MetadataCliError::rpc("fetch Core collection account"))?
+            .value
+            .ok_or(MetadataCliError::AccountNotFound {
+                kind: "Core collection",
+                address: *collection,
+            })?;
+        Collection::from_bytes(&account.data).map_err(|err| {
+            MetadataCliError::InvalidAccountData {
+                kind: "Core collection",
+                address: *collection,
+                reason: err.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// A Core asset as shown to users, with the update authority of its
+    /// collection when the collection holds that role
+    pub async fn core_asset_info(&self, address: &Pubkey) -> Result<CoreAssetInfo> {
+        let asset = self.fetch_core_asset(address).await?;
+        let (update_authority, collection) = self.core_update_authority(&asset).await?;
+        Ok(CoreAssetInfo {
+            address: address.to_string(),
+            owner: asset.base.owner.to_string(),
+            update_authority: update_authority.map(|authority| authority.to_string()),
+            collection: collection.map(|collection| collection.to_string()),
+            name: asset.base.name.clone(),
+            uri: asset.base.uri.clone(),
+            plugins: plugin_infos(&asset.plugin_list),
+        })
+    }
+
+    /// Create a Core asset owned by `owner`, or the payer when `None`, with
+    /// the payer as update authority. With a collection the asset joins it
+    /// and takes the collection's update authority instead, which the payer
+    /// must hold.
+    pub async fn create_core_asset(
+        &self,
+        payer: &dyn Signer,
+        asset: &dyn Signer,
+        name: String,
+        uri: String,
+        owner: Option<&Pubkey>,
+        collection: Option<&Pubkey>,
+    ) -> Result<CoreCreateResult> {
+        let payer_pubkey = payer.pubkey();
+        let owner = owner.copied().unwrap_or(payer_pubkey);
+        if let Some(collection) = collection {
+            let update_authority = self
+                .fetch_core_collection(collection)
+                .await?
+                .base
+                .update_authority;
+            if update_authority != payer_pubkey {
+                return Err(MetadataCliError::NotUpdateAuthority {
+                    signer: payer_pubkey,
+                    update_authority,
+                }
+                .into());
+            }
+        }
+
+        let ix = CreateV1Builder::new()
+            .asset(asset.pubkey())
+            .collection(collection.copied())
+            .authority(collection.map(|_| payer_pubkey))
+            .payer(self.rent_payer(&payer_pubkey))
+            .owner(Some(owner))
+            .update_authority(collection.is_none().then_some(payer_pubkey))
+            .data_state(DataState::AccountState)
+            .name(name.clone())
+            .uri(uri.clone())
+            .instruction();
+
+        let signature = self
+            .send(&[ix], payer, &[asset])
+            .await
+            .context("Failed to send Core create transaction")?;
+
+        Ok(CoreCreateResult {
+            asset: CoreAssetInfo {
+                address: asset.pubkey().to_string(),
+                owner: owner.to_string(),
+                update_authority: Some(payer_pubkey.to_string()),
+                collection: collection.map(|collection| collection.to_string()),
+                name,
+                uri,
+                plugins: Vec::new(),
+            },
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Change the name or URI of a Core asset; `None` keeps the current
+    /// value. The authority must be the asset's update authority, or that of
+    /// its collection.
+    pub async fn update_core_asset(
+        &self,
+        authority: &dyn Signer,
+        address: &Pubkey,
+        name: Option<String>,
+        uri: Option<String>,
+    ) -> Result<CoreUpdateResult> {
+        let authority_pubkey = authority.pubkey();
+        let asset = self.fetch_core_asset(address).await?;
+        let (update_authority, collection) = self.core_update_authority(&asset).await?;
+        match update_authority {
+            Some(update_authority) if update_authority != authority_pubkey => {
+                return Err(MetadataCliError::NotUpdateAuthority {
+                    signer: authority_pubkey,
+                    update_authority,
+                }
+                .into());
+            }
+            Some(_) => {}
+            None => anyhow::bail!("Core asset {} has no update authority", address),
+        }
+
+        let mut builder = UpdateV1Builder::new();
+        builder
+            .asset(*address)
+            .collection(collection)
+            .payer(self.rent_payer(&authority_pubkey))
+            .authority(Some(authority_pubkey));
+        if let Some(name) = &name {
+            builder.new_name(name.clone());
+        }
+        if let Some(uri) = &uri {
+            builder.new_uri(uri.clone());
+        }
+        let ix = builder.instruction();
+
+        let signature = self
+            .send(&[ix], authority, &[])
+            .await
+            .context("Failed to send Core update transaction")?;
+
+        Ok(CoreUpdateResult {
+            address: address.to_string(),
+            name: FieldChange {
+                old: asset.base.name.clone(),
+                new: name.unwrap_or_else(|| asset.base.name.clone()),
+            },
+            uri: FieldChange {
+                old: asset.base.uri.clone(),
+                new: uri.unwrap_or_else(|| asset.base.uri.clone()),
+            },
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Burn a Core asset, which must be owned by the signer. The asset's rent
+    /// goes back to the signer, less the one byte the program leaves behind.
+    pub async fn burn_core_asset(
+        &self,
+        owner: &dyn Signer,
+        address: &Pubkey,
+    ) -> Result<CoreBurnResult> {
+        let owner_pubkey = owner.pubkey();
+        let asset = self.fetch_core_asset(address).await?;
+        if asset.base.owner != owner_pubkey {
+            anyhow::bail!(
+                "{} doesn't own Core asset {} (its owner is {})",
+                owner_pubkey,
+                address,
+                asset.base.owner
+            );
+        }
+        let collection = match asset.base.update_authority {
+            UpdateAuthority::Collection(collection) => Some(collection),
+            _ => None,
+        };
+
+        let ix = BurnV1Builder::new()
+            .asset(*address)
+            .collection(collection)
+            .payer(self.rent_payer(&owner_pubkey))
+            .authority(Some(owner_pubkey))
+            .instruction();
+
+        let signature = self
+            .send(&[ix], owner, &[])
+            .await
+            .context("Failed to send Core burn transaction")?;
+
+        Ok(CoreBurnResult {
+            address: address.to_string(),
+            name: asset.base.name.clone(),
+            collection: collection.map(|collection| collection.to_string()),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// The key that signs updates to an asset, and the collection whose
+    /// update authority it is when the asset defers to its collection
+    async fn core_update_authority(
+        &self,
+        asset: &Asset,
+    ) -> Result<(Option<Pubkey>, Option<Pubkey>)> {
+        Ok(match asset.base.update_authority {
+            UpdateAuthority::None => (None, None),
+            UpdateAuthority::Address(authority) => (Some(authority), None),
+            UpdateAuthority::Collection(collection) => {
+                let collection_account = self.fetch_core_collection(&collection).await?;
+                (
+                    Some(collection_account.base.update_authority),
+                    Some(collection),
+                )
+            }
+        })
+    }
+}
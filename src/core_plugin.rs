@@ -0,0 +1,311 @@
+//! Plugins on Metaplex Core assets and collections: royalties and
+//! attributes, which the update authority manages, and freeze and transfer
+//! delegates, which the owner manages and only assets can have.
+
+use anyhow::{Context, Result};
+use mpl_core::{
+    AuthorityType, BasePlugin, PluginsList,
+    instructions::{
+        AddCollectionPluginV1Builder, AddPluginV1Builder, ApprovePluginAuthorityV1Builder,
+        UpdateCollectionPluginV1Builder, UpdatePluginV1Builder,
+    },
+    types::{
+        Attribute, Attributes, Creator, FreezeDelegate, Plugin, PluginAuthority, PluginType,
+        Royalties, RuleSet, TransferDelegate, UpdateAuthority,
+    },
+};
+use solana_sdk::{instruction::Instruction, signature::Signer};
+
+use crate::client::{MetadataClient, build_creators};
+use crate::types::{CorePlugin, CorePluginInfo, CorePluginResult, CoreTarget};
+use crate::validate::check_seller_fee_basis_points;
+
+impl MetadataClient {
+    /// Add a plugin to a Core asset or collection, or update it when the
+    /// target already has one. Royalties and attributes are signed by the
+    /// update authority. Freeze and transfer delegates are signed by the
+    /// asset's owner, except that changing whether the asset is frozen
+    /// takes the freeze delegate's signature.
+    pub async fn set_core_plugin(
+        &self,
+        authority: &dyn Signer,
+        target: &CoreTarget,
+        plugin: CorePlugin,
+    ) -> Result<CorePluginResult> {
+        let authority_pubkey = authority.pubkey();
+        let (plugins, asset_collection) = match target {
+            CoreTarget::Asset(address) => {
+                let asset = self.fetch_core_asset(address).await?;
+                let collection = match asset.base.update_authority {
+                    UpdateAuthority::Collection(collection) => Some(collection),
+                    _ => None,
+                };
+                (asset.plugin_list, collection)
+            }
+            CoreTarget::Collection(address) => {
+                (self.fetch_core_collection(address).await?.plugin_list, None)
+            }
+        };
+        let kind = plugin.kind();
+        let plugin_type = plugin.plugin_type();
+        if plugin.owner_managed()
+            && let CoreTarget::Collection(_) = target
+        {
+            anyhow::bail!(
+                "The {} plugin is managed by an asset's owner, so only assets can have it",
+                kind
+            );
+        }
+
+        let (change, init_authority, exists) = match plugin {
+            CorePlugin::Royalties {
+                basis_points,
+                creators,
+            } => {
+                check_seller_fee_basis_points(basis_points)?;
+                let creators = build_creators(&creators, Some(&authority_pubkey), None)?
+                    .into_iter()
+                    .map(|creator| Creator {
+                        address: creator.address,
+                        percentage: creator.share,
+                    })
+                    .collect();
+                let rule_set = plugins
+                    .royalties
+                    .as_ref()
+                    .map_or(RuleSet::None, |existing| {
+                        existing.royalties.rule_set.clone()
+                    });
+                let change = Plugin::Royalties(Royalties {
+                    basis_points,
+                    creators,
+                    rule_set,
+                });
+                (Some(change), None, plugins.royalties.is_some())
+            }
+            CorePlugin::Attributes { set, remove } => {
+                let mut attribute_list = plugins
+                    .attributes
+                    .as_ref()
+                    .map(|existing| existing.attributes.attribute_list.clone())
+                    .unwrap_or_default();
+                for key in &remove {
+                    let len = attribute_list.len();
+                    attribute_list.retain(|attribute| attribute.key != *key);
+                    if attribute_list.len() == len {
+                        anyhow::bail!("There is no attribute {:?} to remove", key);
+                    }
+                }
+                for field in set {
+                    match attribute_list
+                        .iter_mut()
+                        .find(|attribute| attribute.key == field.key)
+                    {
+                        Some(attribute) => attribute.value = field.value,
+                        None => attribute_list.push(Attribute {
+                            key: field.key,
+                            value: field.value,
+                        }),
+                    }
+                }
+                let change = Plugin::Attributes(Attributes { attribute_list });
+                (Some(change), None, plugins.attributes.is_some())
+            }
+            CorePlugin::FreezeDelegate { delegate, frozen } => match &plugins.freeze_delegate {
+                Some(_) => {
+                    let change =
+                        frozen.map(|frozen| Plugin::FreezeDelegate(FreezeDelegate { frozen }));
+                    (change, delegate, true)
+                }
+                None => {
+                    let change = Plugin::FreezeDelegate(FreezeDelegate {
+                        frozen: frozen.unwrap_or(false),
+                    });
+                    (Some(change), delegate, false)
+                }
+            },
+            CorePlugin::TransferDelegate { delegate } => (
+                (plugins.transfer_delegate.is_none())
+                    .then_some(Plugin::TransferDelegate(TransferDelegate {})),
+                Some(delegate),
+                plugins.transfer_delegate.is_some(),
+            ),
+        };
+        let init_authority = init_authority.map(|address| PluginAuthority::Address { address });
+
+        let mut ixs: Vec<Instruction> = Vec::new();
+        match target {
+            CoreTarget::Asset(address) if exists => {
+                // Change the plugin's data while the current authority still
+                // holds it, then hand it to the new delegate
+                if let Some(change) = change {
+                    ixs.push(
+                        UpdatePluginV1Builder::new()
+                            .asset(*address)
+                            .collection(asset_collection)
+                            .payer(self.rent_payer(&authority_pubkey))
+                            .authority(Some(authority_pubkey))
+                            .plugin(change)
+                            .instruction(),
+                    );
+                }
+                if let Some(new_authority) = init_authority.clone() {
+                    ixs.push(
+                        ApprovePluginAuthorityV1Builder::new()
+                            .asset(*address)
+                            .collection(asset_collection)
+                            .payer(self.rent_payer(&authority_pubkey))
+                            .authority(Some(authority_pubkey))
+                            .plugin_type(plugin_type)
+                            .new_authority(new_authority)
+                            .instruction(),
+                    );
+                }
+            }
+            CoreTarget::Asset(address) => {
+                let mut builder = AddPluginV1Builder::new();
+                builder
+                    .asset(*address)
+                    .collection(asset_collection)
+                    .payer(self.rent_payer(&authority_pubkey))
+                    .authority(Some(authority_pubkey))
+                    .plugin(change.context("A new plugin needs its data")?);
+                if let Some(init_authority) = init_authority.clone() {
+                    builder.init_authority(init_authority);
+                }
+                ixs.push(builder.instruction());
+            }
+            CoreTarget::Collection(address) => {
+                let change = change.context("A collection plugin needs its data")?;
+                ixs.push(if exists {
+                    UpdateCollectionPluginV1Builder::new()
+                        .collection(*address)
+                        .payer(self.rent_payer(&authority_pubkey))
+                        .authority(Some(authority_pubkey))
+                        .plugin(change)
+                        .instruction()
+                } else {
+                    AddCollectionPluginV1Builder::new()
+                        .collection(*address)
+                        .payer(self.rent_payer(&authority_pubkey))
+                        .authority(Some(authority_pubkey))
+                        .plugin(change)
+                        .instruction()
+                });
+            }
+        }
+        if ixs.is_empty() {
+            anyhow::bail!("Nothing to change on the {} plugin", kind);
+        }
+
+        let signature = self
+            .send(&ixs, authority, &[])
+            .await
+            .context("Failed to send Core plugin transaction")?;
+
+        Ok(CorePluginResult {
+            target: target.address().to_string(),
+            target_kind: target.kind().to_string(),
+            plugin: kind.to_string(),
+            added: !exists,
+            delegate: match init_authority {
+                Some(PluginAuthority::Address { address }) => Some(address.to_string()),
+                _ => None,
+            },
+            signature: signature.to_string(),
+        })
+    }
+}
+
+/// The plugins of an asset or collection as shown to users, with the data
+/// of the ones this CLI manages
+pub(crate) fn plugin_infos(plugins: &PluginsList) -> Vec<CorePluginInfo> {
+    let mut infos = Vec::new();
+    let mut push = |plugin: &str, base: &BasePlugin, details: Option<String>| {
+        infos.push(CorePluginInfo {
+            plugin: plugin.to_string(),
+            authority: authority_label(base),
+            details,
+        })
+    };
+    if let Some(royalties) = &plugins.royalties {
+        let creators: Vec<String> = royalties
+            .royalties
+            .creators
+            .iter()
+            .map(|creator| format!("{}:{}", creator.address, creator.percentage))
+            .collect();
+        push(
+            "royalties",
+            &royalties.base,
+            Some(format!(
+                "{} bps to {}",
+                royalties.royalties.basis_points,
+                creators.join(", ")
+            )),
+        );
+    }
+    if let Some(attributes) = &plugins.attributes {
+        let list: Vec<String> = attributes
+            .attributes
+            .attribute_list
+            .iter()
+            .map(|attribute| format!("{}={}", attribute.key, attribute.value))
+            .collect();
+        push("attributes", &attributes.base, Some(list.join(", ")));
+    }
+    if let Some(freeze) = &plugins.freeze_delegate {
+        let state = if freeze.freeze_delegate.frozen {
+            "frozen"
+        } else {
+            "not frozen"
+        };
+        push("freeze-delegate", &freeze.base, Some(state.to_string()));
+    }
+    if let Some(transfer) = &plugins.transfer_delegate {
+        push("transfer-delegate", &transfer.base, None);
+    }
+    if let Some(plugin) = &plugins.burn_delegate {
+        push("burn-delegate", &plugin.base, None);
+    }
+    if let Some(plugin) = &plugins.update_delegate {
+        push("update-delegate", &plugin.base, None);
+    }
+    if let Some(plugin) = &plugins.permanent_freeze_delegate {
+        push("permanent-freeze-delegate", &plugin.base, None);
+    }
+    if let Some(plugin) = &plugins.permanent_transfer_delegate {
+        push("permanent-transfer-delegate", &plugin.base, None);
+    }
+    if let Some(plugin) = &plugins.permanent_burn_delegate {
+        push("permanent-burn-delegate", &plugin.base, None);
+    }
+    if let Some(plugin) = &plugins.immutable_metadata {
+        push("immutable-metadata", &plugin.base, None);
+    }
+    if let Some(plugin) = &plugins.add_blocker {
+        push("add-blocker", &plugin.base, None);
+    }
+    infos
+}
+
+fn authority_label(base: &BasePlugin) -> String {
+    match (&base.authority.authority_type, base.authority.address) {
+        (AuthorityType::Address, Some(address)) => address.to_string(),
+        (AuthorityType::Owner, _) => "owner".to_string(),
+        (AuthorityType::UpdateAuthority, _) => "update authority".to_string(),
+        _ => "none".to_string(),
+    }
+}
+
+impl CorePlugin {
+    /// The plugin type Core knows the plugin by
+    fn plugin_type(&self) -> PluginType {
+        match self {
+            CorePlugin::Royalties { .. } => PluginType::Royalties,
+            CorePlugin::Attributes { .. } => PluginType::Attributes,
+            CorePlugin::FreezeDelegate { .. } => PluginType::FreezeDelegate,
+            CorePlugin::TransferDelegate { .. } => PluginType::TransferDelegate,
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Creator verification.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{RemoveCreatorVerificationBuilder, SignMetadataBuilder},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use crate::client::MetadataClient;
+use crate::error::MetadataCliError;
+use crate::pda::find_metadata_pda;
+use crate::types::{CreatorVerification, CreatorVerificationResult, CreatorsVerificationResult};
+
+impl MetadataClient {
+    /// Mark the signing creator as verified on a mint's metadata. The signer
+    /// must appear in the metadata's creators list.
+    pub async fn verify_creator(
+        &self,
+        creator: &dyn Signer,
+        mint: &Pubkey,
+    ) -> Result<CreatorVerificationResult> {
+        let metadata_pda = find_metadata_pda(mint);
+
+        let existing = self.fetch_fresh(mint).await?;
+        let verified_before = creator_verified(&existing, &creator.pubkey())?;
+        if verified_before {
+            return Err(MetadataCliError::InvalidField {
+                field: "creators",
+                reason: format!("{} is already verified", creator.pubkey()),
+            }
+            .into());
+        }
+
+        let ix = SignMetadataBuilder::new()
+            .metadata(metadata_pda)
+            .creator(creator.pubkey())
+            .instruction();
+
+        let signature = self
+            .send(&[ix], creator, &[])
+            .await
+            .context("Failed to send verify creator transaction")?;
+
+        let verified_after = creator_verified(&self.fetch_fresh(mint).await?, &creator.pubkey())?;
+
+        Ok(CreatorVerificationResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            creator: creator.pubkey().to_string(),
+            verified_before,
+            verified_after,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Verify several creators of a mint's metadata in one transaction, one
+    /// SignMetadata instruction per creator, paid by `payer`. Every creator
+    /// must be listed in the metadata; those already verified are left out
+    /// of the transaction and reported as they are.
+    pub async fn verify_creators(
+        &self,
+        payer: &dyn Signer,
+        creators: &[&dyn Signer],
+        mint: &Pubkey,
+    ) -> Result<CreatorsVerificationResult> {
+        let metadata_pda = find_metadata_pda(mint);
+
+        let existing = self.fetch_fresh(mint).await?;
+        let mut signers: Vec<&dyn Signer> = Vec::new();
+        let mut verified_before = Vec::new();
+        for creator in creators {
+            // The same keypair given twice signs once
+            if verified_before.iter().any(|(c, _)| *c == creator.pubkey()) {
+                continue;
+            }
+            let verified = creator_verified(&existing, &creator.pubkey())?;
+            verified_before.push((creator.pubkey(), verified));
+            if !verified {
+                signers.push(*creator);
+            }
+        }
+        if signers.is_empty() {
+            return Err(MetadataCliError::InvalidField {
+                field: "creators",
+                reason: "every given creator is already verified".to_string(),
+            }
+            .into());
+        }
+
+        let instructions: Vec<_> = signers
+            .iter()
+            .map(|creator| {
+                SignMetadataBuilder::new()
+                    .metadata(metadata_pda)
+                    .creator(creator.pubkey())
+                    .instruction()
+            })
+            .collect();
+
+        let signature = self
+            .send(&instructions, payer, &signers)
+            .await
+            .context("Failed to send verify creators transaction")?;
+
+        let updated = self.fetch_fresh(mint).await?;
+        let creators = verified_before
+            .into_iter()
+            .map(|(creator, verified_before)| {
+                Ok(CreatorVerification {
+                    creator: creator.to_string(),
+                    verified_before,
+                    verified_after: creator_verified(&updated, &creator)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(CreatorsVerificationResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            creators,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Remove the signing creator's verification from a mint's metadata
+    pub async fn unverify_creator(
+        &self,
+        creator: &dyn Signer,
+        mint: &Pubkey,
+    ) -> Result<CreatorVerificationResult> {
+        let metadata_pda = find_metadata_pda(mint);
+
+        let existing = self.fetch_fresh(mint).await?;
+        let verified_before = creator_verified(&existing, &creator.pubkey())?;
+        if !verified_before {
+            return Err(MetadataCliError::InvalidField {
+                field: "creators",
+                reason: format!("{} is not verified", creator.pubkey()),
+            }
+            .into());
+        }
+
+        let ix = RemoveCreatorVerificationBuilder::new()
+            .metadata(metadata_pda)
+            .creator(creator.pubkey())
+            .instruction();
+
+        let signature = self
+            .send(&[ix], creator, &[])
+            .await
+            .context("Failed to send unverify creator transaction")?;
+
+        let verified_after = creator_verified(&self.fetch_fresh(mint).await?, &creator.pubkey())?;
+
+        Ok(CreatorVerificationResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            creator: creator.pubkey().to_string(),
+            verified_before,
+            verified_after,
+            signature: signature.to_string(),
+        })
+    }
+}
+
+/// Look up the verified flag of a creator, failing if they aren't listed
+fn creator_verified(metadata: &Metadata, creator: &Pubkey) -> Result<bool> {
+    metadata
+        .creators
+        .as_ref()
+        .and_then(|creators| creators.iter().find(|c| c.address == *creator))
+        .map(|c| c.verified)
+        .ok_or_else(|| {
+            MetadataCliError::NotACreator {
+                creator: *creator,
+                mint: metadata.mint,
+            }
+            .into()
+        })
+}
@@ -0,0 +1,461 @@
+//! Queries to the Digital Asset Standard (DAS) API, which indexing RPC
+//! providers serve alongside the regular JSON-RPC methods. Unlike account
+//! scans it also sees compressed NFTs.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::types::{Collection, Creator};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_request::RpcRequest};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::client::MetadataClient;
+use crate::pda::find_metadata_pda;
+use crate::types::{CollectionInfo, CompressionInfo, CreatorInfo, MetadataInfo, MetadataSummary};
+
+/// Largest page the DAS API returns
+const PAGE_LIMIT: usize = 1000;
+
+/// One page of a DAS asset search
+#[derive(Deserialize)]
+struct AssetPage {
+    items: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    id: String,
+    #[serde(default)]
+    content: AssetContent,
+    #[serde(default)]
+    authorities: Vec<AssetAuthority>,
+    #[serde(default)]
+    compression: Option<AssetCompression>,
+    #[serde(default)]
+    grouping: Vec<AssetGroup>,
+    #[serde(default)]
+    royalty: Option<AssetRoyalty>,
+    #[serde(default)]
+    creators: Vec<AssetCreator>,
+    #[serde(default)]
+    mutable: bool,
+    #[serde(default)]
+    ownership: Option<AssetOwnership>,
+    #[serde(default)]
+    supply: Option<AssetSupply>,
+    #[serde(default)]
+    burnt: bool,
+}
+
+#[derive(Default, Deserialize)]
+struct AssetContent {
+    #[serde(default)]
+    json_uri: String,
+    #[serde(default)]
+    metadata: AssetMetadata,
+}
+
+#[derive(Default, Deserialize)]
+struct AssetMetadata {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    symbol: String,
+    #[serde(default)]
+    token_standard: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AssetAuthority {
+    address: String,
+}
+
+#[derive(Deserialize)]
+struct AssetCompression {
+    compressed: bool,
+    #[serde(default)]
+    tree: String,
+    #[serde(default)]
+    leaf_id: u64,
+    #[serde(default)]
+    data_hash: String,
+    #[serde(default)]
+    creator_hash: String,
+}
+
+#[derive(Deserialize)]
+struct AssetOwnership {
+    owner: String,
+    #[serde(default)]
+    delegate: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AssetSupply {
+    #[serde(default)]
+    edition_nonce: Option<u8>,
+}
+
+/// A Merkle proof of a compressed asset's leaf
+#[derive(Deserialize)]
+struct AssetProof {
+    root: String,
+    proof: Vec<String>,
+}
+
+/// Who owns an asset and whether it is burned
+pub(crate) struct AssetState {
+    pub owner: Option<Pubkey>,
+    pub burnt: bool,
+}
+
+/// A compressed asset's leaf as the DAS API indexes it, with a proof of it
+/// against the tree's current root
+pub(crate) struct CompressedLeaf {
+    pub id: Pubkey,
+    pub tree: Pubkey,
+    /// The leaf's nonce, which is also its index in the tree
+    pub leaf_id: u64,
+    pub owner: Pubkey,
+    /// The leaf delegate, which is the owner when none is set
+    pub delegate: Pubkey,
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub root: [u8; 32],
+    /// Sibling nodes from the leaf up to the root
+    pub proof: Vec<Pubkey>,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub creators: Vec<Creator>,
+    pub collection: Option<Collection>,
+}
+
+#[derive(Deserialize)]
+struct AssetGroup {
+    group_key: String,
+    group_value: Option<String>,
+    /// Only reported by providers that index unverified collections
+    verified: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct AssetRoyalty {
+    basis_points: u16,
+    #[serde(default)]
+    primary_sale_happened: bool,
+}
+
+#[derive(Deserialize)]
+struct AssetCreator {
+    address: String,
+    share: u8,
+    verified: bool,
+}
+
+impl MetadataClient {
+    /// Fetch an asset, compressed or not, from the DAS API: the DAS
+    /// endpoint when there is one, or else the RPC node
+    pub async fn fetch_asset(&self, id: &Pubkey) -> Result<MetadataInfo> {
+        Ok(self.get_asset(id).await?.info())
+    }
+
+    /// Who owns an asset and whether it is burned, as the DAS API reports
+    /// it
+    pub(crate) async fn fetch_asset_state(&self, id: &Pubkey) -> Result<AssetState> {
+        let asset = self.get_asset(id).await?;
+        Ok(AssetState {
+            owner: asset
+                .ownership
+                .and_then(|ownership| Pubkey::from_str(&ownership.owner).ok()),
+            burnt: asset.burnt,
+        })
+    }
+
+    async fn get_asset(&self, id: &Pubkey) -> Result<Asset> {
+        let das = self.das().unwrap_or(self.rpc());
+        das.send(
+            RpcRequest::Custom { method: "getAsset" },
+            json!({ "id": id.to_string() }),
+        )
+        .await
+        .with_context(|| format!("DAS getAsset failed for {}", id))
+    }
+
+    /// Fetch a compressed asset's leaf and a proof of it from the DAS API,
+    /// as Bubblegum instructions on the asset need them
+    pub(crate) async fn fetch_compressed_leaf(&self, id: &Pubkey) -> Result<CompressedLeaf> {
+        let asset = self.get_asset(id).await?;
+        if asset.burnt {
+            anyhow::bail!("Asset {} has been burned", id);
+        }
+        let compression = asset
+            .compression
+            .as_ref()
+            .filter(|compression| compression.compressed)
+            .with_context(|| format!("Asset {} isn't compressed", id))?;
+        let ownership = asset
+            .ownership
+            .as_ref()
+            .with_context(|| format!("DAS reports no owner for asset {}", id))?;
+        let proof: AssetProof = self
+            .das()
+            .unwrap_or(self.rpc())
+            .send(
+                RpcRequest::Custom {
+                    method: "getAssetProof",
+                },
+                json!({ "id": id.to_string() }),
+            )
+            .await
+            .with_context(|| format!("DAS getAssetProof failed for {}", id))?;
+
+        let owner = Pubkey::from_str(&ownership.owner).context("DAS reported an invalid owner")?;
+        let delegate = match ownership.delegate.as_deref() {
+            Some(delegate) => {
+                Pubkey::from_str(delegate).context("DAS reported an invalid delegate")?
+            }
+            None => owner,
+        };
+        let creators = asset
+            .creators
+            .iter()
+            .map(|creator| {
+                Ok(Creator {
+                    address: Pubkey::from_str(&creator.address)
+                        .context("DAS reported an invalid creator")?,
+                    verified: creator.verified,
+                    share: creator.share,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let collection = asset
+            .grouping
+            .iter()
+            .find(|group| group.group_key == "collection")
+            .and_then(|group| Some((group.group_value.as_deref()?, group.verified)))
+            .map(|(key, verified)| -> Result<_> {
+                Ok(Collection {
+                    key: Pubkey::from_str(key).context("DAS reported an invalid collection")?,
+                    verified: verified.unwrap_or(true),
+                })
+            })
+            .transpose()?;
+        Ok(CompressedLeaf {
+            id: *id,
+            tree: Pubkey::from_str(&compression.tree).context("DAS reported an invalid tree")?,
+            leaf_id: compression.leaf_id,
+            owner,
+            delegate,
+            data_hash: hash(&compression.data_hash, "data hash")?,
+            creator_hash: hash(&compression.creator_hash, "creator hash")?,
+            root: hash(&proof.root, "root")?,
+            proof: proof
+                .proof
+                .iter()
+                .map(|node| Pubkey::from_str(node).context("DAS reported an invalid proof"))
+                .collect::<Result<_>>()?,
+            name: asset.content.metadata.name,
+            symbol: asset.content.metadata.symbol,
+            uri: asset.content.json_uri,
+            seller_fee_basis_points: asset.royalty.as_ref().map_or(0, |r| r.basis_points),
+            primary_sale_happened: asset
+                .royalty
+                .as_ref()
+                .is_some_and(|r| r.primary_sale_happened),
+            is_mutable: asset.mutable,
+            edition_nonce: asset.supply.and_then(|supply| supply.edition_nonce),
+            creators,
+            collection,
+        })
+    }
+}
+
+/// Decode a base58 hash reported by the DAS API
+fn hash(encoded: &str, what: &str) -> Result<[u8; 32]> {
+    bs58::decode(encoded)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .with_context(|| format!("DAS reported an invalid {} '{}'", what, encoded))
+}
+
+/// Every asset in a collection, each with whether its collection is
+/// verified
+pub(crate) async fn collection_items(
+    das: &RpcClient,
+    collection: &Pubkey,
+) -> Result<Vec<(MetadataSummary, bool)>> {
+    let collection = collection.to_string();
+    let params = json!({
+        "groupKey": "collection",
+        "groupValue": collection,
+        "options": { "showUnverifiedCollections": true },
+    });
+    let assets = search(das, "getAssetsByGroup", params).await?;
+    Ok(assets
+        .into_iter()
+        .filter_map(|asset| {
+            let verified = asset
+                .collection(&collection)
+                .is_none_or(|group| group.verified.unwrap_or(true));
+            Some((asset.summary()?, verified))
+        })
+        .collect())
+}
+
+/// Every asset whose update authority is `authority`
+pub(crate) async fn assets_by_authority(
+    das: &RpcClient,
+    authority: &Pubkey,
+) -> Result<Vec<MetadataSummary>> {
+    let authority = authority.to_string();
+    let params = json!({ "authorityAddress": authority });
+    let assets = search(das, "getAssetsByAuthority", params).await?;
+    // The search matches any of an asset's authorities; the update
+    // authority is the first
+    Ok(assets
+        .into_iter()
+        .filter_map(Asset::summary)
+        .filter(|summary| summary.update_authority == authority)
+        .collect())
+}
+
+/// Every asset listing `creator`, only those it verified with
+/// `verified_only`
+pub(crate) async fn assets_by_creator(
+    das: &RpcClient,
+    creator: &Pubkey,
+    verified_only: bool,
+) -> Result<Vec<MetadataSummary>> {
+    let params = json!({
+        "creatorAddress": creator.to_string(),
+        "onlyVerified": verified_only,
+    });
+    let assets = search(das, "getAssetsByCreator", params).await?;
+    Ok(assets.into_iter().filter_map(Asset::summary).collect())
+}
+
+/// Run a paginated DAS search, collecting every page
+async fn search(das: &RpcClient, method: &'static str, mut params: Value) -> Result<Vec<Asset>> {
+    let mut assets = Vec::new();
+    for page in 1.. {
+        params["page"] = json!(page);
+        params["limit"] = json!(PAGE_LIMIT);
+        let response: AssetPage = das
+            .send(RpcRequest::Custom { method }, params.clone())
+            .await
+            .with_context(|| format!("DAS {} failed on page {}", method, page))?;
+        let count = response.items.len();
+        assets.extend(response.items);
+        if count < PAGE_LIMIT {
+            break;
+        }
+    }
+    Ok(assets)
+}
+
+impl Asset {
+    fn is_compressed(&self) -> bool {
+        self.compression.as_ref().is_some_and(|c| c.compressed)
+    }
+
+    /// The asset's entry for a collection, if it names it
+    fn collection(&self, collection: &str) -> Option<&AssetGroup> {
+        self.grouping.iter().find(|group| {
+            group.group_key == "collection" && group.group_value.as_deref() == Some(collection)
+        })
+    }
+
+    /// A compressed asset has no metadata account, so no address for it
+    fn metadata_pda(&self, mint: &Pubkey) -> String {
+        if self.is_compressed() {
+            String::new()
+        } else {
+            find_metadata_pda(mint).to_string()
+        }
+    }
+
+    fn update_authority(&self) -> String {
+        self.authorities
+            .first()
+            .map(|authority| authority.address.clone())
+            .unwrap_or_default()
+    }
+
+    /// The asset as a metadata summary, if its id is an address
+    fn summary(self) -> Option<MetadataSummary> {
+        let mint = Pubkey::from_str(&self.id).ok()?;
+        Some(MetadataSummary {
+            metadata_pda: self.metadata_pda(&mint),
+            update_authority: self.update_authority(),
+            compressed: self.is_compressed(),
+            mint: self.id,
+            name: self.content.metadata.name,
+            symbol: self.content.metadata.symbol,
+            uri: self.content.json_uri,
+        })
+    }
+
+    /// The asset in the same shape as decoded metadata. DAS doesn't report
+    /// uses.
+    fn info(self) -> MetadataInfo {
+        let metadata_pda = Pubkey::from_str(&self.id)
+            .map(|mint| self.metadata_pda(&mint))
+            .unwrap_or_default();
+        let update_authority = self.update_authority();
+        let collection = self
+            .grouping
+            .iter()
+            .find(|group| group.group_key == "collection")
+            .and_then(|group| {
+                Some(CollectionInfo {
+                    key: group.group_value.clone()?,
+                    verified: group.verified.unwrap_or(true),
+                })
+            });
+        let compression = self
+            .compression
+            .filter(|compression| compression.compressed)
+            .map(|compression| CompressionInfo {
+                tree: compression.tree,
+                leaf_id: compression.leaf_id,
+            });
+        MetadataInfo {
+            mint: self.id,
+            metadata_pda,
+            name: self.content.metadata.name,
+            symbol: self.content.metadata.symbol,
+            uri: self.content.json_uri,
+            seller_fee_basis_points: self.royalty.as_ref().map_or(0, |r| r.basis_points),
+            update_authority,
+            is_mutable: self.mutable,
+            primary_sale_happened: self
+                .royalty
+                .as_ref()
+                .is_some_and(|r| r.primary_sale_happened),
+            token_standard: self.content.metadata.token_standard,
+            creators: (!self.creators.is_empty()).then(|| {
+                self.creators
+                    .into_iter()
+                    .map(|creator| CreatorInfo {
+                        address: creator.address,
+                        verified: creator.verified,
+                        share: creator.share,
+                    })
+                    .collect()
+            }),
+            collection,
+            uses: None,
+            compression,
+            offchain: None,
+            offchain_error: None,
+            delegates: None,
+        }
+    }
+}
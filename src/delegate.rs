@@ -0,0 +1,464 @@
+//! Delegates: metadata delegates, which let another key update parts of a
+//! mint's metadata on the update authority's behalf, and token delegates,
+//! which let another key act on an owner's tokens.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{
+    ID as TOKEN_METADATA_PROGRAM_ID,
+    accounts::{MetadataDelegateRecord, TokenRecord},
+    instructions::{DelegateBuilder, RevokeBuilder},
+    types::{DelegateArgs, Key, ProgrammableConfig, RevokeArgs, TokenStandard},
+};
+use solana_client::rpc_config::RpcProgramAccountsConfig;
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account_client::address::get_associated_token_address_with_program_id;
+
+use crate::client::{MetadataClient, ensure_update_authority};
+use crate::pda::{
+    find_master_edition_pda, find_metadata_delegate_record_pda, find_metadata_pda,
+    find_token_record_pda,
+};
+use crate::ruleset::TOKEN_AUTH_RULES_PROGRAM_ID;
+use crate::types::{DelegateInfo, DelegateResult, DelegateRole};
+
+/// Offset of the mint in a metadata delegate record, after the key and bump
+const RECORD_MINT_OFFSET: usize = 2;
+
+impl MetadataClient {
+    /// Approve `delegate` for a metadata `role` on a mint's metadata. The
+    /// authority must be the update authority, and pays for the delegate
+    /// record.
+    pub async fn approve_metadata_delegate(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        role: DelegateRole,
+        delegate: &Pubkey,
+    ) -> Result<DelegateResult> {
+        let delegate_args = match role {
+            DelegateRole::Data => DelegateArgs::DataV1 {
+                authorization_data: None,
+            },
+            DelegateRole::Collection => DelegateArgs::CollectionV1 {
+                authorization_data: None,
+            },
+            DelegateRole::ProgrammableConfig => DelegateArgs::ProgrammableConfigV1 {
+                authorization_data: None,
+            },
+            _ => anyhow::bail!("{} is a token delegate role, not a metadata one", role),
+        };
+        let accounts = self
+            .metadata_delegate_accounts(authority, mint, role, delegate)
+            .await?;
+        if let Some(record) = accounts.delegate_record
+            && self.rpc().get_account(&record).await.is_ok()
+        {
+            anyhow::bail!("{} is already a {} delegate of {}", delegate, role, mint);
+        }
+
+        let signature = self
+            .send(
+                &[accounts.delegate_instruction(delegate_args)],
+                authority,
+                &[],
+            )
+            .await
+            .context("Failed to send delegate transaction")?;
+
+        Ok(accounts.result(role, None, signature.to_string()))
+    }
+
+    /// Revoke a metadata delegate, closing its record and returning the rent
+    /// to the authority
+    pub async fn revoke_metadata_delegate(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        role: DelegateRole,
+        delegate: &Pubkey,
+    ) -> Result<DelegateResult> {
+        let revoke_args = match role {
+            DelegateRole::Data => RevokeArgs::DataV1,
+            DelegateRole::Collection => RevokeArgs::CollectionV1,
+            DelegateRole::ProgrammableConfig => RevokeArgs::ProgrammableConfigV1,
+            _ => anyhow::bail!("{} is a token delegate role, not a metadata one", role),
+        };
+        let accounts = self
+            .metadata_delegate_accounts(authority, mint, role, delegate)
+            .await?;
+        if let Some(record) = accounts.delegate_record
+            && self.rpc().get_account(&record).await.is_err()
+        {
+            anyhow::bail!("{} is not a {} delegate of {}", delegate, role, mint);
+        }
+
+        let signature = self
+            .send(&[accounts.revoke_instruction(revoke_args)], authority, &[])
+            .await
+            .context("Failed to send revoke transaction")?;
+
+        Ok(accounts.result(role, None, signature.to_string()))
+    }
+
+    /// Approve `delegate` for a token `role` on the owner's tokens of a mint.
+    /// Programmable NFTs take the transfer, sale, utility and staking roles,
+    /// recorded in their token record and checked against their rule set;
+    /// other tokens take the standard role, an SPL Token approval of
+    /// `amount` base units.
+    pub async fn approve_token_delegate(
+        &self,
+        owner: &dyn Signer,
+        mint: &Pubkey,
+        role: DelegateRole,
+        delegate: &Pubkey,
+        amount: u64,
+    ) -> Result<DelegateResult> {
+        let accounts = self
+            .token_delegate_accounts(owner, mint, role, delegate)
+            .await?;
+        // A pNFT is a single token, delegated as a whole
+        let amount = if accounts.token_record.is_some() {
+            1
+        } else {
+            amount
+        };
+        if let Some(token_record) = accounts.token_record {
+            let record = self.fetch_token_record(&token_record).await?;
+            if let Some(existing) = record.delegate {
+                anyhow::bail!(
+                    "The token already has a {:?} delegate ({}); revoke it first",
+                    record.delegate_role.context("Token record has no role")?,
+                    existing
+                );
+            }
+        }
+        let balance = self
+            .token_balance(&accounts.token.context("No token account")?)
+            .await?;
+        if balance < amount {
+            anyhow::bail!(
+                "The token account holds {} tokens, fewer than the {} to delegate",
+                balance,
+                amount
+            );
+        }
+
+        let delegate_args = match role {
+            DelegateRole::Transfer => DelegateArgs::TransferV1 {
+                amount,
+                authorization_data: None,
+            },
+            DelegateRole::Sale => DelegateArgs::SaleV1 {
+                amount,
+                authorization_data: None,
+            },
+            DelegateRole::Utility => DelegateArgs::UtilityV1 {
+                amount,
+                authorization_data: None,
+            },
+            DelegateRole::Staking => DelegateArgs::StakingV1 {
+                amount,
+                authorization_data: None,
+            },
+            DelegateRole::Standard => DelegateArgs::StandardV1 { amount },
+            _ => anyhow::bail!("{} is a metadata delegate role, not a token one", role),
+        };
+
+        let signature = self
+            .send(&[accounts.delegate_instruction(delegate_args)], owner, &[])
+            .await
+            .context("Failed to send delegate transaction")?;
+
+        Ok(accounts.result(role, Some(amount), signature.to_string()))
+    }
+
+    /// Revoke the token delegate of the owner's tokens of a mint, clearing
+    /// it from the pNFT token record
+    pub async fn revoke_token_delegate(
+        &self,
+        owner: &dyn Signer,
+        mint: &Pubkey,
+        role: DelegateRole,
+        delegate: &Pubkey,
+    ) -> Result<DelegateResult> {
+        let accounts = self
+            .token_delegate_accounts(owner, mint, role, delegate)
+            .await?;
+        if let Some(token_record) = accounts.token_record {
+            let record = self.fetch_token_record(&token_record).await?;
+            if record.delegate != Some(*delegate) {
+                anyhow::bail!("{} is not the token delegate of {}", delegate, mint);
+            }
+        }
+
+        let revoke_args = match role {
+            DelegateRole::Transfer => RevokeArgs::TransferV1,
+            DelegateRole::Sale => RevokeArgs::SaleV1,
+            DelegateRole::Utility => RevokeArgs::UtilityV1,
+            DelegateRole::Staking => RevokeArgs::StakingV1,
+            DelegateRole::Standard => RevokeArgs::StandardV1,
+            _ => anyhow::bail!("{} is a metadata delegate role, not a token one", role),
+        };
+
+        let signature = self
+            .send(&[accounts.revoke_instruction(revoke_args)], owner, &[])
+            .await
+            .context("Failed to send revoke transaction")?;
+
+        Ok(accounts.result(role, None, signature.to_string()))
+    }
+
+    /// Find the metadata delegate records of a mint. Records don't store
+    /// their role, so it is recovered by re-deriving each role's address.
+    pub async fn fetch_metadata_delegates(&self, mint: &Pubkey) -> Result<Vec<DelegateInfo>> {
+        let filters = vec![
+            RpcFilterType::DataSize(MetadataDelegateRecord::LEN as u64),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                &[Key::MetadataDelegate as u8],
+            )),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                RECORD_MINT_OFFSET,
+                mint.as_ref(),
+            )),
+        ];
+        let accounts = self
+            .rpc()
+            .get_program_accounts_with_config(
+                &TOKEN_METADATA_PROGRAM_ID,
+                RpcProgramAccountsConfig {
+                    filters: Some(filters),
+                    ..Default::default()
+                },
+            )
+            .await
+            .context("Failed to fetch metadata delegate records")?;
+
+        let mut delegates = Vec::new();
+        for (address, account) in accounts {
+            let Ok(record) = MetadataDelegateRecord::from_bytes(&account.data) else {
+                continue;
+            };
+            let role = DelegateRole::METADATA_ROLES.into_iter().find(|role| {
+                role.metadata_delegate_role().is_some_and(|seed| {
+                    find_metadata_delegate_record_pda(
+                        mint,
+                        seed,
+                        &record.update_authority,
+                        &record.delegate,
+                    ) == address
+                })
+            });
+            if let Some(role) = role {
+                delegates.push(DelegateInfo {
+                    role,
+                    delegate: record.delegate.to_string(),
+                    delegate_record: address.to_string(),
+                    update_authority: record.update_authority.to_string(),
+                });
+            }
+        }
+        Ok(delegates)
+    }
+
+    /// Fetch and decode a pNFT token record
+    pub async fn fetch_token_record(&self, token_record: &Pubkey) -> Result<TokenRecord> {
+        let data = self
+            .rpc()
+            .get_account_data(token_record)
+            .await
+            .with_context(|| format!("Failed to fetch token record {}", token_record))?;
+        TokenRecord::from_bytes(&data)
+            .map_err(|e| anyhow::anyhow!("Failed to deserialize token record: {}", e))
+    }
+
+    /// Check the authority and gather the accounts of a metadata delegate
+    async fn metadata_delegate_accounts(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        role: DelegateRole,
+        delegate: &Pubkey,
+    ) -> Result<DelegateAccounts> {
+        let metadata = self.fetch_fresh(mint).await?;
+        ensure_update_authority(&metadata, &authority.pubkey())?;
+        if role == DelegateRole::ProgrammableConfig
+            && metadata.token_standard != Some(TokenStandard::ProgrammableNonFungible)
+        {
+            anyhow::bail!(
+                "Mint {} is not a programmable NFT; programmable-config delegates only apply to pNFTs",
+                mint
+            );
+        }
+        let seed = role
+            .metadata_delegate_role()
+            .with_context(|| format!("{} is not a metadata delegate role", role))?;
+
+        Ok(DelegateAccounts {
+            delegate_record: Some(find_metadata_delegate_record_pda(
+                mint,
+                seed,
+                &authority.pubkey(),
+                delegate,
+            )),
+            ..self
+                .common_delegate_accounts(authority, mint, delegate)
+                .await?
+        })
+    }
+
+    /// Check the token standard and gather the accounts of a token delegate
+    /// of the owner's associated token account
+    async fn token_delegate_accounts(
+        &self,
+        owner: &dyn Signer,
+        mint: &Pubkey,
+        role: DelegateRole,
+        delegate: &Pubkey,
+    ) -> Result<DelegateAccounts> {
+        if !role.is_token_role() {
+            anyhow::bail!("{} is a metadata delegate role, not a token one", role);
+        }
+        let metadata = self.fetch_fresh(mint).await?;
+        let programmable = metadata.token_standard == Some(TokenStandard::ProgrammableNonFungible);
+        match (programmable, role) {
+            (true, DelegateRole::Standard) => anyhow::bail!(
+                "Mint {} is a programmable NFT; use the transfer, sale, utility or staking role",
+                mint
+            ),
+            (false, role) if role != DelegateRole::Standard => anyhow::bail!(
+                "The {} role only applies to programmable NFTs; use the standard role for {}",
+                role,
+                mint
+            ),
+            _ => {}
+        }
+
+        let accounts = self.common_delegate_accounts(owner, mint, delegate).await?;
+        let token = get_associated_token_address_with_program_id(
+            &owner.pubkey(),
+            mint,
+            &accounts.token_program,
+        );
+        let rule_set = match metadata.programmable_config {
+            Some(ProgrammableConfig::V1 { rule_set }) => rule_set,
+            None => None,
+        };
+        Ok(DelegateAccounts {
+            delegate_record: None,
+            token: Some(token),
+            token_record: programmable.then(|| find_token_record_pda(mint, &token)),
+            rule_set,
+            ..accounts
+        })
+    }
+
+    /// The accounts every delegate instruction needs
+    async fn common_delegate_accounts(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        delegate: &Pubkey,
+    ) -> Result<DelegateAccounts> {
+        let master_edition = find_master_edition_pda(mint);
+        Ok(DelegateAccounts {
+            delegate_record: None,
+            delegate: *delegate,
+            metadata: find_metadata_pda(mint),
+            master_edition: self
+                .rpc()
+                .get_account(&master_edition)
+                .await
+                .ok()
+                .map(|_| master_edition),
+            token_record: None,
+            mint: *mint,
+            token: None,
+            authority: authority.pubkey(),
+            payer: self.rent_payer(&authority.pubkey()),
+            token_program: self.fetch_mint(mint).await?.program.id(),
+            rule_set: None,
+        })
+    }
+
+    /// Base units held by a token account
+    pub(crate) async fn token_balance(&self, token: &Pubkey) -> Result<u64> {
+        let balance = self
+            .rpc()
+            .get_token_account_balance(token)
+            .await
+            .with_context(|| format!("Failed to fetch token account {}", token))?;
+        balance
+            .amount
+            .parse()
+            .context("Invalid token account balance")
+    }
+}
+
+/// Accounts of the unified Delegate and Revoke instructions
+struct DelegateAccounts {
+    delegate_record: Option<Pubkey>,
+    delegate: Pubkey,
+    metadata: Pubkey,
+    master_edition: Option<Pubkey>,
+    token_record: Option<Pubkey>,
+    mint: Pubkey,
+    token: Option<Pubkey>,
+    authority: Pubkey,
+    /// Funds the delegate record or token record changes
+    payer: Pubkey,
+    token_program: Pubkey,
+    rule_set: Option<Pubkey>,
+}
+
+impl DelegateAccounts {
+    fn delegate_instruction(&self, args: DelegateArgs) -> Instruction {
+        DelegateBuilder::new()
+            .delegate_record(self.delegate_record)
+            .delegate(self.delegate)
+            .metadata(self.metadata)
+            .master_edition(self.master_edition)
+            .token_record(self.token_record)
+            .mint(self.mint)
+            .token(self.token)
+            .authority(self.authority)
+            .payer(self.payer)
+            .spl_token_program(Some(self.token_program))
+            .authorization_rules_program(self.rule_set.map(|_| TOKEN_AUTH_RULES_PROGRAM_ID))
+            .authorization_rules(self.rule_set)
+            .delegate_args(args)
+            .instruction()
+    }
+
+    fn revoke_instruction(&self, args: RevokeArgs) -> Instruction {
+        RevokeBuilder::new()
+            .delegate_record(self.delegate_record)
+            .delegate(self.delegate)
+            .metadata(self.metadata)
+            .master_edition(self.master_edition)
+            .token_record(self.token_record)
+            .mint(self.mint)
+            .token(self.token)
+            .authority(self.authority)
+            .payer(self.payer)
+            .spl_token_program(Some(self.token_program))
+            .authorization_rules_program(self.rule_set.map(|_| TOKEN_AUTH_RULES_PROGRAM_ID))
+            .authorization_rules(self.rule_set)
+            .revoke_args(args)
+            .instruction()
+    }
+
+    fn result(&self, role: DelegateRole, amount: Option<u64>, signature: String) -> DelegateResult {
+        DelegateResult {
+            mint: self.mint.to_string(),
+            role,
+            delegate: self.delegate.to_string(),
+            delegate_record: self.delegate_record.map(|record| record.to_string()),
+            token_account: self.token.map(|token| token.to_string()),
+            token_record: self.token_record.map(|record| record.to_string()),
+            amount,
+            signature,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Compare on-chain metadata against an expected local description.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::path::Path;
+
+use crate::{DiffResult, FieldDiff, MetadataClient, MetadataInfo};
+
+/// The metadata a mint is expected to have. Only the fields that are
+/// present are compared.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ExpectedMetadata {
+    #[serde(default)]
+    pub mint: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub uri: Option<String>,
+    #[serde(default)]
+    pub seller_fee_basis_points: Option<u16>,
+    #[serde(default)]
+    pub update_authority: Option<String>,
+    #[serde(default)]
+    pub is_mutable: Option<bool>,
+    /// An empty list expects no creators
+    #[serde(default)]
+    pub creators: Option<Vec<ExpectedCreator>>,
+    /// The collection mint; an empty string expects no collection
+    #[serde(default)]
+    pub collection: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ExpectedCreator {
+    pub address: String,
+    pub share: u8,
+}
+
+/// Load the expected metadata for `mint` from a JSON file. The file holds
+/// either a single object or a manifest array, in which case the entry with
+/// a matching `mint` is used.
+pub fn load_expected(path: impl AsRef<Path>, mint: &Pubkey) -> Result<ExpectedMetadata> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read '{}'", path.display()))?;
+    let json: Value = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse '{}'", path.display()))?;
+    let entry = match json {
+        Value::Array(entries) => entries
+            .into_iter()
+            .find(|entry| entry.get("mint").and_then(Value::as_str) == Some(&mint.to_string()))
+            .with_context(|| format!("No entry for mint {} in '{}'", mint, path.display()))?,
+        entry => entry,
+    };
+    let expected: ExpectedMetadata = serde_json::from_value(entry)
+        .with_context(|| format!("Invalid expected metadata in '{}'", path.display()))?;
+    if let Some(expected_mint) = &expected.mint
+        && *expected_mint != mint.to_string()
+    {
+        anyhow::bail!(
+            "'{}' describes mint {}, not {}",
+            path.display(),
+            expected_mint,
+            mint
+        );
+    }
+    Ok(expected)
+}
+
+impl MetadataClient {
+    /// Compare the on-chain metadata of `mint` field by field against
+    /// `expected`
+    pub async fn diff(&self, mint: &Pubkey, expected: &ExpectedMetadata) -> Result<DiffResult> {
+        let info = MetadataInfo::from(self.fetch(mint).await?);
+        let mut differences = Vec::new();
+        let mut compare = |field: &str, expected: Option<String>, actual: String| {
+            if let Some(expected) = expected
+                && expected != actual
+            {
+                differences.push(FieldDiff {
+                    field: field.to_string(),
+                    expected,
+                    actual,
+                });
+            }
+        };
+
+        compare("name", expected.name.clone(), info.name.clone());
+        compare("symbol", expected.symbol.clone(), info.symbol.clone());
+        compare("uri", expected.uri.clone(), info.uri.clone());
+        compare(
+            "seller_fee_basis_points",
+            expected.seller_fee_basis_points.map(|fee| fee.to_string()),
+            info.seller_fee_basis_points.to_string(),
+        );
+        compare(
+            "update_authority",
+            expected.update_authority.clone(),
+            info.update_authority.clone(),
+        );
+        compare(
+            "is_mutable",
+            expected.is_mutable.map(|mutable| mutable.to_string()),
+            info.is_mutable.to_string(),
+        );
+        compare(
+            "creators",
+            expected.creators.as_ref().map(|creators| {
+                creators
+                    .iter()
+                    .map(|c| format!("{}:{}", c.address, c.share))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }),
+            info.creators
+                .iter()
+                .flatten()
+                .map(|c| format!("{}:{}", c.address, c.share))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        compare(
+            "collection",
+            expected.collection.clone(),
+            info.collection
+                .as_ref()
+                .map(|c| c.key.clone())
+                .unwrap_or_default(),
+        );
+
+        Ok(DiffResult {
+            mint: mint.to_string(),
+            matches: differences.is_empty(),
+            differences,
+        })
+    }
+}
@@ -0,0 +1,193 @@
+//! Master editions and printed editions.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{
+    accounts::MasterEdition,
+    instructions::{CreateMasterEditionV3Builder, MintNewEditionFromMasterEditionViaTokenBuilder},
+    types::MintNewEditionFromMasterEditionViaTokenArgs,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account_client::address::get_associated_token_address;
+
+use crate::client::{MetadataClient, ensure_update_authority};
+use crate::error::MetadataCliError;
+use crate::mint::mint_to_owner_instructions;
+use crate::pda::{find_edition_marker_pda, find_master_edition_pda, find_metadata_pda};
+use crate::types::{CreateMasterEditionResult, PrintEditionResult};
+
+impl MetadataClient {
+    /// Turn a mint with metadata into an NFT by creating its master edition.
+    /// The mint must have 0 decimals and a supply of exactly 1, and the signer
+    /// must be both its mint authority and the metadata's update authority.
+    /// Mint authority passes to the edition account, so no more tokens can be
+    /// minted afterwards. `max_supply` limits how many editions can be printed
+    /// (`None` means unlimited, `Some(0)` means none).
+    pub async fn create_master_edition(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        max_supply: Option<u64>,
+    ) -> Result<CreateMasterEditionResult> {
+        let metadata_pda = find_metadata_pda(mint);
+        let master_edition_pda = find_master_edition_pda(mint);
+
+        let mint_account = self.fetch_mint(mint).await?;
+        if mint_account.decimals != 0 {
+            return Err(MetadataCliError::InvalidMint {
+                mint: *mint,
+                reason: format!(
+                    "has {} decimals; a master edition requires 0",
+                    mint_account.decimals
+                ),
+            }
+            .into());
+        }
+        if mint_account.supply != 1 {
+            return Err(MetadataCliError::InvalidMint {
+                mint: *mint,
+                reason: format!(
+                    "has a supply of {}; a master edition requires exactly 1",
+                    mint_account.supply
+                ),
+            }
+            .into());
+        }
+
+        let metadata = self.fetch_fresh(mint).await?;
+        ensure_update_authority(&metadata, &authority.pubkey())?;
+
+        let mut builder = CreateMasterEditionV3Builder::new();
+        builder
+            .edition(master_edition_pda)
+            .mint(*mint)
+            .update_authority(authority.pubkey())
+            .mint_authority(authority.pubkey())
+            .payer(self.rent_payer(&authority.pubkey()))
+            .metadata(metadata_pda);
+        if let Some(max_supply) = max_supply {
+            builder.max_supply(max_supply);
+        }
+
+        let signature = self
+            .send(&[builder.instruction()], authority, &[])
+            .await
+            .context("Failed to send create master edition transaction")?;
+
+        Ok(CreateMasterEditionResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            master_edition_pda: master_edition_pda.to_string(),
+            max_supply,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Fetch and deserialize the master edition account of a mint
+    pub async fn fetch_master_edition(&self, mint: &Pubkey) -> Result<MasterEdition> {
+        let address = find_master_edition_pda(mint);
+        let account = self
+            .rpc()
+            .get_account_with_commitment(&address, self.rpc().commitment())
+            .await
+            .map_err(MetadataCliError::rpc("fetch master edition account"))?
+            .value
+            .ok_or(MetadataCliError::AccountNotFound {
+                kind: "Master edition",
+                address,
+            })?;
+        MasterEdition::from_bytes(&account.data).map_err(|e| {
+            MetadataCliError::InvalidAccountData {
+                kind: "master edition",
+                address,
+                reason: e.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Print a numbered edition of a master edition into a new mint. The
+    /// owner must hold the master edition token; the new edition is minted to
+    /// the owner's wallet. When `edition` is `None` the next unprinted number
+    /// (current supply + 1) is used.
+    pub async fn print_edition(
+        &self,
+        owner: &dyn Signer,
+        new_mint: &dyn Signer,
+        master_mint: &Pubkey,
+        edition: Option<u64>,
+    ) -> Result<PrintEditionResult> {
+        let owner_pubkey = owner.pubkey();
+        let new_mint_pubkey = new_mint.pubkey();
+
+        let master_metadata = self.fetch_fresh(master_mint).await?;
+        let master_edition = self.fetch_master_edition(master_mint).await?;
+
+        let edition = edition.unwrap_or(master_edition.supply + 1);
+        if let Some(max_supply) = master_edition.max_supply
+            && master_edition.supply >= max_supply
+        {
+            anyhow::bail!(
+                "Master edition {} has already printed all {} editions",
+                master_mint,
+                max_supply
+            );
+        }
+
+        let new_metadata_pda = find_metadata_pda(&new_mint_pubkey);
+        let new_edition_pda = find_master_edition_pda(&new_mint_pubkey);
+
+        let mut instructions = self
+            .create_mint_instructions(
+                &owner_pubkey,
+                &new_mint_pubkey,
+                &owner_pubkey,
+                Some(&owner_pubkey),
+                0,
+            )
+            .await?;
+
+        let (token_account, mint_to) = mint_to_owner_instructions(
+            &self.rent_payer(&owner_pubkey),
+            &new_mint_pubkey,
+            &owner_pubkey,
+            &owner_pubkey,
+            1,
+        )?;
+        instructions.extend(mint_to);
+
+        instructions.push(
+            MintNewEditionFromMasterEditionViaTokenBuilder::new()
+                .new_metadata(new_metadata_pda)
+                .new_edition(new_edition_pda)
+                .master_edition(find_master_edition_pda(master_mint))
+                .new_mint(new_mint_pubkey)
+                .edition_mark_pda(find_edition_marker_pda(master_mint, edition))
+                .new_mint_authority(owner_pubkey)
+                .payer(self.rent_payer(&owner_pubkey))
+                .token_account_owner(owner_pubkey)
+                .token_account(get_associated_token_address(&owner_pubkey, master_mint))
+                .new_metadata_update_authority(master_metadata.update_authority)
+                .metadata(find_metadata_pda(master_mint))
+                .mint_new_edition_from_master_edition_via_token_args(
+                    MintNewEditionFromMasterEditionViaTokenArgs { edition },
+                )
+                .instruction(),
+        );
+
+        let signature = self
+            .send(&instructions, owner, &[new_mint])
+            .await
+            .context("Failed to send print edition transaction")?;
+
+        Ok(PrintEditionResult {
+            master_mint: master_mint.to_string(),
+            mint: new_mint_pubkey.to_string(),
+            metadata_pda: new_metadata_pda.to_string(),
+            edition_pda: new_edition_pda.to_string(),
+            token_account: token_account.to_string(),
+            edition,
+            max_supply: master_edition.max_supply,
+            signature: signature.to_string(),
+        })
+    }
+}
@@ -0,0 +1,156 @@
+//! Failures that callers of the library may want to tell apart.
+//!
+//! Methods still return [`anyhow::Result`], with context added along the
+//! way; the cause can be recovered with
+//! `err.downcast_ref::<MetadataCliError>()`, or by walking `err.chain()`.
+
+use solana_client::client_error::ClientError;
+use solana_sdk::{pubkey::Pubkey, transaction::TransactionError};
+use std::fmt;
+
+#[derive(Debug)]
+pub enum MetadataCliError {
+    /// An RPC request failed: the node was unreachable, timed out or
+    /// answered with an error
+    Rpc {
+        /// What the request was for, e.g. "fetch metadata account"
+        action: &'static str,
+        source: Box<ClientError>,
+    },
+    /// An account that should exist doesn't
+    AccountNotFound {
+        /// What the account holds, e.g. "Metadata" or "Mint"
+        kind: &'static str,
+        address: Pubkey,
+    },
+    /// An account exists but doesn't hold what it should
+    InvalidAccountData {
+        kind: &'static str,
+        address: Pubkey,
+        reason: String,
+    },
+    /// The signer isn't the mint authority of the mint, or the mint has none
+    NotMintAuthority {
+        signer: Pubkey,
+        mint: Pubkey,
+        mint_authority: Option<Pubkey>,
+    },
+    /// The signer isn't the update authority of the metadata
+    NotUpdateAuthority {
+        signer: Pubkey,
+        update_authority: Pubkey,
+    },
+    /// The signer is neither the creator nor the delegate of a compressed
+    /// NFT tree that only they can mint into or update
+    NotTreeAuthority {
+        signer: Pubkey,
+        tree: Pubkey,
+        tree_creator: Pubkey,
+        tree_delegate: Pubkey,
+    },
+    /// The signer isn't in the creators list of the metadata
+    NotACreator { creator: Pubkey, mint: Pubkey },
+    /// The metadata can no longer be changed
+    Immutable {
+        /// The mint, or asset ID of a compressed NFT
+        address: Pubkey,
+    },
+    /// A mint's decimals, supply or authorities don't allow the operation
+    InvalidMint { mint: Pubkey, reason: String },
+    /// A metadata field breaks one of the Token Metadata program's rules
+    InvalidField { field: &'static str, reason: String },
+    /// Simulating a transaction before sending it failed
+    SimulationFailed {
+        error: TransactionError,
+        logs: Vec<String>,
+    },
+}
+
+impl MetadataCliError {
+    /// An [`Rpc`](Self::Rpc) error for a failed `action`, for `map_err`
+    pub fn rpc(action: &'static str) -> impl FnOnce(ClientError) -> Self {
+        move |source| Self::Rpc {
+            action,
+            source: Box::new(source),
+        }
+    }
+}
+
+impl fmt::Display for MetadataCliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // The client error follows as the source
+            Self::Rpc { action, .. } => write!(f, "Failed to {}", action),
+            Self::AccountNotFound { kind, address } => {
+                write!(f, "{} account {} not found", kind, address)
+            }
+            Self::InvalidAccountData {
+                kind,
+                address,
+                reason,
+            } => write!(f, "{} is not a valid {} account: {}", address, kind, reason),
+            Self::NotMintAuthority {
+                signer,
+                mint,
+                mint_authority: Some(mint_authority),
+            } => write!(
+                f,
+                "Keypair {} is not the mint authority of {} (expected {})",
+                signer, mint, mint_authority
+            ),
+            Self::NotMintAuthority {
+                mint,
+                mint_authority: None,
+                ..
+            } => write!(
+                f,
+                "Mint {} has no mint authority, so no metadata can be created for it",
+                mint
+            ),
+            Self::NotUpdateAuthority {
+                signer,
+                update_authority,
+            } => write!(
+                f,
+                "Keypair {} is not the update authority (expected {})",
+                signer, update_authority
+            ),
+            Self::NotTreeAuthority {
+                signer,
+                tree,
+                tree_creator,
+                tree_delegate,
+            } => write!(
+                f,
+                "Keypair {} is not the creator ({}) or delegate ({}) of tree {}",
+                signer, tree_creator, tree_delegate, tree
+            ),
+            Self::NotACreator { creator, mint } => {
+                write!(
+                    f,
+                    "{} is not in the creators list of mint {}",
+                    creator, mint
+                )
+            }
+            Self::Immutable { address } => write!(f, "The metadata of {} is immutable", address),
+            Self::InvalidMint { mint, reason } => write!(f, "Mint {} {}", mint, reason),
+            Self::InvalidField { field, reason } => write!(f, "Invalid {}: {}", field, reason),
+            Self::SimulationFailed { error, logs } => {
+                write!(f, "Transaction simulation failed: {}", error)?;
+                for line in logs {
+                    write!(f, "\n{}", line)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for MetadataCliError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Rpc { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Working out what a batch job will cost before any of it is sent.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::instructions::CreateMetadataAccountV3Builder;
+use mpl_token_metadata::types::DataV2;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::batch::ManifestEntry;
+use crate::client::{MetadataClient, data_from_args};
+use crate::error::MetadataCliError;
+use crate::irys::Irys;
+use crate::pda::find_metadata_pda;
+use crate::types::{BatchEstimate, InvalidRow};
+
+/// Fee of each signature, before any priority fee
+const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+
+impl MetadataClient {
+    /// Estimate the cost of `batch create` for a manifest paid by `payer`:
+    /// the rent of each row's metadata account, sized to its contents, one
+    /// transaction fee per row at the current priority fee, and the Irys
+    /// price of uploading `uploads` (files, or directories of files) to
+    /// Arweave. Rows with an invalid mint or fields are left out of the
+    /// totals and listed instead.
+    pub async fn estimate_batch_create(
+        &self,
+        payer: &Pubkey,
+        entries: &[ManifestEntry],
+        uploads: &[PathBuf],
+    ) -> Result<BatchEstimate> {
+        let mut invalid = Vec::new();
+        let mut sizes = Vec::new();
+        let mut sample = None;
+        for (i, entry) in entries.iter().enumerate() {
+            match metadata_size(payer, entry) {
+                Ok(size) => {
+                    sizes.push(size);
+                    if sample.is_none() {
+                        sample = Some(entry);
+                    }
+                }
+                Err(err) => invalid.push(InvalidRow {
+                    row: i + 1,
+                    mint: entry.mint.clone(),
+                    error: format!("{:#}", err),
+                }),
+            }
+        }
+
+        // Rent only depends on the size, and most rows share a few sizes
+        let mut rent_by_size: HashMap<usize, u64> = HashMap::new();
+        let mut rent_lamports = 0;
+        for size in &sizes {
+            let rent = match rent_by_size.get(size) {
+                Some(rent) => *rent,
+                None => {
+                    let rent = self
+                        .rpc()
+                        .get_minimum_balance_for_rent_exemption(*size)
+                        .await
+                        .map_err(MetadataCliError::rpc("fetch rent-exempt minimum"))?;
+                    rent_by_size.insert(*size, rent);
+                    rent
+                }
+            };
+            rent_lamports += rent;
+        }
+
+        let fee_per_transaction = match sample {
+            Some(entry) => {
+                let mint = Pubkey::from_str(&entry.mint).context("Invalid mint address")?;
+                let ix = CreateMetadataAccountV3Builder::new()
+                    .metadata(find_metadata_pda(&mint))
+                    .mint(mint)
+                    .mint_authority(*payer)
+                    .payer(self.rent_payer(payer))
+                    .update_authority(*payer, true)
+                    .data(data_from_args(&entry.create_args(), payer)?)
+                    .is_mutable(entry.is_mutable)
+                    .instruction();
+                self.transaction_fee(&[ix], payer).await?
+            }
+            None => 0,
+        };
+        let priority_fee_per_transaction =
+            fee_per_transaction.saturating_sub(LAMPORTS_PER_SIGNATURE);
+        let transaction_fees = fee_per_transaction * sizes.len() as u64;
+
+        let files = upload_files(uploads)?;
+        let mut upload_bytes = 0;
+        let mut upload_lamports = 0;
+        if !files.is_empty() {
+            let irys = Irys::for_cluster(self.cluster());
+            let mut price_by_size: HashMap<usize, u64> = HashMap::new();
+            for file in &files {
+                let size = std::fs::metadata(file)
+                    .with_context(|| format!("Failed to read '{}'", file.display()))?
+                    .len() as usize;
+                let price = match price_by_size.get(&size) {
+                    Some(price) => *price,
+                    None => {
+                        let price = irys.price(size).await?;
+                        price_by_size.insert(size, price);
+                        price
+                    }
+                };
+                upload_bytes += size as u64;
+                upload_lamports += price;
+            }
+        }
+
+        Ok(BatchEstimate {
+            rows: entries.len(),
+            transactions: sizes.len(),
+            rent_lamports,
+            fee_per_transaction,
+            priority_fee_per_transaction,
+            transaction_fees,
+            upload_files: files.len(),
+            upload_bytes,
+            upload_lamports,
+            total_lamports: rent_lamports + transaction_fees + upload_lamports,
+            invalid,
+        })
+    }
+}
+
+/// Size of the metadata account `batch create` would create for a row,
+/// after checking the row the way creating it would. The account is sized
+/// to its Borsh-encoded contents.
+fn metadata_size(payer: &Pubkey, entry: &ManifestEntry) -> Result<usize> {
+    Pubkey::from_str(&entry.mint).context("Invalid mint address")?;
+    let data = data_from_args(&entry.create_args(), payer)?;
+    Ok(data_size(&data))
+}
+
+/// Size of a metadata account created with `data`, with its edition nonce
+/// and token standard set as the program sets them
+fn data_size(data: &DataV2) -> usize {
+    let string = |value: &str| 4 + value.len();
+    let creators = data
+        .creators
+        .as_ref()
+        .map_or(0, |creators| 4 + creators.len() * (32 + 1 + 1));
+    // Key, update authority and mint
+    let header = 1 + 32 + 32;
+    // Name, symbol, URI, seller fee and the creators option
+    let fields = string(&data.name) + string(&data.symbol) + string(&data.uri) + 2 + 1 + creators;
+    // Primary sale and mutability, edition nonce and token standard
+    let flags = 1 + 1 + 2 + 2;
+    // The collection (verified flag and key) and uses (method, remaining
+    // and total) options, then the empty collection details and
+    // programmable config options
+    let collection = 1 + data.collection.as_ref().map_or(0, |_| 1 + 32);
+    let uses = 1 + data.uses.as_ref().map_or(0, |_| 1 + 8 + 8);
+    header + fields + flags + collection + uses + 2
+}
+
+/// The files to upload: each path itself, or the files directly inside it
+/// when it is a directory, in name order
+fn upload_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(path)
+                .with_context(|| format!("Failed to read directory '{}'", path.display()))?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<std::io::Result<_>>()
+                .with_context(|| format!("Failed to read directory '{}'", path.display()))?;
+            entries.retain(|entry| entry.is_file());
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh010::BorshSerialize;
+    use mpl_token_metadata::accounts::Metadata;
+    use mpl_token_metadata::types::{Collection, Creator, Key, TokenStandard, UseMethod, Uses};
+
+    /// The metadata account the program would create from `data`
+    fn serialized_len(data: &DataV2) -> usize {
+        Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            name: data.name.clone(),
+            symbol: data.symbol.clone(),
+            uri: data.uri.clone(),
+            seller_fee_basis_points: data.seller_fee_basis_points,
+            creators: data.creators.clone(),
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: Some(255),
+            token_standard: Some(TokenStandard::NonFungible),
+            collection: data.collection.clone(),
+            uses: data.uses.clone(),
+            collection_details: None,
+            programmable_config: None,
+        }
+        .try_to_vec()
+        .unwrap()
+        .len()
+    }
+
+    fn data() -> DataV2 {
+        DataV2 {
+            name: "My NFT #1".to_string(),
+            symbol: "NFT".to_string(),
+            uri: "https://arweave.net/nft.json".to_string(),
+            seller_fee_basis_points: 500,
+            creators: None,
+            collection: None,
+            uses: None,
+        }
+    }
+
+    #[test]
+    fn size_without_creators_or_collection() {
+        let data = data();
+        assert_eq!(data_size(&data), serialized_len(&data));
+    }
+
+    #[test]
+    fn size_with_creators_collection_and_uses() {
+        let creator = |share| Creator {
+            address: Pubkey::new_unique(),
+            verified: false,
+            share,
+        };
+        let data = DataV2 {
+            creators: Some(vec![creator(60), creator(40)]),
+            collection: Some(Collection {
+                verified: false,
+                key: Pubkey::new_unique(),
+            }),
+            uses: Some(Uses {
+                use_method: UseMethod::Multiple,
+                remaining: 3,
+                total: 3,
+            }),
+            ..data()
+        };
+        assert_eq!(data_size(&data), serialized_len(&data));
+    }
+
+    #[test]
+    fn manifest_row_size() {
+        let entry = ManifestEntry {
+            mint: Pubkey::new_unique().to_string(),
+            name: "My NFT #1".to_string(),
+            symbol: "NFT".to_string(),
+            uri: "https://arweave.net/nft.json".to_string(),
+            seller_fee_basis_points: 500,
+            is_mutable: true,
+        };
+        let size = metadata_size(&Pubkey::new_unique(), &entry).unwrap();
+        assert_eq!(size, serialized_len(&data()));
+
+        let entry = ManifestEntry {
+            mint: "not a mint".to_string(),
+            ..entry
+        };
+        assert!(metadata_size(&Pubkey::new_unique(), &entry).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Exporting the metadata of many mints to CSV or JSON for analysis.
+
+use anyhow::{Context, Result};
+use futures_util::{StreamExt, stream};
+use mpl_token_metadata::accounts::Metadata;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::client::MetadataClient;
+use crate::pda::find_metadata_pda;
+
+/// Most accounts getMultipleAccounts returns in one call
+const ACCOUNTS_PER_REQUEST: usize = 100;
+
+/// getMultipleAccounts calls in flight at a time
+const CONCURRENT_REQUESTS: usize = 4;
+
+/// One exported mint, flattened so it fits a CSV row
+#[derive(Clone, Debug, Serialize)]
+pub struct ExportRow {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: Option<u16>,
+    pub update_authority: String,
+    pub is_mutable: Option<bool>,
+    pub primary_sale_happened: Option<bool>,
+    /// Recorded token standard; legacy metadata has none
+    pub token_standard: Option<String>,
+    /// Creators as `<PUBKEY>:<SHARE>`, with `:verified` for verified ones,
+    /// separated by `;`
+    pub creators: String,
+    pub collection: Option<String>,
+    pub collection_verified: Option<bool>,
+    /// Why the mint has no row data
+    pub error: Option<String>,
+}
+
+impl ExportRow {
+    fn new(mint: &str, metadata: &Metadata) -> Self {
+        let creators = metadata
+            .creators
+            .iter()
+            .flatten()
+            .map(|creator| {
+                let verified = if creator.verified { ":verified" } else { "" };
+                format!("{}:{}{}", creator.address, creator.share, verified)
+            })
+            .collect::<Vec<_>>()
+            .join(";");
+        Self {
+            mint: mint.to_string(),
+            metadata_pda: find_metadata_pda(&metadata.mint).to_string(),
+            name: normalize(&metadata.name),
+            symbol: normalize(&metadata.symbol),
+            uri: normalize(&metadata.uri),
+            seller_fee_basis_points: Some(metadata.seller_fee_basis_points),
+            update_authority: metadata.update_authority.to_string(),
+            is_mutable: Some(metadata.is_mutable),
+            primary_sale_happened: Some(metadata.primary_sale_happened),
+            token_standard: metadata.token_standard.map(|s| format!("{:?}", s)),
+            creators,
+            collection: metadata.collection.as_ref().map(|c| c.key.to_string()),
+            collection_verified: metadata.collection.as_ref().map(|c| c.verified),
+            error: None,
+        }
+    }
+
+    fn failed(mint: &str, metadata_pda: String, error: impl Into<String>) -> Self {
+        Self {
+            mint: mint.to_string(),
+            metadata_pda,
+            name: String::new(),
+            symbol: String::new(),
+            uri: String::new(),
+            seller_fee_basis_points: None,
+            update_authority: String::new(),
+            is_mutable: None,
+            primary_sale_happened: None,
+            token_standard: None,
+            creators: String::new(),
+            collection: None,
+            collection_verified: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+impl MetadataClient {
+    /// Fetch the metadata of every mint, a hundred accounts per request,
+    /// with one row per mint in input order. Mints in the cache aren't
+    /// fetched again. Mints that are invalid or have no metadata get a row
+    /// with only the error set. Rows are reported to the progress reporter
+    /// a request at a time.
+    pub async fn export_metadata(&self, mints: &[String]) -> Result<Vec<ExportRow>> {
+        let progress = self.progress();
+        if let Some(progress) = progress {
+            progress.start(mints.len());
+        }
+        let chunks: Vec<Result<Vec<ExportRow>>> = stream::iter(mints.chunks(ACCOUNTS_PER_REQUEST))
+            .map(|chunk| async move {
+                let rows = self.export_chunk(chunk).await;
+                if let (Some(progress), Ok(rows)) = (progress, &rows) {
+                    for row in rows {
+                        progress.advance(row.error.is_none());
+                    }
+                }
+                rows
+            })
+            .buffered(CONCURRENT_REQUESTS)
+            .collect()
+            .await;
+        if let Some(progress) = progress {
+            progress.finish();
+        }
+        let mut rows = Vec::with_capacity(mints.len());
+        for chunk in chunks {
+            rows.extend(chunk?);
+        }
+        Ok(rows)
+    }
+
+    async fn export_chunk(&self, mints: &[String]) -> Result<Vec<ExportRow>> {
+        let mints: Vec<(&String, Option<Pubkey>)> = mints
+            .iter()
+            .map(|mint| (mint, Pubkey::from_str(mint).ok()))
+            .collect();
+        let cached: Vec<Option<Vec<u8>>> = mints
+            .iter()
+            .map(|(_, mint)| {
+                let mint = mint.as_ref()?;
+                self.cache()?.metadata(mint)
+            })
+            .collect();
+        // Only fetch the valid mints the cache doesn't have
+        let missing: Vec<(Pubkey, Pubkey)> = mints
+            .iter()
+            .zip(&cached)
+            .filter_map(|((_, mint), cached)| match (mint, cached) {
+                (Some(mint), None) => Some((*mint, find_metadata_pda(mint))),
+                _ => None,
+            })
+            .collect();
+        let addresses: Vec<Pubkey> = missing.iter().map(|(_, pda)| *pda).collect();
+        let (slot, accounts) = if addresses.is_empty() {
+            (0, Vec::new())
+        } else {
+            let response = self
+                .rpc()
+                .get_multiple_accounts_with_commitment(&addresses, self.rpc().commitment())
+                .await
+                .context("Failed to fetch metadata accounts")?;
+            (response.context.slot, response.value)
+        };
+        let mut fetched = missing.iter().zip(accounts).map(|((mint, pda), account)| {
+            let data = account.map(|account| account.data);
+            if let (Some(cache), Some(data)) = (self.cache(), &data) {
+                cache.store_metadata(mint, pda, slot, data);
+            }
+            data
+        });
+
+        Ok(mints
+            .iter()
+            .zip(cached)
+            .map(|((mint, pubkey), cached)| {
+                let Some(pubkey) = pubkey else {
+                    return ExportRow::failed(mint, String::new(), "Invalid mint address");
+                };
+                let pda = find_metadata_pda(pubkey).to_string();
+                let data = match cached {
+                    Some(data) => Some(data),
+                    None => fetched.next().flatten(),
+                };
+                match data {
+                    None => ExportRow::failed(mint, pda, "No metadata account"),
+                    Some(data) => match Metadata::from_bytes(&data) {
+                        Ok(metadata) => ExportRow::new(mint, &metadata),
+                        Err(e) => ExportRow::failed(
+                            mint,
+                            pda,
+                            format!("Failed to deserialize metadata: {}", e),
+                        ),
+                    },
+                }
+            })
+            .collect())
+    }
+}
+
+/// A metadata string without its NUL padding, surrounding whitespace or
+/// control characters
+fn normalize(value: &str) -> String {
+    value
+        .trim_end_matches('\0')
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect()
+}
+
+/// Write rows to a file: a JSON array when the path ends in `.json`, and
+/// CSV with a header row otherwise
+pub fn write_rows<T: Serialize>(path: impl AsRef<Path>, rows: &[T]) -> Result<()> {
+    let path = path.as_ref();
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        let contents = serde_json::to_string_pretty(rows)?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write '{}'", path.display()))
+    } else {
+        let mut writer = csv::Writer::from_path(path)
+            .with_context(|| format!("Failed to write '{}'", path.display()))?;
+        for row in rows {
+            writer.serialize(row)?;
+        }
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write '{}'", path.display()))
+    }
+}
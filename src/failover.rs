@@ -0,0 +1,139 @@
+//! Sending RPC requests to a list of endpoints, failing over to the next
+//! one when an endpoint is unreachable, rate-limits or is unhealthy. Every
+//! request is logged at debug level, and its response at trace level.
+
+use async_trait::async_trait;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::RpcClientConfig,
+    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    rpc_request::{RpcError, RpcRequest},
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_rpc_client::http_sender::HttpSender;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use tracing::{debug, trace, warn};
+
+use crate::ratelimit::RateLimiter;
+
+/// An [`RpcSender`] over several HTTP endpoints. Requests go to the endpoint
+/// that last answered, and move on to the next one in the list when it
+/// fails. Every request, including one retried on another endpoint, waits
+/// for the rate limiter if there is one.
+pub struct FailoverSender {
+    senders: Vec<HttpSender>,
+    current: AtomicUsize,
+    limiter: Option<RateLimiter>,
+}
+
+impl FailoverSender {
+    /// A sender over `urls`, starting with the first. Panics if `urls` is
+    /// empty.
+    pub fn new(urls: &[String]) -> Self {
+        assert!(!urls.is_empty(), "at least one RPC URL is needed");
+        Self {
+            senders: urls.iter().map(HttpSender::new).collect(),
+            current: AtomicUsize::new(0),
+            limiter: None,
+        }
+    }
+
+    /// Send at most `requests_per_second` requests per second
+    pub fn with_rate_limit(mut self, requests_per_second: Option<u32>) -> Self {
+        self.limiter = requests_per_second.map(RateLimiter::new);
+        self
+    }
+}
+
+#[async_trait]
+impl RpcSender for FailoverSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> ClientResult<serde_json::Value> {
+        let start = self.current.load(Ordering::Relaxed);
+        let count = self.senders.len();
+        let mut offset = 0;
+        loop {
+            let index = (start + offset) % count;
+            let sender = &self.senders[index];
+            if let Some(limiter) = &self.limiter {
+                limiter.acquire().await;
+            }
+            debug!("RPC {} {} to {}", request, params, sender.url());
+            let started = Instant::now();
+            let result = sender.send(request, params.clone()).await;
+            match &result {
+                Ok(value) => {
+                    debug!("RPC {} answered in {:?}", request, started.elapsed());
+                    trace!("RPC {} response: {}", request, value);
+                }
+                Err(err) => debug!("RPC {} failed in {:?}: {}", request, started.elapsed(), err),
+            }
+            match result {
+                Ok(value) => {
+                    if index != start {
+                        self.current.store(index, Ordering::Relaxed);
+                        warn!("Switched to RPC endpoint {}", sender.url());
+                    }
+                    return Ok(value);
+                }
+                Err(err) if offset + 1 < count && should_fail_over(&err) => {
+                    warn!(
+                        "RPC endpoint {} failed ({}); trying the next one...",
+                        sender.url(),
+                        err
+                    );
+                    offset += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.senders
+            .iter()
+            .map(RpcSender::get_transport_stats)
+            .fold(RpcTransportStats::default(), |mut total, stats| {
+                total.request_count += stats.request_count;
+                total.elapsed_time += stats.elapsed_time;
+                total.rate_limited_time += stats.rate_limited_time;
+                total
+            })
+    }
+
+    fn url(&self) -> String {
+        self.senders[self.current.load(Ordering::Relaxed)].url()
+    }
+}
+
+/// An RPC client over one or more endpoints, failing over between them in
+/// the order given, optionally limited to `requests_per_second`
+pub fn rpc_client(
+    urls: &[String],
+    commitment: CommitmentConfig,
+    requests_per_second: Option<u32>,
+) -> RpcClient {
+    RpcClient::new_sender(
+        FailoverSender::new(urls).with_rate_limit(requests_per_second),
+        RpcClientConfig::with_commitment(commitment),
+    )
+}
+
+/// Whether another endpoint might answer a request this one failed: it was
+/// unreachable, timed out, answered with an HTTP error (such as 429 Too Many
+/// Requests, once retries are exhausted) or reported itself unhealthy
+fn should_fail_over(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,94 @@
+//! Compute budget instructions added in front of every transaction.
+
+use anyhow::{Context, Result};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_sdk::{instruction::Instruction, message::Message, pubkey::Pubkey};
+
+use crate::client::MetadataClient;
+use crate::error::MetadataCliError;
+use crate::memo::memo_instruction;
+use crate::types::PriorityFee;
+
+impl MetadataClient {
+    /// The compute budget instructions to prepend to `instructions`
+    pub(crate) async fn compute_budget_instructions(
+        &self,
+        instructions: &[Instruction],
+    ) -> Result<Vec<Instruction>> {
+        let mut budget = Vec::new();
+
+        if let Some(units) = self.compute_units() {
+            budget.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+
+        let micro_lamports = match self.priority_fee() {
+            None => None,
+            Some(PriorityFee::Fixed(micro_lamports)) => Some(micro_lamports),
+            Some(PriorityFee::Auto) => Some(self.estimate_priority_fee(instructions).await?),
+        };
+        if let Some(micro_lamports) = micro_lamports {
+            budget.push(ComputeBudgetInstruction::set_compute_unit_price(
+                micro_lamports,
+            ));
+        }
+
+        Ok(budget)
+    }
+
+    /// The fee of the transaction `send` would build from `instructions`,
+    /// memo and compute budget included, at the current priority fee, paid
+    /// by the fee payer, the client's payer or else `payer`. The durable
+    /// nonce's advance instruction isn't counted.
+    pub(crate) async fn transaction_fee(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+    ) -> Result<u64> {
+        let mut instructions = instructions.to_vec();
+        if let Some(memo) = self.memo() {
+            instructions.push(memo_instruction(memo));
+        }
+        let mut all_instructions = self.compute_budget_instructions(&instructions).await?;
+        all_instructions.extend(instructions);
+        let blockhash = self
+            .rpc()
+            .get_latest_blockhash()
+            .await
+            .map_err(MetadataCliError::rpc("fetch latest blockhash"))?;
+        let payer = self.fee_payer().unwrap_or(self.rent_payer(payer));
+        let message = Message::new_with_blockhash(&all_instructions, Some(&payer), &blockhash);
+        self.rpc()
+            .get_fee_for_message(&message)
+            .await
+            .context("Failed to estimate transaction fee")
+    }
+
+    /// The 75th percentile of the non-zero priority fees recently paid by
+    /// transactions writing to the same accounts, or zero if there are none
+    async fn estimate_priority_fee(&self, instructions: &[Instruction]) -> Result<u64> {
+        let mut writable: Vec<Pubkey> = instructions
+            .iter()
+            .flat_map(|ix| &ix.accounts)
+            .filter(|meta| meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .collect();
+        writable.sort();
+        writable.dedup();
+
+        let mut fees: Vec<u64> = self
+            .rpc()
+            .get_recent_prioritization_fees(&writable)
+            .await
+            .context("Failed to fetch recent prioritization fees")?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .filter(|&fee| fee > 0)
+            .collect();
+        if fees.is_empty() {
+            return Ok(0);
+        }
+
+        fees.sort_unstable();
+        Ok(fees[(fees.len() - 1) * 3 / 4])
+    }
+}
@@ -0,0 +1,137 @@
+//! Generating many metadata JSON files from one template, for batch drops.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::batch::ManifestEntry;
+use crate::offchain::is_uri;
+use crate::types::GeneratedItem;
+
+/// Placeholder values for one generated file, by name
+pub type Row = BTreeMap<String, String>;
+
+/// Read placeholder values from a CSV file with a header row
+pub fn load_rows(path: impl AsRef<Path>) -> Result<Vec<Row>> {
+    let path = path.as_ref();
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .with_context(|| format!("Failed to read data file '{}'", path.display()))?;
+    reader
+        .deserialize()
+        .enumerate()
+        .map(|(i, row)| row.with_context(|| format!("Failed to parse data row {}", i + 1)))
+        .collect()
+}
+
+/// Replace every `{{key}}` in the template's strings with its value. A
+/// string that is a single placeholder whose value is a number or boolean
+/// becomes that JSON value, so `"{{fee}}"` can fill a numeric field.
+pub fn render(template: &Value, vars: &Row) -> Result<Value> {
+    Ok(match template {
+        Value::String(s) => render_string(s, vars)?,
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render(item, vars))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), render(value, vars)?)))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+fn render_string(s: &str, vars: &Row) -> Result<Value> {
+    let mut out = String::new();
+    let mut rest = s;
+    let mut placeholders = 0;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .with_context(|| format!("Unclosed placeholder in '{}'", s))?;
+        let key = rest[start + 2..start + end].trim();
+        let value = vars
+            .get(key)
+            .with_context(|| format!("No value for placeholder '{{{{{}}}}}'", key))?;
+        out.push_str(&rest[..start]);
+        out.push_str(value);
+        rest = &rest[start + end + 2..];
+        placeholders += 1;
+    }
+    let is_single = placeholders == 1 && s.trim().starts_with("{{") && s.trim().ends_with("}}");
+    out.push_str(rest);
+
+    if is_single && let Ok(value @ (Value::Number(_) | Value::Bool(_))) = out.parse::<Value>() {
+        return Ok(value);
+    }
+    Ok(Value::String(out))
+}
+
+/// Render the template once per row into `<index>.json` files in
+/// `out_dir`. Each row also gets an `index` placeholder, counting from
+/// `start_index`.
+pub fn generate_files(
+    template: &Value,
+    rows: Vec<Row>,
+    start_index: usize,
+    out_dir: &Path,
+) -> Result<Vec<GeneratedItem>> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create '{}'", out_dir.display()))?;
+
+    let mut items = Vec::with_capacity(rows.len());
+    for (i, mut vars) in rows.into_iter().enumerate() {
+        let index = start_index + i;
+        vars.insert("index".to_string(), index.to_string());
+        let json = render(template, &vars).with_context(|| format!("Item {}", index))?;
+
+        let file = out_dir.join(format!("{}.json", index));
+        std::fs::write(&file, serde_json::to_vec_pretty(&json)?)
+            .with_context(|| format!("Failed to write '{}'", file.display()))?;
+
+        let text = |key: &str| json.get(key).and_then(Value::as_str).map(str::to_string);
+        items.push(GeneratedItem {
+            index,
+            file: file.display().to_string(),
+            mint: vars.get("mint").filter(|mint| !mint.is_empty()).cloned(),
+            name: text("name").unwrap_or_default(),
+            symbol: text("symbol").unwrap_or_default(),
+            seller_fee_basis_points: json
+                .get("seller_fee_basis_points")
+                .and_then(Value::as_u64)
+                .unwrap_or(0) as u16,
+            image: text("image").filter(|image| !image.is_empty() && !is_uri(image)),
+            uri: None,
+            image_uri: None,
+        });
+    }
+    Ok(items)
+}
+
+/// Write a `batch create` manifest for the generated items to
+/// `manifest.json` in `out_dir`. Items without a `mint` value get an empty
+/// one to fill in.
+pub fn write_manifest(out_dir: &Path, items: &[GeneratedItem]) -> Result<PathBuf> {
+    let entries: Vec<ManifestEntry> = items
+        .iter()
+        .map(|item| ManifestEntry {
+            mint: item.mint.clone().unwrap_or_default(),
+            name: item.name.clone(),
+            symbol: item.symbol.clone(),
+            uri: item.uri.clone().unwrap_or_default(),
+            seller_fee_basis_points: item.seller_fee_basis_points,
+            is_mutable: true,
+        })
+        .collect();
+    let path = out_dir.join("manifest.json");
+    std::fs::write(&path, serde_json::to_vec_pretty(&entries)?)
+        .with_context(|| format!("Failed to write '{}'", path.display()))?;
+    Ok(path)
+}
@@ -0,0 +1,168 @@
+//! Searching for a vanity keypair whose address starts or ends with given
+//! characters, for mints with a branded address.
+
+use anyhow::Result;
+use solana_sdk::signature::{Keypair, Signer};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Characters of the base58 alphabet addresses are written in
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// What a ground address has to look like
+#[derive(Clone, Debug, Default)]
+pub struct GrindPattern {
+    pub starts_with: Option<String>,
+    pub ends_with: Option<String>,
+    /// Compare without regard to case, which makes a match far quicker to find
+    pub ignore_case: bool,
+}
+
+impl GrindPattern {
+    /// Check that the pattern can match at all: it needs a prefix or a
+    /// suffix, and only base58 characters can appear in an address
+    pub fn check(&self) -> Result<()> {
+        if self.starts_with.is_none() && self.ends_with.is_none() {
+            anyhow::bail!("Give a prefix, a suffix or both to grind for");
+        }
+        for part in [&self.starts_with, &self.ends_with].into_iter().flatten() {
+            if let Some(c) = part.chars().find(|c| !BASE58_ALPHABET.contains(*c)) {
+                anyhow::bail!(
+                    "'{}' can't appear in an address; base58 has no 0, O, I or l",
+                    c
+                );
+            }
+            if part.len() > 44 {
+                anyhow::bail!("'{}' is longer than an address", part);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether an address matches the pattern
+    pub fn matches(&self, address: &str) -> bool {
+        let (address, starts_with, ends_with) = if self.ignore_case {
+            (
+                address.to_lowercase(),
+                self.starts_with.as_deref().map(str::to_lowercase),
+                self.ends_with.as_deref().map(str::to_lowercase),
+            )
+        } else {
+            (
+                address.to_string(),
+                self.starts_with.clone(),
+                self.ends_with.clone(),
+            )
+        };
+        starts_with.is_none_or(|prefix| address.starts_with(&prefix))
+            && ends_with.is_none_or(|suffix| address.ends_with(&suffix))
+    }
+
+    /// Roughly how many keypairs have to be generated before one matches
+    pub fn expected_attempts(&self) -> f64 {
+        let characters = [&self.starts_with, &self.ends_with]
+            .into_iter()
+            .flatten()
+            .map(|part| part.len())
+            .sum::<usize>();
+        // Ignoring case, most letters match two characters of the alphabet
+        let alphabet: f64 = if self.ignore_case { 34.0 } else { 58.0 };
+        alphabet.powi(characters as i32)
+    }
+}
+
+/// Generate random keypairs on `threads` threads until one's address
+/// matches `pattern`. Returns the keypair and how many were generated.
+pub fn grind(pattern: &GrindPattern, threads: usize) -> Result<(Keypair, u64)> {
+    pattern.check()?;
+    let found = AtomicBool::new(false);
+    let attempts = AtomicU64::new(0);
+    let keypair = Mutex::new(None);
+    std::thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                let mut tried = 0;
+                while !found.load(Ordering::Relaxed) {
+                    let candidate = Keypair::new();
+                    tried += 1;
+                    if pattern.matches(&candidate.pubkey().to_string()) {
+                        // Another thread may have found one at the same time;
+                        // the first stays
+                        let mut keypair = keypair.lock().unwrap();
+                        if keypair.is_none() {
+                            *keypair = Some(candidate);
+                        }
+                        found.store(true, Ordering::Relaxed);
+                    }
+                }
+                attempts.fetch_add(tried, Ordering::Relaxed);
+            });
+        }
+    });
+    let keypair = keypair
+        .into_inner()
+        .unwrap()
+        .expect("a thread stops only after a match");
+    Ok((keypair, attempts.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(
+        starts_with: Option<&str>,
+        ends_with: Option<&str>,
+        ignore_case: bool,
+    ) -> GrindPattern {
+        GrindPattern {
+            starts_with: starts_with.map(str::to_string),
+            ends_with: ends_with.map(str::to_string),
+            ignore_case,
+        }
+    }
+
+    #[test]
+    fn check_needs_a_base58_prefix_or_suffix() {
+        pattern(Some("abc"), None, false).check().unwrap();
+        pattern(None, Some("xyz"), false).check().unwrap();
+        assert!(pattern(None, None, false).check().is_err());
+        for invalid in ["0", "O", "I", "l"] {
+            assert!(pattern(Some(invalid), None, false).check().is_err());
+            assert!(pattern(None, Some(invalid), true).check().is_err());
+        }
+        assert!(pattern(Some(&"a".repeat(45)), None, false).check().is_err());
+    }
+
+    #[test]
+    fn matches_prefix_and_suffix() {
+        let address = "MintAbc111111111111111111111111111111111Xyz";
+        assert!(pattern(Some("Mint"), None, false).matches(address));
+        assert!(pattern(None, Some("Xyz"), false).matches(address));
+        assert!(pattern(Some("Mint"), Some("Xyz"), false).matches(address));
+        assert!(!pattern(Some("Mint"), Some("Abc"), false).matches(address));
+        assert!(!pattern(Some("mint"), None, false).matches(address));
+        assert!(pattern(Some("mint"), Some("XYZ"), true).matches(address));
+    }
+
+    #[test]
+    fn expected_attempts_grows_per_character() {
+        assert_eq!(pattern(Some("a"), None, false).expected_attempts(), 58.0);
+        assert_eq!(
+            pattern(Some("ab"), Some("c"), false).expected_attempts(),
+            58.0f64.powi(3)
+        );
+        assert_eq!(
+            pattern(Some("ab"), None, true).expected_attempts(),
+            34.0 * 34.0
+        );
+    }
+
+    #[test]
+    fn grind_finds_a_matching_keypair() {
+        let pattern = pattern(Some("a"), None, true);
+        let (keypair, attempts) = grind(&pattern, 2).unwrap();
+        assert!(pattern.matches(&keypair.pubkey().to_string()));
+        assert!(attempts >= 1);
+    }
+}
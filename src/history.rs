@@ -0,0 +1,345 @@
+//! A local journal of metadata as it was before each change the CLI sent,
+//! stored in `~/.local/share/token-metadata-cli/history.sqlite`, so changes
+//! can be looked back on and rolled back.
+//!
+//! Before a transaction is sent, every metadata account it can write is
+//! read; once it lands, their earlier state is recorded along with the slot
+//! it was read at and the transaction's signature.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{
+    ID as TOKEN_METADATA_PROGRAM_ID,
+    accounts::Metadata,
+    instructions::UpdateMetadataAccountV2Builder,
+    types::{Creator, DataV2},
+};
+use rusqlite::{Connection, params};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+    transaction::Transaction,
+};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+use crate::client::{MetadataClient, data_from_metadata, ensure_update_authority};
+use crate::error::MetadataCliError;
+use crate::keypair::expand_tilde;
+use crate::pda::find_metadata_pda;
+use crate::types::{CreatorInfo, FieldChange, HistoryEntry, RollbackResult};
+use crate::validate::check_fields;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    mint TEXT NOT NULL,
+    address TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    signature TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    data BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS history_mint ON history (mint);
+";
+
+/// Location of the history database
+pub fn default_history_path() -> PathBuf {
+    PathBuf::from(expand_tilde(
+        "~/.local/share/token-metadata-cli/history.sqlite",
+    ))
+}
+
+/// The history database
+pub struct History {
+    conn: Mutex<Connection>,
+}
+
+impl History {
+    /// Open the history database, creating it and its directory if needed
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| {
+                format!("Failed to create history directory '{}'", dir.display())
+            })?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open history '{}'", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("Failed to set up history '{}'", path.display()))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Record a metadata account's data as read at `slot`, before the
+    /// transaction `signature` changed it
+    pub fn record(
+        &self,
+        address: &Pubkey,
+        slot: u64,
+        signature: &Signature,
+        data: &[u8],
+    ) -> Result<()> {
+        let metadata = Metadata::from_bytes(data).context("Not a metadata account")?;
+        let recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO history (mint, address, slot, signature, recorded_at, data) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    metadata.mint.to_string(),
+                    address.to_string(),
+                    slot as i64,
+                    signature.to_string(),
+                    recorded_at,
+                    data
+                ],
+            )
+            .context("Failed to write history")?;
+        Ok(())
+    }
+
+    /// Every recorded state of a mint's metadata, newest first
+    pub fn entries(&self, mint: &Pubkey) -> Result<Vec<HistoryEntry>> {
+        self.query(
+            "WHERE mint = ?1 ORDER BY id DESC",
+            params![mint.to_string()],
+        )
+    }
+
+    /// One recorded state of a mint's metadata, or the newest with no `id`
+    pub fn entry(&self, mint: &Pubkey, id: Option<i64>) -> Result<Option<HistoryEntry>> {
+        let entries = match id {
+            Some(id) => self.query("WHERE mint = ?1 AND id = ?2", params![mint.to_string(), id])?,
+            None => self.query(
+                "WHERE mint = ?1 ORDER BY id DESC LIMIT 1",
+                params![mint.to_string()],
+            )?,
+        };
+        Ok(entries.into_iter().next())
+    }
+
+    fn query(&self, filter: &str, params: &[&dyn rusqlite::ToSql]) -> Result<Vec<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn
+            .prepare(&format!(
+                "SELECT id, address, slot, signature, recorded_at, data FROM history {}",
+                filter
+            ))
+            .context("Failed to read history")?;
+        let rows = statement
+            .query_map(params, |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, i64>(4)?,
+                    row.get::<_, Vec<u8>>(5)?,
+                ))
+            })
+            .context("Failed to read history")?;
+        let mut entries = Vec::new();
+        for row in rows {
+            let (id, address, slot, signature, recorded_at, data) =
+                row.context("Failed to read history")?;
+            let metadata = Metadata::from_bytes(&data)
+                .with_context(|| format!("History entry {} is not a metadata account", id))?;
+            entries.push(HistoryEntry::new(
+                id,
+                address,
+                slot as u64,
+                signature,
+                recorded_at as u64,
+                &metadata,
+            ));
+        }
+        Ok(entries)
+    }
+}
+
+/// Metadata accounts a transaction may change, as they were before it was
+/// sent
+pub(crate) struct Before {
+    slot: u64,
+    accounts: Vec<(Pubkey, Vec<u8>)>,
+}
+
+impl MetadataClient {
+    /// Read the metadata accounts `tx` can write, to record once it lands.
+    /// Nothing is read without a history, and a failed read only costs the
+    /// history entry.
+    pub(crate) async fn metadata_before(&self, tx: &Transaction) -> Option<Before> {
+        self.history()?;
+        let writable: Vec<Pubkey> = tx
+            .message
+            .account_keys
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| tx.message.is_maybe_writable(*index, None))
+            .map(|(_, address)| *address)
+            .collect();
+        let response = match self
+            .rpc()
+            .get_multiple_accounts_with_commitment(&writable, self.rpc().commitment())
+            .await
+        {
+            Ok(response) => response,
+            Err(err) => {
+                warn!(
+                    "Not recording history: failed to read the accounts ({})",
+                    err
+                );
+                return None;
+            }
+        };
+        let accounts = writable
+            .into_iter()
+            .zip(response.value)
+            .filter_map(|(address, account)| {
+                let account = account?;
+                (account.owner == TOKEN_METADATA_PROGRAM_ID
+                    && Metadata::from_bytes(&account.data).is_ok())
+                .then_some((address, account.data))
+            })
+            .collect();
+        Some(Before {
+            slot: response.context.slot,
+            accounts,
+        })
+    }
+
+    /// Record the state read before the transaction `signature` landed
+    pub(crate) fn record_history(&self, before: Option<Before>, signature: &Signature) {
+        let (Some(history), Some(before)) = (self.history(), before) else {
+            return;
+        };
+        for (address, data) in &before.accounts {
+            if let Err(err) = history.record(address, before.slot, signature, data) {
+                warn!("Not recording history of {}: {:#}", address, err);
+            }
+        }
+    }
+
+    /// Put back the name, symbol, URI, royalties, creators and update
+    /// authority a mint's metadata had before a recorded change: entry `id`,
+    /// or the latest change with no `id`. Only the current update authority
+    /// can do this, and not once the metadata is immutable. Creators keep
+    /// their verification only where they still have it, or where the
+    /// authority is the creator.
+    pub async fn rollback(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        id: Option<i64>,
+    ) -> Result<RollbackResult> {
+        let history = self
+            .history()
+            .context("Rolling back needs the local history, which is turned off")?;
+        let entry = match history.entry(mint, id)? {
+            Some(entry) => entry,
+            None => match id {
+                Some(id) => anyhow::bail!("No history entry {} for mint {}", id, mint),
+                None => anyhow::bail!("No recorded changes to mint {} to roll back", mint),
+            },
+        };
+        let metadata_pda = find_metadata_pda(mint);
+
+        let existing = self.fetch_fresh(mint).await?;
+        ensure_update_authority(&existing, &authority.pubkey())?;
+        if !existing.is_mutable {
+            return Err(MetadataCliError::Immutable { address: *mint }.into());
+        }
+
+        let creators = entry
+            .creators
+            .as_ref()
+            .map(|creators| {
+                creators
+                    .iter()
+                    .map(|creator| {
+                        let address = Pubkey::from_str(&creator.address)?;
+                        let still_verified = existing
+                            .creators
+                            .iter()
+                            .flatten()
+                            .any(|current| current.address == address && current.verified);
+                        Ok(Creator {
+                            address,
+                            verified: creator.verified
+                                && (still_verified || address == authority.pubkey()),
+                            share: creator.share,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?;
+        check_fields(
+            &entry.name,
+            &entry.symbol,
+            &entry.uri,
+            entry.seller_fee_basis_points,
+        )?;
+        let current = data_from_metadata(&existing);
+        let data = DataV2 {
+            name: entry.name.clone(),
+            symbol: entry.symbol.clone(),
+            uri: entry.uri.clone(),
+            seller_fee_basis_points: entry.seller_fee_basis_points,
+            creators: creators.clone(),
+            ..current
+        };
+        let update_authority = Pubkey::from_str(&entry.update_authority)?;
+
+        let mut builder = UpdateMetadataAccountV2Builder::new();
+        builder
+            .metadata(metadata_pda)
+            .update_authority(authority.pubkey())
+            .data(data);
+        if update_authority != existing.update_authority {
+            builder.new_update_authority(update_authority);
+        }
+        let ix = builder.instruction();
+
+        let signature = self
+            .send(&[ix], authority, &[])
+            .await
+            .context("Failed to send rollback transaction")?;
+
+        Ok(RollbackResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            entry: entry.id,
+            undone_signature: entry.signature.clone(),
+            name: FieldChange {
+                old: existing.name.trim_end_matches('\0').to_string(),
+                new: entry.name,
+            },
+            symbol: FieldChange {
+                old: existing.symbol.trim_end_matches('\0').to_string(),
+                new: entry.symbol,
+            },
+            uri: FieldChange {
+                old: existing.uri.trim_end_matches('\0').to_string(),
+                new: entry.uri,
+            },
+            seller_fee_basis_points: entry.seller_fee_basis_points,
+            creators: creators.map(|creators| creators.iter().map(CreatorInfo::from).collect()),
+            update_authority: (update_authority != existing.update_authority).then(|| {
+                FieldChange {
+                    old: existing.update_authority.to_string(),
+                    new: update_authority.to_string(),
+                }
+            }),
+            signature: signature.to_string(),
+        })
+    }
+}
@@ -0,0 +1,233 @@
+//! Metaplex Inscriptions, which store data such as an NFT's metadata JSON in
+//! an account of the Inscription program derived from the mint, so the NFT
+//! needs nothing off-chain.
+//!
+//! The derived address is what links the inscription to the NFT: the
+//! program only creates it from the mint, signed by its update authority,
+//! so readers find it from the mint alone. The metadata URI is left as it
+//! is, for wallets and marketplaces that don't read inscriptions.
+//!
+//! The instructions are encoded here rather than with the `mpl-inscription`
+//! builders, which are built on Solana 1.16 types.
+
+use anyhow::{Context, Result};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    signature::Signer,
+};
+
+use crate::client::{MetadataClient, ensure_update_authority};
+use crate::error::MetadataCliError;
+use crate::pda::find_metadata_pda;
+use crate::types::{InscribeResult, InscriptionTransaction};
+
+/// The Metaplex Inscription program
+pub const INSCRIPTION_PROGRAM_ID: Pubkey = pubkey!("1NSCRfGeyo7wPUazGbaPBUsTM49e1k2aXewHGARfzSo");
+
+/// Bytes written per transaction, leaving room for the accounts, compute
+/// budget instructions and a memo
+const WRITE_CHUNK_SIZE: usize = 800;
+
+/// Most an instruction may grow an account by
+const MAX_ALLOCATE_INCREASE: u64 = 10 * 1024;
+
+/// Instruction discriminators of the Inscription program
+const INITIALIZE_FROM_MINT: u8 = 1;
+const WRITE_DATA: u8 = 3;
+const ALLOCATE: u8 = 9;
+
+/// Borsh encoding of an absent associated tag: the inscription itself is
+/// written, not one of its associated inscriptions
+const NO_ASSOCIATED_TAG: u8 = 0;
+
+/// Inscriptions are numbered through this many shard counters, so that
+/// concurrent inscriptions don't all lock the same account
+const SHARD_COUNT: u8 = 32;
+
+/// The inscription account derived from a mint
+pub fn find_mint_inscription_pda(mint: &Pubkey) -> Pubkey {
+    let (address, _) = Pubkey::find_program_address(
+        &[
+            b"Inscription",
+            INSCRIPTION_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &INSCRIPTION_PROGRAM_ID,
+    );
+    address
+}
+
+/// The account holding an inscription's authorities and rank
+pub fn find_inscription_metadata_pda(inscription: &Pubkey) -> Pubkey {
+    let (address, _) = Pubkey::find_program_address(
+        &[
+            b"Inscription",
+            INSCRIPTION_PROGRAM_ID.as_ref(),
+            inscription.as_ref(),
+        ],
+        &INSCRIPTION_PROGRAM_ID,
+    );
+    address
+}
+
+/// One of the counters that number inscriptions
+fn find_shard_pda(shard_number: u8) -> Pubkey {
+    let (address, _) = Pubkey::find_program_address(
+        &[
+            b"Inscription",
+            b"Shard",
+            INSCRIPTION_PROGRAM_ID.as_ref(),
+            &[shard_number],
+        ],
+        &INSCRIPTION_PROGRAM_ID,
+    );
+    address
+}
+
+impl MetadataClient {
+    /// Write `data` on-chain in the inscription account of a mint, creating
+    /// it first. The authority must be the update authority of the mint's
+    /// metadata. The data is written in chunks, one transaction each; when
+    /// an earlier run was interrupted, chunks already on-chain are skipped.
+    pub async fn inscribe(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        data: &[u8],
+    ) -> Result<InscribeResult> {
+        if self.is_dry_run() {
+            anyhow::bail!(
+                "Inscribing is not supported with --dry-run, as each write can't be simulated before the previous one lands"
+            );
+        }
+        if data.is_empty() {
+            anyhow::bail!("There is no data to inscribe");
+        }
+        let authority_pubkey = authority.pubkey();
+        let metadata = self.fetch_fresh(mint).await?;
+        ensure_update_authority(&metadata, &authority_pubkey)?;
+
+        let inscription = find_mint_inscription_pda(mint);
+        let inscription_metadata = find_inscription_metadata_pda(&inscription);
+        // Spread mints over the shards without needing randomness
+        let shard = find_shard_pda(mint.to_bytes()[0] % SHARD_COUNT);
+        let existing = self
+            .rpc()
+            .get_account_with_commitment(&inscription, self.rpc().commitment())
+            .await
+            .map_err(MetadataCliError::rpc("fetch inscription account"))?
+            .value
+            .map(|account| account.data);
+        if let Some(existing) = &existing
+            && !existing.is_empty()
+            && existing.len() != data.len()
+        {
+            anyhow::bail!(
+                "Mint {} is already inscribed with {} bytes of other data",
+                mint,
+                existing.len()
+            );
+        }
+
+        let data_accounts = vec![
+            AccountMeta::new(inscription, false),
+            AccountMeta::new(inscription_metadata, false),
+            AccountMeta::new(authority_pubkey, true),
+            AccountMeta::new_readonly(authority_pubkey, true),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ];
+        let mut transactions = Vec::new();
+
+        if existing.is_none() {
+            let ix = Instruction {
+                program_id: INSCRIPTION_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(inscription, false),
+                    AccountMeta::new(inscription_metadata, false),
+                    AccountMeta::new_readonly(*mint, false),
+                    AccountMeta::new_readonly(find_metadata_pda(mint), false),
+                    AccountMeta::new(shard, false),
+                    AccountMeta::new(authority_pubkey, true),
+                    AccountMeta::new_readonly(authority_pubkey, true),
+                    AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+                ],
+                data: vec![INITIALIZE_FROM_MINT],
+            };
+            let signature = self
+                .send(&[ix], authority, &[])
+                .await
+                .context("Failed to send inscription initialize transaction")?;
+            transactions.push(InscriptionTransaction {
+                step: "initialize".to_string(),
+                signature: signature.to_string(),
+            });
+        }
+
+        if existing.as_ref().is_none_or(|existing| existing.is_empty()) {
+            // The account grows by at most MAX_ALLOCATE_INCREASE per
+            // instruction, each one a step closer to the full size
+            let steps = (data.len() as u64).div_ceil(MAX_ALLOCATE_INCREASE);
+            let ixs: Vec<Instruction> = (0..steps)
+                .map(|_| {
+                    let mut ix_data = vec![ALLOCATE, NO_ASSOCIATED_TAG];
+                    // Target size
+                    ix_data.extend_from_slice(&(data.len() as u64).to_le_bytes());
+                    Instruction {
+                        program_id: INSCRIPTION_PROGRAM_ID,
+                        accounts: data_accounts.clone(),
+                        data: ix_data,
+                    }
+                })
+                .collect();
+            let signature = self
+                .send(&ixs, authority, &[])
+                .await
+                .context("Failed to send inscription allocate transaction")?;
+            transactions.push(InscriptionTransaction {
+                step: "allocate".to_string(),
+                signature: signature.to_string(),
+            });
+        }
+
+        let mut chunks_skipped = 0;
+        for (i, chunk) in data.chunks(WRITE_CHUNK_SIZE).enumerate() {
+            let offset = i * WRITE_CHUNK_SIZE;
+            if existing
+                .as_ref()
+                .and_then(|existing| existing.get(offset..offset + chunk.len()))
+                == Some(chunk)
+            {
+                chunks_skipped += 1;
+                continue;
+            }
+            let mut ix_data = vec![WRITE_DATA, NO_ASSOCIATED_TAG];
+            ix_data.extend_from_slice(&(offset as u64).to_le_bytes());
+            // The value, as a Borsh byte vector
+            ix_data.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            ix_data.extend_from_slice(chunk);
+            let ix = Instruction {
+                program_id: INSCRIPTION_PROGRAM_ID,
+                accounts: data_accounts.clone(),
+                data: ix_data,
+            };
+            let signature = self.send(&[ix], authority, &[]).await.with_context(|| {
+                format!("Failed to write inscription bytes at offset {}", offset)
+            })?;
+            transactions.push(InscriptionTransaction {
+                step: format!("write {}..{}", offset, offset + chunk.len()),
+                signature: signature.to_string(),
+            });
+        }
+
+        Ok(InscribeResult {
+            mint: mint.to_string(),
+            inscription: inscription.to_string(),
+            inscription_metadata: inscription_metadata.to_string(),
+            bytes: data.len() as u64,
+            chunks_skipped,
+            transactions,
+        })
+    }
+}
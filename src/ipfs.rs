@@ -0,0 +1,146 @@
+//! IPFS uploads through a pinning service.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, multipart};
+use serde_json::Value;
+use std::str::FromStr;
+
+/// A service that stores and pins files on IPFS
+#[async_trait]
+pub trait PinningProvider {
+    /// Human-readable name of the service
+    fn name(&self) -> &'static str;
+
+    /// Upload and pin a file, returning its CID
+    async fn pin(&self, data: &[u8], file_name: &str, content_type: &str) -> Result<String>;
+}
+
+/// The supported pinning services
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IpfsProvider {
+    #[default]
+    Pinata,
+    NftStorage,
+    Web3Storage,
+}
+
+impl FromStr for IpfsProvider {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "pinata" => Ok(Self::Pinata),
+            "nft.storage" | "nft-storage" | "nftstorage" => Ok(Self::NftStorage),
+            "web3.storage" | "web3-storage" | "web3storage" => Ok(Self::Web3Storage),
+            _ => anyhow::bail!(
+                "Unknown IPFS provider '{}' (expected pinata, nft.storage or web3.storage)",
+                s
+            ),
+        }
+    }
+}
+
+impl IpfsProvider {
+    /// A client for the service, authenticated with an API key (a JWT for
+    /// Pinata)
+    pub fn with_api_key(self, api_key: String) -> Box<dyn PinningProvider + Send + Sync> {
+        let http = Client::new();
+        match self {
+            Self::Pinata => Box::new(Pinata { http, api_key }),
+            Self::NftStorage => Box::new(NftStorage { http, api_key }),
+            Self::Web3Storage => Box::new(Web3Storage { http, api_key }),
+        }
+    }
+}
+
+struct Pinata {
+    http: Client,
+    api_key: String,
+}
+
+#[async_trait]
+impl PinningProvider for Pinata {
+    fn name(&self) -> &'static str {
+        "Pinata"
+    }
+
+    async fn pin(&self, data: &[u8], file_name: &str, content_type: &str) -> Result<String> {
+        let part = multipart::Part::bytes(data.to_vec())
+            .file_name(file_name.to_string())
+            .mime_str(content_type)?;
+        let request = self
+            .http
+            .post("https://api.pinata.cloud/pinning/pinFileToIPFS")
+            .bearer_auth(&self.api_key)
+            .multipart(multipart::Form::new().part("file", part));
+        let response = send_json(request, self.name()).await?;
+        cid(&response["IpfsHash"], self.name())
+    }
+}
+
+struct NftStorage {
+    http: Client,
+    api_key: String,
+}
+
+#[async_trait]
+impl PinningProvider for NftStorage {
+    fn name(&self) -> &'static str {
+        "NFT.Storage"
+    }
+
+    async fn pin(&self, data: &[u8], _file_name: &str, content_type: &str) -> Result<String> {
+        let request = self
+            .http
+            .post("https://api.nft.storage/upload")
+            .bearer_auth(&self.api_key)
+            .header("Content-Type", content_type)
+            .body(data.to_vec());
+        let response = send_json(request, self.name()).await?;
+        cid(&response["value"]["cid"], self.name())
+    }
+}
+
+struct Web3Storage {
+    http: Client,
+    api_key: String,
+}
+
+#[async_trait]
+impl PinningProvider for Web3Storage {
+    fn name(&self) -> &'static str {
+        "web3.storage"
+    }
+
+    async fn pin(&self, data: &[u8], file_name: &str, content_type: &str) -> Result<String> {
+        let request = self
+            .http
+            .post("https://api.web3.storage/upload")
+            .bearer_auth(&self.api_key)
+            .header("Content-Type", content_type)
+            .header("X-Name", file_name)
+            .body(data.to_vec());
+        let response = send_json(request, self.name()).await?;
+        cid(&response["cid"], self.name())
+    }
+}
+
+async fn send_json(request: RequestBuilder, provider: &str) -> Result<Value> {
+    let response = request
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .with_context(|| format!("Failed to upload to {}", provider))?;
+    response
+        .json()
+        .await
+        .with_context(|| format!("Failed to upload to {}", provider))
+}
+
+fn cid(value: &Value, provider: &str) -> Result<String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .with_context(|| format!("{} did not return a CID", provider))
+}
@@ -0,0 +1,352 @@
+//! Permanent Arweave uploads through an Irys bundler node, paid in SOL.
+//!
+//! Data is wrapped in an ANS-104 data item signed with the payer's ed25519
+//! key, so no Arweave wallet is needed.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use sha2::{Digest, Sha384};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use solana_system_interface::instruction::transfer;
+use std::str::FromStr;
+
+use crate::client::MetadataClient;
+use crate::cluster::Cluster;
+
+/// ANS-104 signature type of ed25519 (Solana) signers
+const ED25519_SIGNATURE_TYPE: u16 = 2;
+
+/// An Irys bundler node
+pub struct Irys {
+    node: String,
+    cluster: Cluster,
+    http: reqwest::Client,
+}
+
+/// A finished upload
+#[derive(Clone, Debug)]
+pub struct ArweaveUpload {
+    pub id: String,
+    pub uri: String,
+    /// Lamports transferred to the node to pay for this upload
+    pub lamports_funded: u64,
+}
+
+impl Irys {
+    /// The node for a cluster: mainnet uploads are permanent and paid with
+    /// real SOL, every other cluster uses the devnet node and devnet SOL
+    pub fn for_cluster(cluster: Cluster) -> Self {
+        let node = match cluster {
+            Cluster::MainnetBeta => "https://uploader.irys.xyz",
+            _ => "https://devnet.irys.xyz",
+        };
+        Self {
+            node: node.to_string(),
+            cluster,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Lamports the node charges to store `bytes` bytes
+    pub async fn price(&self, bytes: usize) -> Result<u64> {
+        let price = self
+            .http
+            .get(format!("{}/price/solana/{}", self.node, bytes))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("Failed to fetch upload price from Irys")?
+            .text()
+            .await
+            .context("Failed to fetch upload price from Irys")?;
+        price
+            .trim()
+            .parse()
+            .with_context(|| format!("Unexpected upload price '{}' from Irys", price.trim()))
+    }
+
+    /// Lamports `address` has already funded on the node
+    pub async fn balance(&self, address: &Pubkey) -> Result<u64> {
+        let response: Value = self
+            .http
+            .get(format!(
+                "{}/account/balance/solana?address={}",
+                self.node, address
+            ))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("Failed to fetch Irys balance")?
+            .json()
+            .await
+            .context("Failed to fetch Irys balance")?;
+        json_u64(&response["balance"]).context("Unexpected balance response from Irys")
+    }
+
+    /// The address the node accepts SOL funding at
+    async fn funding_address(&self) -> Result<Pubkey> {
+        let info: Value = self
+            .http
+            .get(format!("{}/info", self.node))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("Failed to fetch Irys node info")?
+            .json()
+            .await
+            .context("Failed to fetch Irys node info")?;
+        let address = info["addresses"]["solana"]
+            .as_str()
+            .context("Irys node has no Solana funding address")?;
+        Pubkey::from_str(address).context("Invalid Irys funding address")
+    }
+
+    /// Tell the node about a funding transfer so it credits the balance
+    async fn register_funding(&self, signature: &str) -> Result<()> {
+        self.http
+            .post(format!("{}/account/balance/solana", self.node))
+            .json(&serde_json::json!({ "tx_id": signature }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("Failed to register funding transaction with Irys")?;
+        Ok(())
+    }
+
+    /// Sign and post a data item, returning its id
+    async fn post(&self, signer: &dyn Signer, data: &[u8], content_type: &str) -> Result<String> {
+        let item = data_item(signer, data, &[("Content-Type", content_type)]);
+        let response: Value = self
+            .http
+            .post(format!("{}/tx/solana", self.node))
+            .header("Content-Type", "application/octet-stream")
+            .body(item)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("Failed to upload to Irys")?
+            .json()
+            .await
+            .context("Failed to upload to Irys")?;
+        response["id"]
+            .as_str()
+            .map(str::to_string)
+            .context("Irys did not return an upload id")
+    }
+
+    /// Where an uploaded item can be read from
+    fn uri(&self, id: &str) -> String {
+        match self.cluster {
+            Cluster::MainnetBeta => format!("https://arweave.net/{}", id),
+            _ => format!("https://gateway.irys.xyz/{}", id),
+        }
+    }
+}
+
+impl MetadataClient {
+    /// Upload data to Arweave through Irys, first topping up the payer's
+    /// Irys balance with a SOL transfer if it can't cover the price. With a
+    /// separate paying wallet, its balance pays and it signs the upload.
+    pub async fn upload_to_arweave(
+        &self,
+        irys: &Irys,
+        payer: &dyn Signer,
+        data: &[u8],
+        content_type: &str,
+    ) -> Result<ArweaveUpload> {
+        if self.is_dry_run() || self.is_sign_only() {
+            anyhow::bail!("Uploading is not supported with --dry-run or --sign-only");
+        }
+        let payer = self.paying_signer(payer);
+
+        let price = irys.price(data.len()).await?;
+        let balance = irys.balance(&payer.pubkey()).await?;
+        let lamports_funded = price.saturating_sub(balance);
+        if lamports_funded > 0 {
+            let ix = transfer(
+                &payer.pubkey(),
+                &irys.funding_address().await?,
+                lamports_funded,
+            );
+            let signature = self
+                .send(&[ix], payer, &[])
+                .await
+                .context("Failed to send Irys funding transaction")?;
+            irys.register_funding(&signature.to_string()).await?;
+        }
+
+        let id = irys.post(payer, data, content_type).await?;
+        Ok(ArweaveUpload {
+            uri: irys.uri(&id),
+            id,
+            lamports_funded,
+        })
+    }
+}
+
+/// Irys returns amounts as either JSON strings or numbers
+fn json_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(s) => s.parse().ok(),
+        value => value.as_u64(),
+    }
+}
+
+/// Build a signed ANS-104 data item without target or anchor
+fn data_item(signer: &dyn Signer, data: &[u8], tags: &[(&str, &str)]) -> Vec<u8> {
+    let owner = signer.pubkey().to_bytes();
+    let tag_bytes = avro_tags(tags);
+
+    let message = deep_hash(&[
+        b"dataitem",
+        b"1",
+        ED25519_SIGNATURE_TYPE.to_string().as_bytes(),
+        &owner,
+        &[],
+        &[],
+        &tag_bytes,
+        data,
+    ]);
+    let signature = signer.sign_message(&message);
+
+    let mut item = Vec::with_capacity(2 + 64 + 32 + 2 + 16 + tag_bytes.len() + data.len());
+    item.extend_from_slice(&ED25519_SIGNATURE_TYPE.to_le_bytes());
+    item.extend_from_slice(signature.as_ref());
+    item.extend_from_slice(&owner);
+    item.push(0); // no target
+    item.push(0); // no anchor
+    item.extend_from_slice(&(tags.len() as u64).to_le_bytes());
+    item.extend_from_slice(&(tag_bytes.len() as u64).to_le_bytes());
+    item.extend_from_slice(&tag_bytes);
+    item.extend_from_slice(data);
+    item
+}
+
+/// Arweave's deep hash of a list of byte strings
+fn deep_hash(chunks: &[&[u8]]) -> Vec<u8> {
+    let mut acc = Sha384::digest(format!("list{}", chunks.len())).to_vec();
+    for chunk in chunks {
+        let tag = Sha384::digest(format!("blob{}", chunk.len()));
+        let blob = Sha384::digest([tag.as_slice(), &Sha384::digest(chunk)].concat());
+        acc = Sha384::digest([acc.as_slice(), &blob].concat()).to_vec();
+    }
+    acc
+}
+
+/// Avro encoding of an array of `{ name: bytes, value: bytes }` records
+fn avro_tags(tags: &[(&str, &str)]) -> Vec<u8> {
+    if tags.is_empty() {
+        return Vec::new();
+    }
+
+    let mut bytes = Vec::new();
+    avro_long(&mut bytes, tags.len() as i64);
+    for (name, value) in tags {
+        for field in [name, value] {
+            avro_long(&mut bytes, field.len() as i64);
+            bytes.extend_from_slice(field.as_bytes());
+        }
+    }
+    avro_long(&mut bytes, 0);
+    bytes
+}
+
+/// Zig-zag varint encoding of an Avro long
+fn avro_long(bytes: &mut Vec<u8>, n: i64) {
+    let mut n = ((n << 1) ^ (n >> 63)) as u64;
+    while n >= 0x80 {
+        bytes.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signature};
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn avro_longs_match_the_spec() {
+        // The examples in the Avro specification
+        for (n, expected) in [
+            (0, vec![0x00]),
+            (-1, vec![0x01]),
+            (1, vec![0x02]),
+            (-2, vec![0x03]),
+            (2, vec![0x04]),
+            (-64, vec![0x7f]),
+            (64, vec![0x80, 0x01]),
+            (8192, vec![0x80, 0x80, 0x01]),
+        ] {
+            let mut bytes = Vec::new();
+            avro_long(&mut bytes, n);
+            assert_eq!(bytes, expected, "{}", n);
+        }
+    }
+
+    #[test]
+    fn avro_tags_encode_a_single_block() {
+        assert!(avro_tags(&[]).is_empty());
+
+        let mut expected = vec![0x02, 0x18];
+        expected.extend_from_slice(b"Content-Type");
+        expected.push(0x20);
+        expected.extend_from_slice(b"application/json");
+        expected.push(0x00);
+        assert_eq!(avro_tags(&[("Content-Type", "application/json")]), expected);
+    }
+
+    #[test]
+    fn deep_hash_matches_arweave_js() {
+        // Computed independently, following the deepHash of arweave-js
+        assert_eq!(
+            hex(&deep_hash(&[])),
+            "a69e7d37fdc7f040a9ec16aae84de24fab4a653dac4de0bd247e36bab9fe45d9\
+             289c5a04a893c95285812f5cefc9707a"
+        );
+        assert_eq!(
+            hex(&deep_hash(&[b"abc"])),
+            "2bdd0e92bd5d4b0fdde0b1cecabb1e001ef640b01f1cf918cbd136ad2189c75b\
+             b9550bbbcda8e5ea1d80663c3e5ccd45"
+        );
+        assert_eq!(
+            hex(&deep_hash(&[b"dataitem", b"1", b"2"])),
+            "a0e030e00b9e9842d8fdf88eabbfb8f6d17a89b366b313f59a30231af981763b\
+             8e330f49985a2677a73bf5cb82f76141"
+        );
+    }
+
+    #[test]
+    fn data_item_follows_ans_104() {
+        let signer = Keypair::new();
+        let tags = [("Content-Type", "application/json")];
+        let tag_bytes = avro_tags(&tags);
+        let item = data_item(&signer, b"{}", &tags);
+
+        assert_eq!(&item[..2], &[2, 0]);
+        let signature = Signature::try_from(&item[2..66]).unwrap();
+        assert_eq!(&item[66..98], signer.pubkey().as_ref());
+        assert_eq!(&item[98..100], &[0, 0]);
+        assert_eq!(&item[100..108], &1u64.to_le_bytes());
+        assert_eq!(&item[108..116], &(tag_bytes.len() as u64).to_le_bytes());
+        assert_eq!(&item[116..116 + tag_bytes.len()], tag_bytes.as_slice());
+        assert_eq!(&item[116 + tag_bytes.len()..], b"{}");
+
+        let message = deep_hash(&[
+            b"dataitem",
+            b"1",
+            b"2",
+            signer.pubkey().as_ref(),
+            &[],
+            &[],
+            &tag_bytes,
+            b"{}",
+        ]);
+        assert!(signature.verify(signer.pubkey().as_ref(), &message));
+    }
+}
@@ -0,0 +1,155 @@
+//! Loading signer keypairs from disk, a seed phrase or the environment.
+
+use anyhow::{Context, Result};
+use bip39::{Language, Mnemonic};
+use solana_derivation_path::DerivationPath;
+use solana_sdk::signature::{
+    Keypair, keypair_from_seed_and_derivation_path, keypair_from_seed_phrase_and_passphrase,
+    read_keypair_file,
+};
+use solana_seed_phrase::generate_seed_from_seed_phrase_and_passphrase;
+
+/// Expand a leading `~` to the current user's home directory
+pub fn expand_tilde(path: &str) -> String {
+    if path.starts_with('~')
+        && let Ok(home) = std::env::var("HOME")
+    {
+        return path.replacen('~', &home, 1);
+    }
+    path.to_string()
+}
+
+/// Load a signer keypair. Besides a Solana CLI-style keypair file (JSON
+/// array of bytes), `prompt://` asks for a seed phrase on the terminal, like
+/// `solana-cli`. A derivation path can be given as `prompt://?key=0/0` or
+/// `prompt://?full-path=m/44/501/0/0`. `env:NAME` reads the secret key
+/// from an environment variable instead.
+pub fn load_keypair(path: &str) -> Result<Keypair> {
+    if let Some(name) = path.strip_prefix("env:") {
+        return keypair_from_env(name);
+    }
+    if let Some(query) = path.strip_prefix("prompt:") {
+        let derivation_path = parse_derivation_path(query)?;
+        return keypair_from_prompt(derivation_path);
+    }
+
+    let expanded = expand_tilde(path);
+    read_keypair_file(&expanded)
+        .map_err(|e| anyhow::anyhow!("Failed to read keypair from '{}': {}", expanded, e))
+}
+
+/// Read a keypair from an environment variable holding either a base58
+/// secret key or a JSON byte array. The value is never included in errors.
+fn keypair_from_env(name: &str) -> Result<Keypair> {
+    let value =
+        std::env::var(name).with_context(|| format!("Environment variable {} is not set", name))?;
+    let value = value.trim();
+
+    let bytes: Vec<u8> = if value.starts_with('[') {
+        serde_json::from_str(value)
+            .map_err(|_| anyhow::anyhow!("{} is not a valid JSON byte array", name))?
+    } else {
+        bs58::decode(value)
+            .into_vec()
+            .map_err(|_| anyhow::anyhow!("{} is not a valid base58 secret key", name))?
+    };
+
+    Keypair::try_from(bytes.as_slice())
+        .map_err(|_| anyhow::anyhow!("{} does not hold a valid 64-byte keypair", name))
+}
+
+/// The derivation path in the query of a `prompt:` signer URI, if any
+fn parse_derivation_path(query: &str) -> Result<Option<DerivationPath>> {
+    let query = query.trim_start_matches("//");
+    let Some(query) = query.strip_prefix('?') else {
+        if !query.is_empty() {
+            anyhow::bail!("Invalid prompt signer '{}'", query);
+        }
+        return Ok(None);
+    };
+
+    let (key, value) = query
+        .split_once('=')
+        .context("Expected prompt://?key=<PATH> or prompt://?full-path=<PATH>")?;
+    let derivation_path = match key {
+        "key" => DerivationPath::from_key_str(value),
+        "full-path" => DerivationPath::from_absolute_path_str(value),
+        _ => anyhow::bail!("Unknown prompt signer query '{}'", key),
+    }
+    .with_context(|| format!("Invalid derivation path '{}'", value))?;
+    Ok(Some(derivation_path))
+}
+
+/// Check a seed phrase against the English BIP39 wordlist and its
+/// checksum, as `solana-cli` does, so a mistyped word fails instead of
+/// deriving a different wallet
+fn check_seed_phrase(seed_phrase: &str) -> Result<()> {
+    Mnemonic::parse_in_normalized(Language::English, seed_phrase)
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!("Invalid seed phrase: {}", e))
+}
+
+/// Read a seed phrase and optional passphrase from the terminal, without
+/// echoing them, and derive the keypair
+fn keypair_from_prompt(derivation_path: Option<DerivationPath>) -> Result<Keypair> {
+    let seed_phrase =
+        rpassword::prompt_password("Seed phrase: ").context("Failed to read seed phrase")?;
+    let seed_phrase = seed_phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    check_seed_phrase(&seed_phrase)?;
+    let passphrase = rpassword::prompt_password(
+        "If this seed phrase has an associated passphrase, enter it now. Otherwise, press ENTER to continue: ",
+    )
+    .context("Failed to read passphrase")?;
+
+    let keypair = match derivation_path {
+        Some(derivation_path) => {
+            let seed = generate_seed_from_seed_phrase_and_passphrase(&seed_phrase, &passphrase);
+            keypair_from_seed_and_derivation_path(&seed, Some(derivation_path))
+        }
+        None => keypair_from_seed_phrase_and_passphrase(&seed_phrase, &passphrase),
+    };
+    keypair.map_err(|e| anyhow::anyhow!("Failed to derive keypair from seed phrase: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_seed_phrase_passes() {
+        check_seed_phrase(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon about",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn unknown_word_fails() {
+        let err = check_seed_phrase(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon abuot",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().starts_with("Invalid seed phrase"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn bad_checksum_fails() {
+        // Every word is in the list, but the last doesn't match the checksum
+        check_seed_phrase(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon abandon",
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn wrong_word_count_fails() {
+        check_seed_phrase("abandon about").unwrap_err();
+    }
+}
@@ -0,0 +1,73 @@
+//! Create, update and inspect Metaplex Token Metadata on Solana.
+//!
+//! The [`MetadataClient`] wraps a nonblocking
+//! [`RpcClient`](solana_client::nonblocking::rpc_client::RpcClient) and
+//! exposes typed async methods for each metadata operation. The
+//! `token-metadata-cli` binary is a thin command-line wrapper around it that
+//! blocks on each call.
+
+mod airdrop;
+pub mod audit;
+mod authority;
+pub mod batch;
+pub mod bubblegum;
+mod burn;
+pub mod cache;
+pub mod client;
+mod close;
+pub mod cluster;
+mod collection;
+pub mod config;
+mod confirm;
+mod core_asset;
+mod core_plugin;
+mod creators;
+mod das;
+mod delegate;
+pub mod diff;
+mod edition;
+pub mod error;
+mod estimate;
+pub mod export;
+pub mod failover;
+mod fees;
+pub mod generate;
+pub mod grind;
+pub mod history;
+pub mod inscription;
+pub mod ipfs;
+pub mod irys;
+pub mod keypair;
+mod list;
+mod lock;
+pub mod logging;
+mod memo;
+mod migrate;
+mod mint;
+mod nft;
+pub mod nonce;
+pub mod offchain;
+pub mod offline;
+pub mod pda;
+mod preflight;
+pub mod ratelimit;
+mod resize;
+mod retry;
+pub mod ruleset;
+pub mod shadow;
+mod simulate;
+pub mod snapshot;
+mod standard;
+mod token2022;
+pub mod token_list;
+mod transfer;
+pub mod types;
+pub mod upload;
+mod uses;
+pub mod validate;
+mod watch;
+pub mod webhook;
+
+pub use client::MetadataClient;
+pub use error::MetadataCliError;
+pub use types::*;
@@ -0,0 +1,239 @@
+//! Discovering metadata accounts by the keys they name, scanning the Token
+//! Metadata program's accounts or searching the DAS API.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{
+    ID as TOKEN_METADATA_PROGRAM_ID, MAX_CREATOR_LEN, MAX_CREATOR_LIMIT, MAX_NAME_LENGTH,
+    MAX_SYMBOL_LENGTH, MAX_URI_LENGTH, accounts::Metadata, types::Key,
+};
+use solana_client::{
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeSet;
+use tracing::warn;
+
+use crate::client::MetadataClient;
+use crate::das;
+use crate::types::{CollectionItems, MetadataSummary};
+
+/// Offset of the update authority in a metadata account, after the key
+const UPDATE_AUTHORITY_OFFSET: usize = 1;
+
+/// Offset of the first creator in a metadata account: the key, update
+/// authority and mint, the name, symbol and URI at their padded lengths
+/// with their length prefixes, the seller fee, the creators option tag and
+/// the vector length
+const CREATORS_OFFSET: usize =
+    1 + 32 + 32 + 4 + MAX_NAME_LENGTH + 4 + MAX_SYMBOL_LENGTH + 4 + MAX_URI_LENGTH + 2 + 1 + 4;
+
+/// Bytes between the first creator and the collection key that don't
+/// depend on optional fields: the primary sale and mutable flags, and the
+/// collection option tag and verified flag
+const COLLECTION_FIXED_BYTES: usize = 1 + 1 + 1 + 1;
+
+impl MetadataClient {
+    /// Find every metadata account whose update authority is `authority`,
+    /// sorted by name. With a DAS endpoint the search runs there, and also
+    /// finds compressed NFTs; otherwise it needs an RPC that allows
+    /// getProgramAccounts.
+    pub async fn list_by_update_authority(
+        &self,
+        authority: &Pubkey,
+    ) -> Result<Vec<MetadataSummary>> {
+        if let Some(das) = self.das() {
+            let mut summaries = das::assets_by_authority(das, authority).await?;
+            sort_summaries(&mut summaries);
+            return Ok(summaries);
+        }
+        let filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            UPDATE_AUTHORITY_OFFSET,
+            authority.as_ref(),
+        ))];
+        let metadata = self
+            .find_metadata_accounts(filters)
+            .await
+            .context("Failed to scan metadata accounts by update authority")?;
+        Ok(summarize(metadata))
+    }
+
+    /// Find every metadata account listing `creator` among its creators, in
+    /// any position, sorted by name. With `verified_only`, only accounts on
+    /// which the creator is verified. With a DAS endpoint the search runs
+    /// there; otherwise it needs an RPC that allows getProgramAccounts.
+    ///
+    /// The scan matches each creator position at its offset in an account
+    /// with padded name, symbol and URI, as the program writes them; old
+    /// accounts with unpadded strings aren't found.
+    pub async fn list_by_creator(
+        &self,
+        creator: &Pubkey,
+        verified_only: bool,
+    ) -> Result<Vec<MetadataSummary>> {
+        if let Some(das) = self.das() {
+            let mut summaries = das::assets_by_creator(das, creator, verified_only).await?;
+            sort_summaries(&mut summaries);
+            return Ok(summaries);
+        }
+        // A creator is its address, then the verified flag and its share
+        let mut bytes = creator.to_bytes().to_vec();
+        if verified_only {
+            bytes.push(1);
+        }
+        let mut metadata = Vec::new();
+        for position in 0..MAX_CREATOR_LIMIT {
+            let filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                CREATORS_OFFSET + position * MAX_CREATOR_LEN,
+                &bytes,
+            ))];
+            let found = self
+                .find_metadata_accounts(filters)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to scan metadata accounts for creator {}",
+                        position + 1
+                    )
+                })?;
+            metadata.extend(found);
+        }
+        // The byte match can't tell a creator from other data at the same
+        // offset, so check the decoded creators as well
+        metadata.retain(|metadata| {
+            metadata.creators.as_ref().is_some_and(|creators| {
+                creators
+                    .iter()
+                    .any(|c| c.address == *creator && (c.verified || !verified_only))
+            })
+        });
+        Ok(summarize(metadata))
+    }
+
+    /// Find every token whose metadata points at `collection`, split into
+    /// verified and unverified members and sorted by name. Uses the DAS
+    /// endpoint when there is one, or else the RPC node's DAS API if it has
+    /// one, and otherwise scans the program's accounts, which needs an RPC
+    /// that allows getProgramAccounts.
+    pub async fn collection_items(&self, collection: &Pubkey) -> Result<CollectionItems> {
+        let das = match self.das() {
+            Some(das) => Ok(das::collection_items(das, collection).await?),
+            None => das::collection_items(self.rpc(), collection).await,
+        };
+        let (members, source) = match das {
+            Ok(members) => (members, "das"),
+            Err(err) => {
+                warn!(
+                    "DAS API unavailable ({:#}); scanning program accounts instead...",
+                    err
+                );
+                (
+                    self.scan_collection_items(collection).await?,
+                    "program-accounts",
+                )
+            }
+        };
+        let (verified, unverified): (Vec<_>, Vec<_>) =
+            members.into_iter().partition(|(_, verified)| *verified);
+        let sorted = |members: Vec<(MetadataSummary, bool)>| {
+            let mut summaries: Vec<MetadataSummary> =
+                members.into_iter().map(|(summary, _)| summary).collect();
+            sort_summaries(&mut summaries);
+            summaries
+        };
+        Ok(CollectionItems {
+            collection: collection.to_string(),
+            source: source.to_string(),
+            verified: sorted(verified),
+            unverified: sorted(unverified),
+        })
+    }
+
+    /// The members of a collection found by scanning metadata accounts,
+    /// with whether each is verified.
+    ///
+    /// The collection key's offset depends on how many creators there are
+    /// and whether the edition nonce and token standard are set, so every
+    /// possible offset is scanned. Like the creator scan this assumes
+    /// padded name, symbol and URI.
+    async fn scan_collection_items(
+        &self,
+        collection: &Pubkey,
+    ) -> Result<Vec<(MetadataSummary, bool)>> {
+        // The creators option tag, and with creators the vector length and
+        // each creator
+        let creators_lengths = std::iter::once(1)
+            .chain((0..=MAX_CREATOR_LIMIT).map(|count| 1 + 4 + count * MAX_CREATOR_LEN));
+        // The edition nonce and token standard are each a tag with an
+        // optional byte
+        let offsets: BTreeSet<usize> = creators_lengths
+            .flat_map(|creators| {
+                (2..=4).map(move |optional| {
+                    CREATORS_OFFSET - 4 - 1 + creators + optional + COLLECTION_FIXED_BYTES
+                })
+            })
+            .collect();
+
+        let scans = offsets.into_iter().map(|offset| {
+            let filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                offset,
+                collection.as_ref(),
+            ))];
+            self.find_metadata_accounts(filters)
+        });
+        let found = futures_util::future::try_join_all(scans)
+            .await
+            .context("Failed to scan metadata accounts by collection")?;
+
+        // Keep only accounts whose decoded collection really is this one
+        Ok(found
+            .into_iter()
+            .flatten()
+            .filter_map(|metadata| {
+                let verified = metadata
+                    .collection
+                    .as_ref()
+                    .filter(|c| c.key == *collection)?
+                    .verified;
+                Some((MetadataSummary::from(&metadata), verified))
+            })
+            .collect())
+    }
+
+    /// The metadata accounts matching `filters`, skipping any that don't
+    /// deserialize
+    async fn find_metadata_accounts(
+        &self,
+        mut filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<Metadata>> {
+        filters.insert(
+            0,
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &[Key::MetadataV1 as u8])),
+        );
+        let accounts = self
+            .rpc()
+            .get_program_accounts_with_config(
+                &TOKEN_METADATA_PROGRAM_ID,
+                RpcProgramAccountsConfig {
+                    filters: Some(filters),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(accounts
+            .into_iter()
+            .filter_map(|(_, account)| Metadata::from_bytes(&account.data).ok())
+            .collect())
+    }
+}
+
+/// Summaries of metadata accounts, sorted by name and then mint
+fn summarize(metadata: Vec<Metadata>) -> Vec<MetadataSummary> {
+    let mut summaries: Vec<MetadataSummary> = metadata.iter().map(MetadataSummary::from).collect();
+    sort_summaries(&mut summaries);
+    summaries
+}
+
+fn sort_summaries(summaries: &mut [MetadataSummary]) {
+    summaries.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.mint.cmp(&b.mint)));
+}
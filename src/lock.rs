@@ -0,0 +1,135 @@
+//! Locking programmable NFTs in their holder's wallet through a utility or
+//! staking delegate.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{
+    instructions::{LockV1Builder, UnlockV1Builder},
+    types::{ProgrammableConfig, TokenDelegateRole, TokenStandard, TokenState},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account_client::address::get_associated_token_address_with_program_id;
+
+use crate::client::MetadataClient;
+use crate::pda::{find_master_edition_pda, find_metadata_pda, find_token_record_pda};
+use crate::ruleset::TOKEN_AUTH_RULES_PROGRAM_ID;
+use crate::types::{LockResult, TokenRecordInfo};
+
+impl MetadataClient {
+    /// Lock a pNFT held by `owner` so it can't be transferred, burned or
+    /// delegated until unlocked. The authority must be its utility or
+    /// staking delegate.
+    pub async fn lock(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        owner: &Pubkey,
+    ) -> Result<LockResult> {
+        self.set_locked(authority, mint, owner, true).await
+    }
+
+    /// Unlock a pNFT locked by its utility or staking delegate
+    pub async fn unlock(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        owner: &Pubkey,
+    ) -> Result<LockResult> {
+        self.set_locked(authority, mint, owner, false).await
+    }
+
+    async fn set_locked(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        lock: bool,
+    ) -> Result<LockResult> {
+        let metadata = self.fetch_fresh(mint).await?;
+        if metadata.token_standard != Some(TokenStandard::ProgrammableNonFungible) {
+            anyhow::bail!("Mint {} is not a programmable NFT", mint);
+        }
+        let token_program = self.fetch_mint(mint).await?.program.id();
+        let token = get_associated_token_address_with_program_id(owner, mint, &token_program);
+        let token_record = find_token_record_pda(mint, &token);
+
+        let before = self.fetch_token_record(&token_record).await?;
+        if before.delegate != Some(authority.pubkey())
+            || !matches!(
+                before.delegate_role,
+                Some(TokenDelegateRole::Utility | TokenDelegateRole::Staking)
+            )
+        {
+            anyhow::bail!(
+                "{} is not the utility or staking delegate of this pNFT; approve it with \
+                 `delegate approve --role utility` or `--role staking` first",
+                authority.pubkey()
+            );
+        }
+        match (lock, &before.state) {
+            (true, TokenState::Locked) => anyhow::bail!("The pNFT is already locked"),
+            (false, TokenState::Unlocked) => anyhow::bail!("The pNFT is not locked"),
+            (_, TokenState::Listed) => anyhow::bail!("The pNFT is listed by a sale delegate"),
+            _ => {}
+        }
+
+        let rule_set = match metadata.programmable_config {
+            Some(ProgrammableConfig::V1 { rule_set }) => rule_set,
+            None => None,
+        };
+        let metadata_pda = find_metadata_pda(mint);
+        let edition = find_master_edition_pda(mint);
+        let ix = if lock {
+            LockV1Builder::new()
+                .authority(authority.pubkey())
+                .token_owner(Some(*owner))
+                .token(token)
+                .mint(*mint)
+                .metadata(metadata_pda)
+                .edition(Some(edition))
+                .token_record(Some(token_record))
+                .payer(self.rent_payer(&authority.pubkey()))
+                .spl_token_program(Some(token_program))
+                .authorization_rules_program(rule_set.map(|_| TOKEN_AUTH_RULES_PROGRAM_ID))
+                .authorization_rules(rule_set)
+                .instruction()
+        } else {
+            UnlockV1Builder::new()
+                .authority(authority.pubkey())
+                .token_owner(Some(*owner))
+                .token(token)
+                .mint(*mint)
+                .metadata(metadata_pda)
+                .edition(Some(edition))
+                .token_record(Some(token_record))
+                .payer(self.rent_payer(&authority.pubkey()))
+                .spl_token_program(Some(token_program))
+                .authorization_rules_program(rule_set.map(|_| TOKEN_AUTH_RULES_PROGRAM_ID))
+                .authorization_rules(rule_set)
+                .instruction()
+        };
+
+        let signature = self.send(&[ix], authority, &[]).await.with_context(|| {
+            let action = if lock { "lock" } else { "unlock" };
+            format!("Failed to send {} transaction", action)
+        })?;
+
+        // Nothing changed on chain when only simulating or signing
+        let after = if self.is_dry_run() || self.is_sign_only() {
+            None
+        } else {
+            Some(TokenRecordInfo::from(
+                &self.fetch_token_record(&token_record).await?,
+            ))
+        };
+
+        Ok(LockResult {
+            mint: mint.to_string(),
+            owner: owner.to_string(),
+            token_account: token.to_string(),
+            token_record: token_record.to_string(),
+            before: TokenRecordInfo::from(&before),
+            after,
+            signature: signature.to_string(),
+        })
+    }
+}
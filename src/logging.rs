@@ -0,0 +1,43 @@
+//! Diagnostic logging through `tracing`: warnings, retries, failovers and,
+//! at debug level, every RPC request. Logs go to stderr, and to a log file
+//! too when one is given, so they never mix with command output on stdout.
+
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::sync::Mutex;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::{fmt, prelude::*};
+
+/// Install the global logger, showing this crate's events at `level` and
+/// with `file` also appending them, timestamped, to that file. Events from
+/// dependencies are kept at warnings and errors unless `level` is lower.
+pub fn init(level: LevelFilter, file: Option<&Path>) -> Result<()> {
+    let filter = Targets::new()
+        .with_target(env!("CARGO_CRATE_NAME"), level)
+        .with_default(level.min(LevelFilter::WARN));
+
+    let stderr = fmt::layer()
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .without_time()
+        .with_target(false);
+    let file = file
+        .map(|path| -> Result<_> {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Failed to open log file '{}'", path.display()))?;
+            Ok(fmt::layer().with_writer(Mutex::new(file)).with_ansi(false))
+        })
+        .transpose()?;
+
+    tracing_subscriber::registry()
+        .with(stderr)
+        .with(file)
+        .with(filter)
+        .try_init()
+        .context("Failed to set up logging")
+}
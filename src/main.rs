@@ -1,18 +1,69 @@
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use mpl_token_metadata::{
-    ID as TOKEN_METADATA_PROGRAM_ID,
-    instructions::{CreateMetadataAccountV3Builder, UpdateMetadataAccountV2Builder},
-    types::DataV2,
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use indicatif::{ProgressDrawTarget, ProgressStyle};
+use mpl_token_metadata::MAX_CREATOR_LIMIT;
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_request::{RpcError, RpcResponseErrorData},
 };
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    hash::Hash,
+    native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
-    signature::{Keypair, Signer, read_keypair_file},
-    transaction::Transaction,
+    signature::{Keypair, Signer, write_keypair_file},
+    transaction::TransactionError,
 };
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::str::FromStr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use token_metadata_cli::{
+    AirdropResult, AuditReport, BatchEstimate, BatchResult, BatchStatus, BroadcastResult,
+    BurnResult, CloseResult, CollectionItems, CompressedBurnResult, CompressedMintResult,
+    CompressedTransferResult, CompressedUpdateResult, CoreAssetInfo, CoreBurnResult,
+    CoreCreateResult, CorePlugin, CorePluginResult, CoreTarget, CoreUpdateResult,
+    CreateCollectionResult, CreateMasterEditionResult, CreateMetadataArgs, CreateMintResult,
+    CreateNftResult, CreateResult, CreateTokenResult, CreateTreeResult, CreatorShare,
+    CreatorVerificationResult, CreatorsVerificationResult, DecompressResult, DelegateResult,
+    DelegateRole, DiffResult, ExportResult, GenerateResult, GrindResult, HistoryResult,
+    InscribeResult, InsufficientFunds, JsonAttribute, LockResult, MakeImmutableResult,
+    MetadataCliError, MetadataClient, MetadataEvent, MetadataField, MetadataInfo, MetadataJsonArgs,
+    MetadataLocation, MetadataSummary, MigrateAction, MigrateResult, MintAccount, OffChainInfo,
+    PrimarySaleResult, PrintEditionResult, PriorityFee, ResizeResult, RollbackResult, RuleSetInfo,
+    RuleSetResult, SetAuthorityResult, SetCollectionResult, SetCollectionSizeResult, Severity,
+    ShadowStorageResult, SimulationReport, SnapshotResult, Standard, Token2022CreateResult,
+    Token2022MetadataArgs, Token2022MetadataInfo, Token2022UpdateArgs, Token2022UpdateResult,
+    TokenListResult, TokenProgram, TokenRecordInfo, TransferResult, UnverifyCollectionResult,
+    UpdateMetadataArgs, UpdateResult, UploadResult, UriHash, UseMethod, UseResult, UsesArgs,
+    ValidationReport, audit::AuditOptions, batch::Progress, batch::load_compressed_manifest,
+    batch::load_manifest, batch::load_mints, batch::load_updates, bubblegum::check_tree_shape,
+    bubblegum::tree_account_size, bubblegum::tree_capacity, cache::Cache, cache::DEFAULT_MAX_AGE,
+    cache::default_cache_path, cluster::Cluster, cluster::Explorer, cluster::resolve_url,
+    config::Config, config::DEFAULT_PROFILE, config::PROFILE_KEYS, config::Profile,
+    config::default_config_path, diff::load_expected, export::write_rows, failover, generate::Row,
+    generate::generate_files, generate::load_rows, generate::write_manifest, grind::GrindPattern,
+    grind::grind, history::History, history::default_history_path,
+    inscription::find_mint_inscription_pda, ipfs::IpfsProvider, keypair::load_keypair, logging,
+    nonce::DurableNonce, offchain::Gateways, offchain::OffChainMetadata, offchain::hash_json,
+    offchain::load_json, offline::read_transactions, offline::write_transactions,
+    pda::find_metadata_pda, ruleset::RuleSet, ruleset::find_rule_set_pda, ruleset::load_operations,
+    shadow::ShadowDrive, shadow::parse_storage_size, token_list, token_list::write_token_list,
+    upload::Storage, upload::build_metadata_json, validate::check_memo, validate::check_name,
+    validate::check_seller_fee_basis_points, validate::check_symbol, validate::check_uri,
+    validate::validate_metadata_json, webhook::Webhook,
+};
+use tracing::{error, warn};
+use tracing_subscriber::filter::LevelFilter;
+
+const DEFAULT_URL: &str = "devnet";
+const DEFAULT_KEYPAIR: &str = "~/.config/solana/id.json";
+const HTTP_TIMEOUT: Duration = Duration::from_secs(15);
 
 #[derive(Parser)]
 #[command(name = "token-metadata-cli")]
@@ -23,287 +74,6018 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Path to the payer/authority keypair file
-    #[arg(short, long, default_value = "~/.config/solana/id.json")]
-    keypair: String,
+    #[command(flatten)]
+    global: GlobalArgs,
+}
+
+#[derive(Args)]
+struct GlobalArgs {
+    /// Path to the payer/authority keypair file (only the payer with --authority), `prompt://` to enter a seed phrase, or `env:NAME` to read it from an environment variable [default: ~/.config/solana/id.json]
+    #[arg(short, long)]
+    keypair: Option<String>,
+
+    /// Keypair paying transaction fees, so --keypair only has to sign as authority (and fund new accounts)
+    #[arg(long, value_name = "KEYPAIR")]
+    fee_payer: Option<String>,
+
+    /// Keypair signing as update, mint or collection authority, while --keypair pays fees and rent
+    #[arg(long, value_name = "KEYPAIR")]
+    authority: Option<String>,
+
+    /// Solana RPC URL or moniker (mainnet, devnet, testnet, localhost); repeat to fail over to further endpoints [default: devnet]
+    #[arg(short, long)]
+    url: Vec<String>,
+
+    /// Explorer for transaction links: solana, solscan, solanafm or xray [default: solana]
+    #[arg(long)]
+    explorer: Option<Explorer>,
+
+    /// Configuration profile to take defaults from
+    #[arg(long, default_value = DEFAULT_PROFILE)]
+    profile: String,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Diagnostics to log: off, error, warn, info, debug (every RPC request) or trace (and every response) [default: info, or warn with --quiet]
+    #[arg(long, value_name = "LEVEL")]
+    log_level: Option<LevelFilter>,
+
+    /// Also append the log, timestamped, to this file
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// Go ahead with operations that can't be undone without asking for confirmation
+    #[arg(short, long, global = true)]
+    yes: bool,
+
+    /// Print only the transaction signature (or only the JSON with --output json), without banners, previews or progress
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Compute unit price in micro-lamports, or `auto` to estimate it from recent fees
+    #[arg(long, value_name = "MICROLAMPORTS|auto")]
+    priority_fee: Option<PriorityFee>,
+
+    /// Compute unit limit to request for every transaction
+    #[arg(long, value_name = "N")]
+    compute_units: Option<u32>,
 
-    /// Solana RPC URL
-    #[arg(short, long, default_value = "https://api.devnet.solana.com")]
-    url: String,
+    /// Nonce account to use instead of a recent blockhash
+    #[arg(long, value_name = "PUBKEY")]
+    nonce_account: Option<Pubkey>,
+
+    /// Keypair of the nonce authority (defaults to --keypair)
+    #[arg(long, value_name = "KEYPAIR", requires = "nonce_account")]
+    nonce_authority: Option<String>,
+
+    /// Add an SPL Memo with this text to every transaction sent, e.g. a ticket ID
+    #[arg(long, value_name = "TEXT")]
+    memo: Option<String>,
+
+    /// Simulate transactions and report what they would do, without sending them
+    #[arg(long, conflicts_with = "sign_only")]
+    dry_run: bool,
+
+    /// Sign transactions without sending them and write them to PATH (requires --blockhash)
+    #[arg(long, value_name = "PATH", requires = "blockhash")]
+    sign_only: Option<PathBuf>,
+
+    /// Recent blockhash (or durable nonce blockhash) to sign with in --sign-only mode
+    #[arg(long, requires = "sign_only")]
+    blockhash: Option<Hash>,
+
+    /// Request an airdrop when the payer can't afford a transaction (devnet, testnet and localnet)
+    #[arg(long)]
+    auto_airdrop: bool,
+
+    /// Times to try sending a transaction when the RPC node is unavailable, times out or drops its blockhash
+    #[arg(long, default_value_t = 3, value_name = "N")]
+    max_attempts: u32,
+
+    /// Most RPC requests to send per second, to stay under a public endpoint's rate limit
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    rps: Option<u32>,
+
+    /// DAS API endpoint for show, list and collection to read assets from instead of decoding or scanning accounts
+    #[arg(long, value_name = "URL")]
+    das_url: Option<String>,
+
+    /// Don't read or write the local cache of fetched metadata and off-chain JSON
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Fetch everything again instead of reading the local cache, and store the fresh copies
+    #[arg(long, conflicts_with = "no_cache")]
+    refresh: bool,
+
+    /// Seconds a cached copy is used before it is fetched again
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_MAX_AGE.as_secs())]
+    cache_ttl: u64,
+
+    /// Don't record metadata in the local history before changing it
+    #[arg(long)]
+    no_history: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable output
+    Text,
+    /// Machine-readable JSON output
+    Json,
+    /// Only the transaction signatures, one per line; selected by --quiet
+    #[value(skip)]
+    Signature,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Create metadata for an existing token mint
-    Create {
-        /// Token mint address
-        #[arg(short, long)]
-        mint: String,
+    Create(CreateArgs),
+    /// Create metadata step by step, answering one prompt per field
+    Wizard,
+    /// Update metadata for an existing token mint
+    Update(UpdateArgs),
+    /// Put back the metadata a mint had before a change recorded in the local history
+    Rollback(RollbackArgs),
+    /// Transfer the update authority of a token mint's metadata
+    SetAuthority(SetAuthorityArgs),
+    /// Permanently lock a token mint's metadata so it can never be updated
+    MakeImmutable(MakeImmutableArgs),
+    /// Mark a token mint's primary sale as happened, as its update authority or a holder
+    SetPrimarySale(SetPrimarySaleArgs),
+    /// Create a new SPL token mint with metadata (and optional initial supply) in one step
+    CreateToken(CreateTokenArgs),
+    /// Create a new SPL mint without metadata, to add metadata to later with `create`
+    CreateMint(CreateMintArgs),
+    /// Create a new NFT (mint, token, metadata and master edition) in one step
+    CreateNft(CreateNftArgs),
+    /// Create the master edition for a 0-decimal, supply-1 mint, making it an NFT
+    CreateMasterEdition(CreateMasterEditionArgs),
+    /// Print a numbered edition from a master edition into a new mint
+    PrintEdition(PrintEditionArgs),
+    /// Mint a new collection NFT (mint, token, metadata and master edition)
+    CreateCollection(CreateCollectionArgs),
+    /// Attach a token mint to a collection and verify it as the collection authority
+    SetCollection(SetCollectionArgs),
+    /// Set the item count of a collection NFT, making it a sized collection
+    SetCollectionSize(SetCollectionSizeArgs),
+    /// Unverify and clear the collection of a token mint
+    UnverifyCollection(UnverifyCollectionArgs),
+    /// Inspect the members of a collection
+    Collection {
+        #[command(subcommand)]
+        command: CollectionCommands,
+    },
+    /// Burn a token (NFT, pNFT or fungible) and reclaim the rent of closed accounts
+    Burn(BurnArgs),
+    /// Shrink a mint's metadata and edition accounts to their contents and reclaim the rent
+    Resize(ResizeArgs),
+    /// Close the metadata and edition accounts of a burned mint and reclaim the rent
+    Close(CloseArgs),
+    /// Transfer an NFT, pNFT or fungible token to another wallet
+    Transfer(TransferArgs),
+    /// Lock a pNFT in its holder's wallet as its utility or staking delegate
+    Lock(LockArgs),
+    /// Unlock a pNFT previously locked by its utility or staking delegate
+    Unlock(LockArgs),
+    /// Consume uses of a utility NFT as its holder or use authority
+    Use(UseArgs),
+    /// Verify the signing keypair as a creator of a token mint
+    VerifyCreator(VerifyCreatorArgs),
+    /// Remove the signing keypair's creator verification from a token mint
+    UnverifyCreator(UnverifyCreatorArgs),
+    /// Upload a local metadata JSON (and optional image) to Arweave or IPFS
+    Upload(UploadArgs),
+    /// Manage Shadow Drive storage accounts to upload into
+    Shadow {
+        #[command(subcommand)]
+        command: ShadowCommands,
+    },
+    /// Generate metadata JSON files from a template, ready for `batch create`
+    Generate(GenerateArgs),
+    /// Manage metadata stored in a Token-2022 mint's TokenMetadata extension
+    #[command(name = "token-2022")]
+    Token2022 {
+        #[command(subcommand)]
+        command: Token2022Commands,
+    },
+    /// Copy a Token-2022 mint's metadata between Metaplex and its TokenMetadata extension
+    Migrate(MigrateArgs),
+    /// Manage Bubblegum Merkle trees that hold compressed NFTs
+    Tree {
+        #[command(subcommand)]
+        command: TreeCommands,
+    },
+    /// Mint a compressed NFT into a Bubblegum Merkle tree
+    Cmint(CmintArgs),
+    /// Change the name, symbol or URI of a compressed NFT
+    Cupdate(CupdateArgs),
+    /// Transfer a compressed NFT you own or are the delegate of
+    Ctransfer(CtransferArgs),
+    /// Burn a compressed NFT you own or are the delegate of
+    Cburn(CburnArgs),
+    /// Turn a compressed NFT you own into a regular NFT
+    Decompress(DecompressArgs),
+    /// Create, update, show and burn Metaplex Core assets
+    Core {
+        #[command(subcommand)]
+        command: CoreCommands,
+    },
+    /// Write a mint's metadata JSON, or any file, on-chain with Metaplex
+    /// Inscriptions
+    Inscribe(InscribeArgs),
+    /// Let another key update a mint's metadata or act on your tokens
+    Delegate {
+        #[command(subcommand)]
+        command: DelegateCommands,
+    },
+    /// Manage token authorization rule sets for programmable NFTs
+    Ruleset {
+        #[command(subcommand)]
+        command: RulesetCommands,
+    },
+    /// Run an operation over many mints from a manifest file
+    Batch {
+        #[command(subcommand)]
+        command: BatchCommands,
+    },
+    /// Request SOL from the faucet of devnet, testnet or localnet
+    Airdrop(AirdropArgs),
+    /// Send transactions signed offline with --sign-only
+    Broadcast(BroadcastArgs),
+    /// Manage named configuration profiles
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Check a metadata JSON file or URI against the Metaplex metadata standard
+    ValidateJson(ValidateJsonArgs),
+    /// Build metadata JSON files locally
+    Json {
+        #[command(subcommand)]
+        command: JsonCommands,
+    },
+    /// Search for a keypair whose address starts and/or ends with given characters, for a branded mint address
+    Grind(GrindArgs),
+    /// Compare a mint's on-chain metadata to an expected JSON description
+    Diff(DiffArgs),
+    /// Show the changes this CLI made to a mint's metadata, from the local history
+    History(HistoryArgs),
+    /// List the metadata accounts whose update authority or creators
+    /// include a given key (needs an RPC that allows getProgramAccounts)
+    List(ListArgs),
+    /// Record the current holders of a mint or of every item of a
+    /// collection, for airdrops
+    Snapshot(SnapshotArgs),
+    /// Write the metadata of many mints to a CSV or JSON file
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Export {
+        #[command(subcommand)]
+        command: Option<ExportCommands>,
 
-        /// Token name
-        #[arg(short, long)]
-        name: String,
+        /// File with one mint address per line
+        #[arg(long, value_name = "PATH", required = true)]
+        mints: Option<PathBuf>,
 
-        /// Token symbol
-        #[arg(short, long)]
-        symbol: String,
+        /// Output file: JSON when it ends in .json, CSV otherwise
+        #[arg(long, value_name = "PATH", required = true)]
+        out: Option<PathBuf>,
+    },
+    /// Watch mints' metadata and report every change to the name, symbol,
+    /// URI or update authority until stopped
+    Watch(WatchArgs),
+    /// Check mints' metadata for common problems and score it, e.g. as a release gate
+    Audit(AuditArgs),
+    /// Show the on-chain metadata for a token mint
+    Show(ShowArgs),
+}
 
-        /// Metadata URI (JSON file URL)
-        #[arg(long, default_value = "")]
-        uri: String,
+#[derive(Args)]
+struct CreateArgs {
+    /// Token mint address
+    #[arg(short, long, required_unless_present = "interactive")]
+    mint: Option<String>,
+
+    /// Token name
+    #[arg(short, long, required_unless_present = "interactive")]
+    name: Option<String>,
+
+    /// Token symbol
+    #[arg(short, long, required_unless_present = "interactive")]
+    symbol: Option<String>,
+
+    /// Metadata URI (JSON file URL)
+    #[arg(long, default_value = "")]
+    uri: String,
+
+    /// Upload this local metadata JSON and use its URI
+    #[arg(long, value_name = "PATH", conflicts_with = "uri")]
+    upload_uri: Option<PathBuf>,
+
+    /// Upload this image, generate and upload a metadata JSON for it, and use its URI
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["uri", "upload_uri", "validate"])]
+    image: Option<PathBuf>,
+
+    /// Description for the generated metadata JSON
+    #[arg(long, requires = "image")]
+    description: Option<String>,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+
+    /// Whether metadata should be mutable
+    #[arg(long, default_value_t = true)]
+    mutable: bool,
+
+    /// Seller fee basis points (0-10000)
+    #[arg(long, default_value_t = 0)]
+    seller_fee_basis_points: u16,
+
+    /// Token standard to record with CreateV1: fungible, fungible-asset, nft or pnft
+    #[arg(long)]
+    standard: Option<Standard>,
+
+    /// Creator and royalty share, e.g. <PUBKEY>:100 (repeatable; shares must sum to 100)
+    #[arg(long = "creator", value_name = "PUBKEY:SHARE")]
+    creators: Vec<CreatorShare>,
+
+    #[command(flatten)]
+    uses: UseMethodArgs,
+
+    /// Validate the metadata JSON behind the URI before creating
+    #[arg(long)]
+    validate: bool,
+
+    /// Fetch the JSON behind the URI and record its SHA-256 in a memo in the same transaction
+    #[arg(long)]
+    hash_uri: bool,
+
+    /// Make this address the update authority instead of the signer; the signer still has to be the mint authority
+    #[arg(long, value_name = "PUBKEY")]
+    update_authority: Option<Pubkey>,
+
+    /// Prompt for each field in turn, offering any values given here as defaults, and confirm before sending
+    #[arg(short, long)]
+    interactive: bool,
+}
+
+#[derive(Args)]
+struct UpdateArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// New token name (optional)
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// New token symbol (optional)
+    #[arg(short, long)]
+    symbol: Option<String>,
+
+    /// New metadata URI (optional)
+    #[arg(long)]
+    uri: Option<String>,
+
+    /// Replace the creators list, e.g. <PUBKEY>:100 (repeatable; shares must sum to 100)
+    #[arg(long = "creator", value_name = "PUBKEY:SHARE")]
+    creators: Vec<CreatorShare>,
+
+    #[command(flatten)]
+    uses: UseMethodArgs,
+
+    /// Mark the primary sale as happened (it can't be cleared again)
+    #[arg(long)]
+    primary_sale_happened: bool,
+
+    /// Validate the metadata JSON behind the new URI before updating
+    #[arg(long, requires = "uri")]
+    validate: bool,
+
+    /// Fetch the JSON behind the new URI and record its SHA-256 in a memo in the same transaction
+    #[arg(long, requires = "uri")]
+    hash_uri: bool,
+}
+
+#[derive(Args)]
+struct RollbackArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// History entry to put back [default: the latest]
+    #[arg(long, value_name = "ID")]
+    entry: Option<i64>,
+}
+
+#[derive(Args)]
+struct SetAuthorityArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// Public key of the new update authority
+    #[arg(long)]
+    new_authority: String,
+}
+
+#[derive(Args)]
+struct MakeImmutableArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+}
+
+#[derive(Args)]
+struct SetPrimarySaleArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+}
+
+#[derive(Args)]
+struct CreateTokenArgs {
+    /// Token name
+    #[arg(short, long)]
+    name: String,
+
+    /// Token symbol
+    #[arg(short, long)]
+    symbol: String,
+
+    /// Metadata URI (JSON file URL)
+    #[arg(long, default_value = "")]
+    uri: String,
+
+    /// Upload this image, generate and upload a metadata JSON for it, and use its URI
+    #[arg(long, value_name = "PATH", conflicts_with = "uri")]
+    image: Option<PathBuf>,
+
+    /// Description for the generated metadata JSON
+    #[arg(long, requires = "image")]
+    description: Option<String>,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+
+    /// Number of decimals
+    #[arg(short, long, default_value_t = 9)]
+    decimals: u8,
+
+    /// Initial supply to mint to your wallet, in whole tokens
+    #[arg(long)]
+    initial_supply: Option<f64>,
+
+    /// Whether metadata should be mutable
+    #[arg(long, default_value_t = true)]
+    mutable: bool,
+
+    /// Creator and royalty share, e.g. <PUBKEY>:100 (repeatable; shares must sum to 100)
+    #[arg(long = "creator", value_name = "PUBKEY:SHARE")]
+    creators: Vec<CreatorShare>,
+
+    /// Keypair of the new mint, e.g. one found with `grind` [default: a new random keypair]
+    #[arg(long, value_name = "KEYPAIR")]
+    mint_keypair: Option<String>,
+}
+
+#[derive(Args)]
+struct CreateMintArgs {
+    /// Number of decimals
+    #[arg(short, long, default_value_t = 9)]
+    decimals: u8,
+
+    /// Initial supply to mint, in whole tokens
+    #[arg(long)]
+    initial_supply: Option<f64>,
+
+    /// Wallet receiving the initial supply [default: your wallet]
+    #[arg(long, value_name = "PUBKEY", requires = "initial_supply")]
+    owner: Option<Pubkey>,
+
+    /// Authority able to freeze token accounts [default: none, freezing disabled]
+    #[arg(long, value_name = "PUBKEY")]
+    freeze_authority: Option<Pubkey>,
+
+    /// Keypair of the new mint, e.g. one found with `grind` [default: a new random keypair]
+    #[arg(long, value_name = "KEYPAIR")]
+    mint_keypair: Option<String>,
+}
+
+#[derive(Args)]
+struct CreateNftArgs {
+    /// NFT name (overrides the metadata file)
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// NFT symbol (overrides the metadata file)
+    #[arg(short, long)]
+    symbol: Option<String>,
+
+    /// Metadata URI (JSON file URL)
+    #[arg(long, default_value = "")]
+    uri: String,
+
+    /// Local metadata JSON to read name, symbol, seller fee and creators from
+    #[arg(long)]
+    metadata_file: Option<PathBuf>,
+
+    /// Seller fee basis points (0-10000; overrides the metadata file)
+    #[arg(long)]
+    seller_fee_basis_points: Option<u16>,
+
+    /// Whether metadata should be mutable
+    #[arg(long, default_value_t = true)]
+    mutable: bool,
+
+    /// Creator and royalty share, e.g. <PUBKEY>:100 (repeatable; overrides the metadata file)
+    #[arg(long = "creator", value_name = "PUBKEY:SHARE")]
+    creators: Vec<CreatorShare>,
+
+    /// Maximum number of editions that can be printed (unlimited if omitted)
+    #[arg(long)]
+    max_supply: Option<u64>,
+
+    /// nft (default) or pnft for a programmable NFT
+    #[arg(long)]
+    standard: Option<Standard>,
+
+    /// Authorization rule set checked on transfers of a pNFT
+    #[arg(long, value_name = "ADDRESS")]
+    rule_set: Option<String>,
+
+    #[command(flatten)]
+    uses: UseMethodArgs,
+
+    /// Keypair of the new mint, e.g. one found with `grind` [default: a new random keypair]
+    #[arg(long, value_name = "KEYPAIR")]
+    mint_keypair: Option<String>,
+}
+
+#[derive(Args)]
+struct CreateMasterEditionArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// Maximum number of editions that can be printed (unlimited if omitted)
+    #[arg(long)]
+    max_supply: Option<u64>,
+}
+
+#[derive(Args)]
+struct PrintEditionArgs {
+    /// Master edition mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// Edition number to print (defaults to the next unprinted number)
+    #[arg(long)]
+    edition: Option<u64>,
+}
+
+#[derive(Args)]
+struct CreateCollectionArgs {
+    /// Collection name
+    #[arg(short, long)]
+    name: String,
+
+    /// Collection symbol
+    #[arg(short, long)]
+    symbol: String,
+
+    /// Metadata URI (JSON file URL)
+    #[arg(long, default_value = "")]
+    uri: String,
+
+    /// Whether metadata should be mutable
+    #[arg(long, default_value_t = true)]
+    mutable: bool,
+
+    /// Seller fee basis points (0-10000)
+    #[arg(long, default_value_t = 0)]
+    seller_fee_basis_points: u16,
+
+    /// Creator and royalty share, e.g. <PUBKEY>:100 (repeatable; shares must sum to 100)
+    #[arg(long = "creator", value_name = "PUBKEY:SHARE")]
+    creators: Vec<CreatorShare>,
+}
+
+#[derive(Args)]
+struct SetCollectionArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// Collection NFT mint address
+    #[arg(short, long)]
+    collection: String,
+}
+
+#[derive(Args)]
+struct SetCollectionSizeArgs {
+    /// Collection NFT mint address
+    #[arg(short, long)]
+    collection: String,
+
+    /// Number of items in the collection
+    #[arg(long)]
+    size: u64,
+}
+
+#[derive(Args)]
+struct UnverifyCollectionArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+}
+
+#[derive(Args)]
+struct BurnArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// Amount of base units to burn for fungible tokens (defaults to the whole balance)
+    #[arg(long)]
+    amount: Option<u64>,
+}
+
+#[derive(Args)]
+struct ResizeArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+}
+
+#[derive(Args)]
+struct CloseArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+}
+
+#[derive(Args)]
+struct TransferArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// Recipient wallet; its token account is created if needed
+    #[arg(long)]
+    to: String,
+
+    /// Base units to send (fungible tokens only; NFTs always move their one token)
+    #[arg(long)]
+    amount: Option<u64>,
+
+    /// Current holder, when transferring a pNFT as its transfer or sale delegate
+    #[arg(long)]
+    from: Option<String>,
+}
+
+#[derive(Args)]
+struct UseArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// Number of uses to consume
+    #[arg(long, default_value_t = 1)]
+    count: u64,
+
+    /// Current holder, when using the token as its use authority
+    #[arg(long)]
+    owner: Option<String>,
+}
+
+#[derive(Args)]
+struct VerifyCreatorArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// Verify this creator keypair instead, in one transaction with the others given (repeatable)
+    #[arg(long = "creator-keypair", value_name = "KEYPAIR")]
+    creator_keypairs: Vec<String>,
+}
+
+#[derive(Args)]
+struct UnverifyCreatorArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+}
+
+#[derive(Args)]
+struct UploadArgs {
+    /// Metadata JSON file
+    #[arg(short, long)]
+    file: PathBuf,
+
+    /// Image to upload first; its URI is written into the JSON's image field
+    #[arg(long)]
+    image: Option<PathBuf>,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /// Template metadata JSON with {{placeholders}}
+    #[arg(short, long)]
+    template: PathBuf,
+
+    /// CSV whose columns fill the placeholders, one file per row
+    #[arg(long, required_unless_present = "count")]
+    data: Option<PathBuf>,
+
+    /// Number of files to generate when no data file is given
+    #[arg(long, conflicts_with = "data")]
+    count: Option<usize>,
+
+    /// Value of {{index}} for the first file
+    #[arg(long, default_value_t = 0)]
+    start_index: usize,
+
+    /// Directory to write the files and manifest.json to
+    #[arg(short, long, default_value = "./metadata")]
+    out_dir: PathBuf,
+
+    /// Upload each file (and its local image) and put the URIs in the manifest
+    #[arg(long)]
+    upload: bool,
+
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+#[derive(Args)]
+struct MigrateArgs {
+    /// Token-2022 mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// Where to write the metadata: metaplex or token-2022 (read from the other)
+    #[arg(long, value_name = "LOCATION")]
+    to: MetadataLocation,
+}
+
+#[derive(Args)]
+struct CmintArgs {
+    /// Merkle tree address
+    #[arg(long)]
+    tree: String,
+
+    /// NFT name
+    #[arg(short, long)]
+    name: String,
+
+    /// NFT symbol
+    #[arg(short, long, default_value = "")]
+    symbol: String,
+
+    /// Metadata URI (JSON file URL)
+    #[arg(long)]
+    uri: String,
+
+    /// Seller fee basis points (0-10000)
+    #[arg(long, default_value_t = 0)]
+    seller_fee_basis_points: u16,
+
+    /// Whether metadata should be mutable
+    #[arg(long, default_value_t = true)]
+    mutable: bool,
+
+    /// Creator and royalty share, e.g. <PUBKEY>:100 (repeatable)
+    #[arg(long = "creator", value_name = "PUBKEY:SHARE")]
+    creators: Vec<CreatorShare>,
+
+    /// Collection NFT mint to mint into, verified (you must be its update authority)
+    #[arg(long)]
+    collection: Option<String>,
+
+    /// Wallet to mint to (defaults to --keypair)
+    #[arg(long)]
+    owner: Option<String>,
+}
+
+#[derive(Args)]
+struct CupdateArgs {
+    /// Asset ID of the compressed NFT
+    #[arg(long)]
+    asset: String,
+
+    /// New name
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// New symbol
+    #[arg(short, long)]
+    symbol: Option<String>,
+
+    /// New metadata URI
+    #[arg(long)]
+    uri: Option<String>,
+}
+
+#[derive(Args)]
+struct CtransferArgs {
+    /// Asset ID of the compressed NFT
+    #[arg(long)]
+    asset: String,
+
+    /// Wallet to transfer to
+    #[arg(long)]
+    to: String,
+}
+
+#[derive(Args)]
+struct CburnArgs {
+    /// Asset ID of the compressed NFT
+    #[arg(long)]
+    asset: String,
+}
+
+#[derive(Args)]
+struct DecompressArgs {
+    /// Asset ID of the compressed NFT
+    #[arg(long)]
+    asset: String,
+}
+
+#[derive(Args)]
+struct InscribeArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// File to inscribe instead of the JSON behind the metadata URI
+    #[arg(long)]
+    file: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct AirdropArgs {
+    /// Amount in SOL
+    #[arg(default_value_t = 1.0)]
+    amount: f64,
+
+    /// Wallet to fund (defaults to --keypair)
+    #[arg(long)]
+    to: Option<String>,
+}
+
+#[derive(Args)]
+struct BroadcastArgs {
+    /// File written by --sign-only
+    #[arg(short, long)]
+    file: PathBuf,
+}
+
+#[derive(Args)]
+struct ValidateJsonArgs {
+    /// Local file path or http(s)://, ipfs:// or ar:// URI
+    #[arg(value_name = "FILE|URI")]
+    source: String,
+
+    /// Don't request the image and file links to check that they resolve
+    #[arg(long)]
+    skip_links: bool,
+}
+
+#[derive(Args)]
+struct GrindArgs {
+    /// Characters the address must start with
+    #[arg(long, required_unless_present = "ends_with")]
+    starts_with: Option<String>,
+
+    /// Characters the address must end with
+    #[arg(long)]
+    ends_with: Option<String>,
+
+    /// Match without regard to case, which is much faster
+    #[arg(long)]
+    ignore_case: bool,
+
+    /// Number of threads to search on [default: one per CPU]
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Keypair file to write [default: <PUBKEY>.json]
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// JSON object with the expected fields, or a manifest array with an
+    /// entry for the mint
+    #[arg(long)]
+    file: String,
+}
+
+#[derive(Args)]
+struct HistoryArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+}
+
+#[derive(Args)]
+struct ListArgs {
+    /// Update authority to look for
+    #[arg(long, required_unless_present = "creator")]
+    update_authority: Option<String>,
+    /// Creator to look for, in any position of the creators list
+    #[arg(long, conflicts_with = "update_authority")]
+    creator: Option<String>,
+    /// Only list metadata on which the creator is verified
+    #[arg(long, requires = "creator")]
+    verified: bool,
+}
+
+#[derive(Args)]
+struct SnapshotArgs {
+    /// Collection NFT mint address, to snapshot every verified item
+    #[arg(short, long, required_unless_present = "mint")]
+    collection: Option<String>,
+
+    /// Token mint address, to snapshot a single mint
+    #[arg(short, long, conflicts_with = "collection")]
+    mint: Option<String>,
+
+    /// Write the holders to this file: JSON when it ends in .json, CSV
+    /// otherwise
+    #[arg(long, value_name = "PATH")]
+    out: Option<PathBuf>,
+
+    /// Number of mints to look up at the same time
+    #[arg(long, default_value_t = 4)]
+    concurrency: usize,
+}
 
-        /// Whether metadata should be mutable
-        #[arg(long, default_value_t = true)]
-        mutable: bool,
+#[derive(Args)]
+struct WatchArgs {
+    /// Token mint address (repeatable)
+    #[arg(short, long = "mint", required_unless_present = "mints")]
+    mint: Vec<String>,
+
+    /// File with one mint address per line
+    #[arg(long, value_name = "PATH")]
+    mints: Option<PathBuf>,
+
+    /// Also POST each change as JSON to this URL
+    #[arg(long, value_name = "URL")]
+    webhook: Option<String>,
+}
+
+#[derive(Args)]
+struct AuditArgs {
+    /// Token mint address (repeatable)
+    #[arg(short, long = "mint", required_unless_present = "mints")]
+    mint: Vec<String>,
+
+    /// File with one mint address per line
+    #[arg(long, value_name = "PATH")]
+    mints: Option<PathBuf>,
+
+    /// Fail unless every mint scores at least this much out of 100
+    #[arg(long, value_name = "SCORE", value_parser = clap::value_parser!(u8).range(0..=100))]
+    min_score: Option<u8>,
+
+    /// Count mutable metadata as an error rather than a warning
+    #[arg(long)]
+    require_immutable: bool,
+
+    /// Don't fetch the JSON behind each URI
+    #[arg(long)]
+    skip_uri: bool,
+
+    /// Seconds to wait for each URI's JSON
+    #[arg(long, default_value_t = 15)]
+    timeout: u64,
+
+    /// Gateway used to resolve ipfs:// URIs
+    #[arg(long, default_value = "https://ipfs.io")]
+    ipfs_gateway: String,
+
+    /// Gateway used to resolve ar:// URIs
+    #[arg(long, default_value = "https://arweave.net")]
+    arweave_gateway: String,
+}
+
+#[derive(Args)]
+struct ShowArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+    /// Also fetch the off-chain JSON from the URI and show its image,
+    /// description and attributes
+    #[arg(long)]
+    offchain: bool,
+    /// Seconds to wait for the off-chain JSON
+    #[arg(long, default_value_t = 15, requires = "offchain")]
+    timeout: u64,
+    /// Gateway used to resolve ipfs:// URIs
+    #[arg(long, default_value = "https://ipfs.io", requires = "offchain")]
+    ipfs_gateway: String,
+    /// Gateway used to resolve ar:// URIs
+    #[arg(long, default_value = "https://arweave.net", requires = "offchain")]
+    arweave_gateway: String,
+    /// Also list the mint's metadata delegates (needs an RPC that allows
+    /// getProgramAccounts)
+    #[arg(long)]
+    delegates: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum StorageBackend {
+    /// Arweave through Irys, paid in SOL
+    Arweave,
+    /// IPFS through a pinning service
+    Ipfs,
+    /// A Shadow Drive storage account (mainnet only)
+    Shadow,
+}
+
+#[derive(Args)]
+struct StorageArgs {
+    /// Where to upload files
+    #[arg(long, value_enum, default_value_t = StorageBackend::Arweave)]
+    storage: StorageBackend,
+
+    /// IPFS pinning service: pinata, nft.storage or web3.storage [default: pinata]
+    #[arg(long)]
+    ipfs_provider: Option<IpfsProvider>,
+
+    /// Shadow Drive storage account to upload into, owned by --keypair
+    #[arg(long, value_name = "PUBKEY")]
+    shadow_storage_account: Option<Pubkey>,
+}
+
+#[derive(Args)]
+struct UseMethodArgs {
+    /// How uses are consumed: burn, multiple or single
+    #[arg(long)]
+    use_method: Option<UseMethod>,
+
+    /// Number of uses (defaults to 1 for single)
+    #[arg(long, requires = "use_method")]
+    use_total: Option<u64>,
+}
+
+impl UseMethodArgs {
+    fn parse(&self) -> Result<Option<UsesArgs>> {
+        self.use_method
+            .map(|method| UsesArgs::new(method, self.use_total))
+            .transpose()
+    }
+}
+
+#[derive(Subcommand)]
+enum BatchCommands {
+    /// Create metadata for every mint listed in a CSV or JSON manifest
+    Create {
+        /// Manifest with columns mint, name, symbol, uri, seller_fee_basis_points (and optionally is_mutable)
+        #[arg(long)]
+        manifest: PathBuf,
+
+        /// Number of transactions to send at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+    },
+    /// Work out what `batch create` would cost for a manifest, without
+    /// sending anything
+    Estimate {
+        /// Manifest in the form `batch create` reads
+        #[arg(long)]
+        manifest: PathBuf,
+
+        /// File to upload to Arweave first, or a directory of them (repeatable)
+        #[arg(long = "upload", value_name = "PATH")]
+        uploads: Vec<PathBuf>,
+    },
+    /// Apply partial metadata updates to many mints from a JSON file
+    Update {
+        /// JSON array of objects with a mint and any of name, symbol, uri, creators
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Number of transactions to send at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+    },
+    /// Resize the metadata of every mint in a list, one address per line
+    Resize {
+        /// File of mint addresses
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Number of transactions to send at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+    },
+    /// Close the accounts of every burned mint in a list, one address per line
+    Close {
+        /// File of mint addresses
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Number of transactions to send at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+    },
+    /// Mint a compressed NFT for every row of a CSV or JSON manifest
+    Cmint {
+        /// Merkle tree address
+        #[arg(long)]
+        tree: String,
+
+        /// Manifest with columns name, uri and optionally symbol, seller_fee_basis_points, is_mutable, owner
+        #[arg(long)]
+        manifest: PathBuf,
+
+        /// Collection NFT mint to mint every row into, verified
+        #[arg(long)]
+        collection: Option<String>,
+
+        /// Number of transactions to send at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum JsonCommands {
+    /// Write a metadata JSON file following the Metaplex metadata standard
+    Generate {
+        /// Token name
+        #[arg(long)]
+        name: String,
+
+        /// Token symbol
+        #[arg(long)]
+        symbol: String,
+
+        /// Description of the token
+        #[arg(long)]
+        description: Option<String>,
+
+        /// URI of the image, already uploaded
+        #[arg(long)]
+        image_uri: String,
+
+        /// MIME type of the image; guessed from the URI's extension by default
+        #[arg(long, value_name = "MIME")]
+        image_type: Option<String>,
+
+        /// URI of a video, 3D model or web page shown instead of the image
+        #[arg(long)]
+        animation_url: Option<String>,
+
+        /// Website of the token or project
+        #[arg(long)]
+        external_url: Option<String>,
+
+        /// Seller fee basis points (0-10000)
+        #[arg(long, default_value_t = 0)]
+        seller_fee_basis_points: u16,
+
+        /// Creator and royalty share, e.g. <PUBKEY>:100 (repeatable; shares must sum to 100)
+        #[arg(long = "creator", value_name = "PUBKEY:SHARE")]
+        creators: Vec<CreatorShare>,
+
+        /// Attribute, e.g. background:blue (repeatable)
+        #[arg(long = "attribute", value_name = "TRAIT:VALUE")]
+        attributes: Vec<JsonAttribute>,
+
+        /// File to write
+        #[arg(long, value_name = "PATH", default_value = "metadata.json")]
+        out: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommands {
+    /// Write the mints as a Solana token list, with their logos and
+    /// decimals
+    TokenList {
+        /// File with one mint address per line
+        #[arg(long, value_name = "PATH")]
+        mints: PathBuf,
+
+        /// Output JSON file
+        #[arg(long, value_name = "PATH")]
+        out: PathBuf,
+
+        /// Name of the list
+        #[arg(long, default_value = "Token List")]
+        name: String,
+
+        /// Chain id of the tokens; defaults to that of the RPC's cluster
+        /// (101 mainnet, 102 testnet, 103 devnet)
+        #[arg(long)]
+        chain_id: Option<u16>,
+
+        /// Seconds to wait for each URI's JSON
+        #[arg(long, default_value_t = 15)]
+        timeout: u64,
+
+        /// Gateway used to resolve ipfs:// URIs
+        #[arg(long, default_value = "https://ipfs.io")]
+        ipfs_gateway: String,
+
+        /// Gateway used to resolve ar:// URIs
+        #[arg(long, default_value = "https://arweave.net")]
+        arweave_gateway: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum Token2022Commands {
+    /// Initialize the metadata of a Token-2022 mint whose metadata pointer points at itself
+    Create {
+        /// Token-2022 mint address
+        #[arg(short, long)]
+        mint: String,
+
+        /// Token name
+        #[arg(short, long)]
+        name: String,
+
+        /// Token symbol
+        #[arg(short, long)]
+        symbol: String,
+
+        /// Metadata URI (JSON file URL)
+        #[arg(long, default_value = "")]
+        uri: String,
+
+        /// Additional metadata field, e.g. website=https://example.com (repeatable)
+        #[arg(long = "field", value_name = "KEY=VALUE")]
+        fields: Vec<MetadataField>,
+    },
+    /// Change fields of a Token-2022 mint's metadata
+    Update {
+        /// Token-2022 mint address
+        #[arg(short, long)]
+        mint: String,
+
+        /// New token name
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// New token symbol
+        #[arg(short, long)]
+        symbol: Option<String>,
+
+        /// New metadata URI
+        #[arg(long)]
+        uri: Option<String>,
+
+        /// Additional metadata field to add or change (repeatable)
+        #[arg(long = "field", value_name = "KEY=VALUE")]
+        fields: Vec<MetadataField>,
+
+        /// Additional metadata field to remove (repeatable)
+        #[arg(long = "remove-field", value_name = "KEY")]
+        remove_fields: Vec<String>,
+    },
+    /// Show the metadata stored in a Token-2022 mint
+    Show {
+        /// Token-2022 mint address
+        #[arg(short, long)]
+        mint: String,
+    },
+}
+
+#[derive(Args)]
+struct LockArgs {
+    /// pNFT mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// Wallet holding the pNFT
+    #[arg(long)]
+    owner: String,
+}
+
+impl LockArgs {
+    fn parse(&self) -> Result<(Pubkey, Pubkey)> {
+        let mint = Pubkey::from_str(&self.mint).context("Invalid mint address")?;
+        let owner = Pubkey::from_str(&self.owner).context("Invalid owner address")?;
+        Ok((mint, owner))
+    }
+}
+
+#[derive(Subcommand)]
+enum CollectionCommands {
+    /// List the tokens that point at a collection, verified and unverified
+    /// separately
+    Items {
+        /// Collection NFT mint address
+        #[arg(short, long)]
+        collection: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TreeCommands {
+    /// Allocate a Merkle tree and create its Bubblegum tree config, with
+    /// the wallet as tree creator
+    Create {
+        /// Depth of the tree; it holds 2^depth compressed NFTs
+        #[arg(long, default_value_t = 14)]
+        max_depth: u32,
+
+        /// Changes the tree can take in the same slot before proofs go stale
+        #[arg(long, default_value_t = 64)]
+        max_buffer_size: u32,
+
+        /// Levels of the tree cached on-chain, so transactions can send
+        /// shorter proofs at the cost of a bigger account
+        #[arg(long, default_value_t = 0)]
+        canopy_depth: u32,
+
+        /// Let anyone mint into the tree, not just the wallet
+        #[arg(long)]
+        public: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ShadowCommands {
+    /// Create a storage account, paid for by staking SHDW (mainnet only)
+    Create {
+        /// Name of the storage account
+        #[arg(short, long)]
+        name: String,
+
+        /// Space to reserve, e.g. 512KB, 10MB or 1GiB
+        #[arg(long, value_parser = parse_storage_size)]
+        size: u64,
+
+        /// Keypair that owns the account and pays for it [default: --keypair]
+        #[arg(long, value_name = "KEYPAIR")]
+        owner: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CoreCommands {
+    /// Create a Core asset, with the wallet as update authority
+    Create {
+        /// Asset name
+        #[arg(short, long)]
+        name: String,
+
+        /// Metadata URI (JSON file URL)
+        #[arg(long)]
+        uri: String,
+
+        /// Wallet to own the asset (defaults to --keypair)
+        #[arg(long)]
+        owner: Option<String>,
+
+        /// Core collection to add the asset to (you must be its update authority)
+        #[arg(long)]
+        collection: Option<String>,
+    },
+    /// Change the name or URI of a Core asset
+    Update {
+        /// Core asset address
+        #[arg(long)]
+        asset: String,
+
+        /// New name
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// New metadata URI
+        #[arg(long)]
+        uri: Option<String>,
+    },
+    /// Show a Core asset
+    Show {
+        /// Core asset address
+        #[arg(long)]
+        asset: String,
+    },
+    /// Burn a Core asset you own
+    Burn {
+        /// Core asset address
+        #[arg(long)]
+        asset: String,
+    },
+    /// Add or replace the royalties plugin of a Core asset or collection
+    Royalties {
+        /// Core asset address
+        #[arg(long, required_unless_present = "collection")]
+        asset: Option<String>,
+
+        /// Core collection address
+        #[arg(long, conflicts_with = "asset")]
+        collection: Option<String>,
+
+        /// Royalty in basis points (100 = 1%)
+        #[arg(long)]
+        seller_fee_basis_points: u16,
+
+        /// Creator and royalty share, e.g. <PUBKEY>:100 (repeatable; shares must sum to 100)
+        #[arg(long = "creator", value_name = "PUBKEY:SHARE", required = true)]
+        creators: Vec<CreatorShare>,
+    },
+    /// Add or change attributes in the attributes plugin of a Core asset or collection
+    Attributes {
+        /// Core asset address
+        #[arg(long, required_unless_present = "collection")]
+        asset: Option<String>,
+
+        /// Core collection address
+        #[arg(long, conflicts_with = "asset")]
+        collection: Option<String>,
+
+        /// Attribute to add or change, e.g. background=blue (repeatable)
+        #[arg(long = "attribute", value_name = "KEY=VALUE")]
+        attributes: Vec<MetadataField>,
+
+        /// Attribute to remove (repeatable)
+        #[arg(long = "remove-attribute", value_name = "KEY")]
+        remove_attributes: Vec<String>,
+    },
+    /// Add the freeze delegate plugin to a Core asset you own, hand it to a
+    /// new delegate, or freeze or thaw the asset as its delegate
+    FreezeDelegate {
+        /// Core asset address
+        #[arg(long)]
+        asset: String,
+
+        /// Key that may freeze and thaw the asset (defaults to the owner)
+        #[arg(long)]
+        delegate: Option<String>,
+
+        /// Freeze (true) or thaw (false) the asset
+        #[arg(long)]
+        frozen: Option<bool>,
+    },
+    /// Add the transfer delegate plugin to a Core asset you own, or hand it
+    /// to a new delegate
+    TransferDelegate {
+        /// Core asset address
+        #[arg(long)]
+        asset: String,
+
+        /// Key that may transfer the asset
+        #[arg(long)]
+        delegate: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum DelegateCommands {
+    /// Approve a delegate for a role on a mint's metadata or your tokens
+    Approve {
+        #[command(flatten)]
+        target: DelegateArgs,
+
+        /// Base units of a regular token to delegate with the standard role
+        /// (programmable NFTs are always delegated whole)
+        #[arg(long, default_value_t = 1)]
+        amount: u64,
+    },
+    /// Revoke a delegate's role on a mint's metadata or your tokens
+    Revoke {
+        #[command(flatten)]
+        target: DelegateArgs,
+    },
+}
+
+#[derive(Args)]
+struct DelegateArgs {
+    /// Token mint address
+    #[arg(short, long)]
+    mint: String,
+
+    /// Delegate role: data, collection or programmable-config on the
+    /// metadata; transfer, sale, utility or staking on a pNFT; standard on
+    /// other tokens
+    #[arg(long)]
+    role: DelegateRole,
+
+    /// Delegate address
+    #[arg(short, long)]
+    delegate: String,
+}
+
+#[derive(Subcommand)]
+enum RulesetCommands {
+    /// Create a rule set owned by the wallet and print its address
+    Create {
+        #[command(flatten)]
+        rules: RuleSetArgs,
+    },
+    /// Replace the rules of a rule set owned by the wallet
+    Update {
+        #[command(flatten)]
+        rules: RuleSetArgs,
+    },
+    /// Show a rule set's address and latest rules
+    Show {
+        /// Rule set name
+        #[arg(short, long, requires = "owner", required_unless_present = "address")]
+        name: Option<String>,
+
+        /// Rule set owner
+        #[arg(long)]
+        owner: Option<String>,
+
+        /// Rule set address
+        #[arg(long, conflicts_with_all = ["name", "owner"])]
+        address: Option<String>,
+    },
+}
+
+#[derive(Args)]
+struct RuleSetArgs {
+    /// Rule set name (at most 32 bytes)
+    #[arg(short, long)]
+    name: String,
+
+    /// JSON file mapping operation names such as Transfer:Owner to rules
+    #[arg(
+        short,
+        long,
+        conflicts_with = "allow_programs",
+        required_unless_present = "allow_programs"
+    )]
+    file: Option<PathBuf>,
+
+    /// Program allowed to hold, receive or move tokens in transfers and delegations (repeatable)
+    #[arg(long = "allow-program", value_name = "PROGRAM")]
+    allow_programs: Vec<String>,
+}
+
+impl DelegateArgs {
+    fn parse(&self) -> Result<(Pubkey, Pubkey)> {
+        let mint = Pubkey::from_str(&self.mint).context("Invalid mint address")?;
+        let delegate = Pubkey::from_str(&self.delegate).context("Invalid delegate address")?;
+        Ok((mint, delegate))
+    }
+}
+
+impl RuleSetArgs {
+    fn build(&self, owner: Pubkey) -> Result<RuleSet> {
+        match &self.file {
+            Some(file) => RuleSet::new(owner, &self.name, load_operations(file)?),
+            None => {
+                let programs = self
+                    .allow_programs
+                    .iter()
+                    .map(|program| {
+                        Pubkey::from_str(program)
+                            .with_context(|| format!("Invalid program address '{}'", program))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                RuleSet::allowlist(owner, &self.name, &programs)
+            }
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Set a value (url, keypair, commitment, priority-fee) on the selected profile
+    Set { key: String, value: String },
+    /// Print a value of the selected profile
+    Get { key: String },
+    /// List all profiles and their values
+    List,
+}
+
+/// Combine command-line fields with those of an optional local metadata
+/// JSON file. Command-line values win.
+fn merge_metadata_file(
+    metadata_file: Option<&Path>,
+    name: Option<String>,
+    symbol: Option<String>,
+    seller_fee_basis_points: Option<u16>,
+    creators: Vec<CreatorShare>,
+) -> Result<(String, String, u16, Vec<CreatorShare>)> {
+    let file = metadata_file
+        .map(OffChainMetadata::from_file)
+        .transpose()?
+        .unwrap_or_default();
+
+    let name = name
+        .or(file.name)
+        .context("A name is required (via --name or the metadata file)")?;
+    let symbol = symbol
+        .or(file.symbol)
+        .context("A symbol is required (via --symbol or the metadata file)")?;
+    let seller_fee_basis_points = seller_fee_basis_points
+        .or(file.seller_fee_basis_points)
+        .unwrap_or(0);
+    let creators = if creators.is_empty() {
+        file.properties
+            .map(|p| p.creators)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|c| {
+                Ok(CreatorShare {
+                    address: Pubkey::from_str(&c.address)
+                        .with_context(|| format!("Invalid creator address '{}'", c.address))?,
+                    share: c.share,
+                })
+            })
+            .collect::<Result<_>>()?
+    } else {
+        creators
+    };
+
+    Ok((name, symbol, seller_fee_basis_points, creators))
+}
+
+/// Print a command result in the selected output format. In a dry run
+/// nothing was sent, so the simulations are printed instead.
+fn emit<T: Serialize>(
+    client: &MetadataClient,
+    output: OutputFormat,
+    result: &T,
+    print_text: fn(&T),
+) -> Result<()> {
+    if client.is_sign_only() {
+        // Reported once the command has signed all of its transactions
+        return Ok(());
+    }
+    if client.is_dry_run() {
+        let simulations = client.take_simulations();
+        return match output {
+            OutputFormat::Text | OutputFormat::Signature => {
+                print_simulations(&simulations);
+                Ok(())
+            }
+            OutputFormat::Json => print_json(&simulations),
+        };
+    }
+
+    match output {
+        OutputFormat::Text => print_text(result),
+        OutputFormat::Json => print_json(result)?,
+        OutputFormat::Signature => print_signatures(result)?,
+    }
+    Ok(())
+}
+
+/// Print every transaction signature in a result, one per line
+fn print_signatures<T: Serialize>(result: &T) -> Result<()> {
+    fn collect(value: &Value, signatures: &mut Vec<String>) {
+        match value {
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    match (key.as_str(), value) {
+                        ("signature", Value::String(signature)) => {
+                            signatures.push(signature.clone())
+                        }
+                        _ => collect(value, signatures),
+                    }
+                }
+            }
+            Value::Array(values) => values.iter().for_each(|value| collect(value, signatures)),
+            _ => {}
+        }
+    }
+
+    let mut signatures = Vec::new();
+    collect(&serde_json::to_value(result)?, &mut signatures);
+    for signature in signatures {
+        println!("{}", signature);
+    }
+    Ok(())
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Asks before operations that can't be undone, such as burning or giving
+/// up authority. Every mutating command with such an effect goes through
+/// [`Confirmation::require`] after printing its summary.
+#[derive(Clone, Copy)]
+struct Confirmation {
+    /// Go ahead without asking: --yes was passed, or nothing will be sent
+    skip: bool,
+}
+
+impl Confirmation {
+    /// Warn that the operation can't be undone and ask whether to go ahead,
+    /// failing with "Aborted" on any answer but yes
+    fn require(&self, warning: &str) -> Result<()> {
+        if self.skip {
+            return Ok(());
+        }
+        eprintln!("\nWARNING: {}\n", warning);
+        if !confirm("Continue?")? {
+            anyhow::bail!("Aborted");
+        }
+        Ok(())
+    }
+}
+
+/// Ask the user a yes/no question on stderr, defaulting to no
+fn confirm(prompt: &str) -> Result<bool> {
+    eprint!("{} [y/N] ", prompt);
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer)? == 0 {
+        anyhow::bail!(
+            "No answer to '{}'; pass --yes to go ahead without asking",
+            prompt
+        );
+    }
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// A Unix time as a UTC date and time, e.g. "2024-05-01 12:30:00 UTC"
+fn lamports_to_sol(lamports: u64) -> f64 {
+    lamports as f64 / LAMPORTS_PER_SOL as f64
+}
+
+fn sol_to_lamports(sol: f64) -> u64 {
+    (sol * LAMPORTS_PER_SOL as f64).round() as u64
+}
+
+/// Explorer, cluster and RPC URL that transaction links point to, set once
+/// at startup
+static EXPLORER: OnceLock<(Explorer, Cluster, String)> = OnceLock::new();
+
+fn explorer_tx_url(signature: &str) -> String {
+    let (explorer, cluster, url) = EXPLORER.get().expect("explorer is set at startup");
+    explorer.tx_url(signature, *cluster, url)
+}
+
+fn print_create_preview(mint: &Pubkey, args: &CreateMetadataArgs) {
+    println!("Creating metadata...");
+    println!("  Mint:         {}", mint);
+    println!("  Metadata PDA: {}", find_metadata_pda(mint));
+    println!("  Name:         {}", args.name);
+    println!("  Symbol:       {}", args.symbol);
+    println!(
+        "  URI:          {}",
+        if args.uri.is_empty() {
+            "(empty)"
+        } else {
+            &args.uri
+        }
+    );
+    println!("  Mutable:      {}", args.is_mutable);
+    if let Some(update_authority) = &args.update_authority {
+        println!("  Update auth:  {}", update_authority);
+    }
+    if let Some(creators) = &args.creators {
+        println!("  Creators:");
+        for creator in creators {
+            println!("    {} (share: {}%)", creator.address, creator.share);
+        }
+    }
+    if let Some(uses) = &args.uses {
+        println!(
+            "  Uses:         {:?} ({} total)",
+            uses.use_method, uses.total
+        );
+    }
+}
+
+fn print_airdrop_result(result: &AirdropResult) {
+    println!("\nAirdrop received!");
+    println!("  Balance:   {} SOL", lamports_to_sol(result.balance));
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_create_result(result: &CreateResult) {
+    println!("\nMetadata created successfully!");
+    if let Some(standard) = &result.token_standard {
+        println!("  Standard:  {}", standard);
+    }
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_update_result(result: &UpdateResult) {
+    println!("  Name:         {} -> {}", result.name.old, result.name.new);
+    println!(
+        "  Symbol:       {} -> {}",
+        result.symbol.old, result.symbol.new
+    );
+    println!("  URI:          {} -> {}", result.uri.old, result.uri.new);
+    if let Some(creators) = &result.creators {
+        println!("  Creators:");
+        for creator in creators {
+            println!(
+                "    {} (share: {}%, verified: {})",
+                creator.address, creator.share, creator.verified
+            );
+        }
+    }
+    if let Some(uses) = &result.uses {
+        println!(
+            "  Uses:         {} ({}/{} remaining)",
+            uses.use_method, uses.remaining, uses.total
+        );
+    }
+    if result.primary_sale_happened.is_some() {
+        println!("  Primary sale: happened");
+    }
+    println!("\nMetadata updated successfully!");
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_history_result(result: &HistoryResult) {
+    if result.entries.is_empty() {
+        println!("No recorded changes to mint {}", result.mint);
+        return;
+    }
+    println!("Recorded changes to mint {}, newest first:", result.mint);
+    for entry in &result.entries {
+        println!("\n  Entry {} (slot {})", entry.id, entry.slot);
+        println!("    Changed by:       {}", entry.signature);
+        println!(
+            "    Recorded at:      {}",
+            humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(entry.recorded_at))
+        );
+        println!("    Before:");
+        println!("      Name:           {}", entry.name);
+        println!("      Symbol:         {}", entry.symbol);
+        println!("      URI:            {}", entry.uri);
+        println!(
+            "      Seller fee:     {} bps ({:.2}%)",
+            entry.seller_fee_basis_points,
+            entry.seller_fee_basis_points as f64 / 100.0
+        );
+        println!("      Update auth:    {}", entry.update_authority);
+        println!("      Mutable:        {}", entry.is_mutable);
+        if let Some(creators) = &entry.creators {
+            println!("      Creators:");
+            for creator in creators {
+                println!(
+                    "        {} (share: {}%, verified: {})",
+                    creator.address, creator.share, creator.verified
+                );
+            }
+        }
+    }
+}
+
+fn print_rollback_result(result: &RollbackResult) {
+    println!("  Undoing:      {}", result.undone_signature);
+    println!("  Name:         {} -> {}", result.name.old, result.name.new);
+    println!(
+        "  Symbol:       {} -> {}",
+        result.symbol.old, result.symbol.new
+    );
+    println!("  URI:          {} -> {}", result.uri.old, result.uri.new);
+    println!(
+        "  Seller fee:   {} bps ({:.2}%)",
+        result.seller_fee_basis_points,
+        result.seller_fee_basis_points as f64 / 100.0
+    );
+    if let Some(update_authority) = &result.update_authority {
+        println!(
+            "  Update auth:  {} -> {}",
+            update_authority.old, update_authority.new
+        );
+    }
+    if let Some(creators) = &result.creators {
+        println!("  Creators:");
+        for creator in creators {
+            println!(
+                "    {} (share: {}%, verified: {})",
+                creator.address, creator.share, creator.verified
+            );
+        }
+    }
+    println!("\nMetadata rolled back to entry {}!", result.entry);
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_set_authority_result(result: &SetAuthorityResult) {
+    println!("\nUpdate authority transferred successfully!");
+    println!("  Old authority: {}", result.old_authority);
+    println!("  New authority: {}", result.new_authority);
+    println!("  Signature:     {}", result.signature);
+    println!("  Explorer:      {}", explorer_tx_url(&result.signature));
+}
+
+fn print_make_immutable_result(result: &MakeImmutableResult) {
+    println!("\nMetadata is now immutable!");
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_primary_sale_result(result: &PrimarySaleResult) {
+    if let Some(token_account) = &result.token_account {
+        println!("  Token account: {}", token_account);
+    }
+    println!("\nPrimary sale marked as happened!");
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_create_mint_result(result: &CreateMintResult) {
+    println!("\nMint created successfully!");
+    println!("  Mint:           {}", result.mint);
+    println!("  Decimals:       {}", result.decimals);
+    println!("  Mint authority: {}", result.mint_authority);
+    println!(
+        "  Freeze auth:    {}",
+        result.freeze_authority.as_deref().unwrap_or("(disabled)")
+    );
+    println!(
+        "  Initial supply: {}",
+        spl_token::amount_to_ui_amount_string_trimmed(result.initial_supply, result.decimals)
+    );
+    if let Some(token_account) = &result.token_account {
+        println!("  Token account:  {}", token_account);
+    }
+    println!("  Signature:      {}", result.signature);
+    println!("  Explorer:       {}", explorer_tx_url(&result.signature));
+    println!(
+        "\nAdd metadata with: create --mint {} --name <NAME> --symbol <SYMBOL>",
+        result.mint
+    );
+}
+
+fn print_create_token_result(result: &CreateTokenResult) {
+    println!("\nToken created successfully!");
+    println!("  Mint:           {}", result.mint);
+    println!("  Metadata PDA:   {}", result.metadata_pda);
+    println!("  Decimals:       {}", result.decimals);
+    println!(
+        "  Initial supply: {}",
+        spl_token::amount_to_ui_amount_string_trimmed(result.initial_supply, result.decimals)
+    );
+    if let Some(token_account) = &result.token_account {
+        println!("  Token account:  {}", token_account);
+    }
+    println!("  Signature:      {}", result.signature);
+    println!("  Explorer:       {}", explorer_tx_url(&result.signature));
+}
+
+fn print_create_nft_result(result: &CreateNftResult) {
+    println!("\nNFT created successfully!");
+    println!("  Mint:           {}", result.mint);
+    println!("  Metadata PDA:   {}", result.metadata_pda);
+    println!("  Master edition: {}", result.master_edition_pda);
+    println!("  Token account:  {}", result.token_account);
+    if let Some(token_record) = &result.token_record {
+        println!("  Token record:   {}", token_record);
+    }
+    if let Some(rule_set) = &result.rule_set {
+        println!("  Rule set:       {}", rule_set);
+    }
+    println!("  Signature:      {}", result.signature);
+    println!("  Explorer:       {}", explorer_tx_url(&result.signature));
+}
+
+fn print_create_tree_result(result: &CreateTreeResult) {
+    println!("\nMerkle tree created successfully!");
+    println!("  Tree:         {}", result.tree);
+    println!("  Tree config:  {}", result.tree_config);
+    println!("  Tree creator: {}", result.tree_creator);
+    println!(
+        "  Shape:        depth {}, buffer {}, canopy {}",
+        result.max_depth, result.max_buffer_size, result.canopy_depth
+    );
+    println!("  Capacity:     {} cNFTs", result.capacity);
+    println!(
+        "  Public:       {}",
+        if result.public { "yes" } else { "no" }
+    );
+    println!(
+        "  Rent:         {} SOL for {} bytes",
+        lamports_to_sol(result.lamports),
+        result.account_size
+    );
+    println!("  Signature:    {}", result.signature);
+    println!("  Explorer:     {}", explorer_tx_url(&result.signature));
+}
+
+fn print_compressed_mint_result(result: &CompressedMintResult) {
+    println!("\nCompressed NFT minted successfully!");
+    println!("  Asset ID:     {}", result.asset_id);
+    println!("  Tree:         {}", result.tree);
+    println!("  Leaf index:   {}", result.leaf_index);
+    println!("  Owner:        {}", result.owner);
+    if let Some(collection) = &result.collection {
+        println!("  Collection:   {} (verified)", collection);
+    }
+    println!("  Signature:    {}", result.signature);
+    println!("  Explorer:     {}", explorer_tx_url(&result.signature));
+}
+
+fn print_compressed_update_result(result: &CompressedUpdateResult) {
+    println!("  Tree:         {}", result.tree);
+    println!("  Name:         {} -> {}", result.name.old, result.name.new);
+    println!(
+        "  Symbol:       {} -> {}",
+        result.symbol.old, result.symbol.new
+    );
+    println!("  URI:          {} -> {}", result.uri.old, result.uri.new);
+    println!("\nCompressed NFT updated successfully!");
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_compressed_transfer_result(result: &CompressedTransferResult) {
+    println!("  Tree:         {}", result.tree);
+    println!("  From:         {}", result.from);
+    println!("\nCompressed NFT transferred successfully!");
+    if !result.owner_confirmed {
+        println!("  The DAS API doesn't report the new owner yet; check again shortly");
+    }
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_compressed_burn_result(result: &CompressedBurnResult) {
+    println!("  Tree:         {}", result.tree);
+    println!("  Owner:        {}", result.owner);
+    println!("\nCompressed NFT burned successfully!");
+    if !result.burn_confirmed {
+        println!("  The DAS API doesn't report the asset as burned yet; check again shortly");
+    }
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_decompress_result(result: &DecompressResult) {
+    println!("  Tree:         {}", result.tree);
+    println!("  Voucher:      {}", result.voucher);
+    println!("\nCompressed NFT decompressed successfully!");
+    println!("  Mint:           {}", result.mint);
+    println!("  Metadata:       {}", result.metadata_pda);
+    println!("  Master edition: {}", result.master_edition_pda);
+    println!("  Token account:  {}", result.token_account);
+    match &result.redeem_signature {
+        Some(signature) => println!("  Redeemed:       {}", signature),
+        None => println!("  Redeemed:       already, before this run"),
+    }
+    println!("  Signature:      {}", result.signature);
+    println!("  Explorer:       {}", explorer_tx_url(&result.signature));
+}
+
+fn print_core_asset(info: &CoreAssetInfo) {
+    println!("Core asset");
+    println!("  Address:               {}", info.address);
+    println!("  Name:                  {}", info.name);
+    println!("  URI:                   {}", info.uri);
+    println!("  Owner:                 {}", info.owner);
+    println!(
+        "  Update authority:      {}",
+        info.update_authority.as_deref().unwrap_or("(none)")
+    );
+    println!(
+        "  Collection:            {}",
+        info.collection.as_deref().unwrap_or("(none)")
+    );
+    if info.plugins.is_empty() {
+        println!("  Plugins:               (none)");
+    } else {
+        println!("  Plugins:");
+        for plugin in &info.plugins {
+            match &plugin.details {
+                Some(details) => {
+                    println!("    {} ({}): {}", plugin.plugin, plugin.authority, details)
+                }
+                None => println!("    {} ({})", plugin.plugin, plugin.authority),
+            }
+        }
+    }
+}
+
+/// The asset or collection from a Core plugin command's --asset and
+/// --collection, of which clap ensures exactly one is given
+fn core_target(asset: Option<String>, collection: Option<String>) -> Result<CoreTarget> {
+    match (asset, collection) {
+        (Some(asset), _) => Ok(CoreTarget::Asset(
+            Pubkey::from_str(&asset).context("Invalid asset address")?,
+        )),
+        (None, Some(collection)) => Ok(CoreTarget::Collection(
+            Pubkey::from_str(&collection).context("Invalid collection address")?,
+        )),
+        (None, None) => anyhow::bail!("Pass --asset or --collection"),
+    }
+}
+
+fn print_core_plugin_preview(output: OutputFormat, target: &CoreTarget, plugin: &CorePlugin) {
+    if output != OutputFormat::Text {
+        return;
+    }
+    println!("Setting Core {} plugin...", plugin.kind());
+    match target {
+        CoreTarget::Asset(asset) => println!("  Asset:        {}", asset),
+        CoreTarget::Collection(collection) => println!("  Collection:   {}", collection),
+    }
+}
+
+fn print_core_plugin_result(result: &CorePluginResult) {
+    if let Some(delegate) = &result.delegate {
+        println!("  Delegate:     {}", delegate);
+    }
+    println!(
+        "\nCore {} plugin {} successfully!",
+        result.plugin,
+        if result.added { "added" } else { "updated" }
+    );
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_core_create_result(result: &CoreCreateResult) {
+    println!("\nCore asset created successfully!");
+    println!("  Asset:     {}", result.asset.address);
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_core_update_result(result: &CoreUpdateResult) {
+    println!("  Name:         {} -> {}", result.name.old, result.name.new);
+    println!("  URI:          {} -> {}", result.uri.old, result.uri.new);
+    println!("\nCore asset updated successfully!");
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_core_burn_result(result: &CoreBurnResult) {
+    println!("  Name:         {}", result.name);
+    println!("\nCore asset burned successfully!");
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_inscribe_result(result: &InscribeResult) {
+    println!("  Metadata:     {}", result.inscription_metadata);
+    if result.chunks_skipped > 0 {
+        println!(
+            "  Skipped:      {} chunks already written",
+            result.chunks_skipped
+        );
+    }
+    println!("\nInscribed {} bytes successfully!", result.bytes);
+    for transaction in &result.transactions {
+        println!("  {:<18} {}", transaction.step, transaction.signature);
+    }
+    if let Some(last) = result.transactions.last() {
+        println!("  Explorer:  {}", explorer_tx_url(&last.signature));
+    }
+}
+
+fn print_create_master_edition_result(result: &CreateMasterEditionResult) {
+    println!("\nMaster edition created successfully!");
+    println!("  Master edition: {}", result.master_edition_pda);
+    match result.max_supply {
+        Some(max_supply) => println!("  Max supply:     {}", max_supply),
+        None => println!("  Max supply:     unlimited"),
+    }
+    println!("  Signature:      {}", result.signature);
+    println!("  Explorer:       {}", explorer_tx_url(&result.signature));
+}
+
+fn print_print_edition_result(result: &PrintEditionResult) {
+    println!("\nEdition printed successfully!");
+    match result.max_supply {
+        Some(max_supply) => println!("  Edition:       {} of {}", result.edition, max_supply),
+        None => println!("  Edition:       {}", result.edition),
+    }
+    println!("  Edition mint:  {}", result.mint);
+    println!("  Metadata PDA:  {}", result.metadata_pda);
+    println!("  Edition PDA:   {}", result.edition_pda);
+    println!("  Token account: {}", result.token_account);
+    println!("  Signature:     {}", result.signature);
+    println!("  Explorer:      {}", explorer_tx_url(&result.signature));
+}
+
+fn print_create_collection_result(result: &CreateCollectionResult) {
+    println!("\nCollection created successfully!");
+    println!("  Collection mint: {}", result.mint);
+    println!("  Metadata PDA:    {}", result.metadata_pda);
+    println!("  Master edition:  {}", result.master_edition_pda);
+    println!("  Token account:   {}", result.token_account);
+    println!("  Signature:       {}", result.signature);
+    println!("  Explorer:        {}", explorer_tx_url(&result.signature));
+}
+
+fn print_set_collection_result(result: &SetCollectionResult) {
+    println!("\nCollection set and verified successfully!");
+    println!("  Collection: {}", result.collection_mint);
+    println!(
+        "  Type:       {}",
+        if result.sized { "sized" } else { "unsized" }
+    );
+    println!("  Signature:  {}", result.signature);
+    println!("  Explorer:   {}", explorer_tx_url(&result.signature));
+}
+
+fn print_set_collection_size_result(result: &SetCollectionSizeResult) {
+    println!("\nCollection size set successfully!");
+    println!("  Collection: {}", result.collection_mint);
+    println!("  Size:       {}", result.size);
+    println!("  Signature:  {}", result.signature);
+    println!("  Explorer:   {}", explorer_tx_url(&result.signature));
+}
+
+fn print_unverify_collection_result(result: &UnverifyCollectionResult) {
+    println!("\nCollection detached successfully!");
+    println!("  Collection: {}", result.collection_mint);
+    println!(
+        "  Type:       {}",
+        if result.sized { "sized" } else { "unsized" }
+    );
+    println!("  Unverified: {}", result.was_verified);
+    println!("  Cleared:    {}", result.cleared);
+    if !result.cleared {
+        println!("  (the collection field can only be cleared by the update authority)");
+    }
+    println!("  Signature:  {}", result.signature);
+    println!("  Explorer:   {}", explorer_tx_url(&result.signature));
+}
+
+fn print_burn_result(result: &BurnResult) {
+    println!("\nBurned successfully!");
+    println!("  Token standard:     {}", result.token_standard);
+    println!("  Amount:             {}", result.amount);
+    print_closed_accounts(result.closed_accounts.as_deref(), result.lamports_recovered);
+    println!("  Signature:          {}", result.signature);
+    println!(
+        "  Explorer:           {}",
+        explorer_tx_url(&result.signature)
+    );
+}
+
+fn print_creator_verification_result(result: &CreatorVerificationResult) {
+    println!(
+        "  Verified:     {} -> {}",
+        result.verified_before, result.verified_after
+    );
+    println!("\nCreator verification updated successfully!");
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_creators_verification_result(result: &CreatorsVerificationResult) {
+    for creator in &result.creators {
+        println!(
+            "  {}: {} -> {}",
+            creator.creator, creator.verified_before, creator.verified_after
+        );
+    }
+    let verified = result
+        .creators
+        .iter()
+        .filter(|creator| !creator.verified_before && creator.verified_after)
+        .count();
+    println!("\nVerified {} creator(s) successfully!", verified);
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_grind_result(result: &GrindResult) {
+    println!(
+        "\nFound {} after {} attempts ({:.1}s)",
+        result.pubkey, result.attempts, result.seconds
+    );
+    println!("  Keypair: {}", result.out);
+    println!(
+        "  Use it with: create-mint --mint-keypair {} (or create-token, create-nft)",
+        result.out
+    );
+}
+
+fn print_generate_result(result: &GenerateResult) {
+    for item in &result.items {
+        match &item.uri {
+            Some(uri) => println!("  {} -> {}", item.file, uri),
+            None => println!("  {}", item.file),
+        }
+    }
+    if result.lamports_paid > 0 {
+        println!("Paid:     {} SOL", lamports_to_sol(result.lamports_paid));
+    }
+    println!("Manifest: {}", result.manifest);
+    if result.items.iter().any(|item| item.mint.is_none()) {
+        println!("Fill in the mint of each entry before running `batch create`.");
+    }
+}
+
+fn print_upload_result(result: &UploadResult) {
+    println!("\nUploaded successfully!");
+    if let Some(image_uri) = &result.image_uri {
+        println!("  Image URI: {}", image_uri);
+    }
+    println!("  URI:       {}", result.uri);
+    println!("  Paid:      {} SOL", lamports_to_sol(result.lamports_paid));
+}
+
+fn print_shadow_storage_result(result: &ShadowStorageResult) {
+    println!("\nStorage account created successfully!");
+    println!("  Storage account: {}", result.storage_account);
+    println!("  Owner:           {}", result.owner);
+    println!("  Size:            {} bytes", result.size);
+    println!("  Signature:       {}", result.signature);
+    println!("  Explorer:        {}", explorer_tx_url(&result.signature));
+    println!(
+        "\nUpload into it with --storage shadow --shadow-storage-account {}",
+        result.storage_account
+    );
+}
+
+fn print_validation_report(report: &ValidationReport) {
+    println!("Validating {}", report.source);
+    for issue in &report.issues {
+        let severity = match issue.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let field = if issue.field.is_empty() {
+            String::new()
+        } else {
+            format!("{}: ", issue.field)
+        };
+        println!("  [{}] {}{}", severity, field, issue.message);
+    }
+    println!(
+        "\n{} error(s), {} warning(s)",
+        report.errors, report.warnings
+    );
+}
+
+fn print_audit_report(report: &AuditReport) {
+    for audit in &report.mints {
+        println!("\n{} (score {}/100)", audit.mint, audit.score);
+        if audit.findings.is_empty() {
+            println!("  No problems found");
+        }
+        for finding in &audit.findings {
+            let severity = match finding.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            println!("  [{}] {}: {}", severity, finding.check, finding.message);
+        }
+    }
+    println!(
+        "\nScore: {}/100 (lowest of {} mint(s)), {} error(s), {} warning(s)",
+        report.score,
+        report.mints.len(),
+        report.errors,
+        report.warnings
+    );
+}
+
+/// A progress bar on stderr with running success and failure counts. It
+/// stays hidden when stderr isn't a terminal.
+struct ProgressBar {
+    bar: indicatif::ProgressBar,
+    succeeded: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+impl ProgressBar {
+    fn new() -> Self {
+        let bar = indicatif::ProgressBar::hidden();
+        bar.set_style(
+            ProgressStyle::with_template("{bar:40} {pos}/{len} [{elapsed_precise}] {msg}")
+                .expect("the template is valid"),
+        );
+        Self {
+            bar,
+            succeeded: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Progress for ProgressBar {
+    fn start(&self, total: usize) {
+        self.succeeded.store(0, Ordering::Relaxed);
+        self.failed.store(0, Ordering::Relaxed);
+        self.bar.set_draw_target(ProgressDrawTarget::stderr());
+        self.bar.set_length(total as u64);
+        self.bar.set_position(0);
+        self.bar.reset_elapsed();
+        self.bar.set_message("0 ok, 0 failed");
+    }
+
+    fn advance(&self, succeeded: bool) {
+        let counter = if succeeded {
+            &self.succeeded
+        } else {
+            &self.failed
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        self.bar.set_message(format!(
+            "{} ok, {} failed",
+            self.succeeded.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed)
+        ));
+        self.bar.inc(1);
+    }
+
+    fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+fn print_batch_result(result: &BatchResult) {
+    let row_width = result
+        .items
+        .iter()
+        .map(|item| item.row.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("Row".len());
+    let mint_width = result
+        .items
+        .iter()
+        .map(|item| item.mint.len())
+        .max()
+        .unwrap_or(0)
+        .max("Mint".len());
+    println!();
+    println!(
+        "  {:<row_width$}  {:<7}  {:<mint_width$}  Result",
+        "Row", "Status", "Mint"
+    );
+    for item in &result.items {
+        let detail = item
+            .signature
+            .as_deref()
+            .or(item.error.as_deref())
+            .unwrap_or("unchanged");
+        let status = match item.status {
+            BatchStatus::Succeeded => "ok",
+            BatchStatus::Unchanged => "skip",
+            BatchStatus::Failed => "failed",
+        };
+        let detail = match item.lamports_recovered {
+            Some(lamports) => format!("{} ({} SOL recovered)", detail, lamports_to_sol(lamports)),
+            None => detail.to_string(),
+        };
+        println!(
+            "  {:<row_width$}  {:<7}  {:<mint_width$}  {}",
+            item.row, status, item.mint, detail
+        );
+    }
+    println!(
+        "\n{} of {} succeeded, {} unchanged, {} failed",
+        result.succeeded, result.total, result.unchanged, result.failed
+    );
+    if let Some(lamports) = result.lamports_recovered {
+        println!(
+            "Lamports recovered: {} ({} SOL)",
+            lamports,
+            lamports_to_sol(lamports)
+        );
+    }
+}
+
+fn print_simulations(simulations: &[SimulationReport]) {
+    println!(
+        "\nDry run: simulated {} transaction(s), nothing was sent",
+        simulations.len()
+    );
+    for simulation in simulations {
+        println!("\n  Would-be signature: {}", simulation.signature);
+        if let Some(units) = simulation.units_consumed {
+            println!("  Compute units:      {}", units);
+        }
+        if !simulation.account_changes.is_empty() {
+            println!("  Account changes:");
+            for change in &simulation.account_changes {
+                println!(
+                    "    {}: {} -> {} SOL, {} -> {} bytes",
+                    change.address,
+                    lamports_to_sol(change.lamports_before),
+                    lamports_to_sol(change.lamports_after),
+                    change.data_len_before,
+                    change.data_len_after
+                );
+            }
+        }
+        println!("  Logs:");
+        for log in &simulation.logs {
+            println!("    {}", log);
+        }
+    }
+}
+
+fn print_signed_transactions(path: &Path, signatures: &[String]) {
+    println!(
+        "\nSigned {} transaction(s), nothing was sent",
+        signatures.len()
+    );
+    for signature in signatures {
+        println!("  {}", signature);
+    }
+    println!("  Written to: {}", path.display());
+    println!(
+        "  Send them from an online machine with: token-metadata-cli broadcast --file {}",
+        path.display()
+    );
+}
+
+fn print_broadcast_result(result: &BroadcastResult) {
+    println!("\nTransactions sent successfully!");
+    for signature in &result.signatures {
+        println!("  Signature: {}", signature);
+        println!("  Explorer:  {}", explorer_tx_url(signature));
+    }
+}
+
+fn print_diff_result(result: &DiffResult) {
+    if result.matches {
+        println!("{} matches the expected metadata", result.mint);
+        return;
+    }
+    println!(
+        "{} differs from the expected metadata in {} field(s)",
+        result.mint,
+        result.differences.len()
+    );
+    for diff in &result.differences {
+        println!("  {}", diff.field);
+        println!("    - expected: {}", diff.expected);
+        println!("    + on-chain: {}", diff.actual);
+    }
+}
+
+fn print_token2022_metadata(info: &Token2022MetadataInfo) {
+    println!("Token-2022 metadata");
+    println!("  Mint:                  {}", info.mint);
+    println!("  Name:                  {}", info.name);
+    println!("  Symbol:                {}", info.symbol);
+    println!(
+        "  URI:                   {}",
+        if info.uri.is_empty() {
+            "(empty)"
+        } else {
+            &info.uri
+        }
+    );
+    println!(
+        "  Update authority:      {}",
+        info.update_authority.as_deref().unwrap_or("(none)")
+    );
+    if info.additional_metadata.is_empty() {
+        println!("  Additional fields:     (none)");
+    } else {
+        println!("  Additional fields:");
+        for field in &info.additional_metadata {
+            println!("    {}: {}", field.key, field.value);
+        }
+    }
+}
+
+fn print_migrate_result(result: &MigrateResult) {
+    println!("  Name:         {}", result.name);
+    println!("  Symbol:       {}", result.symbol);
+    println!("  URI:          {}", result.uri);
+    if !result.dropped_fields.is_empty() {
+        println!(
+            "  Not copied:   {} (Metaplex metadata has no additional fields)",
+            result.dropped_fields.join(", ")
+        );
+    }
+    match (result.action, &result.signature) {
+        (MigrateAction::Unchanged, _) | (_, None) => {
+            println!("\nTarget metadata already matches; nothing to do.")
+        }
+        (action, Some(signature)) => {
+            let verb = if action == MigrateAction::Created {
+                "created"
+            } else {
+                "updated"
+            };
+            println!("\nTarget metadata {} successfully!", verb);
+            println!("  Signature: {}", signature);
+            println!("  Explorer:  {}", explorer_tx_url(signature));
+        }
+    }
+}
+
+fn print_close_result(result: &CloseResult) {
+    println!("\nAccounts closed successfully!");
+    print_closed_accounts(result.closed_accounts.as_deref(), result.lamports_recovered);
+    println!("  Signature:          {}", result.signature);
+    println!(
+        "  Explorer:           {}",
+        explorer_tx_url(&result.signature)
+    );
+}
+
+/// The accounts a burn or close closed and the rent they returned, which
+/// are only known once the transaction landed
+fn print_closed_accounts(closed_accounts: Option<&[String]>, lamports_recovered: Option<u64>) {
+    match closed_accounts {
+        Some([]) => println!("  Closed accounts:    (none)"),
+        Some(closed_accounts) => {
+            println!("  Closed accounts:");
+            for account in closed_accounts {
+                println!("    {}", account);
+            }
+        }
+        None => {}
+    }
+    if let Some(lamports) = lamports_recovered {
+        println!(
+            "  Lamports recovered: {} ({} SOL)",
+            lamports,
+            lamports_to_sol(lamports)
+        );
+    }
+}
+
+fn print_resize_result(result: &ResizeResult) {
+    if let Some(edition) = &result.edition {
+        println!("  Edition:      {}", edition);
+    }
+    match result.size_after {
+        Some(size_after) => println!(
+            "  Size:         {} -> {} bytes",
+            result.size_before, size_after
+        ),
+        None => println!("  Size:         {} bytes", result.size_before),
+    }
+    if let Some(lamports) = result.lamports_recovered {
+        println!(
+            "  Lamports recovered: {} ({} SOL)",
+            lamports,
+            lamports_to_sol(lamports)
+        );
+    }
+    println!("\nMetadata resized successfully!");
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_transfer_result(result: &TransferResult) {
+    println!("  Standard:     {}", result.token_standard);
+    println!("  Amount:       {}", result.amount);
+    println!(
+        "  Destination:  {}{}",
+        result.destination_token,
+        if result.created_destination_token {
+            " (created)"
+        } else {
+            ""
+        }
+    );
+    if let Some(rule_set) = &result.rule_set {
+        println!("  Rule set:     {}", rule_set);
+    }
+    println!("\nTransferred successfully!");
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_use_result(result: &UseResult) {
+    if let Some(record) = &result.use_authority_record {
+        println!("  Use authority record: {}", record);
+    }
+    println!(
+        "  Remaining:    {} -> {} of {}",
+        result.before.remaining, result.remaining, result.before.total
+    );
+    if result.burned {
+        println!("  The last use burned the token");
+    }
+    println!("\nToken used successfully!");
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_lock_result(verb: &str, result: &LockResult) {
+    let describe = |record: &TokenRecordInfo| match (&record.delegate_role, &record.delegate) {
+        (Some(role), Some(delegate)) => {
+            format!("{} ({} delegate {})", record.state, role, delegate)
+        }
+        _ => record.state.clone(),
+    };
+    println!("  Token record: {}", result.token_record);
+    println!("  Before:       {}", describe(&result.before));
+    if let Some(after) = &result.after {
+        println!("  After:        {}", describe(after));
+    }
+    println!("\npNFT {} successfully!", verb);
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+}
+
+fn print_delegate_result(verb: &str, result: &DelegateResult) {
+    println!("\nDelegate {} successfully!", verb);
+    if let Some(record) = &result.delegate_record {
+        println!("  Delegate record: {}", record);
+    }
+    if let Some(token_account) = &result.token_account {
+        println!("  Token account:   {}", token_account);
+    }
+    if let Some(record) = &result.token_record {
+        println!("  Token record:    {}", record);
+    }
+    if let Some(amount) = result.amount {
+        println!("  Amount:          {}", amount);
+    }
+    println!("  Signature:       {}", result.signature);
+    println!("  Explorer:        {}", explorer_tx_url(&result.signature));
+}
+
+fn print_rule_set_preview(verb: &str, rule_set: &RuleSet) {
+    println!("{} rule set...", verb);
+    println!("  Name:         {}", rule_set.rule_set_name);
+    println!(
+        "  Address:      {}",
+        find_rule_set_pda(&rule_set.owner.0, &rule_set.rule_set_name)
+    );
+    println!("  Operations:   {}", rule_set.operations.len());
+}
+
+fn print_rule_set_result(result: &RuleSetResult) {
+    let verb = if result.updated { "updated" } else { "created" };
+    println!("\nRule set {} successfully!", verb);
+    println!("  Rule set:  {}", result.address);
+    println!("  Owner:     {}", result.owner);
+    println!("  Signature: {}", result.signature);
+    println!("  Explorer:  {}", explorer_tx_url(&result.signature));
+    println!(
+        "\nAttach it to new pNFTs with `create-nft --standard pnft --rule-set {}`",
+        result.address
+    );
+}
+
+fn print_rule_set_info(info: &RuleSetInfo) -> Result<()> {
+    println!("Rule Set");
+    println!("========");
+    println!("Address:      {}", info.address);
+    if let Some(name) = &info.name {
+        println!("Name:         {}", name);
+    }
+    if let Some(owner) = &info.owner {
+        println!("Owner:        {}", owner);
+    }
+    println!("Revisions:    {}", info.revisions);
+    println!("Size:         {} bytes", info.size);
+    if let Some(operations) = &info.operations {
+        println!("\nRules:");
+        println!("{}", serde_json::to_string_pretty(operations)?);
+    }
+    if let Some(err) = &info.decode_error {
+        println!("\nRules:        (could not decode: {})", err);
+    }
+    Ok(())
+}
+
+/// The mint metadata is about to be created for, so a wrong address stands
+/// out before anything is sent
+fn print_mint_account(mint: &MintAccount) {
+    println!("Mint account {} ({})", mint.address, mint.program);
+    println!("  Decimals:     {}", mint.decimals);
+    println!(
+        "  Supply:       {}",
+        spl_token::amount_to_ui_amount_string_trimmed(mint.supply, mint.decimals)
+    );
+    println!();
+}
+
+fn print_token2022_create_preview(mint: &Pubkey, args: &Token2022MetadataArgs) {
+    println!("Creating Token-2022 metadata...");
+    println!("  Mint:         {}", mint);
+    println!("  Name:         {}", args.name);
+    println!("  Symbol:       {}", args.symbol);
+    println!(
+        "  URI:          {}",
+        if args.uri.is_empty() {
+            "(empty)"
+        } else {
+            &args.uri
+        }
+    );
+    for field in &args.additional_metadata {
+        println!("  {}: {}", field.key, field.value);
+    }
+}
+
+fn print_token2022_create_result(result: &Token2022CreateResult) {
+    println!("\nToken-2022 metadata created successfully!");
+    if result.lamports_added > 0 {
+        println!(
+            "  Rent added: {} SOL",
+            lamports_to_sol(result.lamports_added)
+        );
+    }
+    println!("  Signature:  {}", result.signature);
+    println!("  Explorer:   {}", explorer_tx_url(&result.signature));
+}
+
+fn print_token2022_update_result(result: &Token2022UpdateResult) {
+    for change in &result.changes {
+        println!(
+            "  {}: {} -> {}",
+            change.field,
+            change.old.as_deref().unwrap_or("(unset)"),
+            change.new.as_deref().unwrap_or("(removed)")
+        );
+    }
+    println!("\nToken-2022 metadata updated successfully!");
+    if result.lamports_added > 0 {
+        println!(
+            "  Rent added: {} SOL",
+            lamports_to_sol(result.lamports_added)
+        );
+    }
+    println!("  Signature:  {}", result.signature);
+    println!("  Explorer:   {}", explorer_tx_url(&result.signature));
+}
+
+fn print_metadata_summaries(items: &[MetadataSummary]) {
+    if items.is_empty() {
+        println!("No metadata accounts found");
+        return;
+    }
+    println!("Found {} metadata account(s)", items.len());
+    for item in items {
+        println!("\n  Mint:         {}", item.mint);
+        println!("  Name:         {}", item.name);
+        if item.compressed {
+            println!("  Compressed:   yes");
+        }
+        println!("  Symbol:       {}", item.symbol);
+        println!(
+            "  URI:          {}",
+            if item.uri.is_empty() {
+                "(empty)"
+            } else {
+                &item.uri
+            }
+        );
+    }
+}
+
+fn print_collection_items(items: &CollectionItems) {
+    println!("Collection {}", items.collection);
+    println!("  Verified items:   {}", items.verified.len());
+    println!("  Unverified items: {}", items.unverified.len());
+    for (label, members) in [
+        ("Verified", &items.verified),
+        ("Unverified", &items.unverified),
+    ] {
+        if members.is_empty() {
+            continue;
+        }
+        println!("\n{}", label);
+        for item in members {
+            let compressed = if item.compressed {
+                "  (compressed)"
+            } else {
+                ""
+            };
+            println!("  {}  {}{}", item.mint, item.name, compressed);
+        }
+    }
+}
+
+fn print_metadata_event(event: &MetadataEvent) {
+    let what = if event.created {
+        "created"
+    } else if event.closed {
+        "closed"
+    } else {
+        "changed"
+    };
+    println!(
+        "\n[slot {}] Metadata of {} {}",
+        event.slot, event.mint, what
+    );
+    for change in &event.changes {
+        println!("  {}: '{}' -> '{}'", change.field, change.old, change.new);
+    }
+}
+
+fn print_export_result(result: &ExportResult) {
+    println!("\nExported {} mints to {}", result.total, result.out);
+    if result.missing > 0 {
+        println!(
+            "  {} without metadata (see the error column)",
+            result.missing
+        );
+    }
+}
+
+fn print_batch_estimate(estimate: &BatchEstimate) {
+    println!("\n  Transactions:  {}", estimate.transactions);
+    println!(
+        "  Rent:          {} SOL",
+        lamports_to_sol(estimate.rent_lamports)
+    );
+    println!(
+        "  Fees:          {} SOL ({} lamports each, {} of it priority fee)",
+        lamports_to_sol(estimate.transaction_fees),
+        estimate.fee_per_transaction,
+        estimate.priority_fee_per_transaction
+    );
+    if estimate.upload_files > 0 {
+        println!(
+            "  Uploads:       {} SOL ({} files, {} bytes)",
+            lamports_to_sol(estimate.upload_lamports),
+            estimate.upload_files,
+            estimate.upload_bytes
+        );
+    }
+    println!(
+        "  Total:         {} SOL",
+        lamports_to_sol(estimate.total_lamports)
+    );
+    if !estimate.invalid.is_empty() {
+        println!(
+            "\n{} of {} rows would fail and aren't counted:",
+            estimate.invalid.len(),
+            estimate.rows
+        );
+        for row in &estimate.invalid {
+            println!("  Row {} ({}): {}", row.row, row.mint, row.error);
+        }
+    }
+}
+
+fn print_token_list_result(result: &TokenListResult) {
+    println!("\nWrote {} tokens to {}", result.tokens, result.out);
+    if result.without_logo > 0 {
+        println!(
+            "  {} without a logo (no image in their off-chain JSON, or it couldn't be fetched)",
+            result.without_logo
+        );
+    }
+    for skip in &result.skipped {
+        println!("  Skipped {}: {}", skip.mint, skip.error);
+    }
+}
+
+fn print_snapshot_result(result: &SnapshotResult, out: Option<&Path>) {
+    if let Some(collection) = &result.collection {
+        println!("Collection {}", collection);
+    }
+    println!("  Mints:    {}", result.mints);
+    println!("  Holdings: {}", result.holders.len());
+    println!("  Owners:   {}", result.owners);
+    match out {
+        Some(path) => println!("  Written:  {}", path.display()),
+        None => {
+            for holder in &result.holders {
+                let state = match (&holder.token_record_state, holder.frozen) {
+                    (Some(state), _) => format!("  ({})", state),
+                    (None, true) => "  (frozen)".to_string(),
+                    (None, false) => String::new(),
+                };
+                println!(
+                    "  {}  {}  {}{}",
+                    holder.mint, holder.owner, holder.amount, state
+                );
+            }
+        }
+    }
+    for failure in &result.failed {
+        println!("  Failed:   {}: {}", failure.mint, failure.error);
+    }
+}
+
+fn print_metadata_info(info: &MetadataInfo) {
+    println!("Metadata");
+    println!("  Mint:                  {}", info.mint);
+    match &info.compression {
+        Some(compression) => println!(
+            "  Compressed:            leaf {} of tree {}",
+            compression.leaf_id, compression.tree
+        ),
+        None => println!("  Metadata PDA:          {}", info.metadata_pda),
+    }
+    println!("  Name:                  {}", info.name);
+    println!("  Symbol:                {}", info.symbol);
+    println!(
+        "  URI:                   {}",
+        if info.uri.is_empty() {
+            "(empty)"
+        } else {
+            &info.uri
+        }
+    );
+    println!(
+        "  Seller fee:            {} bps ({:.2}%)",
+        info.seller_fee_basis_points,
+        info.seller_fee_basis_points as f64 / 100.0
+    );
+    println!("  Update authority:      {}", info.update_authority);
+    println!("  Mutable:               {}", info.is_mutable);
+    println!("  Primary sale happened: {}", info.primary_sale_happened);
+    println!(
+        "  Token standard:        {}",
+        info.token_standard.as_deref().unwrap_or("(none)")
+    );
+
+    match &info.creators {
+        Some(creators) if !creators.is_empty() => {
+            println!("  Creators:");
+            for creator in creators {
+                println!(
+                    "    {} (share: {}%, verified: {})",
+                    creator.address, creator.share, creator.verified
+                );
+            }
+        }
+        _ => println!("  Creators:              (none)"),
+    }
+
+    match &info.collection {
+        Some(collection) => println!(
+            "  Collection:            {} (verified: {})",
+            collection.key, collection.verified
+        ),
+        None => println!("  Collection:            (none)"),
+    }
+
+    match &info.uses {
+        Some(uses) => println!(
+            "  Uses:                  {} ({}/{} remaining)",
+            uses.use_method, uses.remaining, uses.total
+        ),
+        None => println!("  Uses:                  (none)"),
+    }
+
+    if let Some(delegates) = &info.delegates {
+        if delegates.is_empty() {
+            println!("  Delegates:             (none)");
+        } else {
+            println!("  Delegates:");
+            for delegate in delegates {
+                let stale = if delegate.update_authority == info.update_authority {
+                    ""
+                } else {
+                    " (approved by a previous update authority)"
+                };
+                println!("    {} ({}){}", delegate.delegate, delegate.role, stale);
+                println!("      Record: {}", delegate.delegate_record);
+            }
+        }
+    }
+
+    if let Some(error) = &info.offchain_error {
+        println!("\nOff-chain metadata");
+        println!("  Not available:         {}", error);
+    }
+    if let Some(offchain) = &info.offchain {
+        let field = |value: &Option<String>| value.clone().unwrap_or_else(|| "(none)".to_string());
+        println!("\nOff-chain metadata");
+        println!("  Name:                  {}", field(&offchain.name));
+        println!("  Description:           {}", field(&offchain.description));
+        println!("  Image:                 {}", field(&offchain.image));
+        println!(
+            "  Animation URL:         {}",
+            field(&offchain.animation_url)
+        );
+        println!("  External URL:          {}", field(&offchain.external_url));
+        if offchain.attributes.is_empty() {
+            println!("  Attributes:            (none)");
+        } else {
+            println!("  Attributes:");
+            for attribute in &offchain.attributes {
+                println!(
+                    "    {}: {}",
+                    attribute.trait_type.as_deref().unwrap_or("(untyped)"),
+                    attribute.value
+                );
+            }
+        }
+    }
+}
+
+/// The upload backend selected on the command line, with IPFS settings
+/// taken from the profile. The IPFS API key is read from the
+/// `IPFS_API_KEY` environment variable or the `ipfs-api-key` profile key.
+fn storage(args: &StorageArgs, profile: &Profile) -> Result<Storage> {
+    match args.storage {
+        StorageBackend::Arweave => Ok(Storage::Arweave),
+        StorageBackend::Ipfs => {
+            let provider = match args.ipfs_provider {
+                Some(provider) => provider,
+                None => profile.ipfs_provider()?.unwrap_or_default(),
+            };
+            let api_key = std::env::var("IPFS_API_KEY")
+                .ok()
+                .or(profile.ipfs_api_key.clone())
+                .context("An IPFS API key is required (set IPFS_API_KEY or the ipfs-api-key profile key)")?;
+            Ok(Storage::Ipfs(provider.with_api_key(api_key)))
+        }
+        StorageBackend::Shadow => {
+            let storage_account = match args.shadow_storage_account {
+                Some(account) => account,
+                None => profile.shadow_storage_account()?.context(
+                    "A Shadow Drive storage account is required (--shadow-storage-account or the shadow-storage-account profile key); create one with `shadow create`",
+                )?,
+            };
+            Ok(Storage::Shadow(ShadowDrive::new(storage_account)))
+        }
+    }
+}
+
+/// Validate the metadata JSON at a path or URI, failing on errors. The
+/// report is only printed when there is something to report.
+/// Upload an image and a metadata JSON generated for it, returning the
+/// JSON's URI
+async fn upload_image(
+    client: &MetadataClient,
+    payer: &Keypair,
+    storage: &Storage,
+    image: &Path,
+    args: &CreateMetadataArgs,
+    description: Option<&str>,
+    output: OutputFormat,
+) -> Result<String> {
+    if output == OutputFormat::Text {
+        println!("Uploading {} and its metadata JSON...", image.display());
+    }
+    let result = client
+        .upload_image_metadata(payer, storage, image, args, description)
+        .await?;
+    if output == OutputFormat::Text {
+        println!(
+            "  Image URI:    {}",
+            result.image_uri.as_deref().unwrap_or_default()
+        );
+        println!("  Metadata URI: {}\n", result.uri);
+    }
+    Ok(result.uri)
+}
+
+/// Load the keypair a new mint should have, or generate a random one
+fn mint_keypair_or_new(path: Option<&str>) -> Result<Keypair> {
+    path.map_or_else(|| Ok(Keypair::new()), load_keypair)
+}
+
+/// Create Metaplex metadata, or TokenMetadata extension fields when the
+/// mint is a Token-2022 mint that stores its own. With `confirm_first`, the
+/// user is asked before anything is sent.
+async fn create_metadata(
+    client: &MetadataClient,
+    payer: &Keypair,
+    mint_pubkey: &Pubkey,
+    args: CreateMetadataArgs,
+    standard: Option<Standard>,
+    output: OutputFormat,
+    confirm_first: bool,
+) -> Result<()> {
+    // Signing offline, the mint can't be looked up: it is taken to be an SPL
+    // Token mint, and the program checks the mint authority once the
+    // transaction is broadcast
+    let stores_own_metadata = if client.is_sign_only() {
+        if standard.is_some() {
+            anyhow::bail!(
+                "--standard checks the mint on-chain, so it can't be used with --sign-only"
+            );
+        }
+        false
+    } else {
+        let mint_account = detect_mint(client, mint_pubkey, output).await?;
+        mint_account.ensure_mint_authority(&payer.pubkey())?;
+        if output == OutputFormat::Text {
+            print_mint_account(&mint_account);
+        }
+        mint_account.stores_own_metadata()
+    };
+    if stores_own_metadata {
+        if args.seller_fee_basis_points != 0
+            || args.creators.is_some()
+            || !args.is_mutable
+            || standard.is_some()
+            || args.uses.is_some()
+            || args.update_authority.is_some()
+        {
+            anyhow::bail!(
+                "Mint {} stores Token-2022 metadata, which has no royalties, creators, \
+                 immutability flag, token standard or uses, and is created with the signer as \
+                 update authority",
+                mint_pubkey
+            );
+        }
+        let args = Token2022MetadataArgs {
+            name: args.name,
+            symbol: args.symbol,
+            uri: args.uri,
+            additional_metadata: Vec::new(),
+            uri_hash: args.uri_hash,
+        };
+        if output == OutputFormat::Text {
+            print_token2022_create_preview(mint_pubkey, &args);
+        }
+        if confirm_first && !confirm("Create this metadata?")? {
+            anyhow::bail!("Aborted");
+        }
+        let result = client
+            .create_token2022_metadata(payer, mint_pubkey, args)
+            .await?;
+        emit(client, output, &result, print_token2022_create_result)
+    } else {
+        if output == OutputFormat::Text {
+            print_create_preview(mint_pubkey, &args);
+            if let Some(standard) = standard {
+                println!("  Standard:     {}", standard);
+            }
+        }
+        if confirm_first && !confirm("Create this metadata?")? {
+            anyhow::bail!("Aborted");
+        }
+        let result = match standard {
+            Some(standard) => {
+                client
+                    .create_with_standard(payer, mint_pubkey, args, standard)
+                    .await?
+            }
+            None => client.create(payer, mint_pubkey, args).await?,
+        };
+        emit(client, output, &result, print_create_result)
+    }
+}
+
+/// The fields of a `create`, checked against the on-chain limits
+struct CreateAnswers {
+    mint: Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Vec<CreatorShare>,
+}
+
+impl CreateAnswers {
+    fn into_args(self, is_mutable: bool, uses: Option<UsesArgs>) -> CreateMetadataArgs {
+        CreateMetadataArgs {
+            name: self.name,
+            symbol: self.symbol,
+            uri: self.uri,
+            seller_fee_basis_points: self.seller_fee_basis_points,
+            is_mutable,
+            creators: (!self.creators.is_empty()).then_some(self.creators),
+            uses,
+            uri_hash: None,
+            update_authority: None,
+        }
+    }
+}
+
+/// Prompt for the fields of a `create` one at a time, re-asking until each
+/// answer is valid. Given values are offered as defaults; with no `uri` the
+/// URI isn't asked for. Royalties and creators are skipped for Token-2022
+/// mints that store their own metadata, which has neither.
+async fn ask_create(
+    client: &MetadataClient,
+    mint: Option<&str>,
+    name: Option<&str>,
+    symbol: Option<&str>,
+    uri: Option<&str>,
+    seller_fee_basis_points: u16,
+    creators: &[CreatorShare],
+) -> Result<CreateAnswers> {
+    let (mint, mint_account) = loop {
+        let mint = ask("Mint address", mint, |answer| {
+            Pubkey::from_str(answer).context("Invalid mint address")
+        })?;
+        match client.fetch_mint(&mint).await {
+            Ok(mint_account) => break (mint, mint_account),
+            Err(err) => eprintln!("  {:#}", err),
+        }
+    };
+    let name = ask("Name", name, |answer| {
+        check_name(answer)?;
+        if answer.is_empty() {
+            anyhow::bail!("A name is required");
+        }
+        Ok(answer.to_string())
+    })?;
+    let symbol = ask("Symbol", symbol, |answer| {
+        check_symbol(answer)?;
+        Ok(answer.to_string())
+    })?;
+    let uri = match uri {
+        Some(uri) => ask("Metadata URI (empty for none)", Some(uri), |answer| {
+            check_uri(answer)?;
+            if !answer.is_empty() {
+                reqwest::Url::parse(answer).context("Invalid URI")?;
+            }
+            Ok(answer.to_string())
+        })?,
+        None => String::new(),
+    };
+
+    if mint_account.stores_own_metadata() {
+        eprintln!("Token-2022 metadata has no royalties or creators; skipping them");
+        return Ok(CreateAnswers {
+            mint,
+            name,
+            symbol,
+            uri,
+            seller_fee_basis_points: 0,
+            creators: Vec::new(),
+        });
+    }
+
+    let default_fee = seller_fee_basis_points.to_string();
+    let seller_fee_basis_points = ask(
+        "Royalties in basis points, 0-10000 (500 = 5%)",
+        Some(&default_fee),
+        |answer| {
+            let basis_points = answer
+                .parse::<u16>()
+                .context("Royalties must be a whole number of basis points")?;
+            check_seller_fee_basis_points(basis_points)?;
+            Ok(basis_points)
+        },
+    )?;
+
+    eprintln!(
+        "Creators share the royalties; their shares must sum to 100. Leave the address empty to finish."
+    );
+    let creators = 'creators: loop {
+        let mut answers: Vec<CreatorShare> = Vec::new();
+        let mut total = 0;
+        while total < 100 {
+            if answers.len() == MAX_CREATOR_LIMIT {
+                eprintln!(
+                    "  At most {} creators fit, and their shares sum to {}; starting over",
+                    MAX_CREATOR_LIMIT, total
+                );
+                continue 'creators;
+            }
+            let given = creators.get(answers.len());
+            let default_address = given.map(|c| c.address.to_string());
+            let address = ask(
+                &format!("  Creator {} address", answers.len() + 1),
+                default_address.as_deref(),
+                |answer| {
+                    if answer.is_empty() {
+                        return Ok(None);
+                    }
+                    let address = Pubkey::from_str(answer).context("Invalid creator address")?;
+                    if answers.iter().any(|c| c.address == address) {
+                        anyhow::bail!("{} is already a creator", address);
+                    }
+                    Ok(Some(address))
+                },
+            )?;
+            let Some(address) = address else {
+                if answers.is_empty() {
+                    break;
+                }
+                eprintln!("  The shares sum to {}, not 100", total);
+                continue;
+            };
+            let remaining = 100 - total;
+            let default_share = given
+                .map_or(remaining, |c| c.share.min(remaining))
+                .to_string();
+            let share = ask(
+                &format!("  Share of {} (1-{})", address, remaining),
+                Some(&default_share),
+                |answer| {
+                    let share = answer
+                        .parse::<u8>()
+                        .context("The share must be a whole number")?;
+                    if share == 0 || share > remaining {
+                        anyhow::bail!("The share must be between 1 and {}", remaining);
+                    }
+                    Ok(share)
+                },
+            )?;
+            total += share;
+            answers.push(CreatorShare { address, share });
+        }
+        break answers;
+    };
+
+    Ok(CreateAnswers {
+        mint,
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        creators,
+    })
+}
+
+/// Prompt on stderr until `parse` accepts the answer, which is `default`
+/// when left empty
+fn ask<T>(prompt: &str, default: Option<&str>, parse: impl Fn(&str) -> Result<T>) -> Result<T> {
+    loop {
+        match default {
+            Some(default) if !default.is_empty() => eprint!("{} [{}]: ", prompt, default),
+            _ => eprint!("{}: ", prompt),
+        }
+        io::stderr().flush()?;
+
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer)? == 0 {
+            anyhow::bail!("No answer to '{}'", prompt);
+        }
+        let answer = match answer.trim() {
+            "" => default.unwrap_or_default(),
+            answer => answer,
+        };
+        match parse(answer) {
+            Ok(value) => return Ok(value),
+            Err(err) => eprintln!("  {:#}", err),
+        }
+    }
+}
+
+/// Fetch a mint to find its token program, warning when its metadata
+/// pointer leads somewhere this tool doesn't manage
+async fn detect_mint(
+    client: &MetadataClient,
+    mint: &Pubkey,
+    output: OutputFormat,
+) -> Result<MintAccount> {
+    let mint_account = client.fetch_mint(mint).await?;
+    if let Some(warning) = mint_account.metadata_pointer_warning() {
+        warn!("{}", warning);
+    }
+    if output == OutputFormat::Text && mint_account.program == TokenProgram::Token2022 {
+        let location = if mint_account.stores_own_metadata() {
+            "TokenMetadata extension"
+        } else {
+            "Metaplex metadata account"
+        };
+        println!("Token-2022 mint; using its {}\n", location);
+    }
+    Ok(mint_account)
+}
+
+async fn ensure_valid_json(source: &str, output: OutputFormat) -> Result<()> {
+    if source.is_empty() {
+        anyhow::bail!("--validate needs a URI or file to validate");
+    }
+    let json = load_json(source, HTTP_TIMEOUT).await?;
+    let report = validate_metadata_json(source, &json, true, HTTP_TIMEOUT).await;
+    if output == OutputFormat::Text && !report.issues.is_empty() {
+        print_validation_report(&report);
+        println!();
+    }
+    if !report.is_valid() {
+        return Err(invalid_json(source));
+    }
+    Ok(())
+}
+
+/// Fetch and hash the JSON behind `uri`, to record with the transaction
+async fn fetch_uri_hash(uri: &str, output: OutputFormat) -> Result<UriHash> {
+    if uri.is_empty() {
+        anyhow::bail!("--hash-uri needs a URI to hash");
+    }
+    let hash = hash_json(uri, HTTP_TIMEOUT).await?;
+    if output == OutputFormat::Text {
+        println!("URI SHA-256:  {}\n", hash.sha256);
+    }
+    Ok(hash)
+}
+
+fn invalid_json(source: &str) -> anyhow::Error {
+    MetadataCliError::InvalidField {
+        field: "metadata JSON",
+        reason: format!("{} failed validation", source),
+    }
+    .into()
+}
+
+fn run_config_command(
+    command: &ConfigCommands,
+    profile_name: &str,
+    output: OutputFormat,
+) -> Result<()> {
+    let path = default_config_path();
+    let mut config = Config::load(&path)?;
+
+    match command {
+        ConfigCommands::Set { key, value } => {
+            config
+                .profiles
+                .entry(profile_name.to_string())
+                .or_default()
+                .set(key, value)?;
+            config.save(&path)?;
+            if output == OutputFormat::Text {
+                println!("Set {} = {} in profile '{}'", key, value, profile_name);
+            }
+        }
+        ConfigCommands::Get { key } => {
+            let profile = config
+                .profiles
+                .get(profile_name)
+                .cloned()
+                .unwrap_or_default();
+            let value = profile.get(key)?;
+            match output {
+                OutputFormat::Text | OutputFormat::Signature => {
+                    println!("{}", value.unwrap_or("(not set)"))
+                }
+                OutputFormat::Json => print_json(&value)?,
+            }
+        }
+        ConfigCommands::List => match output {
+            OutputFormat::Text | OutputFormat::Signature => {
+                println!("Config file: {}", path.display());
+                for (name, profile) in &config.profiles {
+                    println!("\n[{}]", name);
+                    for key in PROFILE_KEYS {
+                        match profile.get(key)? {
+                            Some(_) if key.ends_with("api-key") => println!("  {} = (set)", key),
+                            Some(value) => println!("  {} = {}", key, value),
+                            None => {}
+                        }
+                    }
+                }
+            }
+            OutputFormat::Json => print_json(&config)?,
+        },
+    }
+    Ok(())
+}
+
+/// Exit codes by what went wrong, so scripts can branch on them. Bad
+/// command-line arguments also exit with 2, from clap.
+const EXIT_FAILED: u8 = 1;
+const EXIT_INVALID: u8 = 2;
+const EXIT_NOT_FOUND: u8 = 3;
+const EXIT_RPC: u8 = 4;
+const EXIT_PROGRAM: u8 = 5;
+const EXIT_INSUFFICIENT_FUNDS: u8 = 6;
+
+fn main() -> ExitCode {
+    let result = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start the async runtime")
+        .and_then(|runtime| runtime.block_on(run()));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
+
+/// The exit code for an error, from the first cause in its chain that has
+/// a class
+fn exit_code(err: &anyhow::Error) -> u8 {
+    for cause in err.chain() {
+        if let Some(err) = cause.downcast_ref::<MetadataCliError>() {
+            return match err {
+                MetadataCliError::Rpc { source, .. } => client_error_code(source),
+                MetadataCliError::AccountNotFound { .. } => EXIT_NOT_FOUND,
+                MetadataCliError::InvalidAccountData { .. }
+                | MetadataCliError::NotMintAuthority { .. }
+                | MetadataCliError::NotUpdateAuthority { .. }
+                | MetadataCliError::NotTreeAuthority { .. }
+                | MetadataCliError::NotACreator { .. }
+                | MetadataCliError::Immutable { .. }
+                | MetadataCliError::InvalidMint { .. }
+                | MetadataCliError::InvalidField { .. } => EXIT_INVALID,
+                MetadataCliError::SimulationFailed { .. } => EXIT_PROGRAM,
+            };
+        }
+        if cause.is::<InsufficientFunds>() {
+            return EXIT_INSUFFICIENT_FUNDS;
+        }
+        if cause.is::<TransactionError>() {
+            return EXIT_PROGRAM;
+        }
+        if let Some(err) = cause.downcast_ref::<ClientError>() {
+            return client_error_code(err);
+        }
+        if cause.is::<reqwest::Error>() {
+            return EXIT_RPC;
+        }
+    }
+    EXIT_FAILED
+}
+
+/// A transaction the program rejected is a program error; anything else
+/// that goes wrong talking to the RPC node is an RPC error
+fn client_error_code(err: &ClientError) -> u8 {
+    match err.kind() {
+        ClientErrorKind::TransactionError(_)
+        | ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(_),
+            ..
+        }) => EXIT_PROGRAM,
+        _ => EXIT_RPC,
+    }
+}
+
+async fn run() -> Result<()> {
+    let Cli { command, global } = Cli::parse();
+    let log_level = global.log_level.unwrap_or(if global.quiet {
+        LevelFilter::WARN
+    } else {
+        LevelFilter::INFO
+    });
+    logging::init(log_level, global.log_file.as_deref())?;
+    let output = match global.output {
+        OutputFormat::Text if global.quiet => OutputFormat::Signature,
+        output => output,
+    };
+
+    let mut app = App {
+        global,
+        output,
+        wallet: None,
+    };
+    match command {
+        Commands::Create(args) => run_create_command(app.wallet()?, args).await,
+        Commands::Wizard => run_wizard_command(app.wallet()?).await,
+        Commands::Update(args) => run_update_command(app.wallet()?, args).await,
+        Commands::Rollback(args) => run_rollback_command(app.wallet()?, args).await,
+        Commands::SetAuthority(args) => run_set_authority_command(app.wallet()?, args).await,
+        Commands::MakeImmutable(args) => run_make_immutable_command(app.wallet()?, args).await,
+        Commands::SetPrimarySale(args) => run_set_primary_sale_command(app.wallet()?, args).await,
+        Commands::CreateToken(args) => run_create_token_command(app.wallet()?, args).await,
+        Commands::CreateMint(args) => run_create_mint_command(app.wallet()?, args).await,
+        Commands::CreateNft(args) => run_create_nft_command(app.wallet()?, args).await,
+        Commands::CreateMasterEdition(args) => {
+            run_create_master_edition_command(app.wallet()?, args).await
+        }
+        Commands::PrintEdition(args) => run_print_edition_command(app.wallet()?, args).await,
+        Commands::CreateCollection(args) => {
+            run_create_collection_command(app.wallet()?, args).await
+        }
+        Commands::SetCollection(args) => run_set_collection_command(app.wallet()?, args).await,
+        Commands::SetCollectionSize(args) => {
+            run_set_collection_size_command(app.wallet()?, args).await
+        }
+        Commands::UnverifyCollection(args) => {
+            run_unverify_collection_command(app.wallet()?, args).await
+        }
+        Commands::Collection { command } => run_collection_command(&app.session()?, command).await,
+        Commands::Burn(args) => run_burn_command(app.wallet()?, args).await,
+        Commands::Resize(args) => run_resize_command(app.wallet()?, args).await,
+        Commands::Close(args) => run_close_command(app.wallet()?, args).await,
+        Commands::Transfer(args) => run_transfer_command(app.wallet()?, args).await,
+        Commands::Lock(target) => run_lock_command(app.wallet()?, target).await,
+        Commands::Unlock(target) => run_unlock_command(app.wallet()?, target).await,
+        Commands::Use(args) => run_use_command(app.wallet()?, args).await,
+        Commands::VerifyCreator(args) => run_verify_creator_command(app.wallet()?, args).await,
+        Commands::UnverifyCreator(args) => run_unverify_creator_command(app.wallet()?, args).await,
+        Commands::Upload(args) => run_upload_command(app.wallet()?, args).await,
+        Commands::Shadow { command } => run_shadow_command(app.wallet()?, command).await,
+        Commands::Generate(args) => run_generate_command(&app.session()?, args).await,
+        Commands::Token2022 { command } => run_token2022_command(&mut app, command).await,
+        Commands::Migrate(args) => run_migrate_command(app.wallet()?, args).await,
+        Commands::Tree { command } => run_tree_command(app.wallet()?, command).await,
+        Commands::Cmint(args) => run_cmint_command(app.wallet()?, args).await,
+        Commands::Cupdate(args) => run_cupdate_command(app.wallet()?, args).await,
+        Commands::Ctransfer(args) => run_ctransfer_command(app.wallet()?, args).await,
+        Commands::Cburn(args) => run_cburn_command(app.wallet()?, args).await,
+        Commands::Decompress(args) => run_decompress_command(app.wallet()?, args).await,
+        Commands::Core { command } => run_core_command(&mut app, command).await,
+        Commands::Inscribe(args) => run_inscribe_command(app.wallet()?, args).await,
+        Commands::Delegate { command } => run_delegate_command(app.wallet()?, command).await,
+        Commands::Ruleset { command } => run_ruleset_command(&mut app, command).await,
+        Commands::Batch { command } => run_batch_command(app.wallet()?, command).await,
+        Commands::Airdrop(args) => run_airdrop_command(app.wallet()?, args).await,
+        Commands::Broadcast(args) => run_broadcast_command(&app.session()?, args).await,
+        Commands::Config { command } => run_config_command(&command, &app.global.profile, output),
+        Commands::ValidateJson(args) => run_validate_json_command(args, output).await,
+        Commands::Json { command } => run_json_command(command, output).await,
+        Commands::Grind(args) => run_grind_command(args, output),
+        Commands::Diff(args) => run_diff_command(&app.session()?, args).await,
+        Commands::History(args) => run_history_command(&app.session()?, args),
+        Commands::List(args) => run_list_command(&app.session()?, args).await,
+        Commands::Snapshot(args) => run_snapshot_command(&app.session()?, args).await,
+        Commands::Export {
+            command,
+            mints,
+            out,
+        } => run_export_command(&app.session()?, command, mints, out).await,
+        Commands::Watch(args) => run_watch_command(&app.session()?, args).await,
+        Commands::Audit(args) => run_audit_command(&app.session()?, args).await,
+        Commands::Show(args) => run_show_command(&app.session()?, args).await,
+    }?;
+    app.finish()
+}
+
+/// The global options, and the wallet once a command has loaded it
+struct App {
+    global: GlobalArgs,
+    output: OutputFormat,
+    wallet: Option<Wallet>,
+}
+
+/// A client for commands that only read from the cluster, and don't need a
+/// wallet
+struct Session {
+    client: MetadataClient,
+    output: OutputFormat,
+    /// The RPC endpoint as shown in banners
+    rpc: String,
+    profile: Profile,
+    keypair_path: String,
+}
+
+/// A client and the wallet, for commands that sign
+struct Wallet {
+    client: MetadataClient,
+    payer: Keypair,
+    output: OutputFormat,
+    profile: Profile,
+    confirmation: Confirmation,
+    /// --yes was passed
+    yes: bool,
+}
+
+impl App {
+    /// Connects to the cluster the profile and options point at
+    fn session(&self) -> Result<Session> {
+        let output = self.output;
+        let config = Config::load(default_config_path())?;
+        let profile = match config.profiles.get(&self.global.profile) {
+            Some(profile) => profile.clone(),
+            None if self.global.profile == DEFAULT_PROFILE => Profile::default(),
+            None => anyhow::bail!(
+                "Profile '{}' not found in the config file",
+                self.global.profile
+            ),
+        };
+        let mut urls = if self.global.url.is_empty() {
+            profile.urls()
+        } else {
+            self.global.url.clone()
+        };
+        if urls.is_empty() {
+            urls.push(DEFAULT_URL.to_string());
+        }
+        let (urls, clusters): (Vec<String>, Vec<Cluster>) =
+            urls.iter().map(|url| resolve_url(url)).unzip();
+        let (url, cluster) = (urls[0].clone(), clusters[0]);
+        let rpc = match urls.len() {
+            1 => format!("{} ({})", url, cluster),
+            n => format!("{} ({}, {} more to fail over to)", url, cluster, n - 1),
+        };
+        let keypair_path = self
+            .global
+            .keypair
+            .clone()
+            .or(profile.keypair.clone())
+            .unwrap_or_else(|| DEFAULT_KEYPAIR.to_string());
+        let commitment = profile
+            .commitment_config()?
+            .unwrap_or(CommitmentConfig::confirmed());
+        let explorer = match self.global.explorer {
+            Some(explorer) => explorer,
+            None => profile.explorer()?.unwrap_or_default(),
+        };
+        EXPLORER
+            .set((explorer, cluster, url.clone()))
+            .expect("explorer is only set once");
+        let rps = match self.global.rps {
+            Some(rps) => Some(rps),
+            None => profile.rps()?,
+        };
+        let priority_fee = match self.global.priority_fee {
+            Some(priority_fee) => Some(priority_fee),
+            None => profile.priority_fee()?,
+        };
+        let das = self
+            .global
+            .das_url
+            .clone()
+            .or(profile.das_url.clone())
+            .map(|url| failover::rpc_client(&[url], commitment, rps));
+
+        // The client reads metadata fresh whenever it decides what to change,
+        // so only reads for display are served from the cache
+        let cache = if self.global.no_cache {
+            None
+        } else {
+            match Cache::open(default_cache_path()) {
+                Ok(cache) => Some(
+                    cache
+                        .with_refresh(self.global.refresh)
+                        .with_max_age(Duration::from_secs(self.global.cache_ttl)),
+                ),
+                Err(err) => {
+                    warn!("Not using the cache: {:#}", err);
+                    None
+                }
+            }
+        };
+
+        let history = if self.global.no_history {
+            None
+        } else {
+            match History::open(default_history_path()) {
+                Ok(history) => Some(history),
+                Err(err) => {
+                    warn!("Not keeping history: {:#}", err);
+                    None
+                }
+            }
+        };
+
+        if let Some(memo) = &self.global.memo {
+            check_memo(memo)?;
+        }
+
+        let client = MetadataClient::new(failover::rpc_client(&urls, commitment, rps))
+            .with_cluster(cluster)
+            .with_das(das)
+            .with_cache(cache)
+            .with_history(history)
+            .with_dry_run(self.global.dry_run)
+            .with_priority_fee(priority_fee)
+            .with_compute_units(self.global.compute_units)
+            .with_sign_only(self.global.blockhash)
+            .with_auto_airdrop(self.global.auto_airdrop)
+            .with_max_attempts(self.global.max_attempts)
+            .with_memo(self.global.memo.clone())
+            .with_progress(
+                (output == OutputFormat::Text)
+                    .then(|| Box::new(ProgressBar::new()) as Box<dyn Progress>),
+            );
+
+        Ok(Session {
+            client,
+            output,
+            rpc,
+            profile,
+            keypair_path,
+        })
+    }
+
+    /// Connects to the cluster and loads the wallet, printing the banner
+    fn wallet(&mut self) -> Result<&Wallet> {
+        let Session {
+            client,
+            output,
+            rpc,
+            profile,
+            keypair_path,
+        } = self.session()?;
+        let payer = load_keypair(&keypair_path)?;
+        let nonce = self
+            .global
+            .nonce_account
+            .map(|account| -> Result<DurableNonce> {
+                // Reuse the payer rather than loading (or prompting for) it twice
+                let authority = match &self.global.nonce_authority {
+                    Some(path) => load_keypair(path)?,
+                    None => payer.insecure_clone(),
+                };
+                Ok(DurableNonce {
+                    account,
+                    authority: Box::new(authority),
+                })
+            })
+            .transpose()?;
+        let fee_payer = self
+            .global
+            .fee_payer
+            .clone()
+            .or(profile.fee_payer.clone())
+            .map(|path| load_keypair(&path))
+            .transpose()?;
+        let mut client = client.with_nonce(nonce).with_fee_payer(
+            fee_payer.map(|fee_payer| Box::new(fee_payer) as Box<dyn Signer + Send + Sync>),
+        );
+        // With a separate authority, every command signs with it where it used
+        // the wallet, and the wallet only pays
+        let payer = match &self.global.authority {
+            Some(path) => {
+                let authority = load_keypair(path)?;
+                client = client.with_payer(Some(Box::new(payer)));
+                authority
+            }
+            None => payer,
+        };
+
+        if output == OutputFormat::Text {
+            println!("Using RPC:    {}", rpc);
+            println!("Using wallet: {}", client.payer().unwrap_or(payer.pubkey()));
+            if client.payer().is_some() {
+                println!("Authority:    {}", payer.pubkey());
+            }
+            if let Some(fee_payer) = client.fee_payer() {
+                println!("Fee payer:    {}", fee_payer);
+            }
+            println!();
+        }
+
+        let confirmation = Confirmation {
+            skip: self.global.yes || self.global.dry_run,
+        };
+
+        Ok(self.wallet.insert(Wallet {
+            client,
+            payer,
+            output,
+            profile,
+            confirmation,
+            yes: self.global.yes,
+        }))
+    }
+
+    /// Writes out the transactions signed with --sign-only
+    fn finish(self) -> Result<()> {
+        let (Some(path), Some(wallet)) = (&self.global.sign_only, &self.wallet) else {
+            return Ok(());
+        };
+        let transactions = wallet.client.take_signed_transactions();
+        write_transactions(path, &transactions)?;
+        let signatures: Vec<String> = transactions
+            .iter()
+            .map(|tx| tx.signatures[0].to_string())
+            .collect();
+        match wallet.output {
+            OutputFormat::Text => print_signed_transactions(path, &signatures),
+            OutputFormat::Signature => signatures.iter().for_each(|s| println!("{}", s)),
+            OutputFormat::Json => print_json(&BroadcastResult { signatures })?,
+        }
+        Ok(())
+    }
+}
+
+async fn run_wizard_command(wallet: &Wallet) -> Result<()> {
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let answers = ask_create(client, None, None, None, Some(""), 0, &[]).await?;
+    let mint_pubkey = answers.mint;
+    let args = answers.into_args(true, None);
+    create_metadata(client, payer, &mint_pubkey, args, None, output, !wallet.yes).await?;
+    Ok(())
+}
+
+fn run_grind_command(args: GrindArgs, output: OutputFormat) -> Result<()> {
+    let GrindArgs {
+        starts_with,
+        ends_with,
+        ignore_case,
+        threads,
+        out,
+    } = &args;
+    let pattern = GrindPattern {
+        starts_with: starts_with.clone(),
+        ends_with: ends_with.clone(),
+        ignore_case: *ignore_case,
+    };
+    pattern.check()?;
+    if let Some(out) = out
+        && out.exists()
+    {
+        anyhow::bail!("'{}' already exists", out.display());
+    }
+    let threads = threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |threads| threads.get()));
+    if output == OutputFormat::Text {
+        println!("Grinding for a vanity address...");
+        if let Some(prefix) = starts_with {
+            println!("  Starts with:  {}", prefix);
+        }
+        if let Some(suffix) = ends_with {
+            println!("  Ends with:    {}", suffix);
+        }
+        println!("  Ignore case:  {}", ignore_case);
+        println!("  Threads:      {}", threads);
+        println!(
+            "  Expected:     ~{:.0} attempts",
+            pattern.expected_attempts()
+        );
+    }
+    let started = Instant::now();
+    let (keypair, attempts) = grind(&pattern, threads)?;
+    let out = match out {
+        Some(out) => out.clone(),
+        None => {
+            let out = PathBuf::from(format!("{}.json", keypair.pubkey()));
+            if out.exists() {
+                anyhow::bail!("'{}' already exists", out.display());
+            }
+            out
+        }
+    };
+    write_keypair_file(&keypair, &out)
+        .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", out.display(), e))?;
+    let result = GrindResult {
+        pubkey: keypair.pubkey().to_string(),
+        out: out.display().to_string(),
+        attempts,
+        seconds: started.elapsed().as_secs_f64(),
+    };
+    match output {
+        OutputFormat::Text | OutputFormat::Signature => print_grind_result(&result),
+        OutputFormat::Json => print_json(&result)?,
+    }
+    Ok(())
+}
+
+async fn run_collection_command(session: &Session, command: CollectionCommands) -> Result<()> {
+    let (client, output) = (&session.client, session.output);
+    match &command {
+        CollectionCommands::Items { collection } => {
+            if output == OutputFormat::Text {
+                println!("Using RPC:    {}\n", session.rpc);
+            }
+            let collection = Pubkey::from_str(collection).context("Invalid collection address")?;
+            let items = client.collection_items(&collection).await?;
+            match output {
+                OutputFormat::Text | OutputFormat::Signature => print_collection_items(&items),
+                OutputFormat::Json => print_json(&items)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_export_command(
+    session: &Session,
+    command: Option<ExportCommands>,
+    mints: Option<PathBuf>,
+    out: Option<PathBuf>,
+) -> Result<()> {
+    let (client, output) = (&session.client, session.output);
+    match (&command, &mints, &out) {
+        (
+            Some(ExportCommands::TokenList {
+                mints,
+                out,
+                name,
+                chain_id,
+                timeout,
+                ipfs_gateway,
+                arweave_gateway,
+            }),
+            _,
+            _,
+        ) => {
+            let mints = load_mints(mints)?;
+            let chain_id = match chain_id {
+                Some(chain_id) => *chain_id,
+                None => token_list::chain_id(client.cluster()).with_context(|| {
+                    format!(
+                        "Can't tell the chain id of {}; pass --chain-id",
+                        client.cluster()
+                    )
+                })?,
+            };
+            if output == OutputFormat::Text {
+                println!("Using RPC:    {}\n", session.rpc);
+                println!("Building a token list of {} mints...", mints.len());
+            }
+            let gateways = Gateways {
+                ipfs: ipfs_gateway.clone(),
+                arweave: arweave_gateway.clone(),
+            };
+            let (list, skipped) = client
+                .export_token_list(
+                    &mints,
+                    name,
+                    chain_id,
+                    &gateways,
+                    Duration::from_secs(*timeout),
+                )
+                .await?;
+            write_token_list(out, &list)?;
+            let result = TokenListResult {
+                tokens: list.tokens.len(),
+                without_logo: list
+                    .tokens
+                    .iter()
+                    .filter(|token| token.logo_uri.is_none())
+                    .count(),
+                skipped,
+                out: out.display().to_string(),
+            };
+            match output {
+                OutputFormat::Text | OutputFormat::Signature => print_token_list_result(&result),
+                OutputFormat::Json => print_json(&result)?,
+            }
+        }
+        (None, Some(mints), Some(out)) => {
+            let mints = load_mints(mints)?;
+            if output == OutputFormat::Text {
+                println!("Using RPC:    {}\n", session.rpc);
+                println!("Exporting metadata for {} mints...", mints.len());
+            }
+            let rows = client.export_metadata(&mints).await?;
+            write_rows(out, &rows)?;
+            let result = ExportResult {
+                total: rows.len(),
+                missing: rows.iter().filter(|row| row.error.is_some()).count(),
+                out: out.display().to_string(),
+            };
+            match output {
+                OutputFormat::Text | OutputFormat::Signature => print_export_result(&result),
+                OutputFormat::Json => print_json(&result)?,
+            }
+        }
+        (None, _, _) => unreachable!("clap requires --mints and --out"),
+    }
+    Ok(())
+}
+
+async fn run_json_command(command: JsonCommands, output: OutputFormat) -> Result<()> {
+    match &command {
+        JsonCommands::Generate {
+            name,
+            symbol,
+            description,
+            image_uri,
+            image_type,
+            animation_url,
+            external_url,
+            seller_fee_basis_points,
+            creators,
+            attributes,
+            out,
+        } => {
+            check_name(name)?;
+            check_symbol(symbol)?;
+            check_seller_fee_basis_points(*seller_fee_basis_points)?;
+            let json = build_metadata_json(&MetadataJsonArgs {
+                name: name.clone(),
+                symbol: symbol.clone(),
+                description: description.clone(),
+                image_uri: image_uri.clone(),
+                image_type: image_type.clone(),
+                animation_url: animation_url.clone(),
+                external_url: external_url.clone(),
+                seller_fee_basis_points: *seller_fee_basis_points,
+                creators: creators.clone(),
+                attributes: attributes.clone(),
+            });
+            // Catch creator shares that don't add up and the like before the
+            // file is uploaded
+            let source = out.display().to_string();
+            let report = validate_metadata_json(&source, &json, false, HTTP_TIMEOUT).await;
+            if !report.is_valid() {
+                match output {
+                    OutputFormat::Text | OutputFormat::Signature => {
+                        print_validation_report(&report)
+                    }
+                    OutputFormat::Json => print_json(&report)?,
+                }
+                return Err(invalid_json(&source));
+            }
+            std::fs::write(out, serde_json::to_string_pretty(&json)?)
+                .with_context(|| format!("Failed to write '{}'", out.display()))?;
+            match output {
+                OutputFormat::Text | OutputFormat::Signature => {
+                    if report.warnings > 0 {
+                        print_validation_report(&report);
+                    }
+                    println!("Wrote metadata JSON to {}", out.display());
+                }
+                OutputFormat::Json => print_json(&report)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_show_command(session: &Session, args: ShowArgs) -> Result<()> {
+    let ShowArgs {
+        mint,
+        offchain,
+        timeout,
+        ipfs_gateway,
+        arweave_gateway,
+        delegates,
+    } = &args;
+    let (client, output) = (&session.client, session.output);
+    if output == OutputFormat::Text {
+        println!("Using RPC:    {}\n", session.rpc);
+    }
+    let mint_pubkey = Pubkey::from_str(mint).context("Invalid mint address")?;
+    // A compressed NFT has no mint account, so with DAS don't look for one
+    if client.das().is_none()
+        && detect_mint(client, &mint_pubkey, output)
+            .await?
+            .stores_own_metadata()
+    {
+        if *offchain || *delegates {
+            warn!(
+                "--offchain and --delegates only apply to Metaplex metadata; showing Token-2022 metadata"
+            );
+        }
+        let info =
+            Token2022MetadataInfo::from(&client.fetch_token2022_metadata(&mint_pubkey).await?);
+        match output {
+            OutputFormat::Text | OutputFormat::Signature => print_token2022_metadata(&info),
+            OutputFormat::Json => print_json(&info)?,
+        }
+        return Ok(());
+    }
+    let mut info = match client.das() {
+        Some(_) => client.fetch_asset(&mint_pubkey).await?,
+        None => MetadataInfo::from(client.fetch(&mint_pubkey).await?),
+    };
+    if *offchain {
+        let gateways = Gateways {
+            ipfs: ipfs_gateway.clone(),
+            arweave: arweave_gateway.clone(),
+        };
+        if info.uri.is_empty() {
+            info.offchain_error = Some("The metadata URI is empty".to_string());
+        } else {
+            match client
+                .fetch_offchain_json(&gateways, &info.uri, Duration::from_secs(*timeout))
+                .await
+            {
+                Ok(json) => info.offchain = Some(OffChainInfo::from(&json)),
+                Err(err) => info.offchain_error = Some(format!("{:#}", err)),
+            }
+        }
+    }
+    if *delegates {
+        info.delegates = Some(client.fetch_metadata_delegates(&mint_pubkey).await?);
+    }
+    match output {
+        OutputFormat::Text | OutputFormat::Signature => print_metadata_info(&info),
+        OutputFormat::Json => print_json(&info)?,
+    }
+    Ok(())
+}
+
+async fn run_list_command(session: &Session, args: ListArgs) -> Result<()> {
+    let ListArgs {
+        update_authority,
+        creator,
+        verified,
+    } = &args;
+    let (client, output) = (&session.client, session.output);
+    if output == OutputFormat::Text {
+        println!("Using RPC:    {}\n", session.rpc);
+    }
+    let items = match (update_authority, creator) {
+        (Some(authority), _) => {
+            let authority =
+                Pubkey::from_str(authority).context("Invalid update authority address")?;
+            client.list_by_update_authority(&authority).await?
+        }
+        (None, Some(creator)) => {
+            let creator = Pubkey::from_str(creator).context("Invalid creator address")?;
+            client.list_by_creator(&creator, *verified).await?
+        }
+        (None, None) => unreachable!("clap requires --update-authority or --creator"),
+    };
+    match output {
+        OutputFormat::Text | OutputFormat::Signature => print_metadata_summaries(&items),
+        OutputFormat::Json => print_json(&items)?,
+    }
+    Ok(())
+}
+
+async fn run_audit_command(session: &Session, args: AuditArgs) -> Result<()> {
+    let AuditArgs {
+        mint,
+        mints,
+        min_score,
+        require_immutable,
+        skip_uri,
+        timeout,
+        ipfs_gateway,
+        arweave_gateway,
+    } = &args;
+    let (client, output) = (&session.client, session.output);
+    let mut addresses = mint.clone();
+    if let Some(path) = mints {
+        addresses.extend(load_mints(path)?);
+    }
+    let mints = addresses
+        .iter()
+        .map(|mint| {
+            Pubkey::from_str(mint).with_context(|| format!("Invalid mint address '{}'", mint))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if output == OutputFormat::Text {
+        println!("Using RPC:    {}\n", session.rpc);
+        println!("Auditing {} mint(s)...", mints.len());
+    }
+    let options = AuditOptions {
+        require_immutable: *require_immutable,
+        check_uri: !skip_uri,
+        gateways: Gateways {
+            ipfs: ipfs_gateway.clone(),
+            arweave: arweave_gateway.clone(),
+        },
+        timeout: Duration::from_secs(*timeout),
+    };
+    let report = client.audit(&mints, &options).await?;
+    match output {
+        OutputFormat::Text | OutputFormat::Signature => print_audit_report(&report),
+        OutputFormat::Json => print_json(&report)?,
+    }
+    if let Some(min_score) = min_score
+        && report.score < *min_score
+    {
+        anyhow::bail!(
+            "Audit score {} is below the minimum of {}",
+            report.score,
+            min_score
+        );
+    }
+    Ok(())
+}
+
+async fn run_watch_command(session: &Session, args: WatchArgs) -> Result<()> {
+    let WatchArgs {
+        mint,
+        mints,
+        webhook,
+    } = &args;
+    let (client, output) = (&session.client, session.output);
+    let mut addresses = mint.clone();
+    if let Some(path) = mints {
+        addresses.extend(load_mints(path)?);
+    }
+    let mints = addresses
+        .iter()
+        .map(|mint| {
+            Pubkey::from_str(mint).with_context(|| format!("Invalid mint address '{}'", mint))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if output == OutputFormat::Text {
+        println!("Using RPC:    {}\n", session.rpc);
+        println!("Watching {} mints (Ctrl-C to stop)...", mints.len());
+    }
+    let webhook = webhook.as_deref().map(Webhook::new).transpose()?;
+    let webhook = webhook.as_ref();
+    client
+        .watch(&mints, |event| async move {
+            if let Some(webhook) = webhook
+                && let Err(err) = webhook.post(&event).await
+            {
+                error!("{:#}", err);
+            }
+            match output {
+                OutputFormat::Text | OutputFormat::Signature => print_metadata_event(&event),
+                // One event per line, for piping into other tools
+                OutputFormat::Json => match serde_json::to_string(&event) {
+                    Ok(line) => println!("{}", line),
+                    Err(err) => error!("Failed to serialize event: {}", err),
+                },
+            }
+        })
+        .await?;
+    Ok(())
+}
+
+async fn run_snapshot_command(session: &Session, args: SnapshotArgs) -> Result<()> {
+    let SnapshotArgs {
+        collection,
+        mint,
+        out,
+        concurrency,
+    } = &args;
+    let (client, output) = (&session.client, session.output);
+    if output == OutputFormat::Text {
+        println!("Using RPC:    {}\n", session.rpc);
+    }
+    let result = match (collection, mint) {
+        (Some(collection), _) => {
+            let collection = Pubkey::from_str(collection).context("Invalid collection address")?;
+            client.collection_holders(&collection, *concurrency).await?
+        }
+        (None, Some(mint)) => {
+            let mint = Pubkey::from_str(mint).context("Invalid mint address")?;
+            client.mint_snapshot(&mint).await?
+        }
+        (None, None) => unreachable!("clap requires --collection or --mint"),
+    };
+    if let Some(path) = out {
+        write_rows(path, &result.holders)?;
+    }
+    match output {
+        OutputFormat::Text | OutputFormat::Signature => {
+            print_snapshot_result(&result, out.as_deref())
+        }
+        OutputFormat::Json => print_json(&result)?,
+    }
+    if !result.failed.is_empty() {
+        anyhow::bail!(
+            "{} of {} mints could not be looked up",
+            result.failed.len(),
+            result.mints
+        );
+    }
+    Ok(())
+}
+
+async fn run_diff_command(session: &Session, args: DiffArgs) -> Result<()> {
+    let DiffArgs { mint, file } = &args;
+    let (client, output) = (&session.client, session.output);
+    let mint_pubkey = Pubkey::from_str(mint).context("Invalid mint address")?;
+    let expected = load_expected(file, &mint_pubkey)?;
+    let result = client.diff(&mint_pubkey, &expected).await?;
+    match output {
+        OutputFormat::Text | OutputFormat::Signature => print_diff_result(&result),
+        OutputFormat::Json => print_json(&result)?,
+    }
+    if !result.matches {
+        anyhow::bail!("On-chain metadata differs from {}", file);
+    }
+    Ok(())
+}
+
+fn run_history_command(session: &Session, args: HistoryArgs) -> Result<()> {
+    let HistoryArgs { mint } = &args;
+    let (client, output) = (&session.client, session.output);
+    let mint_pubkey = Pubkey::from_str(mint).context("Invalid mint address")?;
+    let history = client
+        .history()
+        .context("The local history is turned off")?;
+    let result = HistoryResult {
+        mint: mint_pubkey.to_string(),
+        entries: history.entries(&mint_pubkey)?,
+    };
+    match output {
+        OutputFormat::Text | OutputFormat::Signature => print_history_result(&result),
+        OutputFormat::Json => print_json(&result)?,
+    }
+    Ok(())
+}
+
+async fn run_validate_json_command(args: ValidateJsonArgs, output: OutputFormat) -> Result<()> {
+    let ValidateJsonArgs { source, skip_links } = &args;
+    let json = load_json(source, HTTP_TIMEOUT).await?;
+    let report = validate_metadata_json(source, &json, !skip_links, HTTP_TIMEOUT).await;
+    match output {
+        OutputFormat::Text | OutputFormat::Signature => print_validation_report(&report),
+        OutputFormat::Json => print_json(&report)?,
+    }
+    if !report.is_valid() {
+        return Err(invalid_json(source));
+    }
+    Ok(())
+}
+
+async fn run_generate_command(session: &Session, args: GenerateArgs) -> Result<()> {
+    let GenerateArgs {
+        template,
+        data,
+        count,
+        start_index,
+        out_dir,
+        upload,
+        storage: storage_args,
+    } = &args;
+    let (client, output) = (&session.client, session.output);
+    let contents = std::fs::read_to_string(template)
+        .with_context(|| format!("Failed to read template '{}'", template.display()))?;
+    let template_json: serde_json::Value = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse template '{}'", template.display()))?;
+    let rows = match (data, count) {
+        (Some(data), _) => load_rows(data)?,
+        (None, Some(count)) => vec![Row::new(); *count],
+        (None, None) => unreachable!("clap requires --data or --count"),
+    };
+    let mut items = generate_files(&template_json, rows, *start_index, out_dir)?;
+    if output == OutputFormat::Text {
+        println!("Generated {} file(s) in {}", items.len(), out_dir.display());
+    }
+
+    let mut lamports_paid = 0;
+    if *upload {
+        let payer = load_keypair(&session.keypair_path)?;
+        let storage = storage(storage_args, &session.profile)?;
+        for item in &mut items {
+            if output == OutputFormat::Text {
+                println!("Uploading {}...", item.file);
+            }
+            let result = client
+                .upload_metadata(
+                    &payer,
+                    &storage,
+                    Path::new(&item.file),
+                    item.image.as_deref().map(Path::new),
+                )
+                .await?;
+            lamports_paid += result.lamports_paid;
+            item.uri = Some(result.uri);
+            item.image_uri = result.image_uri;
+        }
+    }
+
+    let manifest = write_manifest(out_dir, &items)?;
+    let result = GenerateResult {
+        out_dir: out_dir.display().to_string(),
+        manifest: manifest.display().to_string(),
+        lamports_paid,
+        items,
+    };
+    match output {
+        OutputFormat::Text | OutputFormat::Signature => print_generate_result(&result),
+        OutputFormat::Json => print_json(&result)?,
+    }
+    Ok(())
+}
+
+async fn run_broadcast_command(session: &Session, args: BroadcastArgs) -> Result<()> {
+    let BroadcastArgs { file } = &args;
+    let (client, output) = (&session.client, session.output);
+    if output == OutputFormat::Text {
+        println!("Using RPC:    {}\n", session.rpc);
+    }
+    let mut signatures = Vec::new();
+    for tx in read_transactions(file)? {
+        signatures.push(client.broadcast(&tx).await?.to_string());
+    }
+    let result = BroadcastResult { signatures };
+    match output {
+        OutputFormat::Text | OutputFormat::Signature => print_broadcast_result(&result),
+        OutputFormat::Json => print_json(&result)?,
+    }
+    Ok(())
+}
+
+async fn run_airdrop_command(wallet: &Wallet, args: AirdropArgs) -> Result<()> {
+    let AirdropArgs { amount, to } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let recipient = match to {
+        Some(to) => Pubkey::from_str(&to).context("Invalid recipient address")?,
+        None => client.payer().unwrap_or(payer.pubkey()),
+    };
+    let lamports = sol_to_lamports(amount);
+    if output == OutputFormat::Text {
+        println!("Requesting airdrop...");
+        println!("  Recipient:    {}", recipient);
+        println!("  Amount:       {} SOL", amount);
+    }
+    let result = client.airdrop(&recipient, lamports).await?;
+    emit(client, output, &result, print_airdrop_result)?;
+    Ok(())
+}
+
+async fn run_create_command(wallet: &Wallet, args: CreateArgs) -> Result<()> {
+    let CreateArgs {
+        mint,
+        name,
+        symbol,
+        uri,
+        upload_uri,
+        image,
+        description,
+        storage: storage_args,
+        mutable,
+        seller_fee_basis_points,
+        standard,
+        creators,
+        uses,
+        validate,
+        hash_uri,
+        update_authority,
+        interactive,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mut answers = if interactive {
+        // An uploaded file or image provides the URI instead
+        let uri = (upload_uri.is_none() && image.is_none()).then_some(uri.as_str());
+        ask_create(
+            client,
+            mint.as_deref(),
+            name.as_deref(),
+            symbol.as_deref(),
+            uri,
+            seller_fee_basis_points,
+            &creators,
+        )
+        .await?
+    } else {
+        // clap requires these without --interactive
+        CreateAnswers {
+            mint: Pubkey::from_str(&mint.unwrap_or_default()).context("Invalid mint address")?,
+            name: name.unwrap_or_default(),
+            symbol: symbol.unwrap_or_default(),
+            uri,
+            seller_fee_basis_points,
+            creators,
+        }
+    };
+    let uses = uses.parse()?;
+    if validate {
+        match &upload_uri {
+            Some(path) => ensure_valid_json(&path.to_string_lossy(), output).await?,
+            None => ensure_valid_json(&answers.uri, output).await?,
+        }
+    }
+    if let Some(path) = upload_uri {
+        let storage = storage(&storage_args, &wallet.profile)?;
+        if output == OutputFormat::Text {
+            println!("Uploading {}...", path.display());
+        }
+        answers.uri = client
+            .upload_metadata(payer, &storage, &path, None)
+            .await?
+            .uri;
+    }
+    let mint_pubkey = answers.mint;
+    let mut args = answers.into_args(mutable, uses);
+    args.update_authority = update_authority;
+    if let Some(image) = image {
+        let storage = storage(&storage_args, &wallet.profile)?;
+        args.uri = upload_image(
+            client,
+            payer,
+            &storage,
+            &image,
+            &args,
+            description.as_deref(),
+            output,
+        )
+        .await?;
+    }
+    if hash_uri {
+        args.uri_hash = Some(fetch_uri_hash(&args.uri, output).await?);
+    }
+    create_metadata(
+        client,
+        payer,
+        &mint_pubkey,
+        args,
+        standard,
+        output,
+        interactive && !wallet.yes,
+    )
+    .await?;
+    Ok(())
+}
+
+async fn run_update_command(wallet: &Wallet, args: UpdateArgs) -> Result<()> {
+    let UpdateArgs {
+        mint,
+        name,
+        symbol,
+        uri,
+        creators,
+        uses,
+        primary_sale_happened,
+        validate,
+        hash_uri,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    let uses = uses.parse()?;
+    if validate && let Some(uri) = &uri {
+        ensure_valid_json(uri, output).await?;
+    }
+    let uri_hash = match &uri {
+        Some(uri) if hash_uri => Some(fetch_uri_hash(uri, output).await?),
+        _ => None,
+    };
+    if detect_mint(client, &mint_pubkey, output)
+        .await?
+        .stores_own_metadata()
+    {
+        if !creators.is_empty() || uses.is_some() || primary_sale_happened {
+            anyhow::bail!(
+                "Mint {} stores Token-2022 metadata, which has no creators, uses or \
+                     primary sale flag",
+                mint_pubkey
+            );
+        }
+        if output == OutputFormat::Text {
+            println!("Updating Token-2022 metadata...");
+            println!("  Mint:         {}", mint_pubkey);
+        }
+        let args = Token2022UpdateArgs {
+            name,
+            symbol,
+            uri,
+            uri_hash,
+            ..Default::default()
+        };
+        let result = client
+            .update_token2022_metadata(payer, &mint_pubkey, args)
+            .await?;
+        emit(client, output, &result, print_token2022_update_result)?
+    } else {
+        if output == OutputFormat::Text {
+            println!("Updating metadata...");
+            println!("  Mint:         {}", mint_pubkey);
+            println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+        }
+        if primary_sale_happened {
+            wallet.confirmation.require(&format!(
+                "marketplaces will treat every later sale of {} as a secondary sale, \
+                     and the flag can't be cleared.",
+                mint_pubkey
+            ))?;
+        }
+        let args = UpdateMetadataArgs {
+            name,
+            symbol,
+            uri,
+            creators: (!creators.is_empty()).then_some(creators),
+            uses,
+            primary_sale_happened,
+            uri_hash,
+        };
+        let result = client.update(payer, &mint_pubkey, args).await?;
+        emit(client, output, &result, print_update_result)?
+    }
+    Ok(())
+}
 
-        /// Seller fee basis points (0-10000)
-        #[arg(long, default_value_t = 0)]
-        seller_fee_basis_points: u16,
-    },
-    /// Update metadata for an existing token mint
-    Update {
-        /// Token mint address
-        #[arg(short, long)]
-        mint: String,
+async fn run_rollback_command(wallet: &Wallet, args: RollbackArgs) -> Result<()> {
+    let RollbackArgs { mint, entry } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    if output == OutputFormat::Text {
+        println!("Rolling back metadata...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+        match entry {
+            Some(entry) => println!("  Entry:        {}", entry),
+            None => println!("  Entry:        latest"),
+        }
+    }
+    let result = client.rollback(payer, &mint_pubkey, entry).await?;
+    emit(client, output, &result, print_rollback_result)?;
+    Ok(())
+}
 
-        /// New token name (optional)
-        #[arg(short, long)]
-        name: Option<String>,
+async fn run_set_authority_command(wallet: &Wallet, args: SetAuthorityArgs) -> Result<()> {
+    let SetAuthorityArgs {
+        mint,
+        new_authority,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    let new_authority =
+        Pubkey::from_str(&new_authority).context("Invalid new authority address")?;
+    if output == OutputFormat::Text {
+        println!("Transferring update authority...");
+        println!("  Mint:          {}", mint_pubkey);
+        println!("  Metadata PDA:  {}", find_metadata_pda(&mint_pubkey));
+        println!("  New authority: {}", new_authority);
+    }
+    wallet.confirmation.require(&format!(
+        "the update authority of {} passes to {}, and only the new authority can \
+             hand it back.",
+        mint_pubkey, new_authority
+    ))?;
+    let result = client
+        .set_update_authority(payer, &mint_pubkey, &new_authority)
+        .await?;
+    emit(client, output, &result, print_set_authority_result)?;
+    Ok(())
+}
 
-        /// New token symbol (optional)
-        #[arg(short, long)]
-        symbol: Option<String>,
+async fn run_make_immutable_command(wallet: &Wallet, args: MakeImmutableArgs) -> Result<()> {
+    let MakeImmutableArgs { mint } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    if output == OutputFormat::Text {
+        println!("Making metadata immutable...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+    }
+    wallet.confirmation.require(&format!(
+        "this is permanent. Once immutable, the name, symbol, URI, royalties and \
+             creators of {} can never be changed again, not even by the update \
+             authority.",
+        mint_pubkey
+    ))?;
+    let result = client.make_immutable(payer, &mint_pubkey).await?;
+    emit(client, output, &result, print_make_immutable_result)?;
+    Ok(())
+}
 
-        /// New metadata URI (optional)
-        #[arg(long)]
-        uri: Option<String>,
-    },
+async fn run_set_primary_sale_command(wallet: &Wallet, args: SetPrimarySaleArgs) -> Result<()> {
+    let SetPrimarySaleArgs { mint } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    if output == OutputFormat::Text {
+        println!("Marking primary sale as happened...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+    }
+    wallet.confirmation.require(&format!(
+        "marketplaces will treat every later sale of {} as a secondary sale, and \
+             the flag can't be cleared.",
+        mint_pubkey
+    ))?;
+    let result = client
+        .set_primary_sale_happened(payer, &mint_pubkey)
+        .await?;
+    emit(client, output, &result, print_primary_sale_result)?;
+    Ok(())
 }
 
-fn expand_tilde(path: &str) -> String {
-    if path.starts_with('~') {
-        if let Ok(home) = std::env::var("HOME") {
-            return path.replacen('~', &home, 1);
-        }
+async fn run_create_token_command(wallet: &Wallet, args: CreateTokenArgs) -> Result<()> {
+    let CreateTokenArgs {
+        name,
+        symbol,
+        uri,
+        image,
+        description,
+        storage: storage_args,
+        decimals,
+        initial_supply,
+        mutable,
+        creators,
+        mint_keypair,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint = mint_keypair_or_new(mint_keypair.as_deref())?;
+    let initial_supply = initial_supply
+        .map(|amount| spl_token::ui_amount_to_amount(amount, decimals))
+        .unwrap_or(0);
+    let mut args = CreateMetadataArgs {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points: 0,
+        is_mutable: mutable,
+        creators: (!creators.is_empty()).then_some(creators),
+        uses: None,
+        uri_hash: None,
+        update_authority: None,
+    };
+    if let Some(image) = image {
+        let storage = storage(&storage_args, &wallet.profile)?;
+        args.uri = upload_image(
+            client,
+            payer,
+            &storage,
+            &image,
+            &args,
+            description.as_deref(),
+            output,
+        )
+        .await?;
     }
-    path.to_string()
+    if output == OutputFormat::Text {
+        println!("Creating token...");
+        print_create_preview(&mint.pubkey(), &args);
+        println!("  Decimals:     {}", decimals);
+    }
+    let result = client
+        .create_token(payer, &mint, decimals, initial_supply, args)
+        .await?;
+    emit(client, output, &result, print_create_token_result)?;
+    Ok(())
 }
 
-fn load_keypair(path: &str) -> Result<Keypair> {
-    let expanded = expand_tilde(path);
-    read_keypair_file(&expanded)
-        .map_err(|e| anyhow::anyhow!("Failed to read keypair from '{}': {}", expanded, e))
+async fn run_create_mint_command(wallet: &Wallet, args: CreateMintArgs) -> Result<()> {
+    let CreateMintArgs {
+        decimals,
+        initial_supply,
+        owner,
+        freeze_authority,
+        mint_keypair,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint = mint_keypair_or_new(mint_keypair.as_deref())?;
+    let owner = owner.unwrap_or(payer.pubkey());
+    let initial_supply = initial_supply
+        .map(|amount| spl_token::ui_amount_to_amount(amount, decimals))
+        .unwrap_or(0);
+    if output == OutputFormat::Text {
+        println!("Creating mint...");
+        println!("  Mint:         {}", mint.pubkey());
+        println!("  Decimals:     {}", decimals);
+        println!("  Mint auth:    {}", payer.pubkey());
+        match &freeze_authority {
+            Some(freeze_authority) => println!("  Freeze auth:  {}", freeze_authority),
+            None => println!("  Freeze auth:  (disabled)"),
+        }
+        if initial_supply > 0 {
+            println!(
+                "  Supply:       {} to {}",
+                spl_token::amount_to_ui_amount_string_trimmed(initial_supply, decimals),
+                owner
+            );
+        }
+    }
+    let result = client
+        .create_mint(
+            payer,
+            &mint,
+            decimals,
+            freeze_authority.as_ref(),
+            initial_supply,
+            &owner,
+        )
+        .await?;
+    emit(client, output, &result, print_create_mint_result)?;
+    Ok(())
 }
 
-/// Derive the metadata PDA for a given mint
-fn find_metadata_pda(mint: &Pubkey) -> Pubkey {
-    let seeds = &[
-        b"metadata".as_ref(),
-        TOKEN_METADATA_PROGRAM_ID.as_ref(),
-        mint.as_ref(),
-    ];
-    Pubkey::find_program_address(seeds, &TOKEN_METADATA_PROGRAM_ID).0
+async fn run_create_nft_command(wallet: &Wallet, args: CreateNftArgs) -> Result<()> {
+    let CreateNftArgs {
+        name,
+        symbol,
+        uri,
+        metadata_file,
+        seller_fee_basis_points,
+        mutable,
+        creators,
+        max_supply,
+        standard,
+        rule_set,
+        uses,
+        mint_keypair,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let programmable = match standard {
+        None | Some(Standard::NonFungible) => false,
+        Some(Standard::ProgrammableNonFungible) => true,
+        Some(standard) => anyhow::bail!(
+            "create-nft creates NFTs; use create-token or create --standard for a {}",
+            standard
+        ),
+    };
+    let rule_set = rule_set
+        .map(|rule_set| Pubkey::from_str(&rule_set).context("Invalid rule set address"))
+        .transpose()?;
+    if rule_set.is_some() && !programmable {
+        anyhow::bail!("--rule-set only applies to --standard pnft");
+    }
+    let (name, symbol, seller_fee_basis_points, creators) = merge_metadata_file(
+        metadata_file.as_deref(),
+        name,
+        symbol,
+        seller_fee_basis_points,
+        creators,
+    )?;
+    let mint = mint_keypair_or_new(mint_keypair.as_deref())?;
+    let args = CreateMetadataArgs {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        is_mutable: mutable,
+        creators: (!creators.is_empty()).then_some(creators),
+        uses: uses.parse()?,
+        uri_hash: None,
+        update_authority: None,
+    };
+    if output == OutputFormat::Text {
+        println!(
+            "Creating {}...",
+            if programmable {
+                "programmable NFT"
+            } else {
+                "NFT"
+            }
+        );
+        print_create_preview(&mint.pubkey(), &args);
+        if let Some(rule_set) = &rule_set {
+            println!("  Rule set:     {}", rule_set);
+        }
+    }
+    let result = if programmable {
+        client
+            .create_programmable_nft(payer, &mint, args, rule_set, max_supply)
+            .await?
+    } else {
+        client.create_nft(payer, &mint, args, max_supply).await?
+    };
+    emit(client, output, &result, print_create_nft_result)?;
+    Ok(())
 }
 
-fn create_metadata(
-    client: &RpcClient,
-    payer: &Keypair,
-    mint: &Pubkey,
-    name: String,
-    symbol: String,
-    uri: String,
-    seller_fee_basis_points: u16,
-    is_mutable: bool,
+async fn run_create_master_edition_command(
+    wallet: &Wallet,
+    args: CreateMasterEditionArgs,
 ) -> Result<()> {
-    let metadata_pda = find_metadata_pda(mint);
+    let CreateMasterEditionArgs { mint, max_supply } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    if output == OutputFormat::Text {
+        println!("Creating master edition...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+    }
+    let result = client
+        .create_master_edition(payer, &mint_pubkey, max_supply)
+        .await?;
+    emit(client, output, &result, print_create_master_edition_result)?;
+    Ok(())
+}
 
-    println!("Creating metadata...");
-    println!("  Mint:         {}", mint);
-    println!("  Metadata PDA: {}", metadata_pda);
-    println!("  Name:         {}", name);
-    println!("  Symbol:       {}", symbol);
-    println!(
-        "  URI:          {}",
-        if uri.is_empty() { "(empty)" } else { &uri }
-    );
-    println!("  Mutable:      {}", is_mutable);
+async fn run_print_edition_command(wallet: &Wallet, args: PrintEditionArgs) -> Result<()> {
+    let PrintEditionArgs { mint, edition } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let master_mint = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    let new_mint = Keypair::new();
+    if output == OutputFormat::Text {
+        println!("Printing edition...");
+        println!("  Master mint:  {}", master_mint);
+        println!("  Edition mint: {}", new_mint.pubkey());
+    }
+    let result = client
+        .print_edition(payer, &new_mint, &master_mint, edition)
+        .await?;
+    emit(client, output, &result, print_print_edition_result)?;
+    Ok(())
+}
 
-    let data = DataV2 {
+async fn run_create_collection_command(wallet: &Wallet, args: CreateCollectionArgs) -> Result<()> {
+    let CreateCollectionArgs {
+        name,
+        symbol,
+        uri,
+        mutable,
+        seller_fee_basis_points,
+        creators,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint = Keypair::new();
+    let args = CreateMetadataArgs {
         name,
         symbol,
         uri,
         seller_fee_basis_points,
-        creators: None,
-        collection: None,
+        is_mutable: mutable,
+        creators: (!creators.is_empty()).then_some(creators),
         uses: None,
+        uri_hash: None,
+        update_authority: None,
     };
+    if output == OutputFormat::Text {
+        println!("Creating collection NFT...");
+        print_create_preview(&mint.pubkey(), &args);
+    }
+    let result = client.create_collection(payer, &mint, args).await?;
+    emit(client, output, &result, print_create_collection_result)?;
+    Ok(())
+}
 
-    let ix = CreateMetadataAccountV3Builder::new()
-        .metadata(metadata_pda)
-        .mint(*mint)
-        .mint_authority(payer.pubkey())
-        .payer(payer.pubkey())
-        .update_authority(payer.pubkey(), true)
-        .data(data)
-        .is_mutable(is_mutable)
-        .instruction();
+async fn run_set_collection_command(wallet: &Wallet, args: SetCollectionArgs) -> Result<()> {
+    let SetCollectionArgs { mint, collection } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    let collection_pubkey =
+        Pubkey::from_str(&collection).context("Invalid collection mint address")?;
+    if output == OutputFormat::Text {
+        println!("Setting collection...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+        println!("  Collection:   {}", collection_pubkey);
+    }
+    let result = client
+        .set_collection(payer, &mint_pubkey, &collection_pubkey)
+        .await?;
+    emit(client, output, &result, print_set_collection_result)?;
+    Ok(())
+}
 
-    let recent_blockhash = client.get_latest_blockhash()?;
-    let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&payer.pubkey()),
-        &[payer],
-        recent_blockhash,
-    );
+async fn run_set_collection_size_command(
+    wallet: &Wallet,
+    args: SetCollectionSizeArgs,
+) -> Result<()> {
+    let SetCollectionSizeArgs { collection, size } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let collection_pubkey =
+        Pubkey::from_str(&collection).context("Invalid collection mint address")?;
+    if output == OutputFormat::Text {
+        println!("Setting collection size...");
+        println!("  Collection:   {}", collection_pubkey);
+        println!("  Metadata PDA: {}", find_metadata_pda(&collection_pubkey));
+        println!("  Size:         {}", size);
+    }
+    let result = client
+        .set_collection_size(payer, &collection_pubkey, size)
+        .await?;
+    emit(client, output, &result, print_set_collection_size_result)?;
+    Ok(())
+}
 
-    let signature = client
-        .send_and_confirm_transaction_with_spinner(&tx)
-        .context("Failed to send create metadata transaction")?;
+async fn run_unverify_collection_command(
+    wallet: &Wallet,
+    args: UnverifyCollectionArgs,
+) -> Result<()> {
+    let UnverifyCollectionArgs { mint } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    if output == OutputFormat::Text {
+        println!("Unverifying collection...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+    }
+    let result = client.unverify_collection(payer, &mint_pubkey).await?;
+    emit(client, output, &result, print_unverify_collection_result)?;
+    Ok(())
+}
 
-    println!("\nMetadata created successfully!");
-    println!("  Signature: {}", signature);
-    println!(
-        "  Explorer:  https://explorer.solana.com/tx/{}?cluster=devnet",
-        signature
-    );
+async fn run_burn_command(wallet: &Wallet, args: BurnArgs) -> Result<()> {
+    let BurnArgs { mint, amount } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    if output == OutputFormat::Text {
+        println!("Burning...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+        println!("  Owner:        {}", payer.pubkey());
+    }
+    wallet.confirmation.require(&format!(
+        "burning {} is permanent; the tokens can't be recovered.",
+        match amount {
+            Some(amount) => format!("{} base units of {}", amount, mint_pubkey),
+            None => format!("every token of {} held by {}", mint_pubkey, payer.pubkey()),
+        }
+    ))?;
+    let result = client.burn(payer, &mint_pubkey, amount).await?;
+    emit(client, output, &result, print_burn_result)?;
+    Ok(())
+}
+
+async fn run_verify_creator_command(wallet: &Wallet, args: VerifyCreatorArgs) -> Result<()> {
+    let VerifyCreatorArgs {
+        mint,
+        creator_keypairs,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    if creator_keypairs.is_empty() {
+        if output == OutputFormat::Text {
+            println!("Verifying creator...");
+            println!("  Mint:         {}", mint_pubkey);
+            println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+            println!("  Creator:      {}", payer.pubkey());
+        }
+        let result = client.verify_creator(payer, &mint_pubkey).await?;
+        emit(client, output, &result, print_creator_verification_result)?;
+    } else {
+        let creators = creator_keypairs
+            .iter()
+            .map(|path| load_keypair(path))
+            .collect::<Result<Vec<_>>>()?;
+        if output == OutputFormat::Text {
+            println!("Verifying creators...");
+            println!("  Mint:         {}", mint_pubkey);
+            println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+            for creator in &creators {
+                println!("  Creator:      {}", creator.pubkey());
+            }
+        }
+        let signers: Vec<&dyn Signer> = creators.iter().map(|c| c as &dyn Signer).collect();
+        let result = client
+            .verify_creators(payer, &signers, &mint_pubkey)
+            .await?;
+        emit(client, output, &result, print_creators_verification_result)?;
+    }
+    Ok(())
+}
 
+async fn run_unverify_creator_command(wallet: &Wallet, args: UnverifyCreatorArgs) -> Result<()> {
+    let UnverifyCreatorArgs { mint } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    if output == OutputFormat::Text {
+        println!("Unverifying creator...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+        println!("  Creator:      {}", payer.pubkey());
+    }
+    let result = client.unverify_creator(payer, &mint_pubkey).await?;
+    emit(client, output, &result, print_creator_verification_result)?;
     Ok(())
 }
 
-fn update_metadata(
-    client: &RpcClient,
-    payer: &Keypair,
-    mint: &Pubkey,
-    name: Option<String>,
-    symbol: Option<String>,
-    uri: Option<String>,
-) -> Result<()> {
-    let metadata_pda = find_metadata_pda(mint);
+async fn run_upload_command(wallet: &Wallet, args: UploadArgs) -> Result<()> {
+    let UploadArgs {
+        file,
+        image,
+        storage: storage_args,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let storage = storage(&storage_args, &wallet.profile)?;
+    if output == OutputFormat::Text {
+        println!("Uploading {}...", file.display());
+    }
+    let result = client
+        .upload_metadata(payer, &storage, &file, image.as_deref())
+        .await?;
+    match output {
+        OutputFormat::Text | OutputFormat::Signature => print_upload_result(&result),
+        OutputFormat::Json => print_json(&result)?,
+    }
+    Ok(())
+}
+
+async fn run_migrate_command(wallet: &Wallet, args: MigrateArgs) -> Result<()> {
+    let MigrateArgs { mint, to } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    if output == OutputFormat::Text {
+        let from = match to {
+            MetadataLocation::Metaplex => "Token-2022 extension",
+            MetadataLocation::Token2022 => "Metaplex metadata",
+        };
+        let target = match to {
+            MetadataLocation::Metaplex => "Metaplex metadata",
+            MetadataLocation::Token2022 => "Token-2022 extension",
+        };
+        println!("Migrating metadata from {} to {}...", from, target);
+        println!("  Mint:         {}", mint_pubkey);
+    }
+    let result = client.migrate(payer, &mint_pubkey, to).await?;
+    emit(client, output, &result, print_migrate_result)?;
+    Ok(())
+}
+
+async fn run_resize_command(wallet: &Wallet, args: ResizeArgs) -> Result<()> {
+    let ResizeArgs { mint } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    if output == OutputFormat::Text {
+        println!("Resizing metadata...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+    }
+    let result = client.resize(payer, &mint_pubkey).await?;
+    emit(client, output, &result, print_resize_result)?;
+    Ok(())
+}
+
+async fn run_close_command(wallet: &Wallet, args: CloseArgs) -> Result<()> {
+    let CloseArgs { mint } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    if output == OutputFormat::Text {
+        println!("Closing accounts of burned mint...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Metadata PDA: {}", find_metadata_pda(&mint_pubkey));
+    }
+    wallet.confirmation.require(&format!(
+        "the metadata and edition accounts of {} will be closed for good.",
+        mint_pubkey
+    ))?;
+    let result = client.close(payer, &mint_pubkey).await?;
+    emit(client, output, &result, print_close_result)?;
+    Ok(())
+}
 
-    // Fetch existing metadata account to get current values
-    let metadata_account = client
-        .get_account_data(&metadata_pda)
-        .context("Failed to fetch metadata account. Does it exist?")?;
+async fn run_transfer_command(wallet: &Wallet, args: TransferArgs) -> Result<()> {
+    let TransferArgs {
+        mint,
+        to,
+        amount,
+        from,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    let destination = Pubkey::from_str(&to).context("Invalid destination address")?;
+    let owner = match from {
+        Some(from) => Pubkey::from_str(&from).context("Invalid owner address")?,
+        None => payer.pubkey(),
+    };
+    if output == OutputFormat::Text {
+        println!("Transferring...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  From:         {}", owner);
+        println!("  To:           {}", destination);
+    }
+    wallet.confirmation.require(&format!(
+        "the tokens of {} will belong to {}; only they can send them back.",
+        mint_pubkey, destination
+    ))?;
+    let result = client
+        .transfer(payer, &mint_pubkey, &owner, &destination, amount)
+        .await?;
+    emit(client, output, &result, print_transfer_result)?;
+    Ok(())
+}
 
-    // Parse existing metadata using borsh
-    // The metadata account has an offset; skip the first byte (key discriminator)
-    // and parse the rest. For simplicity we'll use mpl_token_metadata's deserialization.
-    use borsh::BorshDeserialize;
-    use mpl_token_metadata::accounts::Metadata;
+async fn run_use_command(wallet: &Wallet, args: UseArgs) -> Result<()> {
+    let UseArgs { mint, count, owner } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    let owner = match owner {
+        Some(owner) => Pubkey::from_str(&owner).context("Invalid owner address")?,
+        None => payer.pubkey(),
+    };
+    if output == OutputFormat::Text {
+        println!("Using token...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Owner:        {}", owner);
+        println!("  Uses:         {}", count);
+    }
+    wallet.confirmation.require(&format!(
+        "{} use(s) of {} will be spent, and spent uses can't be restored.",
+        count, mint_pubkey
+    ))?;
+    let result = client.utilize(payer, &mint_pubkey, &owner, count).await?;
+    emit(client, output, &result, print_use_result)?;
+    Ok(())
+}
 
-    let existing = Metadata::from_bytes(&metadata_account)
-        .map_err(|e| anyhow::anyhow!("Failed to deserialize metadata: {}", e))?;
+async fn run_lock_command(wallet: &Wallet, target: LockArgs) -> Result<()> {
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let (mint_pubkey, owner) = target.parse()?;
+    if output == OutputFormat::Text {
+        println!("Locking pNFT...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Owner:        {}", owner);
+    }
+    let result = client.lock(payer, &mint_pubkey, &owner).await?;
+    emit(client, output, &result, |result| {
+        print_lock_result("locked", result)
+    })?;
+    Ok(())
+}
 
-    let updated_name = name.unwrap_or(existing.name.clone());
-    let updated_symbol = symbol.unwrap_or(existing.symbol.clone());
-    let updated_uri = uri.unwrap_or(existing.uri.clone());
+async fn run_unlock_command(wallet: &Wallet, target: LockArgs) -> Result<()> {
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let (mint_pubkey, owner) = target.parse()?;
+    if output == OutputFormat::Text {
+        println!("Unlocking pNFT...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!("  Owner:        {}", owner);
+    }
+    let result = client.unlock(payer, &mint_pubkey, &owner).await?;
+    emit(client, output, &result, |result| {
+        print_lock_result("unlocked", result)
+    })?;
+    Ok(())
+}
 
-    println!("Updating metadata...");
-    println!("  Mint:         {}", mint);
-    println!("  Metadata PDA: {}", metadata_pda);
-    println!(
-        "  Name:         {} -> {}",
-        existing.name.trim_end_matches('\0'),
-        updated_name
-    );
-    println!(
-        "  Symbol:       {} -> {}",
-        existing.symbol.trim_end_matches('\0'),
-        updated_symbol
-    );
-    println!(
-        "  URI:          {} -> {}",
-        existing.uri.trim_end_matches('\0'),
-        updated_uri
-    );
-
-    let new_data = DataV2 {
-        name: updated_name,
-        symbol: updated_symbol,
-        uri: updated_uri,
-        seller_fee_basis_points: existing.seller_fee_basis_points,
-        creators: existing.creators,
-        collection: existing
-            .collection
-            .map(|c| mpl_token_metadata::types::Collection {
-                verified: c.verified,
-                key: c.key,
-            }),
-        uses: existing.uses.map(|u| mpl_token_metadata::types::Uses {
-            use_method: u.use_method,
-            remaining: u.remaining,
-            total: u.total,
-        }),
+async fn run_cmint_command(wallet: &Wallet, args: CmintArgs) -> Result<()> {
+    let CmintArgs {
+        tree,
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        mutable,
+        creators,
+        collection,
+        owner,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let tree = Pubkey::from_str(&tree).context("Invalid tree address")?;
+    let collection = collection
+        .map(|collection| Pubkey::from_str(&collection))
+        .transpose()
+        .context("Invalid collection address")?;
+    let owner = match owner {
+        Some(owner) => Pubkey::from_str(&owner).context("Invalid owner address")?,
+        None => payer.pubkey(),
+    };
+    let args = CreateMetadataArgs {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        is_mutable: mutable,
+        creators: (!creators.is_empty()).then_some(creators),
+        uses: None,
+        uri_hash: None,
+        update_authority: None,
     };
+    if output == OutputFormat::Text {
+        println!("Minting compressed NFT...");
+        println!("  Tree:         {}", tree);
+        println!("  Owner:        {}", owner);
+        println!("  Name:         {}", args.name);
+        println!("  Symbol:       {}", args.symbol);
+        println!("  URI:          {}", args.uri);
+        if let Some(collection) = &collection {
+            println!("  Collection:   {}", collection);
+        }
+    }
+    let result = client
+        .mint_compressed(payer, &tree, &owner, collection.as_ref(), args)
+        .await?;
+    emit(client, output, &result, print_compressed_mint_result)?;
+    Ok(())
+}
 
-    let ix = UpdateMetadataAccountV2Builder::new()
-        .metadata(metadata_pda)
-        .update_authority(payer.pubkey())
-        .data(new_data)
-        .instruction();
+async fn run_cupdate_command(wallet: &Wallet, args: CupdateArgs) -> Result<()> {
+    let CupdateArgs {
+        asset,
+        name,
+        symbol,
+        uri,
+    } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let asset = Pubkey::from_str(&asset).context("Invalid asset ID")?;
+    if name.is_none() && symbol.is_none() && uri.is_none() {
+        anyhow::bail!("Nothing to update; pass --name, --symbol or --uri");
+    }
+    if output == OutputFormat::Text {
+        println!("Updating compressed NFT...");
+        println!("  Asset ID:     {}", asset);
+    }
+    let result = client
+        .update_compressed(payer, &asset, name, symbol, uri)
+        .await?;
+    emit(client, output, &result, print_compressed_update_result)?;
+    Ok(())
+}
 
-    let recent_blockhash = client.get_latest_blockhash()?;
-    let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&payer.pubkey()),
-        &[payer],
-        recent_blockhash,
-    );
+async fn run_ctransfer_command(wallet: &Wallet, args: CtransferArgs) -> Result<()> {
+    let CtransferArgs { asset, to } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let asset = Pubkey::from_str(&asset).context("Invalid asset ID")?;
+    let to = Pubkey::from_str(&to).context("Invalid destination address")?;
+    if output == OutputFormat::Text {
+        println!("Transferring compressed NFT...");
+        println!("  Asset ID:     {}", asset);
+        println!("  To:           {}", to);
+    }
+    wallet.confirmation.require(&format!(
+        "compressed NFT {} will belong to {}; only they can send it back.",
+        asset, to
+    ))?;
+    let result = client.transfer_compressed(payer, &asset, &to).await?;
+    emit(client, output, &result, print_compressed_transfer_result)?;
+    Ok(())
+}
 
-    let signature = client
-        .send_and_confirm_transaction_with_spinner(&tx)
-        .context("Failed to send update metadata transaction")?;
+async fn run_cburn_command(wallet: &Wallet, args: CburnArgs) -> Result<()> {
+    let CburnArgs { asset } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let asset = Pubkey::from_str(&asset).context("Invalid asset ID")?;
+    if output == OutputFormat::Text {
+        println!("Burning compressed NFT...");
+        println!("  Asset ID:     {}", asset);
+    }
+    wallet.confirmation.require(&format!(
+        "burning compressed NFT {} is permanent; it can't be recovered.",
+        asset
+    ))?;
+    let result = client.burn_compressed(payer, &asset).await?;
+    emit(client, output, &result, print_compressed_burn_result)?;
+    Ok(())
+}
 
-    println!("\nMetadata updated successfully!");
-    println!("  Signature: {}", signature);
-    println!(
-        "  Explorer:  https://explorer.solana.com/tx/{}?cluster=devnet",
-        signature
-    );
+async fn run_decompress_command(wallet: &Wallet, args: DecompressArgs) -> Result<()> {
+    let DecompressArgs { asset } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let asset = Pubkey::from_str(&asset).context("Invalid asset ID")?;
+    if output == OutputFormat::Text {
+        println!("Decompressing compressed NFT...");
+        println!("  Asset ID:     {}", asset);
+    }
+    wallet.confirmation.require(&format!(
+        "compressed NFT {} will leave its tree for good; a decompressed NFT can't be \
+             compressed again.",
+        asset
+    ))?;
+    let result = client.decompress(payer, &asset).await?;
+    emit(client, output, &result, print_decompress_result)?;
+    Ok(())
+}
 
+async fn run_inscribe_command(wallet: &Wallet, args: InscribeArgs) -> Result<()> {
+    let InscribeArgs { mint, file } = args;
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+    let data = match &file {
+        Some(file) => {
+            std::fs::read(file).with_context(|| format!("Failed to read '{}'", file.display()))?
+        }
+        None => {
+            let metadata = client.fetch(&mint_pubkey).await?;
+            let uri = metadata.uri.trim_end_matches('\0');
+            if uri.is_empty() {
+                anyhow::bail!("The metadata URI is empty; pass --file to inscribe a file");
+            }
+            serde_json::to_vec(&load_json(uri, HTTP_TIMEOUT).await?)?
+        }
+    };
+    if output == OutputFormat::Text {
+        println!("Inscribing...");
+        println!("  Mint:         {}", mint_pubkey);
+        println!(
+            "  Inscription:  {}",
+            find_mint_inscription_pda(&mint_pubkey)
+        );
+        println!("  Size:         {} bytes", data.len());
+    }
+    let result = client.inscribe(payer, &mint_pubkey, &data).await?;
+    emit(client, output, &result, print_inscribe_result)?;
+    Ok(())
+}
+
+async fn run_shadow_command(wallet: &Wallet, command: ShadowCommands) -> Result<()> {
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    match command {
+        ShadowCommands::Create { name, size, owner } => {
+            let owner = owner.as_deref().map(load_keypair).transpose()?;
+            let owner: &dyn Signer = owner.as_ref().unwrap_or(payer);
+            if output == OutputFormat::Text {
+                println!("Creating Shadow Drive storage account...");
+                println!("  Name:  {}", name);
+                println!("  Owner: {}", owner.pubkey());
+                println!("  Size:  {} bytes", size);
+            }
+            let result = client
+                .create_shadow_storage_account(owner, &name, size)
+                .await?;
+            emit(client, output, &result, print_shadow_storage_result)?;
+        }
+    }
     Ok(())
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn run_tree_command(wallet: &Wallet, command: TreeCommands) -> Result<()> {
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    match command {
+        TreeCommands::Create {
+            max_depth,
+            max_buffer_size,
+            canopy_depth,
+            public,
+        } => {
+            check_tree_shape(max_depth, max_buffer_size, canopy_depth)?;
+            let tree = Keypair::new();
+            if output == OutputFormat::Text {
+                println!("Creating Merkle tree...");
+                println!("  Tree:         {}", tree.pubkey());
+                println!("  Max depth:    {}", max_depth);
+                println!("  Buffer size:  {}", max_buffer_size);
+                println!("  Canopy depth: {}", canopy_depth);
+                println!("  Capacity:     {} cNFTs", tree_capacity(max_depth));
+                println!(
+                    "  Account size: {} bytes",
+                    tree_account_size(max_depth, max_buffer_size, canopy_depth)
+                );
+            }
+            let result = client
+                .create_tree(
+                    payer,
+                    &tree,
+                    max_depth,
+                    max_buffer_size,
+                    canopy_depth,
+                    public,
+                )
+                .await?;
+            emit(client, output, &result, print_create_tree_result)?;
+        }
+    }
+    Ok(())
+}
 
-    let payer = load_keypair(&cli.keypair)?;
-    let client = RpcClient::new_with_commitment(&cli.url, CommitmentConfig::confirmed());
+async fn run_delegate_command(wallet: &Wallet, command: DelegateCommands) -> Result<()> {
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    match command {
+        DelegateCommands::Approve { target, amount } => {
+            let (mint_pubkey, delegate) = target.parse()?;
+            if output == OutputFormat::Text {
+                println!("Approving {} delegate...", target.role);
+                println!("  Mint:         {}", mint_pubkey);
+                println!("  Delegate:     {}", delegate);
+            }
+            let result = if target.role.is_token_role() {
+                client
+                    .approve_token_delegate(payer, &mint_pubkey, target.role, &delegate, amount)
+                    .await?
+            } else {
+                client
+                    .approve_metadata_delegate(payer, &mint_pubkey, target.role, &delegate)
+                    .await?
+            };
+            emit(client, output, &result, |result| {
+                print_delegate_result("approved", result)
+            })?;
+        }
+        DelegateCommands::Revoke { target } => {
+            let (mint_pubkey, delegate) = target.parse()?;
+            if output == OutputFormat::Text {
+                println!("Revoking {} delegate...", target.role);
+                println!("  Mint:         {}", mint_pubkey);
+                println!("  Delegate:     {}", delegate);
+            }
+            let result = if target.role.is_token_role() {
+                client
+                    .revoke_token_delegate(payer, &mint_pubkey, target.role, &delegate)
+                    .await?
+            } else {
+                client
+                    .revoke_metadata_delegate(payer, &mint_pubkey, target.role, &delegate)
+                    .await?
+            };
+            emit(client, output, &result, |result| {
+                print_delegate_result("revoked", result)
+            })?;
+        }
+    }
+    Ok(())
+}
 
-    println!("Using RPC:    {}", cli.url);
-    println!("Using wallet: {}\n", payer.pubkey());
+async fn run_batch_command(wallet: &Wallet, command: BatchCommands) -> Result<()> {
+    let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+    match command {
+        BatchCommands::Create {
+            manifest,
+            concurrency,
+        } => {
+            let entries = load_manifest(&manifest)?;
+            if output == OutputFormat::Text {
+                println!(
+                    "Creating metadata for {} mints from {} (concurrency {})...",
+                    entries.len(),
+                    manifest.display(),
+                    concurrency
+                );
+            }
+            let result = client.batch_create(payer, &entries, concurrency).await;
+            emit(client, output, &result, print_batch_result)?;
+            if result.failed > 0 {
+                anyhow::bail!("{} of {} rows failed", result.failed, result.total);
+            }
+        }
+        BatchCommands::Estimate { manifest, uploads } => {
+            let entries = load_manifest(&manifest)?;
+            if output == OutputFormat::Text {
+                println!(
+                    "Estimating the cost of creating metadata for {} mints from {}...",
+                    entries.len(),
+                    manifest.display()
+                );
+            }
+            let estimate = client
+                .estimate_batch_create(&payer.pubkey(), &entries, &uploads)
+                .await?;
+            match output {
+                OutputFormat::Text | OutputFormat::Signature => print_batch_estimate(&estimate),
+                OutputFormat::Json => print_json(&estimate)?,
+            }
+        }
+        BatchCommands::Update { file, concurrency } => {
+            let entries = load_updates(&file)?;
+            if output == OutputFormat::Text {
+                println!(
+                    "Updating metadata for {} mints from {} (concurrency {})...",
+                    entries.len(),
+                    file.display(),
+                    concurrency
+                );
+            }
+            let result = client.batch_update(payer, &entries, concurrency).await;
+            emit(client, output, &result, print_batch_result)?;
+            if result.failed > 0 {
+                anyhow::bail!("{} of {} entries failed", result.failed, result.total);
+            }
+        }
+        BatchCommands::Resize { file, concurrency } => {
+            let mints = load_mints(&file)?;
+            if output == OutputFormat::Text {
+                println!(
+                    "Resizing metadata for {} mints from {} (concurrency {})...",
+                    mints.len(),
+                    file.display(),
+                    concurrency
+                );
+            }
+            let result = client.batch_resize(payer, &mints, concurrency).await;
+            emit(client, output, &result, print_batch_result)?;
+            if result.failed > 0 {
+                anyhow::bail!("{} of {} mints failed", result.failed, result.total);
+            }
+        }
+        BatchCommands::Close { file, concurrency } => {
+            let mints = load_mints(&file)?;
+            if output == OutputFormat::Text {
+                println!(
+                    "Closing accounts of {} mints from {} (concurrency {})...",
+                    mints.len(),
+                    file.display(),
+                    concurrency
+                );
+            }
+            wallet.confirmation.require(&format!(
+                "the metadata and edition accounts of {} mints will be closed for good.",
+                mints.len()
+            ))?;
+            let result = client.batch_close(payer, &mints, concurrency).await;
+            emit(client, output, &result, print_batch_result)?;
+            if result.failed > 0 {
+                anyhow::bail!("{} of {} mints failed", result.failed, result.total);
+            }
+        }
+        BatchCommands::Cmint {
+            tree,
+            manifest,
+            collection,
+            concurrency,
+        } => {
+            let tree = Pubkey::from_str(&tree).context("Invalid tree address")?;
+            let collection = collection
+                .map(|collection| Pubkey::from_str(&collection))
+                .transpose()
+                .context("Invalid collection address")?;
+            let entries = load_compressed_manifest(&manifest)?;
+            if output == OutputFormat::Text {
+                println!(
+                    "Minting {} compressed NFTs into {} from {} (concurrency {})...",
+                    entries.len(),
+                    tree,
+                    manifest.display(),
+                    concurrency
+                );
+            }
+            let result = client
+                .batch_mint_compressed(payer, &tree, collection.as_ref(), &entries, concurrency)
+                .await;
+            emit(client, output, &result, print_batch_result)?;
+            if result.failed > 0 {
+                anyhow::bail!("{} of {} rows failed", result.failed, result.total);
+            }
+        }
+    }
+    Ok(())
+}
 
-    match cli.command {
-        Commands::Create {
+async fn run_token2022_command(app: &mut App, command: Token2022Commands) -> Result<()> {
+    match command {
+        Token2022Commands::Create {
             mint,
             name,
             symbol,
             uri,
-            mutable,
-            seller_fee_basis_points,
+            fields,
         } => {
+            let wallet = app.wallet()?;
+            let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
             let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
-            create_metadata(
-                &client,
-                &payer,
-                &mint_pubkey,
+            let args = Token2022MetadataArgs {
                 name,
                 symbol,
                 uri,
-                seller_fee_basis_points,
-                mutable,
-            )?;
+                additional_metadata: fields,
+                uri_hash: None,
+            };
+            if output == OutputFormat::Text {
+                print_token2022_create_preview(&mint_pubkey, &args);
+            }
+            let result = client
+                .create_token2022_metadata(payer, &mint_pubkey, args)
+                .await?;
+            emit(client, output, &result, print_token2022_create_result)?;
         }
-        Commands::Update {
+        Token2022Commands::Update {
             mint,
             name,
             symbol,
             uri,
+            fields,
+            remove_fields,
         } => {
+            let wallet = app.wallet()?;
+            let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
             let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
-            update_metadata(&client, &payer, &mint_pubkey, name, symbol, uri)?;
+            if output == OutputFormat::Text {
+                println!("Updating Token-2022 metadata...");
+                println!("  Mint:         {}", mint_pubkey);
+            }
+            let args = Token2022UpdateArgs {
+                name,
+                symbol,
+                uri,
+                set_fields: fields,
+                remove_fields,
+                uri_hash: None,
+            };
+            let result = client
+                .update_token2022_metadata(payer, &mint_pubkey, args)
+                .await?;
+            emit(client, output, &result, print_token2022_update_result)?;
+        }
+        Token2022Commands::Show { mint } => {
+            let session = app.session()?;
+            let (client, output) = (&session.client, session.output);
+            if output == OutputFormat::Text {
+                println!("Using RPC:    {}\n", session.rpc);
+            }
+            let mint_pubkey = Pubkey::from_str(&mint).context("Invalid mint address")?;
+            let info =
+                Token2022MetadataInfo::from(&client.fetch_token2022_metadata(&mint_pubkey).await?);
+            match output {
+                OutputFormat::Text | OutputFormat::Signature => print_token2022_metadata(&info),
+                OutputFormat::Json => print_json(&info)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_core_command(app: &mut App, command: CoreCommands) -> Result<()> {
+    match command {
+        CoreCommands::Create {
+            name,
+            uri,
+            owner,
+            collection,
+        } => {
+            let wallet = app.wallet()?;
+            let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+            let owner = owner
+                .map(|owner| Pubkey::from_str(&owner).context("Invalid owner address"))
+                .transpose()?;
+            let collection = collection
+                .map(|collection| {
+                    Pubkey::from_str(&collection).context("Invalid collection address")
+                })
+                .transpose()?;
+            let asset = Keypair::new();
+            if output == OutputFormat::Text {
+                println!("Creating Core asset...");
+                println!("  Asset:        {}", asset.pubkey());
+                println!("  Name:         {}", name);
+                println!("  URI:          {}", uri);
+                println!("  Owner:        {}", owner.unwrap_or(payer.pubkey()));
+                if let Some(collection) = &collection {
+                    println!("  Collection:   {}", collection);
+                }
+            }
+            let result = client
+                .create_core_asset(
+                    payer,
+                    &asset,
+                    name,
+                    uri,
+                    owner.as_ref(),
+                    collection.as_ref(),
+                )
+                .await?;
+            emit(client, output, &result, print_core_create_result)?;
+        }
+        CoreCommands::Update { asset, name, uri } => {
+            let wallet = app.wallet()?;
+            let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+            let asset = Pubkey::from_str(&asset).context("Invalid asset address")?;
+            if name.is_none() && uri.is_none() {
+                anyhow::bail!("Nothing to update; pass --name or --uri");
+            }
+            if output == OutputFormat::Text {
+                println!("Updating Core asset...");
+                println!("  Asset:        {}", asset);
+            }
+            let result = client.update_core_asset(payer, &asset, name, uri).await?;
+            emit(client, output, &result, print_core_update_result)?;
+        }
+        CoreCommands::Burn { asset } => {
+            let wallet = app.wallet()?;
+            let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+            let asset = Pubkey::from_str(&asset).context("Invalid asset address")?;
+            if output == OutputFormat::Text {
+                println!("Burning Core asset...");
+                println!("  Asset:        {}", asset);
+            }
+            wallet.confirmation.require(&format!(
+                "burning Core asset {} is permanent; it can't be recovered.",
+                asset
+            ))?;
+            let result = client.burn_core_asset(payer, &asset).await?;
+            emit(client, output, &result, print_core_burn_result)?;
+        }
+        CoreCommands::Royalties {
+            asset,
+            collection,
+            seller_fee_basis_points,
+            creators,
+        } => {
+            let wallet = app.wallet()?;
+            let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+            let target = core_target(asset, collection)?;
+            let plugin = CorePlugin::Royalties {
+                basis_points: seller_fee_basis_points,
+                creators,
+            };
+            print_core_plugin_preview(output, &target, &plugin);
+            let result = client.set_core_plugin(payer, &target, plugin).await?;
+            emit(client, output, &result, print_core_plugin_result)?;
+        }
+        CoreCommands::Attributes {
+            asset,
+            collection,
+            attributes,
+            remove_attributes,
+        } => {
+            let wallet = app.wallet()?;
+            let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+            if attributes.is_empty() && remove_attributes.is_empty() {
+                anyhow::bail!("Nothing to change; pass --attribute or --remove-attribute");
+            }
+            let target = core_target(asset, collection)?;
+            let plugin = CorePlugin::Attributes {
+                set: attributes,
+                remove: remove_attributes,
+            };
+            print_core_plugin_preview(output, &target, &plugin);
+            let result = client.set_core_plugin(payer, &target, plugin).await?;
+            emit(client, output, &result, print_core_plugin_result)?;
+        }
+        CoreCommands::FreezeDelegate {
+            asset,
+            delegate,
+            frozen,
+        } => {
+            let wallet = app.wallet()?;
+            let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+            let target = core_target(Some(asset), None)?;
+            let delegate = delegate
+                .map(|delegate| Pubkey::from_str(&delegate).context("Invalid delegate address"))
+                .transpose()?;
+            let plugin = CorePlugin::FreezeDelegate { delegate, frozen };
+            print_core_plugin_preview(output, &target, &plugin);
+            let result = client.set_core_plugin(payer, &target, plugin).await?;
+            emit(client, output, &result, print_core_plugin_result)?;
+        }
+        CoreCommands::TransferDelegate { asset, delegate } => {
+            let wallet = app.wallet()?;
+            let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+            let target = core_target(Some(asset), None)?;
+            let delegate = Pubkey::from_str(&delegate).context("Invalid delegate address")?;
+            let plugin = CorePlugin::TransferDelegate { delegate };
+            print_core_plugin_preview(output, &target, &plugin);
+            let result = client.set_core_plugin(payer, &target, plugin).await?;
+            emit(client, output, &result, print_core_plugin_result)?;
+        }
+        CoreCommands::Show { asset } => {
+            let session = app.session()?;
+            let (client, output) = (&session.client, session.output);
+            if output == OutputFormat::Text {
+                println!("Using RPC:    {}\n", session.rpc);
+            }
+            let asset = Pubkey::from_str(&asset).context("Invalid asset address")?;
+            let info = client.core_asset_info(&asset).await?;
+            match output {
+                OutputFormat::Text | OutputFormat::Signature => print_core_asset(&info),
+                OutputFormat::Json => print_json(&info)?,
+            }
         }
     }
+    Ok(())
+}
 
+async fn run_ruleset_command(app: &mut App, command: RulesetCommands) -> Result<()> {
+    match command {
+        RulesetCommands::Create { rules } => {
+            let wallet = app.wallet()?;
+            let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+            let rule_set = rules.build(payer.pubkey())?;
+            if output == OutputFormat::Text {
+                print_rule_set_preview("Creating", &rule_set);
+            }
+            let result = client.create_rule_set(payer, &rule_set).await?;
+            emit(client, output, &result, print_rule_set_result)?;
+        }
+        RulesetCommands::Update { rules } => {
+            let wallet = app.wallet()?;
+            let (client, payer, output) = (&wallet.client, &wallet.payer, wallet.output);
+            let rule_set = rules.build(payer.pubkey())?;
+            if output == OutputFormat::Text {
+                print_rule_set_preview("Updating", &rule_set);
+            }
+            let result = client.update_rule_set(payer, &rule_set).await?;
+            emit(client, output, &result, print_rule_set_result)?;
+        }
+        RulesetCommands::Show {
+            name,
+            owner,
+            address,
+        } => {
+            let session = app.session()?;
+            let (client, output) = (&session.client, session.output);
+            let address = match (address, name, owner) {
+                (Some(address), _, _) => {
+                    Pubkey::from_str(&address).context("Invalid rule set address")?
+                }
+                (None, Some(name), Some(owner)) => {
+                    let owner = Pubkey::from_str(&owner).context("Invalid owner address")?;
+                    find_rule_set_pda(&owner, &name)
+                }
+                _ => unreachable!("clap requires --address or --name and --owner"),
+            };
+            let info = client.fetch_rule_set(&address).await?;
+            match output {
+                OutputFormat::Text | OutputFormat::Signature => print_rule_set_info(&info)?,
+                OutputFormat::Json => print_json(&info)?,
+            }
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,24 @@
+//! SPL Memo instructions, for leaving a note in a transaction.
+
+use anyhow::Result;
+use solana_sdk::instruction::Instruction;
+
+use crate::types::UriHash;
+
+/// An instruction recording `memo` in the transaction's logs. It needs no
+/// signers, so it can go in any transaction.
+pub(crate) fn memo_instruction(memo: &str) -> Instruction {
+    spl_memo::build_memo(memo.as_bytes(), &[])
+}
+
+/// The memo instruction recording `hash`, checking it is the hash of `uri`
+pub(crate) fn uri_hash_instruction(hash: &UriHash, uri: &str) -> Result<Instruction> {
+    if hash.uri != uri {
+        anyhow::bail!(
+            "The URI hash is of '{}', not of the URI being written ('{}')",
+            hash.uri,
+            uri
+        );
+    }
+    Ok(memo_instruction(&hash.memo()))
+}
@@ -0,0 +1,138 @@
+//! Copying metadata between a Metaplex metadata account and the Token-2022
+//! TokenMetadata extension of the same mint.
+
+use anyhow::Result;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use crate::client::MetadataClient;
+use crate::error::MetadataCliError;
+use crate::types::{
+    CreateMetadataArgs, MetadataLocation, MigrateAction, MigrateResult, Token2022MetadataArgs,
+    Token2022UpdateArgs, UpdateMetadataArgs,
+};
+
+impl MetadataClient {
+    /// Write the name, symbol and URI from one metadata location of a
+    /// Token-2022 mint to the other, creating the target metadata if it
+    /// doesn't exist yet. Nothing is sent when the target already matches.
+    pub async fn migrate(
+        &self,
+        payer: &dyn Signer,
+        mint: &Pubkey,
+        to: MetadataLocation,
+    ) -> Result<MigrateResult> {
+        let mint_account = self.fetch_mint(mint).await?;
+        if !mint_account.stores_own_metadata() {
+            anyhow::bail!(
+                "Mint {} is not a Token-2022 mint whose metadata pointer points at itself",
+                mint
+            );
+        }
+
+        let (name, symbol, uri, dropped_fields) = match to {
+            MetadataLocation::Token2022 => {
+                let source = self.fetch_fresh(mint).await?;
+                (
+                    source.name.trim_end_matches('\0').to_string(),
+                    source.symbol.trim_end_matches('\0').to_string(),
+                    source.uri.trim_end_matches('\0').to_string(),
+                    Vec::new(),
+                )
+            }
+            MetadataLocation::Metaplex => {
+                let source = self.fetch_token2022_metadata(mint).await?;
+                let dropped = source
+                    .additional_metadata
+                    .iter()
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                (source.name, source.symbol, source.uri, dropped)
+            }
+        };
+
+        let (action, signature) = match to {
+            MetadataLocation::Token2022 => match self.fetch_token2022_metadata(mint).await {
+                Ok(target)
+                    if (&target.name, &target.symbol, &target.uri) == (&name, &symbol, &uri) =>
+                {
+                    (MigrateAction::Unchanged, None)
+                }
+                Ok(_) => {
+                    let args = Token2022UpdateArgs {
+                        name: Some(name.clone()),
+                        symbol: Some(symbol.clone()),
+                        uri: Some(uri.clone()),
+                        ..Default::default()
+                    };
+                    let result = self.update_token2022_metadata(payer, mint, args).await?;
+                    (MigrateAction::Updated, Some(result.signature))
+                }
+                Err(_) => {
+                    let args = Token2022MetadataArgs {
+                        name: name.clone(),
+                        symbol: symbol.clone(),
+                        uri: uri.clone(),
+                        additional_metadata: Vec::new(),
+                        uri_hash: None,
+                    };
+                    let result = self.create_token2022_metadata(payer, mint, args).await?;
+                    (MigrateAction::Created, Some(result.signature))
+                }
+            },
+            MetadataLocation::Metaplex => match self.fetch_fresh(mint).await {
+                Ok(target)
+                    if target.name.trim_end_matches('\0') == name
+                        && target.symbol.trim_end_matches('\0') == symbol
+                        && target.uri.trim_end_matches('\0') == uri =>
+                {
+                    (MigrateAction::Unchanged, None)
+                }
+                Ok(_) => {
+                    let args = UpdateMetadataArgs {
+                        name: Some(name.clone()),
+                        symbol: Some(symbol.clone()),
+                        uri: Some(uri.clone()),
+                        creators: None,
+                        uses: None,
+                        primary_sale_happened: false,
+                        uri_hash: None,
+                    };
+                    let result = self.update(payer, mint, args).await?;
+                    (MigrateAction::Updated, Some(result.signature))
+                }
+                Err(err)
+                    if matches!(
+                        err.downcast_ref(),
+                        Some(MetadataCliError::AccountNotFound { .. })
+                    ) =>
+                {
+                    let args = CreateMetadataArgs {
+                        name: name.clone(),
+                        symbol: symbol.clone(),
+                        uri: uri.clone(),
+                        seller_fee_basis_points: 0,
+                        is_mutable: true,
+                        creators: None,
+                        uses: None,
+                        uri_hash: None,
+                        update_authority: None,
+                    };
+                    let result = self.create(payer, mint, args).await?;
+                    (MigrateAction::Created, Some(result.signature))
+                }
+                Err(err) => return Err(err),
+            },
+        };
+
+        Ok(MigrateResult {
+            mint: mint.to_string(),
+            to,
+            action,
+            name,
+            symbol,
+            uri,
+            dropped_fields,
+            signature,
+        })
+    }
+}
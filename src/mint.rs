@@ -0,0 +1,255 @@
+//! SPL Token mint creation helpers shared by the commands that create new mints.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::instructions::CreateMetadataAccountV3Builder;
+use solana_sdk::{
+    instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+    signature::Signer,
+};
+use spl_associated_token_account_client::{
+    address::get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+
+use spl_token_2022::extension::{
+    BaseStateWithExtensions, StateWithExtensions, metadata_pointer::MetadataPointer,
+};
+use spl_token_2022::state::Mint;
+
+use crate::client::{MetadataClient, data_from_args};
+use crate::error::MetadataCliError;
+use crate::pda::find_metadata_pda;
+use crate::types::{
+    CreateMetadataArgs, CreateMintResult, CreateTokenResult, MintAccount, TokenProgram,
+};
+
+impl MetadataClient {
+    /// Fetch and unpack a mint account owned by either SPL Token or
+    /// Token-2022, recording which program owns it
+    pub async fn fetch_mint(&self, mint: &Pubkey) -> Result<MintAccount> {
+        let account = self
+            .rpc()
+            .get_account_with_commitment(mint, self.rpc().commitment())
+            .await
+            .map_err(MetadataCliError::rpc("fetch mint account"))?
+            .value
+            .ok_or(MetadataCliError::AccountNotFound {
+                kind: "Mint",
+                address: *mint,
+            })?;
+        let program = if account.owner == spl_token::id() {
+            TokenProgram::SplToken
+        } else if account.owner == spl_token_2022::id() {
+            TokenProgram::Token2022
+        } else {
+            anyhow::bail!("{} is not a token mint (owned by {})", mint, account.owner);
+        };
+        // Token-2022 mints share the SPL Token layout and append extensions
+        let state = StateWithExtensions::<Mint>::unpack(&account.data).map_err(|err| {
+            MetadataCliError::InvalidAccountData {
+                kind: "mint",
+                address: *mint,
+                reason: if err == ProgramError::UninitializedAccount {
+                    "it isn't initialized".to_string()
+                } else {
+                    format!("{} ({})", err, program)
+                },
+            }
+        })?;
+        let metadata_pointer = state
+            .get_extension::<MetadataPointer>()
+            .ok()
+            .and_then(|pointer| Option::<Pubkey>::from(pointer.metadata_address));
+        Ok(MintAccount {
+            address: *mint,
+            program,
+            mint_authority: state.base.mint_authority.into(),
+            supply: state.base.supply,
+            decimals: state.base.decimals,
+            metadata_pointer,
+        })
+    }
+
+    /// Create a new SPL Token mint in one transaction, with the payer as
+    /// mint authority so metadata can be created for it next, optionally
+    /// minting `initial_supply` base units to `owner`'s associated token
+    /// account. With no `freeze_authority`, token accounts can never be
+    /// frozen.
+    pub async fn create_mint(
+        &self,
+        payer: &dyn Signer,
+        mint: &dyn Signer,
+        decimals: u8,
+        freeze_authority: Option<&Pubkey>,
+        initial_supply: u64,
+        owner: &Pubkey,
+    ) -> Result<CreateMintResult> {
+        let payer_pubkey = payer.pubkey();
+        let mint_pubkey = mint.pubkey();
+
+        let mut instructions = self
+            .create_mint_instructions(
+                &payer_pubkey,
+                &mint_pubkey,
+                &payer_pubkey,
+                freeze_authority,
+                decimals,
+            )
+            .await?;
+
+        let token_account = if initial_supply > 0 {
+            let (token_account, mint_to) = mint_to_owner_instructions(
+                &self.rent_payer(&payer_pubkey),
+                &mint_pubkey,
+                &payer_pubkey,
+                owner,
+                initial_supply,
+            )?;
+            instructions.extend(mint_to);
+            Some(token_account)
+        } else {
+            None
+        };
+
+        let signature = self
+            .send(&instructions, payer, &[mint])
+            .await
+            .context("Failed to send create mint transaction")?;
+
+        Ok(CreateMintResult {
+            mint: mint_pubkey.to_string(),
+            decimals,
+            mint_authority: payer_pubkey.to_string(),
+            freeze_authority: freeze_authority.map(|a| a.to_string()),
+            initial_supply,
+            token_account: token_account.map(|a| a.to_string()),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Create a new fungible token in one transaction: allocate and
+    /// initialize the mint (with the payer as mint authority and no freeze
+    /// authority), optionally mint `initial_supply` base units to the payer's
+    /// associated token account, and create the token's metadata.
+    pub async fn create_token(
+        &self,
+        payer: &dyn Signer,
+        mint: &dyn Signer,
+        decimals: u8,
+        initial_supply: u64,
+        args: CreateMetadataArgs,
+    ) -> Result<CreateTokenResult> {
+        let payer_pubkey = payer.pubkey();
+        let mint_pubkey = mint.pubkey();
+        let metadata_pda = find_metadata_pda(&mint_pubkey);
+
+        let data = data_from_args(&args, &payer_pubkey)?;
+        let update_authority = args.update_authority.unwrap_or(payer_pubkey);
+
+        let mut instructions = self
+            .create_mint_instructions(&payer_pubkey, &mint_pubkey, &payer_pubkey, None, decimals)
+            .await?;
+
+        let token_account = if initial_supply > 0 {
+            let (token_account, mint_to) = mint_to_owner_instructions(
+                &self.rent_payer(&payer_pubkey),
+                &mint_pubkey,
+                &payer_pubkey,
+                &payer_pubkey,
+                initial_supply,
+            )?;
+            instructions.extend(mint_to);
+            Some(token_account)
+        } else {
+            None
+        };
+
+        instructions.push(
+            CreateMetadataAccountV3Builder::new()
+                .metadata(metadata_pda)
+                .mint(mint_pubkey)
+                .mint_authority(payer_pubkey)
+                .payer(self.rent_payer(&payer_pubkey))
+                .update_authority(update_authority, update_authority == payer_pubkey)
+                .data(data)
+                .is_mutable(args.is_mutable)
+                .instruction(),
+        );
+
+        let signature = self
+            .send(&instructions, payer, &[mint])
+            .await
+            .context("Failed to send create token transaction")?;
+
+        Ok(CreateTokenResult {
+            mint: mint_pubkey.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            decimals,
+            initial_supply,
+            token_account: token_account.map(|a| a.to_string()),
+            name: args.name,
+            symbol: args.symbol,
+            uri: args.uri,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Instructions that allocate a new mint account and initialize it. The
+    /// mint keypair must sign the transaction.
+    pub(crate) async fn create_mint_instructions(
+        &self,
+        payer: &Pubkey,
+        mint: &Pubkey,
+        mint_authority: &Pubkey,
+        freeze_authority: Option<&Pubkey>,
+        decimals: u8,
+    ) -> Result<Vec<Instruction>> {
+        let space = spl_token::state::Mint::LEN;
+        let lamports = self
+            .rpc()
+            .get_minimum_balance_for_rent_exemption(space)
+            .await?;
+
+        Ok(vec![
+            solana_system_interface::instruction::create_account(
+                &self.rent_payer(payer),
+                mint,
+                lamports,
+                space as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                mint,
+                mint_authority,
+                freeze_authority,
+                decimals,
+            )?,
+        ])
+    }
+}
+
+/// Instructions that create `owner`'s associated token account (if missing)
+/// and mint `amount` base units into it. Returns the token account address.
+pub(crate) fn mint_to_owner_instructions(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Result<(Pubkey, Vec<Instruction>)> {
+    let token_account = get_associated_token_address(owner, mint);
+
+    let instructions = vec![
+        create_associated_token_account_idempotent(payer, owner, mint, &spl_token::id()),
+        spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint,
+            &token_account,
+            mint_authority,
+            &[],
+            amount,
+        )?,
+    ];
+
+    Ok((token_account, instructions))
+}
@@ -0,0 +1,219 @@
+//! End-to-end NFT creation.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{
+    instructions::{
+        CreateMasterEditionV3Builder, CreateMetadataAccountV3Builder, CreateV1Builder,
+        MintV1Builder,
+    },
+    types::{CollectionDetails, PrintSupply, TokenStandard},
+};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account_client::address::get_associated_token_address;
+
+use crate::client::{MetadataClient, data_from_args};
+use crate::mint::mint_to_owner_instructions;
+use crate::pda::{find_master_edition_pda, find_metadata_pda, find_token_record_pda};
+use crate::ruleset::TOKEN_AUTH_RULES_PROGRAM_ID;
+use crate::types::{CreateMetadataArgs, CreateNftResult};
+
+impl MetadataClient {
+    /// Create a new NFT in one transaction: a 0-decimal mint, one token in the
+    /// payer's associated token account, the metadata and the master edition.
+    /// The transaction is atomic, so on failure nothing is left behind.
+    /// `max_supply` limits how many editions can be printed (`None` means
+    /// unlimited).
+    pub async fn create_nft(
+        &self,
+        payer: &dyn Signer,
+        mint: &dyn Signer,
+        args: CreateMetadataArgs,
+        max_supply: Option<u64>,
+    ) -> Result<CreateNftResult> {
+        let mint_pubkey = mint.pubkey();
+        let metadata_pda = find_metadata_pda(&mint_pubkey);
+        let master_edition_pda = find_master_edition_pda(&mint_pubkey);
+
+        let (token_account, instructions) = self
+            .nft_instructions(&payer.pubkey(), &mint_pubkey, &args, None, max_supply)
+            .await?;
+
+        let signature = self.send(&instructions, payer, &[mint]).await.context(
+            "Failed to send create NFT transaction; no accounts were created and the mint \
+             address is unused",
+        )?;
+
+        Ok(CreateNftResult {
+            mint: mint_pubkey.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            master_edition_pda: master_edition_pda.to_string(),
+            token_account: token_account.to_string(),
+            name: args.name,
+            symbol: args.symbol,
+            uri: args.uri,
+            max_supply,
+            token_record: None,
+            rule_set: None,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Create a new programmable NFT in one transaction with CreateV1 and
+    /// MintV1: the mint, metadata and master edition, then one token in the
+    /// payer's associated token account together with its token record.
+    /// Transfers are checked against `rule_set` when one is given, so
+    /// royalties can be enforced.
+    pub async fn create_programmable_nft(
+        &self,
+        payer: &dyn Signer,
+        mint: &dyn Signer,
+        args: CreateMetadataArgs,
+        rule_set: Option<Pubkey>,
+        max_supply: Option<u64>,
+    ) -> Result<CreateNftResult> {
+        let payer_pubkey = payer.pubkey();
+        let mint_pubkey = mint.pubkey();
+        let metadata_pda = find_metadata_pda(&mint_pubkey);
+        let master_edition_pda = find_master_edition_pda(&mint_pubkey);
+        let token_account = get_associated_token_address(&payer_pubkey, &mint_pubkey);
+        let token_record = find_token_record_pda(&mint_pubkey, &token_account);
+
+        ensure_signer_update_authority(&args)?;
+        let data = data_from_args(&args, &payer_pubkey)?;
+
+        let mut create = CreateV1Builder::new();
+        create
+            .metadata(metadata_pda)
+            .master_edition(Some(master_edition_pda))
+            .mint(mint_pubkey, true)
+            .authority(payer_pubkey)
+            .payer(self.rent_payer(&payer_pubkey))
+            .update_authority(payer_pubkey, true)
+            .spl_token_program(Some(spl_token::id()))
+            .name(data.name)
+            .symbol(data.symbol)
+            .uri(data.uri)
+            .seller_fee_basis_points(data.seller_fee_basis_points)
+            .is_mutable(args.is_mutable)
+            .token_standard(TokenStandard::ProgrammableNonFungible)
+            .print_supply(match max_supply {
+                None => PrintSupply::Unlimited,
+                Some(0) => PrintSupply::Zero,
+                Some(max_supply) => PrintSupply::Limited(max_supply),
+            });
+        if let Some(creators) = data.creators {
+            create.creators(creators);
+        }
+        if let Some(uses) = data.uses {
+            create.uses(uses);
+        }
+        if let Some(rule_set) = rule_set {
+            create.rule_set(rule_set);
+        }
+
+        let mint_to = MintV1Builder::new()
+            .token(token_account)
+            .token_owner(Some(payer_pubkey))
+            .metadata(metadata_pda)
+            .master_edition(Some(master_edition_pda))
+            .token_record(Some(token_record))
+            .mint(mint_pubkey)
+            .authority(payer_pubkey)
+            .payer(self.rent_payer(&payer_pubkey))
+            .spl_token_program(spl_token::id())
+            .authorization_rules_program(rule_set.map(|_| TOKEN_AUTH_RULES_PROGRAM_ID))
+            .authorization_rules(rule_set)
+            .amount(1)
+            .instruction();
+
+        let signature = self
+            .send(&[create.instruction(), mint_to], payer, &[mint])
+            .await
+            .context(
+                "Failed to send create pNFT transaction; no accounts were created and the mint \
+                 address is unused",
+            )?;
+
+        Ok(CreateNftResult {
+            mint: mint_pubkey.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            master_edition_pda: master_edition_pda.to_string(),
+            token_account: token_account.to_string(),
+            name: args.name,
+            symbol: args.symbol,
+            uri: args.uri,
+            max_supply,
+            token_record: Some(token_record.to_string()),
+            rule_set: rule_set.map(|rule_set| rule_set.to_string()),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Instructions that create a mint, mint a single token to the payer, and
+    /// create its metadata and master edition. Returns the token account.
+    pub(crate) async fn nft_instructions(
+        &self,
+        payer: &Pubkey,
+        mint: &Pubkey,
+        args: &CreateMetadataArgs,
+        collection_details: Option<CollectionDetails>,
+        max_supply: Option<u64>,
+    ) -> Result<(Pubkey, Vec<Instruction>)> {
+        let metadata_pda = find_metadata_pda(mint);
+        let master_edition_pda = find_master_edition_pda(mint);
+
+        ensure_signer_update_authority(args)?;
+        let data = data_from_args(args, payer)?;
+
+        let mut instructions = self
+            .create_mint_instructions(payer, mint, payer, Some(payer), 0)
+            .await?;
+
+        let (token_account, mint_to) =
+            mint_to_owner_instructions(&self.rent_payer(payer), mint, payer, payer, 1)?;
+        instructions.extend(mint_to);
+
+        let mut metadata = CreateMetadataAccountV3Builder::new();
+        metadata
+            .metadata(metadata_pda)
+            .mint(*mint)
+            .mint_authority(*payer)
+            .payer(self.rent_payer(payer))
+            .update_authority(*payer, true)
+            .data(data)
+            .is_mutable(args.is_mutable);
+        if let Some(collection_details) = collection_details {
+            metadata.collection_details(collection_details);
+        }
+        instructions.push(metadata.instruction());
+
+        let mut master_edition = CreateMasterEditionV3Builder::new();
+        master_edition
+            .edition(master_edition_pda)
+            .mint(*mint)
+            .update_authority(*payer)
+            .mint_authority(*payer)
+            .payer(self.rent_payer(payer))
+            .metadata(metadata_pda);
+        if let Some(max_supply) = max_supply {
+            master_edition.max_supply(max_supply);
+        }
+        instructions.push(master_edition.instruction());
+
+        Ok((token_account, instructions))
+    }
+}
+
+/// The master edition is created in the same transaction and needs the
+/// update authority's signature, so an NFT's update authority can only be
+/// handed over once it exists
+fn ensure_signer_update_authority(args: &CreateMetadataArgs) -> Result<()> {
+    if let Some(update_authority) = args.update_authority {
+        anyhow::bail!(
+            "An NFT is created with the signer as update authority; hand it to {} afterwards \
+             with set-authority",
+            update_authority
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,39 @@
+//! Durable nonce transactions, which use the blockhash stored in a nonce
+//! account instead of a recent one and so don't expire.
+
+use anyhow::{Context, Result};
+use solana_client::nonce_utils;
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Signer};
+
+use crate::client::MetadataClient;
+
+/// A nonce account and its authority, which signs the AdvanceNonceAccount
+/// instruction of every transaction
+pub struct DurableNonce {
+    pub account: Pubkey,
+    pub authority: Box<dyn Signer + Send + Sync>,
+}
+
+impl MetadataClient {
+    /// The durable blockhash currently stored in the nonce account
+    pub(crate) async fn nonce_blockhash(&self, nonce: &DurableNonce) -> Result<Hash> {
+        let account = nonce_utils::nonblocking::get_account_with_commitment(
+            self.rpc(),
+            &nonce.account,
+            self.rpc().commitment(),
+        )
+        .await
+        .with_context(|| format!("Failed to fetch nonce account {}", nonce.account))?;
+        let data = nonce_utils::nonblocking::data_from_account(&account)
+            .with_context(|| format!("{} is not an initialized nonce account", nonce.account))?;
+
+        if data.authority != nonce.authority.pubkey() {
+            anyhow::bail!(
+                "Keypair {} is not the nonce authority (expected {})",
+                nonce.authority.pubkey(),
+                data.authority
+            );
+        }
+        Ok(data.blockhash())
+    }
+}
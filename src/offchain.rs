@@ -0,0 +1,161 @@
+//! The off-chain metadata JSON format of the Metaplex Token Metadata standard.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::MetadataCliError;
+use crate::types::UriHash;
+
+/// Off-chain metadata JSON, as referenced by the on-chain URI
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct OffChainMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seller_fee_basis_points: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Properties>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Properties {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub creators: Vec<OffChainCreator>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OffChainCreator {
+    pub address: String,
+    pub share: u8,
+}
+
+impl OffChainMetadata {
+    /// Read and parse a metadata JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read metadata file '{}'", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse metadata file '{}'", path.display()))
+    }
+}
+
+/// Public gateways used to resolve `ipfs://` and `ar://` URIs
+#[derive(Clone, Debug)]
+pub struct Gateways {
+    pub ipfs: String,
+    pub arweave: String,
+}
+
+impl Default for Gateways {
+    fn default() -> Self {
+        Self {
+            ipfs: "https://ipfs.io".to_string(),
+            arweave: "https://arweave.net".to_string(),
+        }
+    }
+}
+
+impl Gateways {
+    /// The HTTP(S) URL to fetch a metadata URI from
+    pub fn resolve(&self, uri: &str) -> String {
+        if let Some(path) = uri.strip_prefix("ipfs://") {
+            format!(
+                "{}/ipfs/{}",
+                self.ipfs.trim_end_matches('/'),
+                path.trim_start_matches("ipfs/")
+            )
+        } else if let Some(id) = uri.strip_prefix("ar://") {
+            format!("{}/{}", self.arweave.trim_end_matches('/'), id)
+        } else {
+            uri.to_string()
+        }
+    }
+
+    /// Fetch and parse the JSON behind a metadata URI
+    pub async fn fetch_json(&self, uri: &str, timeout: Duration) -> Result<Value> {
+        let url = self.resolve(uri);
+        let http = reqwest::Client::builder().timeout(timeout).build()?;
+        http.get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Failed to fetch metadata JSON from '{}'", url))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to fetch metadata JSON from '{}'", url))
+    }
+
+    /// Fetch the JSON behind a metadata URI and hash the bytes served,
+    /// failing unless they parse as JSON
+    pub async fn hash_json(&self, uri: &str, timeout: Duration) -> Result<UriHash> {
+        let url = self.resolve(uri);
+        let http = reqwest::Client::builder().timeout(timeout).build()?;
+        let body = http
+            .get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Failed to fetch metadata JSON from '{}'", url))?
+            .bytes()
+            .await
+            .with_context(|| format!("Failed to fetch metadata JSON from '{}'", url))?;
+        if let Err(err) = serde_json::from_slice::<Value>(&body) {
+            return Err(MetadataCliError::InvalidField {
+                field: "metadata JSON",
+                reason: format!("'{}' doesn't serve JSON: {}", url, err),
+            }
+            .into());
+        }
+        Ok(UriHash {
+            uri: uri.to_string(),
+            sha256: format!("{:x}", Sha256::digest(&body)),
+        })
+    }
+}
+
+/// The HTTP(S) URL to fetch a metadata URI from. `ipfs://` and `ar://`
+/// URIs are resolved through the default public gateways.
+pub fn gateway_url(uri: &str) -> String {
+    Gateways::default().resolve(uri)
+}
+
+/// Whether a string is a URI to fetch rather than a local path
+pub fn is_uri(s: &str) -> bool {
+    ["http://", "https://", "ipfs://", "ar://"]
+        .iter()
+        .any(|scheme| s.starts_with(scheme))
+}
+
+/// Fetch and parse the JSON behind a metadata URI through the default
+/// gateways
+pub async fn fetch_json(uri: &str, timeout: Duration) -> Result<Value> {
+    Gateways::default().fetch_json(uri, timeout).await
+}
+
+/// Fetch and hash the JSON behind a metadata URI through the default
+/// gateways
+pub async fn hash_json(uri: &str, timeout: Duration) -> Result<UriHash> {
+    Gateways::default().hash_json(uri, timeout).await
+}
+
+/// Read metadata JSON from a local file or fetch it from a URI
+pub async fn load_json(file_or_uri: &str, timeout: Duration) -> Result<Value> {
+    if is_uri(file_or_uri) {
+        return fetch_json(file_or_uri, timeout).await;
+    }
+    let contents = std::fs::read_to_string(file_or_uri)
+        .with_context(|| format!("Failed to read metadata file '{}'", file_or_uri))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse metadata file '{}'", file_or_uri))
+}
@@ -0,0 +1,111 @@
+//! Offline signing: signed transactions written to a file on one machine
+//! and broadcast from another.
+
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use solana_sdk::{signature::Signature, transaction::Transaction};
+use std::path::Path;
+
+use crate::client::MetadataClient;
+
+/// Write signed transactions to a file, one base64-encoded transaction per
+/// line
+pub fn write_transactions(path: impl AsRef<Path>, transactions: &[Transaction]) -> Result<()> {
+    let path = path.as_ref();
+    let mut contents = String::new();
+    for tx in transactions {
+        let bytes = bincode::serialize(tx).context("Failed to serialize transaction")?;
+        contents.push_str(&STANDARD.encode(bytes));
+        contents.push('\n');
+    }
+    std::fs::write(path, contents)
+        .with_context(|| format!("Failed to write transactions to '{}'", path.display()))
+}
+
+/// Read transactions written by [`write_transactions`]
+pub fn read_transactions(path: impl AsRef<Path>) -> Result<Vec<Transaction>> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read transactions from '{}'", path.display()))?;
+
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            let bytes = STANDARD
+                .decode(line)
+                .with_context(|| format!("Transaction {} is not valid base64", i + 1))?;
+            bincode::deserialize(&bytes)
+                .with_context(|| format!("Transaction {} could not be decoded", i + 1))
+        })
+        .collect()
+}
+
+impl MetadataClient {
+    /// Check, send an already signed transaction and wait for confirmation
+    pub async fn broadcast(&self, tx: &Transaction) -> Result<Signature> {
+        if !tx.is_signed() {
+            anyhow::bail!("Transaction {} is missing signatures", tx.signatures[0]);
+        }
+        self.preflight(tx).await?;
+        self.send_and_confirm(tx).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use solana_system_interface::instruction as system_instruction;
+
+    fn signed_transfer(payer: &Keypair, lamports: u64) -> Transaction {
+        let ix = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), lamports);
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[payer],
+            Hash::new_unique(),
+        )
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "token-metadata-cli-{}-{}",
+            std::process::id(),
+            name
+        ))
+    }
+
+    #[test]
+    fn signed_transactions_round_trip() {
+        let payer = Keypair::new();
+        let transactions = vec![signed_transfer(&payer, 1), signed_transfer(&payer, 2)];
+        let path = temp_path("round-trip.txt");
+        write_transactions(&path, &transactions).unwrap();
+        let read = read_transactions(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read, transactions);
+        assert!(read.iter().all(|tx| tx.is_signed()));
+        read[0].verify().unwrap();
+    }
+
+    #[test]
+    fn blank_lines_are_skipped_and_bad_lines_named() {
+        let payer = Keypair::new();
+        let path = temp_path("bad-lines.txt");
+        write_transactions(&path, &[signed_transfer(&payer, 1)]).unwrap();
+        let mut contents = std::fs::read_to_string(&path).unwrap();
+        contents.insert(0, '\n');
+        std::fs::write(&path, &contents).unwrap();
+        assert_eq!(read_transactions(&path).unwrap().len(), 1);
+
+        contents.push_str("not base64!\n");
+        std::fs::write(&path, &contents).unwrap();
+        let err = read_transactions(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(err.to_string(), "Transaction 2 is not valid base64");
+    }
+}
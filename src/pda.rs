@@ -0,0 +1,95 @@
+//! Program-derived addresses used by the Token Metadata program.
+
+use mpl_token_metadata::{
+    EDITION_MARKER_BIT_SIZE, ID as TOKEN_METADATA_PROGRAM_ID, types::MetadataDelegateRole,
+};
+use solana_sdk::pubkey::Pubkey;
+
+/// Derive the metadata PDA for a given mint
+pub fn find_metadata_pda(mint: &Pubkey) -> Pubkey {
+    let seeds = &[
+        b"metadata".as_ref(),
+        TOKEN_METADATA_PROGRAM_ID.as_ref(),
+        mint.as_ref(),
+    ];
+    Pubkey::find_program_address(seeds, &TOKEN_METADATA_PROGRAM_ID).0
+}
+
+/// Derive the master edition PDA for a given mint. Printed editions live at
+/// the same address derived from their own mint.
+pub fn find_master_edition_pda(mint: &Pubkey) -> Pubkey {
+    let seeds = &[
+        b"metadata".as_ref(),
+        TOKEN_METADATA_PROGRAM_ID.as_ref(),
+        mint.as_ref(),
+        b"edition".as_ref(),
+    ];
+    Pubkey::find_program_address(seeds, &TOKEN_METADATA_PROGRAM_ID).0
+}
+
+/// Derive the token record PDA tracking a programmable NFT's token account
+pub fn find_token_record_pda(mint: &Pubkey, token: &Pubkey) -> Pubkey {
+    let seeds = &[
+        b"metadata".as_ref(),
+        TOKEN_METADATA_PROGRAM_ID.as_ref(),
+        mint.as_ref(),
+        b"token_record".as_ref(),
+        token.as_ref(),
+    ];
+    Pubkey::find_program_address(seeds, &TOKEN_METADATA_PROGRAM_ID).0
+}
+
+/// Derive the record PDA of a metadata delegate approved by `update_authority`
+pub fn find_metadata_delegate_record_pda(
+    mint: &Pubkey,
+    role: MetadataDelegateRole,
+    update_authority: &Pubkey,
+    delegate: &Pubkey,
+) -> Pubkey {
+    let role = role.to_string();
+    let seeds = &[
+        b"metadata".as_ref(),
+        TOKEN_METADATA_PROGRAM_ID.as_ref(),
+        mint.as_ref(),
+        role.as_bytes(),
+        update_authority.as_ref(),
+        delegate.as_ref(),
+    ];
+    Pubkey::find_program_address(seeds, &TOKEN_METADATA_PROGRAM_ID).0
+}
+
+/// Derive the record PDA of a use authority approved for a mint
+pub fn find_use_authority_record_pda(mint: &Pubkey, use_authority: &Pubkey) -> Pubkey {
+    let seeds = &[
+        b"metadata".as_ref(),
+        TOKEN_METADATA_PROGRAM_ID.as_ref(),
+        mint.as_ref(),
+        b"user".as_ref(),
+        use_authority.as_ref(),
+    ];
+    Pubkey::find_program_address(seeds, &TOKEN_METADATA_PROGRAM_ID).0
+}
+
+/// Derive the PDA the program signs with when burning a token on its last use
+pub fn find_burner_pda() -> Pubkey {
+    let seeds = &[
+        b"metadata".as_ref(),
+        TOKEN_METADATA_PROGRAM_ID.as_ref(),
+        b"burn".as_ref(),
+    ];
+    Pubkey::find_program_address(seeds, &TOKEN_METADATA_PROGRAM_ID).0
+}
+
+/// Derive the edition marker PDA that records which edition numbers of a
+/// master edition have been printed
+pub fn find_edition_marker_pda(master_mint: &Pubkey, edition: u64) -> Pubkey {
+    let marker = (edition / EDITION_MARKER_BIT_SIZE).to_string();
+    let seeds = &[
+        b"metadata".as_ref(),
+        TOKEN_METADATA_PROGRAM_ID.as_ref(),
+        master_mint.as_ref(),
+        b"edition".as_ref(),
+        marker.as_bytes(),
+    ];
+    Pubkey::find_program_address(seeds, &TOKEN_METADATA_PROGRAM_ID).0
+}
@@ -0,0 +1,134 @@
+//! Checking before a transaction is sent that its payer can afford it.
+
+use anyhow::{Context, Result};
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::message::Message;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use crate::client::MetadataClient;
+use crate::error::MetadataCliError;
+use crate::types::InsufficientFunds;
+
+impl MetadataClient {
+    /// Simulate a signed transaction before sending it, in place of the RPC
+    /// node's own preflight check. When the payer can't cover the fee and
+    /// the rent of the accounts the transaction creates, or another signer
+    /// can't cover what it is debited, this fails with
+    /// [`InsufficientFunds`] instead of the program error the failed
+    /// account creation would surface.
+    pub(crate) async fn preflight(&self, tx: &Transaction) -> Result<()> {
+        let payer = tx.message.account_keys[0];
+        let balance = self
+            .rpc()
+            .get_balance(&payer)
+            .await
+            .context("Failed to fetch payer balance")?;
+        let fee = self
+            .rpc()
+            .get_fee_for_message(&tx.message)
+            .await
+            .context("Failed to estimate transaction fee")?;
+        if balance < fee {
+            return Err(InsufficientFunds {
+                payer,
+                balance,
+                needed: fee,
+            }
+            .into());
+        }
+
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            commitment: Some(self.rpc().commitment()),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let simulation = self
+            .rpc()
+            .simulate_transaction_with_config(tx, config)
+            .await
+            .context("Failed to simulate transaction")?
+            .value;
+        let Some(err) = simulation.err else {
+            return Ok(());
+        };
+        let logs = simulation.logs.unwrap_or_default();
+        if let Some(insufficient) = self.insufficient_funds(&tx.message, &err, &logs).await? {
+            return Err(insufficient.into());
+        }
+        if err == TransactionError::BlockhashNotFound {
+            return Err(err.into());
+        }
+        Err(MetadataCliError::SimulationFailed { error: err, logs }.into())
+    }
+
+    /// The wallet a failed simulation couldn't debit, with its balance and
+    /// the lamports it needs, when the simulation failed for lack of funds.
+    /// That is not always the fee payer: a separate rent payer or authority
+    /// signing a transfer can be the one short.
+    pub(crate) async fn insufficient_funds(
+        &self,
+        message: &Message,
+        err: &TransactionError,
+        logs: &[String],
+    ) -> Result<Option<InsufficientFunds>> {
+        let signers = &message.account_keys[..message.header.num_required_signatures as usize];
+        // The System program logs "Transfer: insufficient lamports <have>, need <need>"
+        let transfer = logs.iter().find_map(|line| {
+            let (have, need) = line
+                .split_once("insufficient lamports ")?
+                .1
+                .split_once(", need ")?;
+            Some((have.parse::<u64>().ok()?, need.trim().parse::<u64>().ok()?))
+        });
+        let (index, shortfall) = match (transfer, err) {
+            (Some((have, need)), _) => {
+                // The log only gives the balance of the debited account, so
+                // it is told apart from the other signers by that
+                let balances = self
+                    .rpc()
+                    .get_multiple_accounts(signers)
+                    .await
+                    .context("Failed to fetch signer balances")?;
+                let fee = self
+                    .rpc()
+                    .get_fee_for_message(message)
+                    .await
+                    .context("Failed to estimate transaction fee")?;
+                // The fee was already deducted when the failing transfer ran
+                let index = balances
+                    .iter()
+                    .enumerate()
+                    .position(|(i, account)| {
+                        let balance = account.as_ref().map_or(0, |account| account.lamports);
+                        let fee = if i == 0 { fee } else { 0 };
+                        balance.saturating_sub(fee) == have
+                    })
+                    .unwrap_or(0);
+                (index, need.saturating_sub(have))
+            }
+            // Paying would leave a wallet below the rent-exempt minimum
+            (None, TransactionError::InsufficientFundsForRent { account_index })
+                if (*account_index as usize) < signers.len() =>
+            {
+                let rent = self
+                    .rpc()
+                    .get_minimum_balance_for_rent_exemption(0)
+                    .await
+                    .context("Failed to fetch rent-exempt minimum")?;
+                (*account_index as usize, rent)
+            }
+            _ => return Ok(None),
+        };
+        let payer = signers[index];
+        let balance = self
+            .rpc()
+            .get_balance(&payer)
+            .await
+            .context("Failed to fetch payer balance")?;
+        Ok(Some(InsufficientFunds {
+            payer,
+            balance,
+            needed: balance + shortfall,
+        }))
+    }
+}
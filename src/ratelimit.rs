@@ -0,0 +1,50 @@
+//! Client-side rate limiting of RPC requests, so large jobs against public
+//! endpoints slow down instead of failing with 429 Too Many Requests.
+
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// A token bucket allowing a steady number of requests per second, in
+/// bursts of up to one second's worth
+pub struct RateLimiter {
+    rate: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Allow `requests_per_second` requests per second. Panics if it is
+    /// zero.
+    pub fn new(requests_per_second: u32) -> Self {
+        assert!(requests_per_second > 0, "the rate limit must be positive");
+        let rate = f64::from(requests_per_second);
+        Self {
+            rate,
+            bucket: Mutex::new(Bucket {
+                tokens: rate,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until another request may be sent. Waiting callers are served
+    /// in turn.
+    pub async fn acquire(&self) {
+        let mut bucket = self.bucket.lock().await;
+        let now = Instant::now();
+        let refilled = now.duration_since(bucket.updated).as_secs_f64() * self.rate;
+        bucket.tokens = (bucket.tokens + refilled).min(self.rate);
+        bucket.updated = now;
+        if bucket.tokens < 1.0 {
+            let wait = Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate);
+            tokio::time::sleep(wait).await;
+            bucket.tokens = 1.0;
+            bucket.updated = Instant::now();
+        }
+        bucket.tokens -= 1.0;
+    }
+}
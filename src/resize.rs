@@ -0,0 +1,148 @@
+//! Shrinking metadata and edition accounts to their actual size with the
+//! Resize instruction, returning the excess rent.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{instructions::ResizeBuilder, types::TokenStandard};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account_client::address::get_associated_token_address_with_program_id;
+use std::str::FromStr;
+
+use crate::client::MetadataClient;
+use crate::pda::{find_master_edition_pda, find_metadata_pda};
+use crate::types::{BatchItemResult, BatchResult, ResizeResult};
+
+/// Size of a metadata account created at the fixed maximum size, before
+/// accounts were sized to their contents
+const MAX_METADATA_LEN: usize = 679;
+
+impl MetadataClient {
+    /// Resize a mint's metadata account (and its edition, if any) down to
+    /// the size of its contents. The payer must hold the token of an NFT or
+    /// be the mint authority of a fungible token, and receives the excess
+    /// rent.
+    pub async fn resize(&self, payer: &dyn Signer, mint: &Pubkey) -> Result<ResizeResult> {
+        let metadata_pda = find_metadata_pda(mint);
+        let size_before = self.account_len(&metadata_pda).await?;
+        if size_before < MAX_METADATA_LEN {
+            anyhow::bail!(
+                "Metadata for mint {} is already resized ({} bytes)",
+                mint,
+                size_before
+            );
+        }
+        self.resize_unchecked(payer, mint, size_before).await
+    }
+
+    /// Resize the metadata of every mint, sending up to `concurrency`
+    /// transactions at a time. Mints whose metadata is already resized are
+    /// skipped. The total rent recovered is reported alongside each mint's.
+    pub async fn batch_resize(
+        &self,
+        payer: &(dyn Signer + Sync),
+        mints: &[String],
+        concurrency: usize,
+    ) -> BatchResult {
+        let outcomes = self
+            .run_batch(mints, concurrency, |mint| async move {
+                let mint = Pubkey::from_str(mint).context("Invalid mint address")?;
+                let size_before = self.account_len(&find_metadata_pda(&mint)).await?;
+                if size_before < MAX_METADATA_LEN {
+                    return Ok(None);
+                }
+                self.resize_unchecked(payer, &mint, size_before)
+                    .await
+                    .map(Some)
+            })
+            .await;
+
+        let items = mints
+            .iter()
+            .zip(outcomes)
+            .enumerate()
+            .map(|(i, (mint, outcome))| {
+                let recovered = outcome
+                    .as_ref()
+                    .ok()
+                    .and_then(|result| result.as_ref()?.lamports_recovered);
+                let outcome = outcome.map(|result| result.map(|result| result.signature));
+                BatchItemResult {
+                    lamports_recovered: recovered,
+                    ..BatchItemResult::new(i + 1, mint, outcome)
+                }
+            })
+            .collect();
+        BatchResult::from_items(items)
+    }
+
+    async fn resize_unchecked(
+        &self,
+        payer: &dyn Signer,
+        mint: &Pubkey,
+        size_before: usize,
+    ) -> Result<ResizeResult> {
+        let metadata_pda = find_metadata_pda(mint);
+        let metadata = self.fetch_fresh(mint).await?;
+        // Fungible tokens pass the uninitialized edition address
+        let edition = find_master_edition_pda(mint);
+        let has_edition = self.rpc().get_account(&edition).await.is_ok();
+        let token = match self.token_standard(mint, &metadata).await {
+            TokenStandard::Fungible | TokenStandard::FungibleAsset => None,
+            _ => {
+                let token_program = self.fetch_mint(mint).await?.program.id();
+                Some(get_associated_token_address_with_program_id(
+                    &payer.pubkey(),
+                    mint,
+                    &token_program,
+                ))
+            }
+        };
+        let tracked = [metadata_pda, edition];
+        let lamports_before: u64 = self.account_lamports(&tracked).await?.iter().sum();
+
+        // The payer doubles as the authority unless a separate authority signs
+        let rent_payer = self.rent_payer(&payer.pubkey());
+        let ix = ResizeBuilder::new()
+            .metadata(metadata_pda)
+            .edition(edition)
+            .mint(*mint)
+            .payer(rent_payer, true)
+            .authority((rent_payer != payer.pubkey()).then(|| payer.pubkey()))
+            .token(token)
+            .instruction();
+
+        let signature = self
+            .send(&[ix], payer, &[])
+            .await
+            .context("Failed to send resize transaction")?;
+
+        // Nothing changed on chain when only simulating or signing
+        let (size_after, lamports_recovered) = if self.is_dry_run() || self.is_sign_only() {
+            (None, None)
+        } else {
+            let lamports_after: u64 = self.account_lamports(&tracked).await?.iter().sum();
+            (
+                Some(self.account_len(&metadata_pda).await?),
+                Some(lamports_before.saturating_sub(lamports_after)),
+            )
+        };
+
+        Ok(ResizeResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            edition: has_edition.then(|| edition.to_string()),
+            size_before,
+            size_after,
+            lamports_recovered,
+            signature: signature.to_string(),
+        })
+    }
+
+    async fn account_len(&self, address: &Pubkey) -> Result<usize> {
+        let data = self
+            .rpc()
+            .get_account_data(address)
+            .await
+            .with_context(|| format!("Failed to fetch account {}", address))?;
+        Ok(data.len())
+    }
+}
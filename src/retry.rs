@@ -0,0 +1,131 @@
+//! Retrying transaction submission when the RPC node is unavailable, times
+//! out or no longer knows the blockhash.
+
+use anyhow::Result;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_request::RpcError,
+};
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    signature::{Signature, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::client::MetadataClient;
+use crate::cluster::Cluster;
+use crate::types::InsufficientFunds;
+
+/// Delay before the first retry; it doubles with every attempt
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// JSON-RPC error code of a node that is behind or otherwise unhealthy
+const NODE_UNHEALTHY: i64 = -32005;
+
+impl MetadataClient {
+    /// Send `tx` and wait for confirmation, retrying transient failures up
+    /// to the client's attempt limit with exponential backoff.
+    ///
+    /// A transaction is only re-signed with a fresh blockhash once its old
+    /// blockhash has expired and it hasn't landed, so an earlier attempt can
+    /// never land as well. Until then the same transaction is resent.
+    pub(crate) async fn send_with_retries(
+        &self,
+        mut tx: Transaction,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+    ) -> Result<Signature> {
+        let max_attempts = self.max_attempts().max(1);
+        let mut attempt = 1;
+        loop {
+            let err = match self.submit(&tx).await {
+                Ok(signature) => return Ok(signature),
+                Err(err) => err,
+            };
+            if !is_transient(&err) {
+                return Err(err);
+            }
+            if attempt >= max_attempts {
+                return Err(err.context(format!("Giving up after {} attempts", attempt)));
+            }
+
+            let signature = tx.signatures[0];
+            match self.rpc().get_signature_status(&signature).await {
+                Ok(Some(Ok(()))) => return Ok(signature),
+                Ok(Some(Err(tx_err))) => return Err(tx_err.into()),
+                _ => {}
+            }
+
+            let delay = BASE_DELAY * 2u32.pow(attempt - 1);
+            warn!(
+                "Send failed ({:#}); retrying in {:.1}s (attempt {} of {})...",
+                err,
+                delay.as_secs_f64(),
+                attempt + 1,
+                max_attempts
+            );
+            tokio::time::sleep(delay).await;
+
+            if self.blockhash_expired(&tx.message.recent_blockhash).await {
+                tx = self.sign(instructions, signers).await?;
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Run the pre-flight check, then send without the node's own preflight
+    async fn submit(&self, tx: &Transaction) -> Result<Signature> {
+        if let Err(err) = self.preflight(tx).await {
+            match err.downcast_ref::<InsufficientFunds>() {
+                // Mainnet has no faucet, so the shortfall stands
+                Some(_) if self.auto_airdrop() && self.cluster() == Cluster::MainnetBeta => {
+                    return Err(
+                        err.context("--auto-airdrop only works on devnet, testnet and localnet")
+                    );
+                }
+                Some(funds) if self.auto_airdrop() => {
+                    self.airdrop_shortfall(&funds.payer, funds.shortfall())
+                        .await?;
+                    self.preflight(tx).await?;
+                }
+                _ => return Err(err),
+            }
+        }
+        self.send_and_confirm(tx).await
+    }
+
+    /// Whether a blockhash is known to have expired. When the node can't
+    /// be asked it is assumed to still be valid.
+    async fn blockhash_expired(&self, blockhash: &Hash) -> bool {
+        self.rpc()
+            .is_blockhash_valid(blockhash, self.rpc().commitment())
+            .await
+            .is_ok_and(|valid| !valid)
+    }
+}
+
+/// Whether a failed send is worth retrying: the node was unreachable, timed
+/// out, was unhealthy or didn't know the blockhash, or the transaction
+/// couldn't be confirmed before its blockhash expired
+fn is_transient(err: &anyhow::Error) -> bool {
+    if let Some(TransactionError::BlockhashNotFound) = err.downcast_ref::<TransactionError>() {
+        return true;
+    }
+    let Some(client_err) = err.downcast_ref::<ClientError>() else {
+        return false;
+    };
+    match client_err.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+        ClientErrorKind::TransactionError(TransactionError::BlockhashNotFound) => true,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == NODE_UNHEALTHY
+        }
+        ClientErrorKind::RpcError(RpcError::ForUser(message)) => {
+            message.starts_with("unable to confirm transaction")
+        }
+        _ => false,
+    }
+}
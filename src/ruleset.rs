@@ -0,0 +1,404 @@
+//! Authorization rule sets of the Metaplex Token Authorization Rules
+//! program, which decide what programmable NFTs may be transferred to or
+//! delegated to.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    signature::Signer,
+};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::client::MetadataClient;
+use crate::types::{RuleSetInfo, RuleSetResult};
+
+/// The Metaplex Token Authorization Rules program, which evaluates pNFT rule sets
+pub const TOKEN_AUTH_RULES_PROGRAM_ID: Pubkey =
+    pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+
+/// Rule set names are stored in a 32-byte field
+pub const MAX_RULE_SET_NAME_LEN: usize = 32;
+
+/// Largest serialized rule set sent inline; bigger ones are written to a
+/// buffer account in chunks of this size
+const MAX_INLINE_RULE_SET_LEN: usize = 900;
+
+/// Size of the account header: a key byte and the offset of the revision map
+const HEADER_LEN: usize = 9;
+
+/// Operations Token Metadata asks a pNFT's rule set about
+pub const TRANSFER_OPERATIONS: &[&str] = &[
+    "Transfer:Owner",
+    "Transfer:TransferDelegate",
+    "Transfer:SaleDelegate",
+    "Transfer:MigrationDelegate",
+    "Transfer:WalletToWallet",
+];
+pub const DELEGATE_OPERATIONS: &[&str] = &[
+    "Delegate:Transfer",
+    "Delegate:Sale",
+    "Delegate:Utility",
+    "Delegate:Staking",
+    "Delegate:LockedTransfer",
+];
+
+/// The rule set address for an owner and name
+pub fn find_rule_set_pda(owner: &Pubkey, name: &str) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"rule_set", owner.as_ref(), name.as_bytes()],
+        &TOKEN_AUTH_RULES_PROGRAM_ID,
+    )
+    .0
+}
+
+/// The payer's staging buffer for rule sets too large for one transaction
+pub fn find_buffer_pda(payer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"rule_set", payer.as_ref()], &TOKEN_AUTH_RULES_PROGRAM_ID).0
+}
+
+/// A public key that is written as base58 in JSON and as raw bytes in the
+/// on-chain MessagePack encoding
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RulePubkey(pub Pubkey);
+
+impl Serialize for RulePubkey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.0.to_string())
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RulePubkey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            Pubkey::from_str(&s)
+                .map(Self)
+                .map_err(|_| serde::de::Error::custom(format!("Invalid public key '{}'", s)))
+        } else {
+            Pubkey::deserialize(deserializer).map(Self)
+        }
+    }
+}
+
+/// Comparison used by [`Rule::Amount`]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum CompareOp {
+    Lt,
+    LtEq,
+    Eq,
+    GtEq,
+    Gt,
+}
+
+/// A rule of the version 1 rule set format. Fields name the payload value
+/// the rule inspects, such as `Destination`, `Source`, `Authority` or
+/// `Amount`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Rule {
+    All {
+        rules: Vec<Rule>,
+    },
+    Any {
+        rules: Vec<Rule>,
+    },
+    Not {
+        rule: Box<Rule>,
+    },
+    AdditionalSigner {
+        account: RulePubkey,
+    },
+    PubkeyMatch {
+        pubkey: RulePubkey,
+        field: String,
+    },
+    PubkeyListMatch {
+        pubkeys: Vec<RulePubkey>,
+        field: String,
+    },
+    PubkeyTreeMatch {
+        root: [u8; 32],
+        pubkey_field: String,
+        proof_field: String,
+    },
+    PDAMatch {
+        program: Option<RulePubkey>,
+        pda_field: String,
+        seeds_field: String,
+    },
+    ProgramOwned {
+        program: RulePubkey,
+        field: String,
+    },
+    ProgramOwnedList {
+        programs: Vec<RulePubkey>,
+        field: String,
+    },
+    ProgramOwnedTree {
+        root: [u8; 32],
+        pubkey_field: String,
+        proof_field: String,
+    },
+    Amount {
+        amount: u64,
+        operator: CompareOp,
+        field: String,
+    },
+    Frequency {
+        authority: RulePubkey,
+    },
+    IsWallet {
+        field: String,
+    },
+    Pass,
+    Namespace,
+}
+
+/// A version 1 rule set: the rule that applies to each operation
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub lib_version: u8,
+    pub owner: RulePubkey,
+    pub rule_set_name: String,
+    pub operations: BTreeMap<String, Rule>,
+}
+
+impl RuleSet {
+    pub fn new(owner: Pubkey, name: &str, operations: BTreeMap<String, Rule>) -> Result<Self> {
+        if name.is_empty() || name.len() > MAX_RULE_SET_NAME_LEN {
+            anyhow::bail!(
+                "Rule set name must be 1 to {} bytes long",
+                MAX_RULE_SET_NAME_LEN
+            );
+        }
+        if operations.is_empty() {
+            anyhow::bail!("A rule set needs at least one operation");
+        }
+        Ok(Self {
+            lib_version: 1,
+            owner: RulePubkey(owner),
+            rule_set_name: name.to_string(),
+            operations,
+        })
+    }
+
+    /// The common allowlist: wallet-to-wallet transfers always pass, and
+    /// every other transfer or delegation must involve a program in
+    /// `programs` as the source, destination or authority
+    pub fn allowlist(owner: Pubkey, name: &str, programs: &[Pubkey]) -> Result<Self> {
+        let programs: Vec<RulePubkey> = programs.iter().copied().map(RulePubkey).collect();
+        let owned_by = |field: &str| Rule::ProgramOwnedList {
+            programs: programs.clone(),
+            field: field.to_string(),
+        };
+        let mut operations = BTreeMap::new();
+        for operation in TRANSFER_OPERATIONS {
+            let rule = match *operation {
+                "Transfer:WalletToWallet" => Rule::Pass,
+                _ => Rule::Any {
+                    rules: vec![
+                        owned_by("Source"),
+                        owned_by("Destination"),
+                        owned_by("Authority"),
+                    ],
+                },
+            };
+            operations.insert(operation.to_string(), rule);
+        }
+        for operation in DELEGATE_OPERATIONS {
+            operations.insert(operation.to_string(), owned_by("Delegate"));
+        }
+        Self::new(owner, name, operations)
+    }
+}
+
+/// Read a rule set's operations from a JSON object mapping each operation
+/// name to a rule, e.g. `{"Transfer:WalletToWallet": "Pass"}`
+pub fn load_operations(path: impl AsRef<Path>) -> Result<BTreeMap<String, Rule>> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read rule set file '{}'", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse rule set file '{}'", path.display()))
+}
+
+impl MetadataClient {
+    /// Create a new rule set owned by the payer
+    pub async fn create_rule_set(
+        &self,
+        payer: &dyn Signer,
+        rule_set: &RuleSet,
+    ) -> Result<RuleSetResult> {
+        self.write_rule_set(payer, rule_set, false).await
+    }
+
+    /// Replace the rules of an existing rule set owned by the payer. The
+    /// program keeps earlier revisions in the account.
+    pub async fn update_rule_set(
+        &self,
+        payer: &dyn Signer,
+        rule_set: &RuleSet,
+    ) -> Result<RuleSetResult> {
+        self.write_rule_set(payer, rule_set, true).await
+    }
+
+    async fn write_rule_set(
+        &self,
+        payer: &dyn Signer,
+        rule_set: &RuleSet,
+        update: bool,
+    ) -> Result<RuleSetResult> {
+        if rule_set.owner.0 != payer.pubkey() {
+            anyhow::bail!("The rule set owner must be the signing wallet");
+        }
+        let address = find_rule_set_pda(&payer.pubkey(), &rule_set.rule_set_name);
+        let exists = self.rpc().get_account(&address).await.is_ok();
+        match (update, exists) {
+            (false, true) => anyhow::bail!(
+                "Rule set '{}' already exists at {}; use `ruleset update`",
+                rule_set.rule_set_name,
+                address
+            ),
+            (true, false) => anyhow::bail!(
+                "Rule set '{}' doesn't exist yet; use `ruleset create`",
+                rule_set.rule_set_name
+            ),
+            _ => {}
+        }
+
+        let serialized = rmp_serde::to_vec(rule_set).context("Failed to serialize the rule set")?;
+        let mut accounts = vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ];
+
+        // Rule sets too large for one transaction are staged in the payer's
+        // buffer account first, then copied from there
+        let inline = if serialized.len() > MAX_INLINE_RULE_SET_LEN {
+            let buffer = find_buffer_pda(&payer.pubkey());
+            for (i, chunk) in serialized.chunks(MAX_INLINE_RULE_SET_LEN).enumerate() {
+                // RuleSetInstruction::WriteToBuffer(WriteToBufferArgs::V1 { .. })
+                let mut data = vec![2u8, 0u8];
+                data.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+                data.extend_from_slice(chunk);
+                data.push((i == 0) as u8);
+                let ix = Instruction {
+                    program_id: TOKEN_AUTH_RULES_PROGRAM_ID,
+                    accounts: vec![
+                        AccountMeta::new(payer.pubkey(), true),
+                        AccountMeta::new(buffer, false),
+                        AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+                    ],
+                    data,
+                };
+                self.send(&[ix], payer, &[])
+                    .await
+                    .context("Failed to write the rule set buffer")?;
+            }
+            accounts.push(AccountMeta::new_readonly(buffer, false));
+            &[][..]
+        } else {
+            &serialized[..]
+        };
+
+        // RuleSetInstruction::CreateOrUpdate(CreateOrUpdateArgs::V1 { .. })
+        let mut data = vec![0u8, 0u8];
+        data.extend_from_slice(&(inline.len() as u32).to_le_bytes());
+        data.extend_from_slice(inline);
+        let ix = Instruction {
+            program_id: TOKEN_AUTH_RULES_PROGRAM_ID,
+            accounts,
+            data,
+        };
+
+        let signature = self
+            .send(&[ix], payer, &[])
+            .await
+            .context("Failed to send rule set transaction")?;
+
+        Ok(RuleSetResult {
+            address: address.to_string(),
+            owner: payer.pubkey().to_string(),
+            name: rule_set.rule_set_name.clone(),
+            operations: rule_set.operations.keys().cloned().collect(),
+            updated: update,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Fetch a rule set account and decode its latest revision
+    pub async fn fetch_rule_set(&self, address: &Pubkey) -> Result<RuleSetInfo> {
+        let account = self
+            .rpc()
+            .get_account(address)
+            .await
+            .with_context(|| format!("Failed to fetch rule set account {}", address))?;
+        if account.owner != TOKEN_AUTH_RULES_PROGRAM_ID {
+            anyhow::bail!("{} is not a Token Authorization Rules account", address);
+        }
+        let data = &account.data;
+        if data.len() < HEADER_LEN {
+            anyhow::bail!("Rule set account {} is too small", address);
+        }
+        let rev_map_location = u64::from_le_bytes(data[1..HEADER_LEN].try_into()?) as usize;
+
+        // The revision map is a version byte followed by a Borsh vector of
+        // revision offsets
+        let revisions: Vec<usize> = data
+            .get(rev_map_location + 1..)
+            .and_then(|map| {
+                let count = u32::from_le_bytes(map.get(..4)?.try_into().ok()?) as usize;
+                (0..count)
+                    .map(|i| {
+                        let start = 4 + i * 8;
+                        let bytes = map.get(start..start + 8)?;
+                        Some(u64::from_le_bytes(bytes.try_into().ok()?) as usize)
+                    })
+                    .collect()
+            })
+            .context("Invalid rule set revision map")?;
+
+        let latest = revisions.last().map(|&start| {
+            let end = revisions
+                .iter()
+                .copied()
+                .find(|&offset| offset > start)
+                .unwrap_or(rev_map_location);
+            match data.get(start..end) {
+                Some([1, rest @ ..]) => rmp_serde::from_slice::<RuleSet>(rest)
+                    .map_err(|err| format!("Failed to decode revision: {}", err)),
+                Some([version, ..]) => Err(format!(
+                    "Revision uses rule set format version {}, which can't be shown",
+                    version
+                )),
+                _ => Err("Revision is out of bounds".to_string()),
+            }
+        });
+
+        let (rule_set, decode_error) = match latest {
+            Some(Ok(rule_set)) => (Some(rule_set), None),
+            Some(Err(err)) => (None, Some(err)),
+            None => (None, None),
+        };
+        Ok(RuleSetInfo {
+            address: address.to_string(),
+            revisions: revisions.len(),
+            size: data.len(),
+            name: rule_set.as_ref().map(|r| r.rule_set_name.clone()),
+            owner: rule_set.as_ref().map(|r| r.owner.0.to_string()),
+            operations: rule_set
+                .map(|r| serde_json::to_value(r.operations))
+                .transpose()?,
+            decode_error,
+        })
+    }
+}
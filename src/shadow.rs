@@ -0,0 +1,328 @@
+//! GenesysGo Shadow Drive storage accounts: creating one, and uploading
+//! files into it.
+
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use reqwest::{Client, multipart};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signer,
+    sysvar,
+    transaction::Transaction,
+};
+use spl_associated_token_account_client::address::get_associated_token_address;
+
+use crate::client::MetadataClient;
+use crate::cluster::Cluster;
+use crate::types::ShadowStorageResult;
+
+const SHDW_DRIVE_ENDPOINT: &str = "https://shadow-storage.genesysgo.net";
+const SHDW_DRIVE_URL: &str = "https://shdw-drive.genesysgo.net";
+
+/// The Shadow Drive storage program
+const SHDW_DRIVE_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("2e1wdyNhUvE76y6yUCvah2KaviavMJYKoRun8acMRBZZ");
+
+/// The SHDW token, staked to pay for storage
+const SHDW_MINT: Pubkey = solana_sdk::pubkey!("SHDWyBxihqiCj6YekG2GUr7wqKLeLAMK1gHZck9pL6y");
+
+/// Offset of the uploader key in the storage config account: the account
+/// discriminator, shades per GiB, storage available, token account and
+/// second admin come first
+const STORAGE_CONFIG_UPLOADER_OFFSET: usize = 8 + 8 + 16 + 32 + 32;
+
+/// Offset of the account counter in a user info account, after the
+/// discriminator
+const USER_INFO_COUNTER_OFFSET: usize = 8;
+
+/// Longest name a storage account can have
+pub const MAX_STORAGE_NAME_LENGTH: usize = 64;
+
+/// Smallest storage account the program creates
+pub const MIN_STORAGE_SIZE: u64 = 1024;
+
+/// Parse a storage size such as `512KB`, `10MB`, `1GiB` or a plain number
+/// of bytes. KB, MB and GB are powers of 1000; KiB, MiB and GiB of 1024.
+pub fn parse_storage_size(size: &str) -> Result<u64> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: u64 = number
+        .parse()
+        .with_context(|| format!("Invalid storage size '{}'", size))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        _ => anyhow::bail!(
+            "Unknown unit in storage size '{}' (expected KB, MB, GB, KiB, MiB or GiB)",
+            size
+        ),
+    };
+    let bytes = number
+        .checked_mul(multiplier)
+        .with_context(|| format!("Storage size '{}' is too large", size))?;
+    if bytes < MIN_STORAGE_SIZE {
+        anyhow::bail!(
+            "Storage size '{}' is less than the minimum of {} bytes",
+            size,
+            MIN_STORAGE_SIZE
+        );
+    }
+    Ok(bytes)
+}
+
+/// The storage account an owner's `counter`-th storage account is created at
+pub fn find_storage_account_pda(owner: &Pubkey, counter: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"storage-account", owner.as_ref(), &counter.to_le_bytes()],
+        &SHDW_DRIVE_PROGRAM_ID,
+    )
+    .0
+}
+
+/// The InitializeAccount2 instruction, creating a storage account of
+/// `size` bytes for `owner`, who pays the rent and stakes SHDW for it. The
+/// uploader must sign too, which the Shadow Drive server does.
+fn initialize_account_instruction(
+    owner: &Pubkey,
+    uploader: &Pubkey,
+    counter: u32,
+    name: &str,
+    size: u64,
+) -> Instruction {
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &SHDW_DRIVE_PROGRAM_ID).0;
+    let storage_account = find_storage_account_pda(owner, counter);
+
+    // The Anchor discriminator, then the name as a borsh string and the size
+    let mut data = Sha256::digest(b"global:initialize_account2")[..8].to_vec();
+    data.extend_from_slice(&(name.len() as u32).to_le_bytes());
+    data.extend_from_slice(name.as_bytes());
+    data.extend_from_slice(&size.to_le_bytes());
+
+    Instruction {
+        program_id: SHDW_DRIVE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(pda(&[b"storage-config"]), false),
+            AccountMeta::new(pda(&[b"user-info", owner.as_ref()]), false),
+            AccountMeta::new(storage_account, false),
+            AccountMeta::new(pda(&[b"stake-account", storage_account.as_ref()]), false),
+            AccountMeta::new_readonly(SHDW_MINT, false),
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(*uploader, true),
+            AccountMeta::new(get_associated_token_address(owner, &SHDW_MINT), false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+        data,
+    }
+}
+
+/// A Shadow Drive storage account owned by the uploading keypair
+pub struct ShadowDrive {
+    storage_account: Pubkey,
+    http: Client,
+}
+
+impl ShadowDrive {
+    pub fn new(storage_account: Pubkey) -> Self {
+        Self {
+            storage_account,
+            http: Client::new(),
+        }
+    }
+
+    /// Upload a file, signed by the storage account owner, returning its
+    /// shdw-drive URL. An existing file with the same name is not replaced.
+    pub async fn upload(
+        &self,
+        owner: &dyn Signer,
+        data: &[u8],
+        file_name: &str,
+        content_type: &str,
+    ) -> Result<String> {
+        let file_names_hash = hex(&Sha256::digest(file_name.as_bytes()));
+        let message = format!(
+            "Shadow Drive Signed Message:\nStorage Account: {}\nUpload files with hash: {}",
+            self.storage_account, file_names_hash
+        );
+        let signature = owner.sign_message(message.as_bytes());
+
+        let file = multipart::Part::bytes(data.to_vec())
+            .file_name(file_name.to_string())
+            .mime_str(content_type)?;
+        let form = multipart::Form::new()
+            .part("file", file)
+            .text("message", bs58::encode(signature.as_ref()).into_string())
+            .text("signer", owner.pubkey().to_string())
+            .text("storage_account", self.storage_account.to_string())
+            .text("fileNames", file_name.to_string());
+
+        let response: Value = self
+            .http
+            .post(format!("{}/upload", SHDW_DRIVE_ENDPOINT))
+            .multipart(form)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("Failed to upload to Shadow Drive")?
+            .json()
+            .await
+            .context("Failed to upload to Shadow Drive")?;
+
+        if let Some(errors) = response["upload_errors"].as_array()
+            && !errors.is_empty()
+        {
+            anyhow::bail!(
+                "Shadow Drive rejected the upload: {}",
+                Value::from(errors.clone())
+            );
+        }
+        Ok(response["finalized_locations"][0]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                format!("{}/{}/{}", SHDW_DRIVE_URL, self.storage_account, file_name)
+            }))
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl MetadataClient {
+    /// Create a Shadow Drive storage account of `size` bytes owned by
+    /// `owner`, who pays its rent and stakes SHDW from their token account
+    /// for the space. The transaction is signed by the owner and sent to the
+    /// Shadow Drive server, which co-signs it as uploader and submits it.
+    pub async fn create_shadow_storage_account(
+        &self,
+        owner: &dyn Signer,
+        name: &str,
+        size: u64,
+    ) -> Result<ShadowStorageResult> {
+        if self.is_dry_run() || self.is_sign_only() {
+            anyhow::bail!(
+                "Creating a storage account is not supported with --dry-run or --sign-only"
+            );
+        }
+        if self.cluster() != Cluster::MainnetBeta {
+            anyhow::bail!("Shadow Drive is only available on mainnet-beta");
+        }
+        if name.is_empty() || name.len() > MAX_STORAGE_NAME_LENGTH {
+            anyhow::bail!(
+                "Storage account names are 1 to {} bytes, got {}",
+                MAX_STORAGE_NAME_LENGTH,
+                name.len()
+            );
+        }
+
+        let config_pda =
+            Pubkey::find_program_address(&[b"storage-config"], &SHDW_DRIVE_PROGRAM_ID).0;
+        let config = self
+            .rpc()
+            .get_account_data(&config_pda)
+            .await
+            .context("Failed to fetch the Shadow Drive storage config")?;
+        let uploader = config
+            .get(STORAGE_CONFIG_UPLOADER_OFFSET..STORAGE_CONFIG_UPLOADER_OFFSET + 32)
+            .and_then(|bytes| Pubkey::try_from(bytes).ok())
+            .context("The Shadow Drive storage config is too short")?;
+
+        // The new account is numbered after those the owner already created
+        let user_info = Pubkey::find_program_address(
+            &[b"user-info", owner.pubkey().as_ref()],
+            &SHDW_DRIVE_PROGRAM_ID,
+        )
+        .0;
+        let counter = self
+            .rpc()
+            .get_account_with_commitment(&user_info, self.rpc().commitment())
+            .await
+            .context("Failed to fetch the Shadow Drive user info")?
+            .value
+            .and_then(|account| {
+                let bytes = account
+                    .data
+                    .get(USER_INFO_COUNTER_OFFSET..USER_INFO_COUNTER_OFFSET + 4)?;
+                Some(u32::from_le_bytes(bytes.try_into().ok()?))
+            })
+            .unwrap_or(0);
+
+        let ix = initialize_account_instruction(&owner.pubkey(), &uploader, counter, name, size);
+        let mut tx = Transaction::new_with_payer(&[ix], Some(&owner.pubkey()));
+        let blockhash = self.rpc().get_latest_blockhash().await?;
+        tx.try_partial_sign(&[owner], blockhash)
+            .context("Failed to sign the storage account transaction")?;
+        let tx = bincode::serialize(&tx).context("Failed to serialize transaction")?;
+
+        let response: Value = Client::new()
+            .post(format!("{}/storage-account", SHDW_DRIVE_ENDPOINT))
+            .json(&json!({ "transaction": STANDARD.encode(tx) }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("Failed to create the Shadow Drive storage account")?
+            .json()
+            .await
+            .context("Failed to create the Shadow Drive storage account")?;
+        let signature = response["transaction_signature"]
+            .as_str()
+            .context("Shadow Drive did not return a transaction signature")?;
+
+        Ok(ShadowStorageResult {
+            storage_account: find_storage_account_pda(&owner.pubkey(), counter).to_string(),
+            owner: owner.pubkey().to_string(),
+            name: name.to_string(),
+            size,
+            signature: signature.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_sizes_parse() {
+        assert_eq!(parse_storage_size("2048").unwrap(), 2048);
+        assert_eq!(parse_storage_size("512KB").unwrap(), 512_000);
+        assert_eq!(parse_storage_size("10mb").unwrap(), 10_000_000);
+        assert_eq!(parse_storage_size("1 GiB").unwrap(), 1 << 30);
+        assert_eq!(parse_storage_size("3MiB").unwrap(), 3 << 20);
+    }
+
+    #[test]
+    fn bad_storage_sizes_fail() {
+        assert!(parse_storage_size("").is_err());
+        assert!(parse_storage_size("MB").is_err());
+        assert!(parse_storage_size("10XB").is_err());
+        assert!(parse_storage_size("1000").is_err());
+        assert!(parse_storage_size("99999999999999999999GB").is_err());
+    }
+
+    #[test]
+    fn initialize_account_data_layout() {
+        let owner = Pubkey::new_unique();
+        let ix = initialize_account_instruction(&owner, &Pubkey::new_unique(), 3, "nfts", 1024);
+        assert_eq!(ix.data.len(), 8 + 4 + 4 + 8);
+        assert_eq!(&ix.data[8..12], &4u32.to_le_bytes());
+        assert_eq!(&ix.data[12..16], b"nfts");
+        assert_eq!(&ix.data[16..], &1024u64.to_le_bytes());
+        assert_eq!(ix.accounts[2].pubkey, find_storage_account_pda(&owner, 3));
+        let signers: Vec<_> = ix.accounts.iter().filter(|a| a.is_signer).collect();
+        assert_eq!(signers.len(), 2);
+    }
+}
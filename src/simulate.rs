@@ -0,0 +1,87 @@
+//! Simulating transactions for `--dry-run`, without broadcasting them.
+
+use anyhow::{Context, Result};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::rpc_config::{
+    RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+};
+use solana_sdk::{account::Account, pubkey::Pubkey, transaction::Transaction};
+
+use crate::client::MetadataClient;
+use crate::error::MetadataCliError;
+use crate::types::{AccountChange, SimulationReport};
+
+impl MetadataClient {
+    /// Simulate a signed transaction and report its logs, compute units and
+    /// the changes it would make to its writable accounts. Fails if the
+    /// simulated transaction fails.
+    pub(crate) async fn simulate(&self, tx: &Transaction) -> Result<SimulationReport> {
+        let message = &tx.message;
+        let writable: Vec<Pubkey> = message
+            .account_keys
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| message.is_maybe_writable(*i, None))
+            .map(|(_, key)| *key)
+            .collect();
+
+        let before = self
+            .rpc()
+            .get_multiple_accounts(&writable)
+            .await
+            .context("Failed to fetch accounts before simulation")?;
+
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            commitment: Some(self.rpc().commitment()),
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: writable.iter().map(|key| key.to_string()).collect(),
+            }),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let simulation = self
+            .rpc()
+            .simulate_transaction_with_config(tx, config)
+            .await
+            .context("Failed to simulate transaction")?
+            .value;
+
+        let logs = simulation.logs.unwrap_or_default();
+        if let Some(err) = simulation.err {
+            if let Some(insufficient) = self.insufficient_funds(message, &err, &logs).await? {
+                return Err(insufficient.into());
+            }
+            return Err(MetadataCliError::SimulationFailed { error: err, logs }.into());
+        }
+
+        let after: Vec<Option<Account>> = simulation
+            .accounts
+            .unwrap_or_default()
+            .into_iter()
+            .map(|account| account.and_then(|account| account.decode()))
+            .collect();
+
+        let account_changes = writable
+            .iter()
+            .zip(before)
+            .zip(after)
+            .filter(|((_, before), after)| before != after)
+            .map(|((address, before), after)| AccountChange {
+                address: address.to_string(),
+                lamports_before: before.as_ref().map_or(0, |a| a.lamports),
+                lamports_after: after.as_ref().map_or(0, |a| a.lamports),
+                data_len_before: before.as_ref().map_or(0, |a| a.data.len()),
+                data_len_after: after.as_ref().map_or(0, |a| a.data.len()),
+                owner_after: after.as_ref().map(|a| a.owner.to_string()),
+            })
+            .collect();
+
+        Ok(SimulationReport {
+            signature: tx.signatures[0].to_string(),
+            units_consumed: simulation.units_consumed,
+            logs,
+            account_changes,
+        })
+    }
+}
@@ -0,0 +1,153 @@
+//! Snapshots of who holds a mint, or every item of a collection.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{accounts::TokenRecord, types::TokenState};
+use solana_client::{
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{account::Account, program_pack::Pack, pubkey::Pubkey};
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    state::{Account as TokenAccount, AccountState},
+};
+use std::str::FromStr;
+
+use crate::client::MetadataClient;
+use crate::pda::find_token_record_pda;
+use crate::types::{Holder, SnapshotFailure, SnapshotResult, TokenProgram};
+
+impl MetadataClient {
+    /// The current holders of a mint: every token account with a non-zero
+    /// balance, with its owner and whether it can be moved. NFTs are found
+    /// through the mint's largest accounts; other tokens by scanning the
+    /// token program's accounts, which needs an RPC that allows
+    /// getProgramAccounts.
+    pub async fn mint_holders(&self, mint: &Pubkey) -> Result<Vec<Holder>> {
+        let mint_account = self.fetch_mint(mint).await?;
+        let is_nft = mint_account.decimals == 0 && mint_account.supply <= 1;
+
+        let accounts: Vec<(Pubkey, Account)> = if is_nft {
+            let largest = self
+                .rpc()
+                .get_token_largest_accounts(mint)
+                .await
+                .with_context(|| format!("Failed to fetch the token accounts of {}", mint))?;
+            let addresses = largest
+                .iter()
+                .filter(|balance| balance.amount.amount != "0")
+                .map(|balance| Pubkey::from_str(&balance.address))
+                .collect::<Result<Vec<_>, _>>()
+                .context("Invalid token account address")?;
+            let accounts = self
+                .rpc()
+                .get_multiple_accounts(&addresses)
+                .await
+                .context("Failed to fetch token accounts")?;
+            addresses
+                .into_iter()
+                .zip(accounts)
+                .filter_map(|(address, account)| Some((address, account?)))
+                .collect()
+        } else {
+            let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                mint.as_ref(),
+            ))];
+            // Token-2022 accounts can be longer, with extensions
+            if mint_account.program == TokenProgram::SplToken {
+                filters.push(RpcFilterType::DataSize(TokenAccount::LEN as u64));
+            }
+            self.rpc()
+                .get_program_accounts_with_config(
+                    &mint_account.program.id(),
+                    RpcProgramAccountsConfig {
+                        filters: Some(filters),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .with_context(|| format!("Failed to scan the token accounts of {}", mint))?
+        };
+
+        let mut holders = Vec::new();
+        for (address, account) in accounts {
+            let Ok(state) = StateWithExtensions::<TokenAccount>::unpack(&account.data) else {
+                continue;
+            };
+            let token = state.base;
+            if token.mint != *mint || token.amount == 0 {
+                continue;
+            }
+            // A pNFT's token account is always frozen; whether it can move
+            // is up to its token record
+            let record = if is_nft {
+                self.rpc()
+                    .get_account_data(&find_token_record_pda(mint, &address))
+                    .await
+                    .ok()
+                    .and_then(|data| TokenRecord::from_bytes(&data).ok())
+            } else {
+                None
+            };
+            let frozen = match &record {
+                Some(record) => record.state != TokenState::Unlocked,
+                None => token.state == AccountState::Frozen,
+            };
+            holders.push(Holder {
+                mint: mint.to_string(),
+                owner: token.owner.to_string(),
+                token_account: address.to_string(),
+                amount: token.amount,
+                frozen,
+                token_record_state: record.map(|record| format!("{:?}", record.state)),
+            });
+        }
+        holders.sort_by(|a, b| b.amount.cmp(&a.amount).then_with(|| a.owner.cmp(&b.owner)));
+        Ok(holders)
+    }
+
+    /// A snapshot of the holders of one mint
+    pub async fn mint_snapshot(&self, mint: &Pubkey) -> Result<SnapshotResult> {
+        let holders = self.mint_holders(mint).await?;
+        Ok(SnapshotResult::new(None, 1, holders, Vec::new()))
+    }
+
+    /// The holders of every verified item of a collection, looking up up to
+    /// `concurrency` mints at a time. A mint that can't be looked up is
+    /// recorded as a failure without stopping the others. Compressed items,
+    /// which only a DAS endpoint lists, have no token accounts and are left
+    /// out.
+    pub async fn collection_holders(
+        &self,
+        collection: &Pubkey,
+        concurrency: usize,
+    ) -> Result<SnapshotResult> {
+        let mut items = self.collection_items(collection).await?.verified;
+        items.retain(|item| !item.compressed);
+        let outcomes = self
+            .run_batch(&items, concurrency, |item| async move {
+                let mint = Pubkey::from_str(&item.mint).context("Invalid mint address")?;
+                self.mint_holders(&mint).await
+            })
+            .await;
+
+        let mut holders = Vec::new();
+        let mut failed = Vec::new();
+        for (item, outcome) in items.iter().zip(outcomes) {
+            match outcome {
+                Ok(found) => holders.extend(found),
+                Err(err) => failed.push(SnapshotFailure {
+                    mint: item.mint.clone(),
+                    error: format!("{:#}", err),
+                }),
+            }
+        }
+        Ok(SnapshotResult::new(
+            Some(collection.to_string()),
+            items.len(),
+            holders,
+            failed,
+        ))
+    }
+}
@@ -0,0 +1,120 @@
+//! Creating metadata with an explicit token standard through the unified
+//! CreateV1 instruction.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::instructions::CreateV1Builder;
+use mpl_token_metadata::types::PrintSupply;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use crate::client::{MetadataClient, data_from_args};
+use crate::error::MetadataCliError;
+use crate::memo::uri_hash_instruction;
+use crate::pda::{find_master_edition_pda, find_metadata_pda};
+use crate::types::{CreateMetadataArgs, CreateResult, CreatorInfo, MintAccount, Standard};
+
+impl MetadataClient {
+    /// Create metadata for an existing mint with CreateV1, recording
+    /// `standard` on-chain. The mint's decimals and supply are checked
+    /// against the standard first. Non-fungible standards also get a master
+    /// edition (with no prints), which takes over the mint authority.
+    pub async fn create_with_standard(
+        &self,
+        payer: &dyn Signer,
+        mint: &Pubkey,
+        args: CreateMetadataArgs,
+        standard: Standard,
+    ) -> Result<CreateResult> {
+        let mint_account = self.fetch_mint(mint).await?;
+        mint_account.ensure_mint_authority(&payer.pubkey())?;
+        check_standard(&mint_account, standard)?;
+
+        let metadata_pda = find_metadata_pda(mint);
+        let data = data_from_args(&args, &payer.pubkey())?;
+        let creators = data.creators.clone();
+        let token_standard = standard.token_standard();
+        let update_authority = args.update_authority.unwrap_or(payer.pubkey());
+
+        let mut builder = CreateV1Builder::new();
+        builder
+            .metadata(metadata_pda)
+            .mint(*mint, false)
+            .authority(payer.pubkey())
+            .payer(self.rent_payer(&payer.pubkey()))
+            .update_authority(update_authority, update_authority == payer.pubkey())
+            .spl_token_program(Some(mint_account.program.id()))
+            .name(data.name)
+            .symbol(data.symbol)
+            .uri(data.uri)
+            .seller_fee_basis_points(data.seller_fee_basis_points)
+            .is_mutable(args.is_mutable)
+            .token_standard(token_standard);
+        if let Some(creators) = data.creators {
+            builder.creators(creators);
+        }
+        if let Some(uses) = data.uses {
+            builder.uses(uses);
+        }
+        if standard.is_non_fungible() {
+            builder
+                .master_edition(Some(find_master_edition_pda(mint)))
+                .print_supply(PrintSupply::Zero);
+        }
+
+        let mut instructions = vec![builder.instruction()];
+        if let Some(hash) = &args.uri_hash {
+            instructions.push(uri_hash_instruction(hash, &args.uri)?);
+        }
+
+        let signature = self
+            .send(&instructions, payer, &[])
+            .await
+            .context("Failed to send create metadata transaction")?;
+
+        Ok(CreateResult {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            name: args.name,
+            symbol: args.symbol,
+            uri: args.uri,
+            seller_fee_basis_points: args.seller_fee_basis_points,
+            is_mutable: args.is_mutable,
+            creators: creators.map(|creators| creators.iter().map(CreatorInfo::from).collect()),
+            token_standard: Some(format!("{:?}", token_standard)),
+            uri_sha256: args.uri_hash.map(|hash| hash.sha256),
+            signature: signature.to_string(),
+        })
+    }
+}
+
+/// Check that a mint's decimals and supply fit a token standard
+fn check_standard(mint: &MintAccount, standard: Standard) -> Result<()> {
+    let reason = match standard {
+        Standard::Fungible if mint.decimals == 0 => {
+            "has 0 decimals; use the fungible-asset standard for tokens without decimals"
+                .to_string()
+        }
+        Standard::FungibleAsset if mint.decimals != 0 => format!(
+            "has {} decimals; a fungible asset requires 0",
+            mint.decimals
+        ),
+        Standard::NonFungible | Standard::ProgrammableNonFungible if mint.decimals != 0 => {
+            format!("has {} decimals; a {} requires 0", mint.decimals, standard)
+        }
+        Standard::NonFungible if mint.supply > 1 => format!(
+            "has a supply of {}; a {} allows at most 1",
+            mint.supply, standard
+        ),
+        // pNFT tokens must be minted through the Token Metadata program so
+        // that their token record exists
+        Standard::ProgrammableNonFungible if mint.supply != 0 => format!(
+            "has a supply of {}; a {} must be created before any token is minted",
+            mint.supply, standard
+        ),
+        _ => return Ok(()),
+    };
+    Err(MetadataCliError::InvalidMint {
+        mint: mint.address,
+        reason,
+    }
+    .into())
+}
@@ -0,0 +1,312 @@
+//! Metadata stored in a Token-2022 mint through the metadata-pointer and
+//! TokenMetadata extensions, instead of a Metaplex metadata account.
+
+use anyhow::{Context, Result};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use solana_system_interface::instruction as system_instruction;
+use spl_token_2022::extension::{
+    BaseStateWithExtensions, StateWithExtensions, metadata_pointer::MetadataPointer,
+};
+use spl_token_2022::state::Mint;
+use spl_token_metadata_interface::instruction::{initialize, remove_key, update_field};
+use spl_token_metadata_interface::state::{Field, TokenMetadata};
+
+use crate::client::MetadataClient;
+use crate::error::MetadataCliError;
+use crate::memo::uri_hash_instruction;
+use crate::types::{
+    FieldUpdate, MetadataField, Token2022CreateResult, Token2022MetadataArgs,
+    Token2022MetadataInfo, Token2022UpdateArgs, Token2022UpdateResult,
+};
+
+impl MetadataClient {
+    /// Fetch the TokenMetadata extension stored in a Token-2022 mint
+    pub async fn fetch_token2022_metadata(&self, mint: &Pubkey) -> Result<TokenMetadata> {
+        let (_, metadata) = self.token2022_mint(mint).await?;
+        metadata.with_context(|| format!("Mint {} has no Token-2022 metadata", mint))
+    }
+
+    /// Initialize the TokenMetadata extension of a Token-2022 mint whose
+    /// metadata pointer points at itself, then set any additional fields.
+    /// The payer must be the mint authority and becomes the update
+    /// authority. The lamports needed for the larger account are
+    /// transferred first.
+    pub async fn create_token2022_metadata(
+        &self,
+        payer: &dyn Signer,
+        mint: &Pubkey,
+        args: Token2022MetadataArgs,
+    ) -> Result<Token2022CreateResult> {
+        let (data_len, existing) = self.token2022_mint(mint).await?;
+        if existing.is_some() {
+            anyhow::bail!(
+                "Mint {} already has Token-2022 metadata; update it instead",
+                mint
+            );
+        }
+
+        let mut metadata = TokenMetadata {
+            update_authority: Some(payer.pubkey()).try_into()?,
+            mint: *mint,
+            name: args.name.clone(),
+            symbol: args.symbol.clone(),
+            uri: args.uri.clone(),
+            additional_metadata: Vec::new(),
+        };
+        for field in &args.additional_metadata {
+            metadata.set_key_value(field.key.clone(), field.value.clone());
+        }
+        let new_len = data_len + metadata.tlv_size_of()?;
+        let uri_memo = args
+            .uri_hash
+            .as_ref()
+            .map(|hash| uri_hash_instruction(hash, &args.uri))
+            .transpose()?;
+
+        let mut instructions = Vec::new();
+        let lamports_added = self.rent_top_up(mint, new_len).await?;
+        if lamports_added > 0 {
+            instructions.push(system_instruction::transfer(
+                &self.rent_payer(&payer.pubkey()),
+                mint,
+                lamports_added,
+            ));
+        }
+        instructions.push(initialize(
+            &spl_token_2022::id(),
+            mint,
+            &payer.pubkey(),
+            mint,
+            &payer.pubkey(),
+            args.name,
+            args.symbol,
+            args.uri,
+        ));
+        for field in &args.additional_metadata {
+            instructions.push(update_field(
+                &spl_token_2022::id(),
+                mint,
+                &payer.pubkey(),
+                Field::Key(field.key.clone()),
+                field.value.clone(),
+            ));
+        }
+        instructions.extend(uri_memo);
+
+        let signature = self
+            .send(&instructions, payer, &[])
+            .await
+            .context("Failed to send Token-2022 metadata transaction")?;
+
+        Ok(Token2022CreateResult {
+            metadata: Token2022MetadataInfo::from(&metadata),
+            lamports_added,
+            uri_sha256: args.uri_hash.map(|hash| hash.sha256),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Change the name, symbol, URI and additional fields of a Token-2022
+    /// mint's metadata. Only the fields that differ are sent.
+    pub async fn update_token2022_metadata(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        args: Token2022UpdateArgs,
+    ) -> Result<Token2022UpdateResult> {
+        let (data_len, existing) = self.token2022_mint(mint).await?;
+        let existing =
+            existing.with_context(|| format!("Mint {} has no Token-2022 metadata", mint))?;
+        let update_authority: Option<Pubkey> = existing.update_authority.into();
+        match update_authority {
+            None => return Err(MetadataCliError::Immutable { address: *mint }.into()),
+            Some(update_authority) if update_authority != authority.pubkey() => {
+                return Err(MetadataCliError::NotUpdateAuthority {
+                    signer: authority.pubkey(),
+                    update_authority,
+                }
+                .into());
+            }
+            Some(_) => {}
+        }
+
+        let uri_memo = args
+            .uri_hash
+            .as_ref()
+            .map(|hash| uri_hash_instruction(hash, args.uri.as_deref().unwrap_or(&existing.uri)))
+            .transpose()?;
+        let mut metadata = existing.clone();
+        let mut changes = Vec::new();
+        let mut instructions = Vec::new();
+        let mut set = |field: Field, label: String, old: Option<String>, new: String| {
+            if old.as_deref() == Some(new.as_str()) {
+                return;
+            }
+            metadata.update(field.clone(), new.clone());
+            changes.push(FieldUpdate {
+                field: label,
+                old,
+                new: Some(new.clone()),
+            });
+            instructions.push(update_field(
+                &spl_token_2022::id(),
+                mint,
+                &authority.pubkey(),
+                field,
+                new,
+            ));
+        };
+
+        if let Some(name) = args.name {
+            set(
+                Field::Name,
+                "name".to_string(),
+                Some(existing.name.clone()),
+                name,
+            );
+        }
+        if let Some(symbol) = args.symbol {
+            set(
+                Field::Symbol,
+                "symbol".to_string(),
+                Some(existing.symbol.clone()),
+                symbol,
+            );
+        }
+        if let Some(uri) = args.uri {
+            set(
+                Field::Uri,
+                "uri".to_string(),
+                Some(existing.uri.clone()),
+                uri,
+            );
+        }
+        for field in args.set_fields {
+            let old = existing
+                .additional_metadata
+                .iter()
+                .find(|(key, _)| *key == field.key)
+                .map(|(_, value)| value.clone());
+            set(Field::Key(field.key.clone()), field.key, old, field.value);
+        }
+        for key in args.remove_fields {
+            let old = existing
+                .additional_metadata
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, value)| value.clone());
+            if old.is_none() {
+                continue;
+            }
+            metadata.remove_key(&key);
+            instructions.push(remove_key(
+                &spl_token_2022::id(),
+                mint,
+                &authority.pubkey(),
+                key.clone(),
+                false,
+            ));
+            changes.push(FieldUpdate {
+                field: key,
+                old,
+                new: None,
+            });
+        }
+
+        if instructions.is_empty() {
+            anyhow::bail!("Nothing to update: the metadata already has these values");
+        }
+
+        let new_len = data_len - existing.tlv_size_of()? + metadata.tlv_size_of()?;
+        let lamports_added = self.rent_top_up(mint, new_len).await?;
+        if lamports_added > 0 {
+            instructions.insert(
+                0,
+                system_instruction::transfer(
+                    &self.rent_payer(&authority.pubkey()),
+                    mint,
+                    lamports_added,
+                ),
+            );
+        }
+
+        instructions.extend(uri_memo);
+
+        let signature = self
+            .send(&instructions, authority, &[])
+            .await
+            .context("Failed to send Token-2022 metadata update transaction")?;
+
+        Ok(Token2022UpdateResult {
+            mint: mint.to_string(),
+            changes,
+            lamports_added,
+            uri_sha256: args.uri_hash.map(|hash| hash.sha256),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Fetch a Token-2022 mint that stores its own metadata, returning its
+    /// account size and its TokenMetadata, if initialized
+    async fn token2022_mint(&self, mint: &Pubkey) -> Result<(usize, Option<TokenMetadata>)> {
+        let account = self
+            .rpc()
+            .get_account(mint)
+            .await
+            .with_context(|| format!("Failed to fetch mint account {}", mint))?;
+        if account.owner != spl_token_2022::id() {
+            anyhow::bail!("{} is not a Token-2022 mint", mint);
+        }
+        let state = StateWithExtensions::<Mint>::unpack(&account.data)
+            .with_context(|| format!("{} is not a valid Token-2022 mint", mint))?;
+        let pointer = state
+            .get_extension::<MetadataPointer>()
+            .ok()
+            .and_then(|pointer| Option::<Pubkey>::from(pointer.metadata_address));
+        if pointer != Some(*mint) {
+            anyhow::bail!(
+                "Mint {} has no metadata pointer to itself; embedded metadata needs a mint \
+                 created with the metadata-pointer extension",
+                mint
+            );
+        }
+        let metadata = state.get_variable_len_extension::<TokenMetadata>().ok();
+        Ok((account.data.len(), metadata))
+    }
+
+    /// Lamports to add to an account so it stays rent-exempt at `new_len`
+    async fn rent_top_up(&self, address: &Pubkey, new_len: usize) -> Result<u64> {
+        let required = self
+            .rpc()
+            .get_minimum_balance_for_rent_exemption(new_len)
+            .await
+            .context("Failed to fetch rent exemption")?;
+        let balance = self
+            .rpc()
+            .get_balance(address)
+            .await
+            .with_context(|| format!("Failed to fetch balance of {}", address))?;
+        Ok(required.saturating_sub(balance))
+    }
+}
+
+impl From<&TokenMetadata> for Token2022MetadataInfo {
+    fn from(metadata: &TokenMetadata) -> Self {
+        Self {
+            mint: metadata.mint.to_string(),
+            update_authority: Option::<Pubkey>::from(metadata.update_authority)
+                .map(|authority| authority.to_string()),
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+            additional_metadata: metadata
+                .additional_metadata
+                .iter()
+                .map(|(key, value)| MetadataField {
+                    key: key.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Exporting mints as a Solana token list, the JSON document exchanges and
+//! wallets read token names, symbols, logos and decimals from.
+
+use anyhow::{Context, Result};
+use futures_util::{StreamExt, stream};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::client::MetadataClient;
+use crate::cluster::Cluster;
+use crate::export::ExportRow;
+use crate::offchain::Gateways;
+use crate::types::TokenListSkip;
+
+/// Mints whose decimals and off-chain JSON are fetched at a time
+const CONCURRENT_TOKENS: usize = 8;
+
+/// A token list in the format of the Solana token-list registry
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenList {
+    pub name: String,
+    #[serde(rename = "logoURI", skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    pub keywords: Vec<String>,
+    pub tags: BTreeMap<String, Value>,
+    /// When the list was generated, in RFC 3339
+    pub timestamp: String,
+    pub tokens: Vec<TokenInfo>,
+    pub version: TokenListVersion,
+}
+
+/// One token of a token list
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenInfo {
+    pub chain_id: u16,
+    pub address: String,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+    #[serde(rename = "logoURI", skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    pub tags: Vec<String>,
+    /// The website and description from the off-chain JSON
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TokenListVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+/// The token-list chain id of a cluster: 101 for mainnet, 102 for testnet
+/// and 103 for devnet
+pub fn chain_id(cluster: Cluster) -> Option<u16> {
+    match cluster {
+        Cluster::MainnetBeta => Some(101),
+        Cluster::Testnet => Some(102),
+        Cluster::Devnet => Some(103),
+        Cluster::Localnet | Cluster::Unknown => None,
+    }
+}
+
+impl MetadataClient {
+    /// Build a token list named `name` from the on-chain metadata and mint
+    /// account of every mint and the image, website and description in the
+    /// JSON behind its URI. Mints that are invalid or have no metadata are
+    /// left out and returned with the reason. When the JSON can't be
+    /// fetched the token is still listed, without a logo.
+    pub async fn export_token_list(
+        &self,
+        mints: &[String],
+        name: &str,
+        chain_id: u16,
+        gateways: &Gateways,
+        timeout: Duration,
+    ) -> Result<(TokenList, Vec<TokenListSkip>)> {
+        let rows = self.export_metadata(mints).await?;
+        let entries: Vec<Result<TokenInfo, TokenListSkip>> = stream::iter(rows)
+            .map(|row| async move { self.token_info(row, chain_id, gateways, timeout).await })
+            .buffered(CONCURRENT_TOKENS)
+            .collect()
+            .await;
+        let mut tokens = Vec::new();
+        let mut skipped = Vec::new();
+        for entry in entries {
+            match entry {
+                Ok(token) => tokens.push(token),
+                Err(skip) => skipped.push(skip),
+            }
+        }
+        let list = TokenList {
+            name: name.to_string(),
+            logo_uri: None,
+            keywords: Vec::new(),
+            tags: BTreeMap::new(),
+            timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            tokens,
+            version: TokenListVersion {
+                major: 1,
+                minor: 0,
+                patch: 0,
+            },
+        };
+        Ok((list, skipped))
+    }
+
+    async fn token_info(
+        &self,
+        row: ExportRow,
+        chain_id: u16,
+        gateways: &Gateways,
+        timeout: Duration,
+    ) -> Result<TokenInfo, TokenListSkip> {
+        let skip = |error: String| TokenListSkip {
+            mint: row.mint.clone(),
+            error,
+        };
+        if let Some(error) = &row.error {
+            return Err(skip(error.clone()));
+        }
+        let address = Pubkey::from_str(&row.mint).map_err(|e| skip(e.to_string()))?;
+        let mint = self
+            .fetch_mint(&address)
+            .await
+            .map_err(|e| skip(e.to_string()))?;
+        let json = if row.uri.is_empty() {
+            None
+        } else {
+            self.fetch_offchain_json(gateways, &row.uri, timeout)
+                .await
+                .ok()
+        };
+        let field = |key: &str| {
+            json.as_ref()
+                .and_then(|json| json.get(key))
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let mut extensions = BTreeMap::new();
+        if let Some(website) = field("external_url") {
+            extensions.insert("website".to_string(), website);
+        }
+        if let Some(description) = field("description") {
+            extensions.insert("description".to_string(), description);
+        }
+        Ok(TokenInfo {
+            chain_id,
+            address: row.mint,
+            symbol: row.symbol,
+            name: row.name,
+            decimals: mint.decimals,
+            logo_uri: field("image").map(|image| gateways.resolve(&image)),
+            tags: Vec::new(),
+            extensions,
+        })
+    }
+}
+
+/// Write a token list to a file as pretty-printed JSON
+pub fn write_token_list(path: impl AsRef<Path>, list: &TokenList) -> Result<()> {
+    let path = path.as_ref();
+    let contents = serde_json::to_string_pretty(list)?;
+    std::fs::write(path, contents).with_context(|| format!("Failed to write '{}'", path.display()))
+}
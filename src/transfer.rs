@@ -0,0 +1,134 @@
+//! Transferring NFTs, pNFTs and fungible assets with the unified Transfer
+//! instruction.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{
+    instructions::TransferV1Builder,
+    types::{ProgrammableConfig, TokenStandard, TokenState},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account_client::address::get_associated_token_address_with_program_id;
+
+use crate::client::MetadataClient;
+use crate::pda::{find_master_edition_pda, find_metadata_pda, find_token_record_pda};
+use crate::ruleset::TOKEN_AUTH_RULES_PROGRAM_ID;
+use crate::types::TransferResult;
+
+impl MetadataClient {
+    /// Transfer tokens of a mint from `owner`'s associated token account to
+    /// `destination`'s, which the program creates when missing. The
+    /// authority is the owner, or the transfer or sale delegate of a pNFT.
+    ///
+    /// NFTs and pNFTs always move their single token; fungible assets move
+    /// `amount` base units. For pNFTs the token records of both sides are
+    /// passed, and the transfer is checked against the rule set.
+    pub async fn transfer(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        destination: &Pubkey,
+        amount: Option<u64>,
+    ) -> Result<TransferResult> {
+        if owner == destination {
+            anyhow::bail!("The destination is the current owner");
+        }
+        let metadata = self.fetch_fresh(mint).await?;
+        let token_standard = self.token_standard(mint, &metadata).await;
+        let programmable = matches!(
+            token_standard,
+            TokenStandard::ProgrammableNonFungible | TokenStandard::ProgrammableNonFungibleEdition
+        );
+        let amount = match token_standard {
+            TokenStandard::Fungible | TokenStandard::FungibleAsset => {
+                amount.context("--amount is required for fungible tokens")?
+            }
+            _ => 1,
+        };
+
+        let token_program = self.fetch_mint(mint).await?.program.id();
+        let token = get_associated_token_address_with_program_id(owner, mint, &token_program);
+        let destination_token =
+            get_associated_token_address_with_program_id(destination, mint, &token_program);
+        let destination_exists = self.rpc().get_account(&destination_token).await.is_ok();
+
+        let (token_record, destination_token_record) = if programmable {
+            let token_record = find_token_record_pda(mint, &token);
+            let record = self.fetch_token_record(&token_record).await?;
+            match record.state {
+                TokenState::Locked => anyhow::bail!(
+                    "The pNFT is locked by its {:?} delegate; it must be unlocked first",
+                    record
+                        .delegate_role
+                        .context("Locked token record has no delegate")?
+                ),
+                TokenState::Listed if record.delegate != Some(authority.pubkey()) => {
+                    anyhow::bail!("The pNFT is listed; only its sale delegate can transfer it")
+                }
+                _ => {}
+            }
+            if authority.pubkey() != *owner && record.delegate != Some(authority.pubkey()) {
+                anyhow::bail!(
+                    "{} is neither the owner nor the token delegate of this pNFT",
+                    authority.pubkey()
+                );
+            }
+            (
+                Some(token_record),
+                Some(find_token_record_pda(mint, &destination_token)),
+            )
+        } else {
+            if authority.pubkey() != *owner {
+                anyhow::bail!("Only the owner can transfer tokens that aren't programmable");
+            }
+            (None, None)
+        };
+
+        let rule_set = match metadata.programmable_config {
+            Some(ProgrammableConfig::V1 { rule_set }) if programmable => rule_set,
+            _ => None,
+        };
+        // Master editions and print editions live at the same derived address
+        let edition = match token_standard {
+            TokenStandard::Fungible | TokenStandard::FungibleAsset => None,
+            _ => Some(find_master_edition_pda(mint)),
+        };
+
+        let ix = TransferV1Builder::new()
+            .token(token)
+            .token_owner(*owner)
+            .destination_token(destination_token)
+            .destination_owner(*destination)
+            .mint(*mint)
+            .metadata(find_metadata_pda(mint))
+            .edition(edition)
+            .token_record(token_record)
+            .destination_token_record(destination_token_record)
+            .authority(authority.pubkey())
+            .payer(self.rent_payer(&authority.pubkey()))
+            .spl_token_program(token_program)
+            .authorization_rules_program(rule_set.map(|_| TOKEN_AUTH_RULES_PROGRAM_ID))
+            .authorization_rules(rule_set)
+            .amount(amount)
+            .instruction();
+
+        let signature = self
+            .send(&[ix], authority, &[])
+            .await
+            .context("Failed to send transfer transaction")?;
+
+        Ok(TransferResult {
+            mint: mint.to_string(),
+            token_standard: format!("{:?}", token_standard),
+            from: owner.to_string(),
+            to: destination.to_string(),
+            source_token: token.to_string(),
+            destination_token: destination_token.to_string(),
+            created_destination_token: !destination_exists,
+            amount,
+            destination_token_record: destination_token_record.map(|record| record.to_string()),
+            rule_set: rule_set.map(|rule_set| rule_set.to_string()),
+            signature: signature.to_string(),
+        })
+    }
+}
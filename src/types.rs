@@ -0,0 +1,2001 @@
+//! Arguments and results of [`MetadataClient`](crate::MetadataClient) operations.
+
+use anyhow::Context;
+use mpl_token_metadata::{
+    accounts::{Metadata, TokenRecord},
+    types::{Creator, MetadataDelegateRole, TokenStandard, Uses},
+};
+use serde::Serialize;
+use serde_json::Value;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use crate::error::MetadataCliError;
+use crate::pda::find_metadata_pda;
+
+/// A creator address and its royalty share, parsed from `<PUBKEY>:<SHARE>`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CreatorShare {
+    pub address: Pubkey,
+    pub share: u8,
+}
+
+impl FromStr for CreatorShare {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (address, share) = s
+            .rsplit_once(':')
+            .context("Expected creator in the form <PUBKEY>:<SHARE>")?;
+        let address = Pubkey::from_str(address).context("Invalid creator address")?;
+        let share = share
+            .parse::<u8>()
+            .context("Creator share must be a number between 0 and 100")?;
+        Ok(Self { address, share })
+    }
+}
+
+/// Compute unit price for transactions, parsed from a number of
+/// micro-lamports or `auto`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriorityFee {
+    /// A fixed price in micro-lamports per compute unit
+    Fixed(u64),
+    /// Estimated from the fees recently paid for the same accounts
+    Auto,
+}
+
+impl FromStr for PriorityFee {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(Self::Auto);
+        }
+        let micro_lamports = s
+            .parse::<u64>()
+            .context("Priority fee must be a number of micro-lamports or 'auto'")?;
+        Ok(Self::Fixed(micro_lamports))
+    }
+}
+
+/// The token program that owns a mint
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenProgram {
+    /// The original SPL Token program
+    SplToken,
+    /// The Token-2022 (Token Extensions) program
+    Token2022,
+}
+
+impl TokenProgram {
+    /// The program's address
+    pub fn id(&self) -> Pubkey {
+        match self {
+            Self::SplToken => spl_token::id(),
+            Self::Token2022 => spl_token_2022::id(),
+        }
+    }
+}
+
+impl std::fmt::Display for TokenProgram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SplToken => write!(f, "SPL Token"),
+            Self::Token2022 => write!(f, "Token-2022"),
+        }
+    }
+}
+
+/// A mint account of either token program
+#[derive(Clone, Debug)]
+pub struct MintAccount {
+    pub address: Pubkey,
+    pub program: TokenProgram,
+    pub mint_authority: Option<Pubkey>,
+    pub supply: u64,
+    pub decimals: u8,
+    /// Where a Token-2022 mint's metadata pointer points, if it has one
+    pub metadata_pointer: Option<Pubkey>,
+}
+
+impl MintAccount {
+    /// Whether the mint keeps its metadata in its own TokenMetadata
+    /// extension rather than in a Metaplex metadata account
+    pub fn stores_own_metadata(&self) -> bool {
+        self.program == TokenProgram::Token2022 && self.metadata_pointer == Some(self.address)
+    }
+
+    /// Fail unless `signer` is the mint authority, which creating metadata
+    /// needs
+    pub fn ensure_mint_authority(&self, signer: &Pubkey) -> anyhow::Result<()> {
+        if self.mint_authority != Some(*signer) {
+            return Err(MetadataCliError::NotMintAuthority {
+                signer: *signer,
+                mint: self.address,
+                mint_authority: self.mint_authority,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// A warning when the mint's metadata pointer names neither the mint
+    /// itself nor its Metaplex metadata account, so wallets will read the
+    /// metadata from somewhere this tool does not write to
+    pub fn metadata_pointer_warning(&self) -> Option<String> {
+        let pointer = self.metadata_pointer?;
+        if pointer == self.address || pointer == find_metadata_pda(&self.address) {
+            return None;
+        }
+        Some(format!(
+            "The metadata pointer of mint {} points at {}, which is neither the mint nor its \
+             Metaplex metadata account; wallets will read metadata from there",
+            self.address, pointer
+        ))
+    }
+}
+
+/// Token standard to record when creating metadata, parsed from
+/// `fungible`, `fungible-asset`, `non-fungible` (`nft`) or
+/// `programmable-non-fungible` (`pnft`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Standard {
+    /// A fungible token with decimals
+    Fungible,
+    /// A semi-fungible token without decimals, such as a game item
+    FungibleAsset,
+    /// A classic NFT with a master edition
+    NonFungible,
+    /// An NFT whose transfers are checked against a rule set
+    ProgrammableNonFungible,
+}
+
+impl Standard {
+    pub fn token_standard(&self) -> TokenStandard {
+        match self {
+            Self::Fungible => TokenStandard::Fungible,
+            Self::FungibleAsset => TokenStandard::FungibleAsset,
+            Self::NonFungible => TokenStandard::NonFungible,
+            Self::ProgrammableNonFungible => TokenStandard::ProgrammableNonFungible,
+        }
+    }
+
+    /// Whether the standard needs a master edition and a 0-decimal mint
+    pub fn is_non_fungible(&self) -> bool {
+        matches!(self, Self::NonFungible | Self::ProgrammableNonFungible)
+    }
+}
+
+impl FromStr for Standard {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "fungible" => Ok(Self::Fungible),
+            "fungible-asset" => Ok(Self::FungibleAsset),
+            "non-fungible" | "nft" => Ok(Self::NonFungible),
+            "programmable-non-fungible" | "pnft" => Ok(Self::ProgrammableNonFungible),
+            _ => anyhow::bail!(
+                "Standard must be fungible, fungible-asset, non-fungible (nft) or \
+                 programmable-non-fungible (pnft)"
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for Standard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fungible => write!(f, "fungible token"),
+            Self::FungibleAsset => write!(f, "fungible asset"),
+            Self::NonFungible => write!(f, "non-fungible token"),
+            Self::ProgrammableNonFungible => write!(f, "programmable non-fungible token"),
+        }
+    }
+}
+
+/// What a delegate may do, parsed from `data`, `collection` or
+/// `programmable-config` for metadata delegates, and `transfer`, `sale`,
+/// `utility`, `staking` or `standard` for token delegates
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DelegateRole {
+    /// May update the metadata's data fields
+    Data,
+    /// May set and verify collections
+    Collection,
+    /// May change a pNFT's rule set
+    ProgrammableConfig,
+    /// May transfer a pNFT once
+    Transfer,
+    /// May transfer a pNFT while it is listed; the owner can't move it
+    Sale,
+    /// May lock, unlock and burn a pNFT
+    Utility,
+    /// May lock and unlock a pNFT
+    Staking,
+    /// An SPL Token delegate of a regular token, which may also freeze it
+    Standard,
+}
+
+impl DelegateRole {
+    /// Roles stored in a metadata delegate record
+    pub const METADATA_ROLES: [Self; 3] = [Self::Data, Self::Collection, Self::ProgrammableConfig];
+
+    /// The metadata delegate record role, or `None` for token delegates
+    pub fn metadata_delegate_role(&self) -> Option<MetadataDelegateRole> {
+        match self {
+            Self::Data => Some(MetadataDelegateRole::Data),
+            Self::Collection => Some(MetadataDelegateRole::Collection),
+            Self::ProgrammableConfig => Some(MetadataDelegateRole::ProgrammableConfig),
+            _ => None,
+        }
+    }
+
+    /// Whether the delegate acts on the owner's tokens rather than the metadata
+    pub fn is_token_role(&self) -> bool {
+        self.metadata_delegate_role().is_none()
+    }
+}
+
+impl FromStr for DelegateRole {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "data" => Ok(Self::Data),
+            "collection" => Ok(Self::Collection),
+            "programmable-config" => Ok(Self::ProgrammableConfig),
+            "transfer" => Ok(Self::Transfer),
+            "sale" => Ok(Self::Sale),
+            "utility" => Ok(Self::Utility),
+            "staking" => Ok(Self::Staking),
+            "standard" => Ok(Self::Standard),
+            _ => anyhow::bail!(
+                "Role must be data, collection, programmable-config, transfer, sale, utility, \
+                 staking or standard"
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for DelegateRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Data => write!(f, "data"),
+            Self::Collection => write!(f, "collection"),
+            Self::ProgrammableConfig => write!(f, "programmable-config"),
+            Self::Transfer => write!(f, "transfer"),
+            Self::Sale => write!(f, "sale"),
+            Self::Utility => write!(f, "utility"),
+            Self::Staking => write!(f, "staking"),
+            Self::Standard => write!(f, "standard"),
+        }
+    }
+}
+
+/// How a use is consumed, parsed from `burn`, `multiple` or `single`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UseMethod {
+    /// The token is burned on its last use
+    Burn,
+    /// The token can be used several times and is kept
+    Multiple,
+    /// The token can be used once and is kept
+    Single,
+}
+
+impl FromStr for UseMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "burn" => Ok(Self::Burn),
+            "multiple" => Ok(Self::Multiple),
+            "single" => Ok(Self::Single),
+            _ => anyhow::bail!("Use method must be burn, multiple or single"),
+        }
+    }
+}
+
+/// Uses to configure on a token, e.g. the entries of a ticket
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UsesArgs {
+    pub use_method: UseMethod,
+    pub total: u64,
+}
+
+impl UsesArgs {
+    /// Validate a use method and total. A single use has a total of 1,
+    /// which is also its default.
+    pub fn new(use_method: UseMethod, total: Option<u64>) -> anyhow::Result<Self> {
+        let total = match (use_method, total) {
+            (UseMethod::Single, None | Some(1)) => 1,
+            (UseMethod::Single, Some(_)) => anyhow::bail!("A single use has a total of 1"),
+            (_, None) => anyhow::bail!("--use-total is required for {:?} uses", use_method),
+            (_, Some(0)) => anyhow::bail!("--use-total must be at least 1"),
+            (_, Some(total)) => total,
+        };
+        Ok(Self { use_method, total })
+    }
+
+    /// The on-chain uses, with `remaining` uses left
+    pub fn uses(&self, remaining: u64) -> Uses {
+        Uses {
+            use_method: match self.use_method {
+                UseMethod::Burn => mpl_token_metadata::types::UseMethod::Burn,
+                UseMethod::Multiple => mpl_token_metadata::types::UseMethod::Multiple,
+                UseMethod::Single => mpl_token_metadata::types::UseMethod::Single,
+            },
+            remaining,
+            total: self.total,
+        }
+    }
+}
+
+/// Fields written when creating a metadata account
+#[derive(Clone, Debug)]
+pub struct CreateMetadataArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub is_mutable: bool,
+    pub creators: Option<Vec<CreatorShare>>,
+    pub uses: Option<UsesArgs>,
+    /// Hash of the JSON behind the URI, recorded in a memo in the same
+    /// transaction
+    pub uri_hash: Option<UriHash>,
+    /// Update authority of the new metadata; the signer when `None`
+    pub update_authority: Option<Pubkey>,
+}
+
+/// Fields to change on an existing metadata account; `None` keeps the current value
+#[derive(Clone, Debug, Default)]
+pub struct UpdateMetadataArgs {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub uri: Option<String>,
+    pub creators: Option<Vec<CreatorShare>>,
+    pub uses: Option<UsesArgs>,
+    /// Mark the primary sale as happened; it can't be cleared once set
+    pub primary_sale_happened: bool,
+    /// Hash of the JSON behind the new URI, recorded in a memo in the same
+    /// transaction
+    pub uri_hash: Option<UriHash>,
+}
+
+/// The SHA-256 of the JSON a metadata URI served, so a later swap of the
+/// off-chain content can be detected
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct UriHash {
+    pub uri: String,
+    /// Lowercase hex digest of the bytes served
+    pub sha256: String,
+}
+
+impl UriHash {
+    /// The memo recording the hash: `{"uri":"...","sha256":"..."}`
+    pub fn memo(&self) -> String {
+        serde_json::json!({ "uri": self.uri, "sha256": self.sha256 }).to_string()
+    }
+}
+
+/// Result of creating a metadata account
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub is_mutable: bool,
+    pub creators: Option<Vec<CreatorInfo>>,
+    /// The standard recorded by CreateV1, when one was chosen
+    pub token_standard: Option<String>,
+    /// The hash of the URI's JSON recorded in the transaction's memo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri_sha256: Option<String>,
+    pub signature: String,
+}
+
+/// Old and new value of a metadata field touched by an update
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldChange {
+    pub old: String,
+    pub new: String,
+}
+
+/// Result of updating a metadata account
+#[derive(Clone, Debug, Serialize)]
+pub struct UpdateResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub name: FieldChange,
+    pub symbol: FieldChange,
+    pub uri: FieldChange,
+    /// The new creators list, if it was replaced
+    pub creators: Option<Vec<CreatorInfo>>,
+    /// The new uses, if they were changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uses: Option<UsesInfo>,
+    /// Set when the update marked the primary sale as happened
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_sale_happened: Option<bool>,
+    /// The hash of the new URI's JSON recorded in the transaction's memo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri_sha256: Option<String>,
+    pub signature: String,
+}
+
+/// The state of a mint's metadata before a change the CLI sent, from the
+/// local history
+#[derive(Clone, Debug, Serialize)]
+pub struct HistoryEntry {
+    /// Pass to `rollback` to put this state back
+    pub id: i64,
+    pub mint: String,
+    pub metadata_pda: String,
+    /// Slot the state was read at, just before the change was sent
+    pub slot: u64,
+    /// The transaction that changed it
+    pub signature: String,
+    /// Unix time the change landed
+    pub recorded_at: u64,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<CreatorInfo>>,
+    pub update_authority: String,
+    pub is_mutable: bool,
+    pub primary_sale_happened: bool,
+}
+
+impl HistoryEntry {
+    pub(crate) fn new(
+        id: i64,
+        metadata_pda: String,
+        slot: u64,
+        signature: String,
+        recorded_at: u64,
+        metadata: &Metadata,
+    ) -> Self {
+        Self {
+            id,
+            mint: metadata.mint.to_string(),
+            metadata_pda,
+            slot,
+            signature,
+            recorded_at,
+            name: metadata.name.trim_end_matches('\0').to_string(),
+            symbol: metadata.symbol.trim_end_matches('\0').to_string(),
+            uri: metadata.uri.trim_end_matches('\0').to_string(),
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            creators: metadata
+                .creators
+                .as_ref()
+                .map(|creators| creators.iter().map(CreatorInfo::from).collect()),
+            update_authority: metadata.update_authority.to_string(),
+            is_mutable: metadata.is_mutable,
+            primary_sale_happened: metadata.primary_sale_happened,
+        }
+    }
+}
+
+/// Recorded states of a mint's metadata, newest first
+#[derive(Clone, Debug, Serialize)]
+pub struct HistoryResult {
+    pub mint: String,
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// Result of putting back a recorded state of a mint's metadata
+#[derive(Clone, Debug, Serialize)]
+pub struct RollbackResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    /// The history entry that was put back
+    pub entry: i64,
+    /// The transaction whose change was undone
+    pub undone_signature: String,
+    pub name: FieldChange,
+    pub symbol: FieldChange,
+    pub uri: FieldChange,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<CreatorInfo>>,
+    /// Set when the update authority was handed back
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_authority: Option<FieldChange>,
+    pub signature: String,
+}
+
+/// Result of transferring the update authority of a metadata account
+#[derive(Clone, Debug, Serialize)]
+pub struct SetAuthorityResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub old_authority: String,
+    pub new_authority: String,
+    pub signature: String,
+}
+
+/// Result of making a metadata account immutable
+#[derive(Clone, Debug, Serialize)]
+pub struct MakeImmutableResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub signature: String,
+}
+
+/// Result of closing the accounts of a burned mint
+#[derive(Clone, Debug, Serialize)]
+pub struct CloseResult {
+    pub mint: String,
+    /// Accounts closed, and the rent returned to the payer from them;
+    /// absent for dry runs and offline signing
+    pub closed_accounts: Option<Vec<String>>,
+    pub lamports_recovered: Option<u64>,
+    pub signature: String,
+}
+
+/// Result of resizing a mint's metadata account
+#[derive(Clone, Debug, Serialize)]
+pub struct ResizeResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    /// The master edition or edition, which is resized too
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edition: Option<String>,
+    pub size_before: usize,
+    /// Size and rent recovered once the transaction landed; absent for dry
+    /// runs and offline signing
+    pub size_after: Option<usize>,
+    pub lamports_recovered: Option<u64>,
+    pub signature: String,
+}
+
+/// Result of marking a mint's primary sale as happened
+#[derive(Clone, Debug, Serialize)]
+pub struct PrimarySaleResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    /// The holder's token account, when a token holder signed rather than
+    /// the update authority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_account: Option<String>,
+    pub signature: String,
+}
+
+/// Result of creating a bare mint, without metadata
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateMintResult {
+    pub mint: String,
+    pub decimals: u8,
+    pub mint_authority: String,
+    /// `None` when freezing is disabled
+    pub freeze_authority: Option<String>,
+    /// Base units minted to the owner
+    pub initial_supply: u64,
+    /// Token account holding the initial supply, if any was minted
+    pub token_account: Option<String>,
+    pub signature: String,
+}
+
+/// Result of creating a new fungible token with metadata
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateTokenResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub decimals: u8,
+    /// Base units minted to the payer
+    pub initial_supply: u64,
+    /// Token account holding the initial supply, if any was minted
+    pub token_account: Option<String>,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub signature: String,
+}
+
+/// Result of creating a new NFT end-to-end
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateNftResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub master_edition_pda: String,
+    pub token_account: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    /// Maximum number of printable editions; `None` means unlimited
+    pub max_supply: Option<u64>,
+    /// Token record of a programmable NFT's token account
+    pub token_record: Option<String>,
+    /// Authorization rule set checked on transfers of a programmable NFT
+    pub rule_set: Option<String>,
+    pub signature: String,
+}
+
+/// Result of creating a Shadow Drive storage account
+#[derive(Clone, Debug, Serialize)]
+pub struct ShadowStorageResult {
+    pub storage_account: String,
+    pub owner: String,
+    pub name: String,
+    /// Bytes of storage reserved
+    pub size: u64,
+    pub signature: String,
+}
+
+/// Result of creating a Merkle tree for compressed NFTs
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateTreeResult {
+    pub tree: String,
+    pub tree_config: String,
+    pub tree_creator: String,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub canopy_depth: u32,
+    /// Whether anyone may mint into the tree, not just its creator
+    pub public: bool,
+    /// Compressed NFTs the tree can hold
+    pub capacity: u64,
+    /// Size of the tree account in bytes
+    pub account_size: u64,
+    /// Rent paid for the tree account
+    pub lamports: u64,
+    pub signature: String,
+}
+
+/// Result of minting a compressed NFT
+#[derive(Clone, Debug, Serialize)]
+pub struct CompressedMintResult {
+    /// ID of the asset, derived from the tree and the leaf's index
+    pub asset_id: String,
+    pub tree: String,
+    pub leaf_index: u64,
+    pub owner: String,
+    /// Collection the NFT was minted into, verified
+    pub collection: Option<String>,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub signature: String,
+}
+
+/// Result of updating a compressed NFT's metadata
+#[derive(Clone, Debug, Serialize)]
+pub struct CompressedUpdateResult {
+    pub asset_id: String,
+    pub tree: String,
+    pub name: FieldChange,
+    pub symbol: FieldChange,
+    pub uri: FieldChange,
+    pub signature: String,
+}
+
+/// Result of transferring a compressed NFT
+#[derive(Clone, Debug, Serialize)]
+pub struct CompressedTransferResult {
+    pub asset_id: String,
+    pub tree: String,
+    pub from: String,
+    pub to: String,
+    /// Whether the DAS API reported the new owner before we stopped waiting
+    pub owner_confirmed: bool,
+    pub signature: String,
+}
+
+/// Result of burning a compressed NFT
+#[derive(Clone, Debug, Serialize)]
+pub struct CompressedBurnResult {
+    pub asset_id: String,
+    pub tree: String,
+    pub owner: String,
+    /// Whether the DAS API reported the asset as burned before we stopped
+    /// waiting
+    pub burn_confirmed: bool,
+    pub signature: String,
+}
+
+/// Result of decompressing a compressed NFT into a regular NFT
+#[derive(Clone, Debug, Serialize)]
+pub struct DecompressResult {
+    pub asset_id: String,
+    pub tree: String,
+    pub voucher: String,
+    pub mint: String,
+    pub metadata_pda: String,
+    pub master_edition_pda: String,
+    pub token_account: String,
+    /// `None` when the asset had already been redeemed
+    pub redeem_signature: Option<String>,
+    pub signature: String,
+}
+
+/// A Metaplex Core asset
+#[derive(Clone, Debug, Serialize)]
+pub struct CoreAssetInfo {
+    pub address: String,
+    pub owner: String,
+    /// The key that signs updates, which is the collection's update
+    /// authority when the asset is in a collection; `None` when the asset
+    /// can no longer be updated
+    pub update_authority: Option<String>,
+    pub collection: Option<String>,
+    pub name: String,
+    pub uri: String,
+    pub plugins: Vec<CorePluginInfo>,
+}
+
+/// A plugin on a Core asset or collection
+#[derive(Clone, Debug, Serialize)]
+pub struct CorePluginInfo {
+    pub plugin: String,
+    /// Who manages the plugin: an address, "owner", "update authority" or
+    /// "none"
+    pub authority: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+/// The Core asset or collection a plugin command works on
+#[derive(Clone, Copy, Debug)]
+pub enum CoreTarget {
+    Asset(Pubkey),
+    Collection(Pubkey),
+}
+
+impl CoreTarget {
+    pub fn address(&self) -> &Pubkey {
+        match self {
+            Self::Asset(address) | Self::Collection(address) => address,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Asset(_) => "asset",
+            Self::Collection(_) => "collection",
+        }
+    }
+}
+
+/// A Core plugin to add, or to change on a target that already has it
+#[derive(Clone, Debug)]
+pub enum CorePlugin {
+    /// Replace the royalty rate and the creators it is split between
+    Royalties {
+        basis_points: u16,
+        creators: Vec<CreatorShare>,
+    },
+    /// Set or change attributes by key, and remove others
+    Attributes {
+        set: Vec<MetadataField>,
+        remove: Vec<String>,
+    },
+    /// Hand the freeze delegate role to `delegate`, and freeze or thaw the
+    /// asset
+    FreezeDelegate {
+        delegate: Option<Pubkey>,
+        frozen: Option<bool>,
+    },
+    /// Hand the transfer delegate role to `delegate`
+    TransferDelegate { delegate: Pubkey },
+}
+
+impl CorePlugin {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Royalties { .. } => "royalties",
+            Self::Attributes { .. } => "attributes",
+            Self::FreezeDelegate { .. } => "freeze-delegate",
+            Self::TransferDelegate { .. } => "transfer-delegate",
+        }
+    }
+
+    /// Whether the asset's owner, rather than the update authority, manages
+    /// the plugin
+    pub fn owner_managed(&self) -> bool {
+        matches!(
+            self,
+            Self::FreezeDelegate { .. } | Self::TransferDelegate { .. }
+        )
+    }
+}
+
+/// Result of adding or updating a Core plugin
+#[derive(Clone, Debug, Serialize)]
+pub struct CorePluginResult {
+    pub target: String,
+    /// "asset" or "collection"
+    pub target_kind: String,
+    pub plugin: String,
+    /// Whether the plugin was added, rather than updated
+    pub added: bool,
+    /// The delegate the plugin was handed to, if any
+    pub delegate: Option<String>,
+    pub signature: String,
+}
+
+/// Result of creating a Core asset
+#[derive(Clone, Debug, Serialize)]
+pub struct CoreCreateResult {
+    pub asset: CoreAssetInfo,
+    pub signature: String,
+}
+
+/// Result of updating a Core asset
+#[derive(Clone, Debug, Serialize)]
+pub struct CoreUpdateResult {
+    pub address: String,
+    pub name: FieldChange,
+    pub uri: FieldChange,
+    pub signature: String,
+}
+
+/// Result of burning a Core asset
+#[derive(Clone, Debug, Serialize)]
+pub struct CoreBurnResult {
+    pub address: String,
+    pub name: String,
+    pub collection: Option<String>,
+    pub signature: String,
+}
+
+/// A transaction sent while inscribing
+#[derive(Clone, Debug, Serialize)]
+pub struct InscriptionTransaction {
+    /// "initialize", "allocate", or "write <START>..<END>" with the byte
+    /// range written
+    pub step: String,
+    pub signature: String,
+}
+
+/// Result of grinding a vanity keypair
+#[derive(Clone, Debug, Serialize)]
+pub struct GrindResult {
+    pub pubkey: String,
+    /// Keypair file the keypair was written to
+    pub out: String,
+    pub attempts: u64,
+    pub seconds: f64,
+}
+
+/// Result of inscribing data on-chain for a mint
+#[derive(Clone, Debug, Serialize)]
+pub struct InscribeResult {
+    pub mint: String,
+    pub inscription: String,
+    pub inscription_metadata: String,
+    pub bytes: u64,
+    /// Chunks that an earlier, interrupted run had already written
+    pub chunks_skipped: usize,
+    pub transactions: Vec<InscriptionTransaction>,
+}
+
+/// Result of minting a new collection NFT
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateCollectionResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub master_edition_pda: String,
+    pub token_account: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub signature: String,
+}
+
+/// Result of setting the size of a collection NFT
+#[derive(Clone, Debug, Serialize)]
+pub struct SetCollectionSizeResult {
+    pub collection_mint: String,
+    pub metadata_pda: String,
+    pub size: u64,
+    pub signature: String,
+}
+
+/// Result of creating a master edition for an existing mint
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateMasterEditionResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub master_edition_pda: String,
+    /// Maximum number of printable editions; `None` means unlimited
+    pub max_supply: Option<u64>,
+    pub signature: String,
+}
+
+/// Result of printing a numbered edition from a master edition
+#[derive(Clone, Debug, Serialize)]
+pub struct PrintEditionResult {
+    pub master_mint: String,
+    /// Mint of the newly printed edition
+    pub mint: String,
+    pub metadata_pda: String,
+    pub edition_pda: String,
+    pub token_account: String,
+    /// Edition number that was printed
+    pub edition: u64,
+    /// Maximum number of printable editions; `None` means unlimited
+    pub max_supply: Option<u64>,
+    pub signature: String,
+}
+
+/// Result of attaching a mint to a verified collection
+#[derive(Clone, Debug, Serialize)]
+pub struct SetCollectionResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub collection_mint: String,
+    /// Whether the collection tracks its size on-chain
+    pub sized: bool,
+    pub signature: String,
+}
+
+/// Result of detaching a mint from its collection
+#[derive(Clone, Debug, Serialize)]
+pub struct UnverifyCollectionResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub collection_mint: String,
+    /// Whether the collection tracks its size on-chain
+    pub sized: bool,
+    /// Whether the collection was verified before this operation
+    pub was_verified: bool,
+    /// Whether the collection field was cleared from the metadata
+    pub cleared: bool,
+    pub signature: String,
+}
+
+/// Result of burning an asset
+#[derive(Clone, Debug, Serialize)]
+pub struct BurnResult {
+    pub mint: String,
+    pub token_standard: String,
+    /// Base units burned
+    pub amount: u64,
+    /// Accounts closed by the burn, and the rent returned to the owner from
+    /// them; absent for dry runs and offline signing
+    pub closed_accounts: Option<Vec<String>>,
+    pub lamports_recovered: Option<u64>,
+    pub signature: String,
+}
+
+/// Result of verifying or unverifying a creator on a metadata account
+#[derive(Clone, Debug, Serialize)]
+pub struct CreatorVerificationResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub creator: String,
+    pub verified_before: bool,
+    pub verified_after: bool,
+    pub signature: String,
+}
+
+/// Result of verifying several creators on a metadata account in one
+/// transaction
+#[derive(Clone, Debug, Serialize)]
+pub struct CreatorsVerificationResult {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub creators: Vec<CreatorVerification>,
+    pub signature: String,
+}
+
+/// Verified flag of one creator before and after a verification
+#[derive(Clone, Debug, Serialize)]
+pub struct CreatorVerification {
+    pub creator: String,
+    pub verified_before: bool,
+    pub verified_after: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CreatorInfo {
+    pub address: String,
+    pub verified: bool,
+    pub share: u8,
+}
+
+impl From<&Creator> for CreatorInfo {
+    fn from(creator: &Creator) -> Self {
+        Self {
+            address: creator.address.to_string(),
+            verified: creator.verified,
+            share: creator.share,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CollectionInfo {
+    pub key: String,
+    pub verified: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct UsesInfo {
+    pub use_method: String,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+impl From<&Uses> for UsesInfo {
+    fn from(uses: &Uses) -> Self {
+        Self {
+            use_method: format!("{:?}", uses.use_method),
+            remaining: uses.remaining,
+            total: uses.total,
+        }
+    }
+}
+
+/// Display-friendly view of an on-chain metadata account
+#[derive(Clone, Debug, Serialize)]
+pub struct MetadataInfo {
+    pub mint: String,
+    pub metadata_pda: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub update_authority: String,
+    pub is_mutable: bool,
+    pub primary_sale_happened: bool,
+    pub token_standard: Option<String>,
+    pub creators: Option<Vec<CreatorInfo>>,
+    pub collection: Option<CollectionInfo>,
+    pub uses: Option<UsesInfo>,
+    /// Where a compressed NFT lives, when it was read from the DAS API
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionInfo>,
+    /// Fields from the off-chain JSON, when it was requested and fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offchain: Option<OffChainInfo>,
+    /// Why the off-chain JSON could not be fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offchain_error: Option<String>,
+    /// Metadata delegates, when they were requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegates: Option<Vec<DelegateInfo>>,
+}
+
+/// The identifying fields of a metadata account, as listed by a scan
+#[derive(Clone, Debug, Serialize)]
+pub struct MetadataSummary {
+    /// The mint, or for a compressed NFT its asset id
+    pub mint: String,
+    /// Empty for compressed NFTs, which have no metadata account
+    pub metadata_pda: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub update_authority: String,
+    /// Only assets read from the DAS API can be compressed
+    pub compressed: bool,
+}
+
+impl From<&Metadata> for MetadataSummary {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            mint: metadata.mint.to_string(),
+            metadata_pda: find_metadata_pda(&metadata.mint).to_string(),
+            name: metadata.name.trim_end_matches('\0').to_string(),
+            symbol: metadata.symbol.trim_end_matches('\0').to_string(),
+            uri: metadata.uri.trim_end_matches('\0').to_string(),
+            update_authority: metadata.update_authority.to_string(),
+            compressed: false,
+        }
+    }
+}
+
+/// The tokens whose metadata points at a collection
+#[derive(Clone, Debug, Serialize)]
+pub struct CollectionItems {
+    pub collection: String,
+    /// Where the items came from: `das` or `program-accounts`
+    pub source: String,
+    /// Members the collection authority has verified
+    pub verified: Vec<MetadataSummary>,
+    /// Tokens that name the collection without being verified
+    pub unverified: Vec<MetadataSummary>,
+}
+
+/// A watched field whose value changed
+#[derive(Clone, Debug, Serialize)]
+pub struct ChangedField {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// A change to a watched metadata account
+#[derive(Clone, Debug, Serialize)]
+pub struct MetadataEvent {
+    pub mint: String,
+    pub metadata_pda: String,
+    /// Slot the new state was observed at
+    pub slot: u64,
+    /// The account didn't exist before
+    pub created: bool,
+    /// The account no longer exists, or no longer holds metadata
+    pub closed: bool,
+    pub changes: Vec<ChangedField>,
+    /// The latest transaction touching the account, most likely the one
+    /// that made the change
+    pub signature: Option<String>,
+}
+
+/// Summary of a metadata export
+#[derive(Clone, Debug, Serialize)]
+pub struct ExportResult {
+    pub total: usize,
+    /// Mints that were invalid or had no readable metadata
+    pub missing: usize,
+    pub out: String,
+}
+
+/// A mint left out of a token list
+#[derive(Clone, Debug, Serialize)]
+pub struct TokenListSkip {
+    pub mint: String,
+    pub error: String,
+}
+
+/// Summary of a token-list export
+#[derive(Clone, Debug, Serialize)]
+pub struct TokenListResult {
+    /// Tokens written to the list
+    pub tokens: usize,
+    /// Listed tokens whose off-chain JSON had no usable image
+    pub without_logo: usize,
+    pub skipped: Vec<TokenListSkip>,
+    pub out: String,
+}
+
+/// A token account holding a mint, as recorded by a snapshot
+#[derive(Clone, Debug, Serialize)]
+pub struct Holder {
+    pub mint: String,
+    pub owner: String,
+    pub token_account: String,
+    pub amount: u64,
+    /// Whether the owner can't move the token: the account is frozen or,
+    /// for a pNFT, its token record is locked or listed
+    pub frozen: bool,
+    /// Unlocked, Locked or Listed, for pNFTs
+    pub token_record_state: Option<String>,
+}
+
+/// A mint whose holders could not be looked up
+#[derive(Clone, Debug, Serialize)]
+pub struct SnapshotFailure {
+    pub mint: String,
+    pub error: String,
+}
+
+/// The holders of a mint or of every item of a collection
+#[derive(Clone, Debug, Serialize)]
+pub struct SnapshotResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    /// Mints looked up
+    pub mints: usize,
+    /// Distinct owners across all holders
+    pub owners: usize,
+    pub holders: Vec<Holder>,
+    pub failed: Vec<SnapshotFailure>,
+}
+
+impl SnapshotResult {
+    pub(crate) fn new(
+        collection: Option<String>,
+        mints: usize,
+        holders: Vec<Holder>,
+        failed: Vec<SnapshotFailure>,
+    ) -> Self {
+        let owners = holders
+            .iter()
+            .map(|holder| holder.owner.as_str())
+            .collect::<BTreeSet<_>>()
+            .len();
+        Self {
+            collection,
+            mints,
+            owners,
+            holders,
+            failed,
+        }
+    }
+}
+
+/// A metadata delegate record of a mint
+#[derive(Clone, Debug, Serialize)]
+pub struct DelegateInfo {
+    pub role: DelegateRole,
+    pub delegate: String,
+    pub delegate_record: String,
+    /// The update authority that approved the delegate; the delegate only
+    /// acts while this is still the update authority
+    pub update_authority: String,
+}
+
+impl From<Metadata> for MetadataInfo {
+    fn from(metadata: Metadata) -> Self {
+        Self {
+            mint: metadata.mint.to_string(),
+            metadata_pda: find_metadata_pda(&metadata.mint).to_string(),
+            name: metadata.name.trim_end_matches('\0').to_string(),
+            symbol: metadata.symbol.trim_end_matches('\0').to_string(),
+            uri: metadata.uri.trim_end_matches('\0').to_string(),
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            update_authority: metadata.update_authority.to_string(),
+            is_mutable: metadata.is_mutable,
+            primary_sale_happened: metadata.primary_sale_happened,
+            token_standard: metadata.token_standard.map(|s| format!("{:?}", s)),
+            creators: metadata
+                .creators
+                .map(|creators| creators.iter().map(CreatorInfo::from).collect()),
+            collection: metadata.collection.map(|c| CollectionInfo {
+                key: c.key.to_string(),
+                verified: c.verified,
+            }),
+            uses: metadata.uses.as_ref().map(UsesInfo::from),
+            compression: None,
+            offchain: None,
+            offchain_error: None,
+            delegates: None,
+        }
+    }
+}
+
+/// The Merkle tree leaf of a compressed NFT
+#[derive(Clone, Debug, Serialize)]
+pub struct CompressionInfo {
+    pub tree: String,
+    pub leaf_id: u64,
+}
+
+/// The display fields of the off-chain metadata JSON
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct OffChainInfo {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub animation_url: Option<String>,
+    pub external_url: Option<String>,
+    pub attributes: Vec<AttributeInfo>,
+}
+
+/// A single trait from the off-chain `attributes` list
+#[derive(Clone, Debug, Serialize)]
+pub struct AttributeInfo {
+    pub trait_type: Option<String>,
+    pub value: String,
+}
+
+impl From<&Value> for OffChainInfo {
+    fn from(json: &Value) -> Self {
+        let text = |key: &str| json.get(key).and_then(Value::as_str).map(str::to_string);
+        let attributes = json
+            .get("attributes")
+            .and_then(Value::as_array)
+            .map(|attributes| {
+                attributes
+                    .iter()
+                    .filter_map(|attribute| {
+                        let value = match attribute.get("value")? {
+                            Value::String(value) => value.clone(),
+                            value => value.to_string(),
+                        };
+                        Some(AttributeInfo {
+                            trait_type: attribute
+                                .get("trait_type")
+                                .and_then(Value::as_str)
+                                .map(str::to_string),
+                            value,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            name: text("name"),
+            description: text("description"),
+            image: text("image"),
+            animation_url: text("animation_url"),
+            external_url: text("external_url"),
+            attributes,
+        }
+    }
+}
+
+/// A field whose on-chain value differs from the expected one
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Result of comparing on-chain metadata to the expected values
+#[derive(Clone, Debug, Serialize)]
+pub struct DiffResult {
+    pub mint: String,
+    pub matches: bool,
+    pub differences: Vec<FieldDiff>,
+}
+
+/// Whether a batch row was applied
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    /// The transaction was sent and confirmed
+    Succeeded,
+    /// The on-chain values already match, so nothing was sent
+    Unchanged,
+    /// The row could not be applied
+    Failed,
+}
+
+/// Outcome of one row of a batch operation
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchItemResult {
+    pub row: usize,
+    pub mint: String,
+    pub status: BatchStatus,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+    /// Rent returned to the payer, for operations that shrink or close
+    /// accounts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lamports_recovered: Option<u64>,
+}
+
+impl BatchItemResult {
+    /// `Ok(None)` means the row was skipped because nothing would change
+    pub(crate) fn new(row: usize, mint: &str, outcome: anyhow::Result<Option<String>>) -> Self {
+        let (status, signature, error) = match outcome {
+            Ok(Some(signature)) => (BatchStatus::Succeeded, Some(signature), None),
+            Ok(None) => (BatchStatus::Unchanged, None, None),
+            Err(e) => (BatchStatus::Failed, None, Some(format!("{:#}", e))),
+        };
+        Self {
+            row,
+            mint: mint.to_string(),
+            status,
+            signature,
+            error,
+            lamports_recovered: None,
+        }
+    }
+}
+
+/// A manifest row that would fail before its transaction is sent
+#[derive(Clone, Debug, Serialize)]
+pub struct InvalidRow {
+    /// 1-based row number in the manifest
+    pub row: usize,
+    pub mint: String,
+    pub error: String,
+}
+
+/// What a batch job is expected to cost, worked out before anything is sent
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchEstimate {
+    pub rows: usize,
+    /// One per valid row
+    pub transactions: usize,
+    /// Rent of the accounts the job creates
+    pub rent_lamports: u64,
+    /// Fee of one transaction, priority fee included
+    pub fee_per_transaction: u64,
+    /// The part of each fee that is priority fee
+    pub priority_fee_per_transaction: u64,
+    pub transaction_fees: u64,
+    pub upload_files: usize,
+    pub upload_bytes: u64,
+    /// Arweave upload price through Irys
+    pub upload_lamports: u64,
+    pub total_lamports: u64,
+    pub invalid: Vec<InvalidRow>,
+}
+
+/// Per-row outcomes and totals of a batch operation
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchResult {
+    pub total: usize,
+    pub succeeded: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+    /// Total rent returned to the payer, for operations that shrink or
+    /// close accounts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lamports_recovered: Option<u64>,
+    pub items: Vec<BatchItemResult>,
+}
+
+impl BatchResult {
+    pub(crate) fn from_items(items: Vec<BatchItemResult>) -> Self {
+        let count = |status| items.iter().filter(|item| item.status == status).count();
+        Self {
+            total: items.len(),
+            succeeded: count(BatchStatus::Succeeded),
+            unchanged: count(BatchStatus::Unchanged),
+            failed: count(BatchStatus::Failed),
+            lamports_recovered: items
+                .iter()
+                .filter_map(|item| item.lamports_recovered)
+                .reduce(|a, b| a + b),
+            items,
+        }
+    }
+}
+
+/// Change a simulated transaction would make to one writable account.
+/// Missing accounts count as zero lamports and zero bytes.
+#[derive(Clone, Debug, Serialize)]
+pub struct AccountChange {
+    pub address: String,
+    pub lamports_before: u64,
+    pub lamports_after: u64,
+    pub data_len_before: usize,
+    pub data_len_after: usize,
+    pub owner_after: Option<String>,
+}
+
+/// Outcome of simulating a transaction during a dry run
+#[derive(Clone, Debug, Serialize)]
+pub struct SimulationReport {
+    /// The signature the transaction would have had
+    pub signature: String,
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+    pub account_changes: Vec<AccountChange>,
+}
+
+/// The payer can't cover the rent and fees of a transaction. Returned by
+/// the pre-flight check before anything is sent, so callers can top up the
+/// payer and retry.
+#[derive(Clone, Debug)]
+pub struct InsufficientFunds {
+    pub payer: Pubkey,
+    pub balance: u64,
+    /// Lamports needed at least; accounts created after the first one the
+    /// payer can't afford aren't counted
+    pub needed: u64,
+}
+
+impl InsufficientFunds {
+    /// Lamports the payer is short
+    pub fn shortfall(&self) -> u64 {
+        self.needed.saturating_sub(self.balance)
+    }
+}
+
+impl std::fmt::Display for InsufficientFunds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sol = |lamports: u64| lamports as f64 / LAMPORTS_PER_SOL as f64;
+        write!(
+            f,
+            "Payer {} has {} SOL, but this transaction needs at least {} SOL for rent and \
+             fees: need {} more SOL",
+            self.payer,
+            sol(self.balance),
+            sol(self.needed),
+            sol(self.shortfall())
+        )
+    }
+}
+
+impl std::error::Error for InsufficientFunds {}
+
+/// Result of an airdrop from a test cluster's faucet
+#[derive(Clone, Debug, Serialize)]
+pub struct AirdropResult {
+    pub recipient: String,
+    pub lamports: u64,
+    /// The recipient's balance once the airdrop landed
+    pub balance: u64,
+    pub signature: String,
+}
+
+/// Result of broadcasting offline-signed transactions
+#[derive(Clone, Debug, Serialize)]
+pub struct BroadcastResult {
+    pub signatures: Vec<String>,
+}
+
+/// Result of uploading a metadata JSON file
+#[derive(Clone, Debug, Serialize)]
+pub struct UploadResult {
+    /// URI of the uploaded metadata JSON, to use as the on-chain URI
+    pub uri: String,
+    pub image_uri: Option<String>,
+    /// Lamports transferred to the storage provider for these uploads
+    pub lamports_paid: u64,
+}
+
+/// An additional Token-2022 metadata field, parsed from `<KEY>=<VALUE>`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MetadataField {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for MetadataField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (key, value) = s
+            .split_once('=')
+            .context("Expected field in the form <KEY>=<VALUE>")?;
+        if key.is_empty() {
+            anyhow::bail!("Field key must not be empty");
+        }
+        if ["name", "symbol", "uri"].contains(&key) {
+            anyhow::bail!("Use --{} to set the {} field", key, key);
+        }
+        Ok(Self {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// An attribute of a metadata JSON, parsed from `<TRAIT>:<VALUE>`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct JsonAttribute {
+    pub trait_type: String,
+    pub value: String,
+}
+
+impl FromStr for JsonAttribute {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (trait_type, value) = s
+            .split_once(':')
+            .context("Expected attribute in the form <TRAIT>:<VALUE>")?;
+        if trait_type.is_empty() {
+            anyhow::bail!("Attribute trait must not be empty");
+        }
+        Ok(Self {
+            trait_type: trait_type.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// Fields of a metadata JSON file built from scratch
+#[derive(Clone, Debug, Default)]
+pub struct MetadataJsonArgs {
+    pub name: String,
+    pub symbol: String,
+    pub description: Option<String>,
+    pub image_uri: String,
+    /// MIME type of the image; guessed from the URI's extension when `None`
+    pub image_type: Option<String>,
+    pub animation_url: Option<String>,
+    pub external_url: Option<String>,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<CreatorShare>,
+    pub attributes: Vec<JsonAttribute>,
+}
+
+/// Fields written when initializing Token-2022 metadata
+#[derive(Clone, Debug)]
+pub struct Token2022MetadataArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub additional_metadata: Vec<MetadataField>,
+    /// Hash of the JSON behind the URI, recorded in a memo in the same
+    /// transaction
+    pub uri_hash: Option<UriHash>,
+}
+
+/// Changes to Token-2022 metadata; `None` and empty lists keep the current
+/// values
+#[derive(Clone, Debug, Default)]
+pub struct Token2022UpdateArgs {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub uri: Option<String>,
+    pub set_fields: Vec<MetadataField>,
+    pub remove_fields: Vec<String>,
+    /// Hash of the JSON behind the new URI, recorded in a memo in the same
+    /// transaction
+    pub uri_hash: Option<UriHash>,
+}
+
+/// Display-friendly view of the metadata embedded in a Token-2022 mint
+#[derive(Clone, Debug, Serialize)]
+pub struct Token2022MetadataInfo {
+    pub mint: String,
+    pub update_authority: Option<String>,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub additional_metadata: Vec<MetadataField>,
+}
+
+/// Result of initializing Token-2022 metadata
+#[derive(Clone, Debug, Serialize)]
+pub struct Token2022CreateResult {
+    pub metadata: Token2022MetadataInfo,
+    /// Lamports added to the mint to keep it rent-exempt at its new size
+    pub lamports_added: u64,
+    /// The hash of the URI's JSON recorded in the transaction's memo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri_sha256: Option<String>,
+    pub signature: String,
+}
+
+/// A Token-2022 metadata field that was set, changed or removed
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldUpdate {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Result of updating Token-2022 metadata
+#[derive(Clone, Debug, Serialize)]
+pub struct Token2022UpdateResult {
+    pub mint: String,
+    pub changes: Vec<FieldUpdate>,
+    pub lamports_added: u64,
+    /// The hash of the new URI's JSON recorded in the transaction's memo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri_sha256: Option<String>,
+    pub signature: String,
+}
+
+/// Where a mint's metadata is stored, parsed from `metaplex` or `token-2022`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MetadataLocation {
+    /// A Metaplex Token Metadata account
+    Metaplex,
+    /// The mint's own Token-2022 TokenMetadata extension
+    Token2022,
+}
+
+impl FromStr for MetadataLocation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "metaplex" | "mpl" => Ok(Self::Metaplex),
+            "token-2022" | "token2022" => Ok(Self::Token2022),
+            _ => anyhow::bail!("Metadata location must be 'metaplex' or 'token-2022'"),
+        }
+    }
+}
+
+/// What a migration did to the target metadata
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrateAction {
+    Created,
+    Updated,
+    /// The target already had the same name, symbol and URI
+    Unchanged,
+}
+
+/// Result of copying metadata between Metaplex and Token-2022
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrateResult {
+    pub mint: String,
+    pub to: MetadataLocation,
+    pub action: MigrateAction,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    /// Additional Token-2022 fields that Metaplex metadata cannot hold
+    pub dropped_fields: Vec<String>,
+    pub signature: Option<String>,
+}
+
+/// Result of creating or updating a token authorization rule set
+#[derive(Clone, Debug, Serialize)]
+pub struct RuleSetResult {
+    /// Rule set PDA, to pass as `--rule-set` when creating pNFTs
+    pub address: String,
+    pub owner: String,
+    pub name: String,
+    pub operations: Vec<String>,
+    /// Whether a new revision was added to an existing rule set
+    pub updated: bool,
+    pub signature: String,
+}
+
+/// A rule set account and its latest revision
+#[derive(Clone, Debug, Serialize)]
+pub struct RuleSetInfo {
+    pub address: String,
+    pub revisions: usize,
+    /// Account size in bytes
+    pub size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// The rule for each operation, in the same JSON form `ruleset create --file` reads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operations: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decode_error: Option<String>,
+}
+
+/// Result of approving or revoking a delegate
+#[derive(Clone, Debug, Serialize)]
+pub struct DelegateResult {
+    pub mint: String,
+    pub role: DelegateRole,
+    pub delegate: String,
+    /// Metadata delegate record, for metadata roles
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegate_record: Option<String>,
+    /// The owner's token account, for token roles
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_account: Option<String>,
+    /// The pNFT token record that holds the token delegate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_record: Option<String>,
+    /// Tokens delegated, for token roles
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<u64>,
+    pub signature: String,
+}
+
+/// State of a pNFT token record
+#[derive(Clone, Debug, Serialize)]
+pub struct TokenRecordInfo {
+    /// Unlocked, Locked or Listed
+    pub state: String,
+    pub delegate: Option<String>,
+    pub delegate_role: Option<String>,
+}
+
+impl From<&TokenRecord> for TokenRecordInfo {
+    fn from(record: &TokenRecord) -> Self {
+        Self {
+            state: format!("{:?}", record.state),
+            delegate: record.delegate.map(|delegate| delegate.to_string()),
+            delegate_role: record
+                .delegate_role
+                .as_ref()
+                .map(|role| format!("{:?}", role)),
+        }
+    }
+}
+
+/// Result of locking or unlocking a pNFT
+#[derive(Clone, Debug, Serialize)]
+pub struct LockResult {
+    pub mint: String,
+    pub owner: String,
+    pub token_account: String,
+    pub token_record: String,
+    pub before: TokenRecordInfo,
+    /// The token record once the transaction landed; absent for dry runs
+    /// and offline signing
+    pub after: Option<TokenRecordInfo>,
+    pub signature: String,
+}
+
+/// Result of consuming uses of a utility NFT
+#[derive(Clone, Debug, Serialize)]
+pub struct UseResult {
+    pub mint: String,
+    pub owner: String,
+    pub token_account: String,
+    pub use_authority: String,
+    /// The use authority record, when a delegated use authority signed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_authority_record: Option<String>,
+    pub number_of_uses: u64,
+    pub before: UsesInfo,
+    pub remaining: u64,
+    /// Whether the last use burned the token
+    pub burned: bool,
+    pub signature: String,
+}
+
+/// Result of transferring tokens of a mint
+#[derive(Clone, Debug, Serialize)]
+pub struct TransferResult {
+    pub mint: String,
+    pub token_standard: String,
+    pub from: String,
+    pub to: String,
+    pub source_token: String,
+    pub destination_token: String,
+    /// Whether the destination token account was created by the transfer
+    pub created_destination_token: bool,
+    pub amount: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination_token_record: Option<String>,
+    /// Rule set the pNFT transfer was checked against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_set: Option<String>,
+    pub signature: String,
+}
+
+/// One metadata JSON file produced by `generate`
+#[derive(Clone, Debug, Serialize)]
+pub struct GeneratedItem {
+    pub index: usize,
+    pub file: String,
+    /// The row's `mint` value, if the data file has that column
+    pub mint: Option<String>,
+    pub name: String,
+    pub symbol: String,
+    pub seller_fee_basis_points: u16,
+    /// Local image file referenced by the JSON, uploaded along with it
+    pub image: Option<String>,
+    /// URI of the uploaded JSON, when uploading
+    pub uri: Option<String>,
+    pub image_uri: Option<String>,
+}
+
+/// Result of generating metadata files from a template
+#[derive(Clone, Debug, Serialize)]
+pub struct GenerateResult {
+    pub out_dir: String,
+    /// Manifest ready for `batch create`
+    pub manifest: String,
+    pub lamports_paid: u64,
+    pub items: Vec<GeneratedItem>,
+}
+
+/// How serious a metadata JSON validation issue or audit finding is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in metadata JSON
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Path of the offending field, e.g. `properties.files[0]`
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub(crate) fn warning(field: &str, message: &str) -> Self {
+        Self {
+            severity: Severity::Warning,
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// A problem found by an audit
+#[derive(Clone, Debug, Serialize)]
+pub struct AuditFinding {
+    pub severity: Severity,
+    /// What was checked: a metadata field such as `uri`, or `metadata` when
+    /// the account couldn't be read
+    pub check: String,
+    pub message: String,
+}
+
+impl AuditFinding {
+    pub(crate) fn error(check: &str, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            check: check.to_string(),
+            message,
+        }
+    }
+
+    pub(crate) fn warning(check: &str, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            check: check.to_string(),
+            message,
+        }
+    }
+}
+
+/// The audit of one mint
+#[derive(Clone, Debug, Serialize)]
+pub struct MintAudit {
+    pub mint: String,
+    pub metadata_pda: String,
+    /// Out of 100: 25 points off per error and 10 per warning
+    pub score: u8,
+    pub errors: usize,
+    pub warnings: usize,
+    pub findings: Vec<AuditFinding>,
+}
+
+impl MintAudit {
+    pub(crate) fn new(mint: &Pubkey, metadata_pda: &Pubkey, findings: Vec<AuditFinding>) -> Self {
+        let count = |severity| findings.iter().filter(|f| f.severity == severity).count();
+        let errors = count(Severity::Error);
+        let warnings = count(Severity::Warning);
+        Self {
+            mint: mint.to_string(),
+            metadata_pda: metadata_pda.to_string(),
+            score: 100usize.saturating_sub(errors * 25 + warnings * 10) as u8,
+            errors,
+            warnings,
+            findings,
+        }
+    }
+
+    /// The audit of a mint whose metadata couldn't be read, which scores 0
+    pub(crate) fn unreadable(mint: &Pubkey, metadata_pda: &Pubkey, message: String) -> Self {
+        Self {
+            score: 0,
+            ..Self::new(
+                mint,
+                metadata_pda,
+                vec![AuditFinding::error("metadata", message)],
+            )
+        }
+    }
+}
+
+/// Result of auditing one or more mints
+#[derive(Clone, Debug, Serialize)]
+pub struct AuditReport {
+    /// The lowest score of any mint
+    pub score: u8,
+    pub errors: usize,
+    pub warnings: usize,
+    pub mints: Vec<MintAudit>,
+}
+
+impl AuditReport {
+    pub(crate) fn new(mints: Vec<MintAudit>) -> Self {
+        Self {
+            score: mints.iter().map(|mint| mint.score).min().unwrap_or(100),
+            errors: mints.iter().map(|mint| mint.errors).sum(),
+            warnings: mints.iter().map(|mint| mint.warnings).sum(),
+            mints,
+        }
+    }
+}
+
+/// Result of validating metadata JSON
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidationReport {
+    pub source: String,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub(crate) fn new(source: &str, issues: Vec<ValidationIssue>) -> Self {
+        let count = |severity| issues.iter().filter(|i| i.severity == severity).count();
+        Self {
+            source: source.to_string(),
+            errors: count(Severity::Error),
+            warnings: count(Severity::Warning),
+            issues,
+        }
+    }
+
+    /// Whether no errors were found (warnings are allowed)
+    pub fn is_valid(&self) -> bool {
+        self.errors == 0
+    }
+}
@@ -0,0 +1,261 @@
+//! Uploading a metadata JSON file, and optionally its image, to off-chain
+//! storage, and building the metadata JSON that gets uploaded.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use solana_sdk::signature::Signer;
+use std::path::Path;
+
+use crate::client::MetadataClient;
+use crate::cluster::Cluster;
+use crate::ipfs::PinningProvider;
+use crate::irys::Irys;
+use crate::shadow::ShadowDrive;
+use crate::types::{CreateMetadataArgs, MetadataJsonArgs, UploadResult};
+
+/// Where uploaded files are stored
+pub enum Storage {
+    /// Arweave through Irys, paid in SOL from the payer
+    Arweave,
+    /// IPFS through a pinning service, returning `ipfs://` URIs
+    Ipfs(Box<dyn PinningProvider + Send + Sync>),
+    /// An existing Shadow Drive storage account owned by the payer
+    Shadow(ShadowDrive),
+}
+
+impl MetadataClient {
+    /// Upload a metadata JSON file. If an image is given it is uploaded
+    /// first and its URI written into the JSON's `image` field and
+    /// `properties.files`.
+    pub async fn upload_metadata(
+        &self,
+        payer: &dyn Signer,
+        storage: &Storage,
+        metadata_path: &Path,
+        image_path: Option<&Path>,
+    ) -> Result<UploadResult> {
+        let contents = std::fs::read_to_string(metadata_path).with_context(|| {
+            format!("Failed to read metadata file '{}'", metadata_path.display())
+        })?;
+        let metadata: Value = serde_json::from_str(&contents).with_context(|| {
+            format!(
+                "Failed to parse metadata file '{}'",
+                metadata_path.display()
+            )
+        })?;
+        if !metadata.is_object() {
+            anyhow::bail!("Metadata file must contain a JSON object");
+        }
+        self.upload_json(
+            payer,
+            storage,
+            metadata,
+            file_name(metadata_path),
+            image_path,
+        )
+        .await
+    }
+
+    /// Upload an image, then a metadata JSON generated from `args` that
+    /// points at it. The JSON's URI is returned for use as the on-chain URI.
+    pub async fn upload_image_metadata(
+        &self,
+        payer: &dyn Signer,
+        storage: &Storage,
+        image_path: &Path,
+        args: &CreateMetadataArgs,
+        description: Option<&str>,
+    ) -> Result<UploadResult> {
+        let metadata = metadata_json(args, description);
+        self.upload_json(payer, storage, metadata, "metadata.json", Some(image_path))
+            .await
+    }
+
+    async fn upload_json(
+        &self,
+        payer: &dyn Signer,
+        storage: &Storage,
+        mut metadata: Value,
+        json_name: &str,
+        image_path: Option<&Path>,
+    ) -> Result<UploadResult> {
+        let mut lamports_paid = 0;
+        let mut image_uri = None;
+        if let Some(image_path) = image_path {
+            let image = std::fs::read(image_path)
+                .with_context(|| format!("Failed to read image '{}'", image_path.display()))?;
+            let content_type = content_type(image_path);
+            let (uri, lamports) = self
+                .store(storage, payer, &image, file_name(image_path), content_type)
+                .await?;
+            lamports_paid += lamports;
+
+            set_image(&mut metadata, &uri, content_type);
+            image_uri = Some(uri);
+        }
+
+        let json = serde_json::to_vec_pretty(&metadata)?;
+        let (uri, lamports) = self
+            .store(storage, payer, &json, json_name, "application/json")
+            .await?;
+        lamports_paid += lamports;
+
+        Ok(UploadResult {
+            uri,
+            image_uri,
+            lamports_paid,
+        })
+    }
+
+    /// Upload one file, returning its URI and the lamports paid for it
+    async fn store(
+        &self,
+        storage: &Storage,
+        payer: &dyn Signer,
+        data: &[u8],
+        file_name: &str,
+        content_type: &str,
+    ) -> Result<(String, u64)> {
+        match storage {
+            Storage::Arweave => {
+                let irys = Irys::for_cluster(self.cluster());
+                let upload = self
+                    .upload_to_arweave(&irys, payer, data, content_type)
+                    .await?;
+                Ok((upload.uri, upload.lamports_funded))
+            }
+            Storage::Ipfs(provider) => {
+                let cid = provider.pin(data, file_name, content_type).await?;
+                Ok((format!("ipfs://{}", cid), 0))
+            }
+            Storage::Shadow(drive) => {
+                if self.cluster() != Cluster::MainnetBeta {
+                    anyhow::bail!("Shadow Drive is only available on mainnet-beta");
+                }
+                let uri = drive.upload(payer, data, file_name, content_type).await?;
+                Ok((uri, 0))
+            }
+        }
+    }
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("file")
+}
+
+/// A Metaplex metadata JSON for a token whose image is added by
+/// [`set_image`]
+fn metadata_json(args: &CreateMetadataArgs, description: Option<&str>) -> Value {
+    let creators: Vec<Value> = args
+        .creators
+        .iter()
+        .flatten()
+        .map(|c| serde_json::json!({ "address": c.address.to_string(), "share": c.share }))
+        .collect();
+    serde_json::json!({
+        "name": args.name,
+        "symbol": args.symbol,
+        "description": description.unwrap_or_default(),
+        "seller_fee_basis_points": args.seller_fee_basis_points,
+        "attributes": [],
+        "properties": {
+            "category": "image",
+            "files": [],
+            "creators": creators,
+        },
+    })
+}
+
+/// A Metaplex metadata JSON with the image, and the animation when given,
+/// listed in `properties.files`
+pub fn build_metadata_json(args: &MetadataJsonArgs) -> Value {
+    let creators: Vec<Value> = args
+        .creators
+        .iter()
+        .map(|c| serde_json::json!({ "address": c.address.to_string(), "share": c.share }))
+        .collect();
+    let attributes: Vec<Value> = args
+        .attributes
+        .iter()
+        .map(|a| serde_json::json!({ "trait_type": a.trait_type, "value": a.value }))
+        .collect();
+    let mut metadata = serde_json::json!({
+        "name": args.name,
+        "symbol": args.symbol,
+        "description": args.description.as_deref().unwrap_or_default(),
+        "seller_fee_basis_points": args.seller_fee_basis_points,
+        "attributes": attributes,
+        "properties": {
+            "category": "image",
+            "files": [],
+            "creators": creators,
+        },
+    });
+    let image_type = args
+        .image_type
+        .as_deref()
+        .unwrap_or_else(|| content_type(Path::new(uri_path(&args.image_uri))));
+    set_image(&mut metadata, &args.image_uri, image_type);
+    if let Some(external_url) = &args.external_url {
+        metadata["external_url"] = Value::String(external_url.clone());
+    }
+    if let Some(animation_url) = &args.animation_url {
+        let animation_type = content_type(Path::new(uri_path(animation_url)));
+        metadata["animation_url"] = Value::String(animation_url.clone());
+        metadata["properties"]["category"] = Value::String(
+            if animation_type.starts_with("video/") {
+                "video"
+            } else {
+                "html"
+            }
+            .to_string(),
+        );
+        if let Some(files) = metadata["properties"]["files"].as_array_mut() {
+            files.push(serde_json::json!({ "uri": animation_url, "type": animation_type }));
+        }
+    }
+    metadata
+}
+
+/// A URI without its query string or fragment, so its extension can be read
+fn uri_path(uri: &str) -> &str {
+    uri.split(['?', '#']).next().unwrap_or(uri)
+}
+
+/// Point the metadata's `image` at the uploaded file and list it in
+/// `properties.files`
+fn set_image(metadata: &mut Value, uri: &str, content_type: &str) {
+    metadata["image"] = Value::String(uri.to_string());
+
+    let properties = &mut metadata["properties"];
+    if !properties.is_object() {
+        *properties = serde_json::json!({});
+    }
+    let files = &mut properties["files"];
+    if !files.is_array() {
+        *files = serde_json::json!([]);
+    }
+    if let Some(files) = files.as_array_mut() {
+        files.push(serde_json::json!({ "uri": uri, "type": content_type }));
+    }
+}
+
+/// MIME type of a file, from its extension
+fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        Some("mp4") => "video/mp4",
+        Some("json") => "application/json",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,103 @@
+//! Consuming uses of utility NFTs, such as the entries of a ticket.
+
+use anyhow::{Context, Result};
+use mpl_token_metadata::{
+    accounts::UseAuthorityRecord,
+    instructions::UtilizeBuilder,
+    types::{TokenStandard, UseMethod},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account_client::address::get_associated_token_address_with_program_id;
+
+use crate::client::MetadataClient;
+use crate::pda::{find_burner_pda, find_metadata_pda, find_use_authority_record_pda};
+use crate::types::{UseResult, UsesInfo};
+
+impl MetadataClient {
+    /// Consume `number_of_uses` uses of a token held by `owner`. The
+    /// authority is the owner or a use authority approved for the mint.
+    /// With the burn method the token is burned once its last use is
+    /// consumed.
+    pub async fn utilize(
+        &self,
+        authority: &dyn Signer,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        number_of_uses: u64,
+    ) -> Result<UseResult> {
+        if number_of_uses == 0 {
+            anyhow::bail!("At least one use must be consumed");
+        }
+        let metadata = self.fetch_fresh(mint).await?;
+        if metadata.token_standard == Some(TokenStandard::ProgrammableNonFungible) {
+            anyhow::bail!("Uses of programmable NFTs can't be consumed with Utilize");
+        }
+        let uses = metadata
+            .uses
+            .as_ref()
+            .with_context(|| format!("Mint {} has no uses", mint))?;
+        if uses.remaining < number_of_uses {
+            anyhow::bail!("Only {} of {} uses remain", uses.remaining, uses.total);
+        }
+        if uses.use_method == UseMethod::Single && number_of_uses > 1 {
+            anyhow::bail!("A single-use token can only be used once");
+        }
+
+        let authority_pubkey = authority.pubkey();
+        let use_authority_record = if authority_pubkey == *owner {
+            None
+        } else {
+            let record = find_use_authority_record_pda(mint, &authority_pubkey);
+            let data = self.rpc().get_account_data(&record).await.map_err(|_| {
+                anyhow::anyhow!(
+                    "{} is neither the owner nor an approved use authority of this token",
+                    authority_pubkey
+                )
+            })?;
+            let allowed = UseAuthorityRecord::from_bytes(&data)
+                .map_err(|e| anyhow::anyhow!("Failed to deserialize use authority record: {}", e))?
+                .allowed_uses;
+            if allowed < number_of_uses {
+                anyhow::bail!("The use authority may only consume {} more uses", allowed);
+            }
+            Some(record)
+        };
+
+        let token_program = self.fetch_mint(mint).await?.program.id();
+        let token = get_associated_token_address_with_program_id(owner, mint, &token_program);
+        if self.token_balance(&token).await? == 0 {
+            anyhow::bail!("{} holds no tokens of mint {}", owner, mint);
+        }
+        let burned = uses.use_method == UseMethod::Burn && uses.remaining == number_of_uses;
+
+        let ix = UtilizeBuilder::new()
+            .metadata(find_metadata_pda(mint))
+            .token_account(token)
+            .mint(*mint)
+            .use_authority(authority_pubkey)
+            .owner(*owner)
+            .token_program(token_program)
+            .use_authority_record(use_authority_record)
+            .burner((uses.use_method == UseMethod::Burn).then(find_burner_pda))
+            .number_of_uses(number_of_uses)
+            .instruction();
+
+        let signature = self
+            .send(&[ix], authority, &[])
+            .await
+            .context("Failed to send use transaction")?;
+
+        Ok(UseResult {
+            mint: mint.to_string(),
+            owner: owner.to_string(),
+            token_account: token.to_string(),
+            use_authority: authority_pubkey.to_string(),
+            use_authority_record: use_authority_record.map(|record| record.to_string()),
+            number_of_uses,
+            before: UsesInfo::from(uses),
+            remaining: uses.remaining - number_of_uses,
+            burned,
+            signature: signature.to_string(),
+        })
+    }
+}
@@ -0,0 +1,320 @@
+//! Checking off-chain metadata JSON against the Metaplex Token Metadata
+//! standard.
+
+use anyhow::Result;
+use mpl_token_metadata::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::error::MetadataCliError;
+use crate::offchain::gateway_url;
+use crate::types::{Severity, ValidationIssue, ValidationReport};
+
+/// Highest seller fee, 100%
+pub const MAX_SELLER_FEE_BASIS_POINTS: u16 = 10000;
+
+/// Longest memo that fits in a transaction alongside a metadata instruction
+pub const MAX_MEMO_LENGTH: usize = 566;
+
+/// Check the fields of a metadata account against the limits the Token
+/// Metadata program enforces, failing with
+/// [`InvalidField`](MetadataCliError::InvalidField) for the first that
+/// breaks one. Creators are checked when the list is built.
+pub fn check_fields(
+    name: &str,
+    symbol: &str,
+    uri: &str,
+    seller_fee_basis_points: u16,
+) -> Result<()> {
+    check_name(name)?;
+    check_symbol(symbol)?;
+    check_uri(uri)?;
+    check_seller_fee_basis_points(seller_fee_basis_points)
+}
+
+pub fn check_name(name: &str) -> Result<()> {
+    check_length("name", name, MAX_NAME_LENGTH)
+}
+
+pub fn check_symbol(symbol: &str) -> Result<()> {
+    check_length("symbol", symbol, MAX_SYMBOL_LENGTH)
+}
+
+pub fn check_uri(uri: &str) -> Result<()> {
+    check_length("uri", uri, MAX_URI_LENGTH)
+}
+
+pub fn check_seller_fee_basis_points(seller_fee_basis_points: u16) -> Result<()> {
+    if seller_fee_basis_points > MAX_SELLER_FEE_BASIS_POINTS {
+        return Err(MetadataCliError::InvalidField {
+            field: "seller_fee_basis_points",
+            reason: format!(
+                "{} is more than {} (100%)",
+                seller_fee_basis_points, MAX_SELLER_FEE_BASIS_POINTS
+            ),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+pub fn check_memo(memo: &str) -> Result<()> {
+    check_length("memo", memo, MAX_MEMO_LENGTH)
+}
+
+fn check_length(field: &'static str, value: &str, max: usize) -> Result<()> {
+    if value.len() > max {
+        return Err(MetadataCliError::InvalidField {
+            field,
+            reason: format!(
+                "{} bytes, more than the on-chain limit of {}",
+                value.len(),
+                max
+            ),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Validate metadata JSON. With `check_links`, the image and file URIs are
+/// also requested to make sure they resolve.
+pub async fn validate_metadata_json(
+    source: &str,
+    json: &Value,
+    check_links: bool,
+    timeout: Duration,
+) -> ValidationReport {
+    let mut issues = Vec::new();
+    let mut error = |field: &str, message: String| {
+        issues.push(ValidationIssue {
+            severity: Severity::Error,
+            field: field.to_string(),
+            message,
+        })
+    };
+
+    let Some(object) = json.as_object() else {
+        error("", "Metadata JSON must be an object".to_string());
+        return ValidationReport::new(source, issues);
+    };
+
+    match object.get("name") {
+        None => error("name", "Missing required field".to_string()),
+        Some(Value::String(name)) if name.len() > MAX_NAME_LENGTH => error(
+            "name",
+            format!(
+                "Longer than the on-chain limit of {} bytes",
+                MAX_NAME_LENGTH
+            ),
+        ),
+        Some(Value::String(_)) => {}
+        Some(_) => error("name", "Must be a string".to_string()),
+    }
+    match object.get("symbol") {
+        None => error("symbol", "Missing required field".to_string()),
+        Some(Value::String(symbol)) if symbol.len() > MAX_SYMBOL_LENGTH => error(
+            "symbol",
+            format!(
+                "Longer than the on-chain limit of {} bytes",
+                MAX_SYMBOL_LENGTH
+            ),
+        ),
+        Some(Value::String(_)) => {}
+        Some(_) => error("symbol", "Must be a string".to_string()),
+    }
+    match object.get("image") {
+        None => error("image", "Missing required field".to_string()),
+        Some(Value::String(_)) => {}
+        Some(_) => error("image", "Must be a string".to_string()),
+    }
+    for field in ["description", "external_url", "animation_url"] {
+        if object.get(field).is_some_and(|value| !value.is_string()) {
+            error(field, "Must be a string".to_string());
+        }
+    }
+    if let Some(fee) = object.get("seller_fee_basis_points")
+        && fee.as_u64().is_none_or(|fee| fee > 10_000)
+    {
+        error(
+            "seller_fee_basis_points",
+            "Must be an integer between 0 and 10000".to_string(),
+        );
+    }
+
+    match object.get("attributes") {
+        None => error("attributes", "Missing required field".to_string()),
+        Some(Value::Array(attributes)) => {
+            for (i, attribute) in attributes.iter().enumerate() {
+                let field = format!("attributes[{}]", i);
+                if !attribute["trait_type"].is_string() {
+                    error(&field, "trait_type must be a string".to_string());
+                }
+                if !(attribute["value"].is_string() || attribute["value"].is_number()) {
+                    error(&field, "value must be a string or number".to_string());
+                }
+            }
+        }
+        Some(_) => error("attributes", "Must be an array".to_string()),
+    }
+
+    let properties = object.get("properties");
+    match properties.map(|p| &p["files"]) {
+        None | Some(Value::Null) => error("properties.files", "Missing required field".to_string()),
+        Some(Value::Array(files)) => {
+            for (i, file) in files.iter().enumerate() {
+                let field = format!("properties.files[{}]", i);
+                if !file["uri"].is_string() {
+                    error(&field, "uri must be a string".to_string());
+                }
+                if !file["type"].is_string() {
+                    error(&field, "type must be a MIME type string".to_string());
+                }
+            }
+        }
+        Some(_) => error("properties.files", "Must be an array".to_string()),
+    }
+    if let Some(creators) = properties.map(|p| &p["creators"])
+        && !creators.is_null()
+    {
+        match creators.as_array() {
+            Some(creators) => {
+                let mut total = 0;
+                for (i, creator) in creators.iter().enumerate() {
+                    let field = format!("properties.creators[{}]", i);
+                    if creator["address"]
+                        .as_str()
+                        .is_none_or(|a| Pubkey::from_str(a).is_err())
+                    {
+                        error(&field, "address must be a valid public key".to_string());
+                    }
+                    match creator["share"].as_u64() {
+                        Some(share) if share <= 100 => total += share,
+                        _ => error(
+                            &field,
+                            "share must be an integer between 0 and 100".to_string(),
+                        ),
+                    }
+                }
+                if !creators.is_empty() && total != 100 {
+                    error(
+                        "properties.creators",
+                        format!("Shares must sum to 100 (got {})", total),
+                    );
+                }
+            }
+            None => error("properties.creators", "Must be an array".to_string()),
+        }
+    }
+
+    let mut links: Vec<(String, &str)> = ["image", "animation_url", "external_url"]
+        .into_iter()
+        .filter_map(|field| Some((field.to_string(), object.get(field)?.as_str()?)))
+        .collect();
+    if let Some(files) = properties.and_then(|p| p["files"].as_array()) {
+        for (i, file) in files.iter().enumerate() {
+            if let Some(uri) = file["uri"].as_str() {
+                links.push((format!("properties.files[{}].uri", i), uri));
+            }
+        }
+    }
+    let http = reqwest::Client::builder().timeout(timeout).build().ok();
+    for (field, uri) in links {
+        if uri.is_empty() {
+            issues.push(ValidationIssue::warning(&field, "Empty URI"));
+            continue;
+        }
+        if uri.starts_with("http://") {
+            issues.push(ValidationIssue::warning(
+                &field,
+                "Uses http:// instead of https://",
+            ));
+        } else if !["https://", "ipfs://", "ar://"]
+            .iter()
+            .any(|scheme| uri.starts_with(scheme))
+        {
+            issues.push(ValidationIssue::warning(
+                &field,
+                "Not an https, ipfs or ar URI",
+            ));
+            continue;
+        }
+
+        if check_links
+            && field != "external_url"
+            && let Some(http) = &http
+        {
+            let reachable = http
+                .get(gateway_url(uri))
+                .send()
+                .await
+                .is_ok_and(|r| r.status().is_success());
+            if !reachable {
+                issues.push(ValidationIssue {
+                    severity: Severity::Error,
+                    field,
+                    message: format!("Link does not resolve: {}", uri),
+                });
+            }
+        }
+    }
+
+    ValidationReport::new(source, issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid_field(result: Result<()>) -> &'static str {
+        match result.unwrap_err().downcast::<MetadataCliError>() {
+            Ok(MetadataCliError::InvalidField { field, .. }) => field,
+            other => panic!("expected InvalidField, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fields_at_the_limits_pass() {
+        let name = "n".repeat(MAX_NAME_LENGTH);
+        let symbol = "s".repeat(MAX_SYMBOL_LENGTH);
+        let uri = "u".repeat(MAX_URI_LENGTH);
+        check_fields(&name, &symbol, &uri, MAX_SELLER_FEE_BASIS_POINTS).unwrap();
+        check_fields("", "", "", 0).unwrap();
+    }
+
+    #[test]
+    fn first_field_over_its_limit_fails() {
+        let long_name = "n".repeat(MAX_NAME_LENGTH + 1);
+        let long_symbol = "s".repeat(MAX_SYMBOL_LENGTH + 1);
+        let long_uri = "u".repeat(MAX_URI_LENGTH + 1);
+        assert_eq!(invalid_field(check_fields(&long_name, "", "", 0)), "name");
+        assert_eq!(
+            invalid_field(check_fields("", &long_symbol, &long_uri, 0)),
+            "symbol"
+        );
+        assert_eq!(invalid_field(check_fields("", "", &long_uri, 0)), "uri");
+        assert_eq!(
+            invalid_field(check_fields("", "", "", MAX_SELLER_FEE_BASIS_POINTS + 1)),
+            "seller_fee_basis_points"
+        );
+    }
+
+    #[test]
+    fn lengths_are_counted_in_bytes() {
+        // 9 characters, 36 bytes
+        let name = "🦀".repeat(MAX_NAME_LENGTH / 4 + 1);
+        assert!(name.chars().count() < MAX_NAME_LENGTH);
+        assert_eq!(invalid_field(check_name(&name)), "name");
+    }
+
+    #[test]
+    fn memo_limit() {
+        check_memo(&"m".repeat(MAX_MEMO_LENGTH)).unwrap();
+        assert_eq!(
+            invalid_field(check_memo(&"m".repeat(MAX_MEMO_LENGTH + 1))),
+            "memo"
+        );
+    }
+}
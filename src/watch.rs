@@ -0,0 +1,245 @@
+//! Watching metadata accounts for changes over the RPC node's websocket,
+//! reconnecting whenever the connection drops.
+
+use anyhow::{Context, Result};
+use futures_util::{StreamExt, stream::select_all};
+use mpl_token_metadata::accounts::Metadata;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    nonblocking::pubsub_client::PubsubClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::RpcAccountInfoConfig,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use crate::client::MetadataClient;
+use crate::confirm::websocket_url;
+use crate::pda::find_metadata_pda;
+use crate::types::{ChangedField, MetadataEvent};
+
+/// Delay before the first reconnection attempt; it doubles with every
+/// failed attempt
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between reconnection attempts
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// The watched fields of a metadata account
+#[derive(Clone, Default, PartialEq, Eq)]
+struct Watched {
+    name: String,
+    symbol: String,
+    uri: String,
+    update_authority: String,
+}
+
+impl Watched {
+    /// The watched fields of account data, or `None` when the account is
+    /// closed or isn't metadata
+    fn from_data(data: &[u8]) -> Option<Self> {
+        let metadata = Metadata::from_bytes(data).ok()?;
+        Some(Self {
+            name: metadata.name.trim_end_matches('\0').to_string(),
+            symbol: metadata.symbol.trim_end_matches('\0').to_string(),
+            uri: metadata.uri.trim_end_matches('\0').to_string(),
+            update_authority: metadata.update_authority.to_string(),
+        })
+    }
+}
+
+/// One watched mint and what its metadata was last seen as
+struct Target {
+    mint: Pubkey,
+    metadata_pda: Pubkey,
+    last: Option<Watched>,
+}
+
+impl Target {
+    /// Record the account as seen at `slot`, returning an event when a
+    /// watched field changed
+    fn observe(&mut self, slot: u64, now: Option<Watched>) -> Option<MetadataEvent> {
+        if now == self.last {
+            return None;
+        }
+        let before = self.last.clone().unwrap_or_default();
+        let after = now.clone().unwrap_or_default();
+        let changes = [
+            ("name", &before.name, &after.name),
+            ("symbol", &before.symbol, &after.symbol),
+            ("uri", &before.uri, &after.uri),
+            (
+                "update_authority",
+                &before.update_authority,
+                &after.update_authority,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| ChangedField {
+            field: field.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        })
+        .collect();
+        let event = MetadataEvent {
+            mint: self.mint.to_string(),
+            metadata_pda: self.metadata_pda.to_string(),
+            slot,
+            created: self.last.is_none(),
+            closed: now.is_none(),
+            changes,
+            signature: None,
+        };
+        self.last = now;
+        Some(event)
+    }
+}
+
+impl MetadataClient {
+    /// Watch the metadata of `mints`, calling `on_event` whenever a name,
+    /// symbol, URI or update authority changes, or an account is created or
+    /// closed. Each event carries the latest signature on the account, which
+    /// is most likely the change's. Runs until the process is stopped; only
+    /// fetching the starting state can fail.
+    ///
+    /// Notifications come from account subscriptions on the RPC node's
+    /// websocket. When the connection drops it is reopened with backoff,
+    /// and the accounts are fetched again so changes made while it was down
+    /// are still reported.
+    pub async fn watch<F, Fut>(&self, mints: &[Pubkey], mut on_event: F) -> Result<()>
+    where
+        F: FnMut(MetadataEvent) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let url = websocket_url(&self.rpc().url())
+            .context("The RPC URL has no websocket endpoint to subscribe on")?;
+        let mut targets: Vec<Target> = mints
+            .iter()
+            .map(|mint| Target {
+                mint: *mint,
+                metadata_pda: find_metadata_pda(mint),
+                last: None,
+            })
+            .collect();
+        // The starting state isn't a change
+        let (_, current) = self.fetch_watched(&targets).await?;
+        for (target, now) in targets.iter_mut().zip(current) {
+            target.last = now;
+        }
+
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.rpc().commitment()),
+            ..RpcAccountInfoConfig::default()
+        };
+        let mut delay = MIN_RECONNECT_DELAY;
+        let mut connected_before = false;
+        loop {
+            match PubsubClient::new(&url).await {
+                Err(err) => warn!("Websocket connection to {} failed ({})", url, err),
+                Ok(pubsub) => {
+                    let mut streams = Vec::new();
+                    for (index, target) in targets.iter().enumerate() {
+                        match pubsub
+                            .account_subscribe(&target.metadata_pda, Some(config.clone()))
+                            .await
+                        {
+                            Ok((stream, _)) => {
+                                streams.push(stream.map(move |response| (index, response)))
+                            }
+                            Err(err) => {
+                                warn!("Subscribing to {} failed ({})", target.metadata_pda, err);
+                                break;
+                            }
+                        }
+                    }
+
+                    if streams.len() == targets.len() {
+                        delay = MIN_RECONNECT_DELAY;
+                        if connected_before {
+                            info!("Reconnected to {}", url);
+                            match self.fetch_watched(&targets).await {
+                                Ok((slot, current)) => {
+                                    for (target, now) in targets.iter_mut().zip(current) {
+                                        if let Some(event) = target.observe(slot, now) {
+                                            on_event(self.with_signature(event).await).await;
+                                        }
+                                    }
+                                }
+                                Err(err) => warn!(
+                                    "Catching up on missed changes failed ({:#}); they will show with the next change",
+                                    err
+                                ),
+                            }
+                        }
+                        connected_before = true;
+
+                        let mut notifications = select_all(streams);
+                        while let Some((index, response)) = notifications.next().await {
+                            let now = response
+                                .value
+                                .data
+                                .decode()
+                                .and_then(|data| Watched::from_data(&data));
+                            if let Some(event) = targets[index].observe(response.context.slot, now)
+                            {
+                                on_event(self.with_signature(event).await).await;
+                            }
+                        }
+                        warn!("Websocket connection to {} closed", url);
+                    }
+                }
+            }
+            info!("Reconnecting in {}s...", delay.as_secs());
+            sleep(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    /// Fill in the latest signature for the event's account, if the node
+    /// can say
+    async fn with_signature(&self, mut event: MetadataEvent) -> MetadataEvent {
+        let Ok(address) = Pubkey::from_str(&event.metadata_pda) else {
+            return event;
+        };
+        let config = GetConfirmedSignaturesForAddress2Config {
+            limit: Some(1),
+            commitment: Some(self.rpc().commitment()),
+            ..GetConfirmedSignaturesForAddress2Config::default()
+        };
+        event.signature = self
+            .rpc()
+            .get_signatures_for_address_with_config(&address, config)
+            .await
+            .ok()
+            .and_then(|signatures| signatures.into_iter().next())
+            .map(|status| status.signature);
+        event
+    }
+
+    /// The current watched fields of every target, and the slot they were
+    /// read at
+    async fn fetch_watched(&self, targets: &[Target]) -> Result<(u64, Vec<Option<Watched>>)> {
+        let mut slot = 0;
+        let mut watched = Vec::with_capacity(targets.len());
+        for chunk in targets.chunks(100) {
+            let addresses: Vec<Pubkey> = chunk.iter().map(|target| target.metadata_pda).collect();
+            let response = self
+                .rpc()
+                .get_multiple_accounts_with_commitment(&addresses, self.rpc().commitment())
+                .await
+                .context("Failed to fetch metadata accounts")?;
+            slot = slot.max(response.context.slot);
+            watched.extend(
+                response
+                    .value
+                    .into_iter()
+                    .map(|account| Watched::from_data(&account?.data)),
+            );
+        }
+        Ok((slot, watched))
+    }
+}
@@ -0,0 +1,57 @@
+//! Posting JSON notifications to a webhook.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Attempts at delivering one notification
+const ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; it doubles with every attempt
+const BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// How long to wait for the webhook to answer
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A webhook that notifications are POSTed to as JSON
+pub struct Webhook {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl Webhook {
+    pub fn new(url: &str) -> Result<Self> {
+        reqwest::Url::parse(url).with_context(|| format!("Invalid webhook URL '{}'", url))?;
+        Ok(Self {
+            url: url.to_string(),
+            http: reqwest::Client::builder().timeout(TIMEOUT).build()?,
+        })
+    }
+
+    /// POST `payload`, retrying with backoff when the webhook can't be
+    /// reached or answers with an error status
+    pub async fn post<T: Serialize>(&self, payload: &T) -> Result<()> {
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .http
+                .post(&self.url)
+                .json(payload)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            match result {
+                Ok(_) => return Ok(()),
+                Err(err) if attempt >= ATTEMPTS => {
+                    return Err(err).with_context(|| {
+                        format!("Failed to notify {} after {} attempts", self.url, attempt)
+                    });
+                }
+                Err(_) => {
+                    tokio::time::sleep(BASE_DELAY * 2u32.pow(attempt - 1)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}